@@ -1,23 +1,25 @@
 use cache::KgCache;
 use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
-use grc20_core::neo4rs;
+use grc20_core::connection::Graph;
 use std::sync::Arc;
 
+use crate::loader::Loaders;
+
 const EMBEDDING_MODEL: EmbeddingModel = EmbeddingModel::AllMiniLML6V2;
 
 #[derive(Clone)]
 pub struct KnowledgeGraph {
-    pub neo4j: Arc<neo4rs::Graph>,
+    pub neo4j: Arc<Graph>,
     pub cache: Option<Arc<KgCache>>,
     pub embedding_model: Arc<TextEmbedding>,
+    pub loaders: Arc<Loaders>,
 }
 
 impl juniper::Context for KnowledgeGraph {}
 
 impl KnowledgeGraph {
-    pub fn new(neo4j: Arc<neo4rs::Graph>, cache: Option<Arc<KgCache>>) -> Self {
+    pub fn new(neo4j: Arc<Graph>, cache: Option<Arc<KgCache>>) -> Self {
         Self {
-            neo4j,
             cache,
             embedding_model: Arc::new(
                 TextEmbedding::try_new(
@@ -25,6 +27,17 @@ impl KnowledgeGraph {
                 )
                 .expect("Failed to initialize embedding model"),
             ),
+            loaders: Arc::new(Loaders::new(neo4j.clone())),
+            neo4j,
+        }
+    }
+
+    /// Returns a copy of the context with fresh loaders. Should be called once per
+    /// GraphQL request so that batched values are not cached across requests.
+    pub fn for_request(&self) -> Self {
+        Self {
+            loaders: Arc::new(Loaders::new(self.neo4j.clone())),
+            ..self.clone()
         }
     }
 }
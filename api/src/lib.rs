@@ -1,3 +1,4 @@
 pub mod context;
+pub mod loader;
 pub mod query_mapping;
 pub mod schema;
@@ -0,0 +1,430 @@
+//! DataLoader-style batching for field resolvers.
+//!
+//! Field resolvers of list items are resolved concurrently by juniper. Instead of
+//! querying Neo4j once per item, resolvers enqueue their key in a [`BatchLoader`]
+//! which dispatches a single batch query for all keys enqueued during the same
+//! tick. Loaders are scoped to a single GraphQL request (see
+//! [`KnowledgeGraph::for_request`](crate::context::KnowledgeGraph::for_request)) so
+//! that cached values never leak across requests.
+use std::{
+    collections::HashMap,
+    future::Future,
+    hash::Hash,
+    sync::{Arc, Mutex},
+};
+
+use futures::{
+    future::{self, BoxFuture, Shared},
+    FutureExt,
+};
+use grc20_core::{
+    connection::Graph,
+    error::DatabaseError,
+    mapping::{
+        aggregation::SpaceRanking, entity, query_utils::Query, relation, triple, EntityNode,
+        Pluralism,
+    },
+    system_ids,
+};
+use grc20_sdk::models;
+
+/// Maximum number of types loaded per entity (same as the default page size of
+/// the unbatched query)
+const MAX_TYPES_PER_ENTITY: usize = 100;
+
+type BatchFn<K, V> =
+    Arc<dyn Fn(Vec<K>) -> BoxFuture<'static, Result<HashMap<K, V>, DatabaseError>> + Send + Sync>;
+
+type BatchResult<K, V> = Result<Arc<HashMap<K, V>>, Arc<DatabaseError>>;
+
+struct Batch<K, V> {
+    /// Keys of the batch. Set to `None` once the batch has been dispatched.
+    keys: Arc<Mutex<Option<Vec<K>>>>,
+    result: Shared<BoxFuture<'static, BatchResult<K, V>>>,
+}
+
+impl<K, V> Batch<K, V>
+where
+    K: Clone + Eq + Hash + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    fn new(key: K, batch_fn: BatchFn<K, V>) -> Self {
+        let keys = Arc::new(Mutex::new(Some(vec![key])));
+        let batch_keys = keys.clone();
+
+        let result = async move {
+            // Yield once so that sibling resolvers get a chance to enqueue their keys
+            tokio::task::yield_now().await;
+
+            let keys = batch_keys
+                .lock()
+                .expect("Batch keys lock poisoned")
+                .take()
+                .unwrap_or_default();
+
+            batch_fn(keys).await.map(Arc::new).map_err(Arc::new)
+        }
+        .boxed()
+        .shared();
+
+        Self { keys, result }
+    }
+
+    /// Add a key to the batch. Returns false if the batch was already dispatched.
+    fn enqueue(&self, key: &K) -> bool {
+        match self.keys.lock().expect("Batch keys lock poisoned").as_mut() {
+            Some(keys) => {
+                if !keys.contains(key) {
+                    keys.push(key.clone());
+                }
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+struct LoaderState<K, V> {
+    cache: HashMap<K, Option<V>>,
+    batch: Option<Batch<K, V>>,
+}
+
+/// Batches and caches the loading of values by key.
+pub struct BatchLoader<K, V> {
+    state: Mutex<LoaderState<K, V>>,
+    batch_fn: BatchFn<K, V>,
+}
+
+impl<K, V> BatchLoader<K, V>
+where
+    K: Clone + Eq + Hash + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    /// Create a new loader. `batch_fn` receives the deduplicated keys of a batch and
+    /// returns the values found for these keys. Keys missing from the returned map
+    /// are loaded as `None`.
+    pub fn new<F, Fut>(batch_fn: F) -> Self
+    where
+        F: Fn(Vec<K>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<HashMap<K, V>, DatabaseError>> + Send + 'static,
+    {
+        Self {
+            state: Mutex::new(LoaderState {
+                cache: HashMap::new(),
+                batch: None,
+            }),
+            batch_fn: Arc::new(move |keys| batch_fn(keys).boxed()),
+        }
+    }
+
+    pub async fn load(&self, key: K) -> Result<Option<V>, Arc<DatabaseError>> {
+        let result = {
+            let mut state = self.state.lock().expect("Loader state lock poisoned");
+
+            if let Some(value) = state.cache.get(&key) {
+                return Ok(value.clone());
+            }
+
+            match &state.batch {
+                Some(batch) if batch.enqueue(&key) => batch.result.clone(),
+                _ => {
+                    let batch = Batch::new(key.clone(), self.batch_fn.clone());
+                    let result = batch.result.clone();
+                    state.batch = Some(batch);
+                    result
+                }
+            }
+        };
+
+        let value = result.await?.get(&key).cloned();
+
+        self.state
+            .lock()
+            .expect("Loader state lock poisoned")
+            .cache
+            .insert(key, value.clone());
+
+        Ok(value)
+    }
+}
+
+/// Key of a value that depends on the space and version in which it is loaded
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EntityKey {
+    pub id: String,
+    pub space_id: String,
+    pub space_version: Option<String>,
+}
+
+impl EntityKey {
+    pub fn new(
+        id: impl Into<String>,
+        space_id: impl Into<String>,
+        space_version: Option<String>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            space_id: space_id.into(),
+            space_version,
+        }
+    }
+}
+
+/// Key of an attribute value of an entity
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AttributeKey {
+    pub entity_id: String,
+    pub attribute_id: String,
+    pub space_id: String,
+    pub space_version: Option<String>,
+    /// Spaces to aggregate the attribute value from. `None` means that only
+    /// `space_id` is considered.
+    pub hierarchy: Option<Vec<SpaceRanking>>,
+}
+
+/// Key of the types defined in a space
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SpaceTypesKey {
+    /// Spaces in which the types are looked up (i.e.: the space itself and, unless
+    /// strict, its parent spaces)
+    pub space_ids: Vec<String>,
+    pub first: usize,
+    pub skip: usize,
+}
+
+/// Per-request loaders
+pub struct Loaders {
+    /// Entity nodes
+    pub entities: BatchLoader<EntityKey, EntityNode>,
+    /// Attribute values (e.g.: names)
+    pub attributes: BatchLoader<AttributeKey, String>,
+    /// Types of entities
+    pub types: BatchLoader<EntityKey, Vec<EntityNode>>,
+    /// IDs of the entities merged into entities
+    pub merged_ids: BatchLoader<String, Vec<String>>,
+    /// Types defined in spaces
+    pub space_types: BatchLoader<SpaceTypesKey, Vec<EntityNode>>,
+}
+
+impl Loaders {
+    pub fn new(neo4j: Arc<Graph>) -> Self {
+        Self {
+            entities: BatchLoader::new({
+                let neo4j = neo4j.clone();
+                move |keys| load_entities(neo4j.clone(), keys)
+            }),
+            attributes: BatchLoader::new({
+                let neo4j = neo4j.clone();
+                move |keys| load_attributes(neo4j.clone(), keys)
+            }),
+            types: BatchLoader::new({
+                let neo4j = neo4j.clone();
+                move |keys| load_types(neo4j.clone(), keys)
+            }),
+            merged_ids: BatchLoader::new({
+                let neo4j = neo4j.clone();
+                move |ids| load_merged_ids(neo4j.clone(), ids)
+            }),
+            space_types: BatchLoader::new(move |keys| load_space_types(neo4j.clone(), keys)),
+        }
+    }
+}
+
+async fn load_entities(
+    neo4j: Arc<Graph>,
+    keys: Vec<EntityKey>,
+) -> Result<HashMap<EntityKey, EntityNode>, DatabaseError> {
+    let mut ids = keys.iter().map(|key| key.id.clone()).collect::<Vec<_>>();
+    ids.sort();
+    ids.dedup();
+
+    let nodes = entity::find_many_by_ids::<EntityNode>(&neo4j, ids)
+        .send()
+        .await?
+        .into_iter()
+        .map(|node| (node.id.clone(), node))
+        .collect::<HashMap<_, _>>();
+
+    Ok(keys
+        .into_iter()
+        .filter_map(|key| nodes.get(&key.id).cloned().map(|node| (key, node)))
+        .collect())
+}
+
+async fn load_attributes(
+    neo4j: Arc<Graph>,
+    keys: Vec<AttributeKey>,
+) -> Result<HashMap<AttributeKey, String>, DatabaseError> {
+    // Group the keys that can be loaded with the same query
+    let mut groups: HashMap<_, Vec<AttributeKey>> = HashMap::new();
+    for key in keys {
+        groups
+            .entry((
+                key.attribute_id.clone(),
+                key.space_id.clone(),
+                key.space_version.clone(),
+                key.hierarchy.clone(),
+            ))
+            .or_default()
+            .push(key);
+    }
+
+    let results = future::try_join_all(groups.into_iter().map(
+        |((attribute_id, space_id, space_version, hierarchy), keys)| {
+            let neo4j = neo4j.clone();
+            async move {
+                let triples = triple::find_many_by_ids(
+                    &neo4j,
+                    attribute_id,
+                    keys.iter().map(|key| key.entity_id.clone()).collect(),
+                    space_id,
+                    space_version,
+                )
+                .pluralism(match hierarchy {
+                    Some(spaces) => Pluralism::Hierarchy(spaces),
+                    None => Pluralism::None,
+                })
+                .send()
+                .await?
+                .into_iter()
+                .map(|triple| (triple.entity, triple.value.value))
+                .collect::<HashMap<_, _>>();
+
+                Result::<_, DatabaseError>::Ok(
+                    keys.into_iter()
+                        .filter_map(|key| {
+                            triples
+                                .get(&key.entity_id)
+                                .cloned()
+                                .map(|value| (key, value))
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            }
+        },
+    ))
+    .await?;
+
+    Ok(results.into_iter().flatten().collect())
+}
+
+async fn load_types(
+    neo4j: Arc<Graph>,
+    keys: Vec<EntityKey>,
+) -> Result<HashMap<EntityKey, Vec<EntityNode>>, DatabaseError> {
+    // Group the keys that can be loaded with the same query
+    let mut groups: HashMap<_, Vec<String>> = HashMap::new();
+    for key in keys {
+        groups
+            .entry((key.space_id, key.space_version))
+            .or_default()
+            .push(key.id);
+    }
+
+    let results =
+        future::try_join_all(groups.into_iter().map(|((space_id, space_version), ids)| {
+            let neo4j = neo4j.clone();
+            async move {
+                let mut targets = relation::find_targets(
+                    &neo4j,
+                    ids.clone(),
+                    vec![system_ids::TYPES_ATTRIBUTE.to_string()],
+                    space_id.clone(),
+                )
+                .version_opt(space_version.clone())
+                .send()
+                .await?;
+
+                // Types are limited per entity
+                let type_ids = ids
+                    .into_iter()
+                    .map(|id| {
+                        let mut type_ids = targets
+                            .remove(&(id.clone(), system_ids::TYPES_ATTRIBUTE.to_string()))
+                            .unwrap_or_default();
+                        type_ids.truncate(MAX_TYPES_PER_ENTITY);
+                        (id, type_ids)
+                    })
+                    .collect::<Vec<_>>();
+
+                let mut distinct_type_ids = type_ids
+                    .iter()
+                    .flat_map(|(_, type_ids)| type_ids.iter().map(|type_id| type_id.0.clone()))
+                    .collect::<Vec<_>>();
+                distinct_type_ids.sort();
+                distinct_type_ids.dedup();
+
+                let type_nodes = entity::find_many_by_ids::<EntityNode>(&neo4j, distinct_type_ids)
+                    .send()
+                    .await?
+                    .into_iter()
+                    .map(|node| (node.id.clone(), node))
+                    .collect::<HashMap<_, _>>();
+
+                Result::<_, DatabaseError>::Ok(
+                    type_ids
+                        .into_iter()
+                        .map(|(id, type_ids)| {
+                            (
+                                EntityKey::new(id, space_id.clone(), space_version.clone()),
+                                type_ids
+                                    .into_iter()
+                                    .filter_map(|type_id| type_nodes.get(&type_id.0).cloned())
+                                    .collect(),
+                            )
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            }
+        }))
+        .await?;
+
+    Ok(results.into_iter().flatten().collect())
+}
+
+async fn load_merged_ids(
+    neo4j: Arc<Graph>,
+    ids: Vec<String>,
+) -> Result<HashMap<String, Vec<String>>, DatabaseError> {
+    entity::merged_ids_by_ids(&neo4j, ids).send().await
+}
+
+async fn load_space_types(
+    neo4j: Arc<Graph>,
+    keys: Vec<SpaceTypesKey>,
+) -> Result<HashMap<SpaceTypesKey, Vec<EntityNode>>, DatabaseError> {
+    // Group the keys that can be loaded with the same query
+    let mut groups: HashMap<_, Vec<SpaceTypesKey>> = HashMap::new();
+    for key in keys {
+        groups.entry((key.first, key.skip)).or_default().push(key);
+    }
+
+    let results = future::try_join_all(groups.into_iter().map(|((first, skip), keys)| {
+        let neo4j = neo4j.clone();
+        async move {
+            // The same space can be requested with and without its parent spaces, so
+            // the types are keyed by position rather than by space ID
+            let mut types = models::space::types_by_spaces(
+                &neo4j,
+                keys.iter()
+                    .enumerate()
+                    .map(|(i, key)| (i.to_string(), key.space_ids.clone()))
+                    .collect(),
+            )
+            .limit(first)
+            .skip(skip)
+            .send()
+            .await?;
+
+            Result::<_, DatabaseError>::Ok(
+                keys.into_iter()
+                    .enumerate()
+                    .map(|(i, key)| (key, types.remove(&i.to_string()).unwrap_or_default()))
+                    .collect::<Vec<_>>(),
+            )
+        }
+    }))
+    .await?;
+
+    Ok(results.into_iter().flatten().collect())
+}
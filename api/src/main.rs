@@ -10,7 +10,7 @@ use axum::{
 };
 use cache::{CacheConfig, KgCache};
 use clap::{Args, Parser};
-use grc20_core::neo4rs;
+use grc20_core::connection::Neo4jArgs;
 use juniper::{EmptyMutation, EmptySubscription, RootNode};
 use juniper_axum::{extract::JuniperRequest, graphiql, playground, response::JuniperResponse};
 use std::time::Duration;
@@ -30,12 +30,7 @@ async fn main() -> anyhow::Result<()> {
 
     let args = AppArgs::parse();
 
-    let neo4j = neo4rs::Graph::new(
-        &args.neo4j_args.neo4j_uri,
-        &args.neo4j_args.neo4j_user,
-        &args.neo4j_args.neo4j_pass,
-    )
-    .await?;
+    let neo4j = args.neo4j_args.connect().await?;
 
     let cache = if let Some(uri) = args.cache_args.memcache_uri {
         let cache_config = CacheConfig::new(vec![uri])
@@ -108,21 +103,6 @@ struct CacheArgs {
     memcache_default_expiry: u64,
 }
 
-#[derive(Debug, Args)]
-struct Neo4jArgs {
-    /// Neo4j database host
-    #[arg(long)]
-    neo4j_uri: String,
-
-    /// Neo4j database user name
-    #[arg(long)]
-    neo4j_user: String,
-
-    /// Neo4j database user password
-    #[arg(long)]
-    neo4j_pass: String,
-}
-
 fn init_tracing() {
     tracing_subscriber::registry()
         .with(
@@ -144,7 +124,7 @@ async fn custom_graphql(
     Extension(kg): Extension<KnowledgeGraph>,
     JuniperRequest(request): JuniperRequest,
 ) -> JuniperResponse {
-    JuniperResponse(request.execute(&*schema, &kg).await)
+    JuniperResponse(request.execute(&*schema, &kg.for_request()).await)
 }
 
 async fn homepage() -> Html<&'static str> {
@@ -1,9 +1,9 @@
 use juniper::{graphql_object, FieldResult, ScalarValue};
 
 use grc20_core::{
+    connection::Graph,
     indexer_ids,
     mapping::{query_utils::Query, Entity},
-    neo4rs,
 };
 use grc20_sdk::models::{account, Account as SdkAccount};
 
@@ -18,7 +18,7 @@ impl Account {
         Self { entity }
     }
 
-    pub async fn load(neo4j: &neo4rs::Graph, id: impl Into<String>) -> FieldResult<Option<Self>> {
+    pub async fn load(neo4j: &Graph, id: impl Into<String>) -> FieldResult<Option<Self>> {
         let id = id.into();
 
         Ok(account::find_one(neo4j, &id, indexer_ids::INDEXER_SPACE_ID)
@@ -2,17 +2,19 @@ use futures::TryStreamExt;
 use juniper::{graphql_object, Executor, FieldResult, ScalarValue};
 
 use grc20_core::{
+    connection::Graph,
     entity,
     mapping::{
         aggregation::SpaceRanking,
         query_utils::{prop_filter, Query, QueryStream},
-        triple, EntityNode, Pluralism, RelationEdge,
+        triple, EntityNode, RelationEdge,
     },
-    neo4rs, relation, system_ids,
+    relation, system_ids,
 };
 
 use crate::{
     context::KnowledgeGraph,
+    loader::{AttributeKey, EntityKey},
     schema::{Relation, Triple},
 };
 
@@ -64,7 +66,7 @@ impl Entity {
     }
 
     pub async fn load(
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         id: impl Into<String>,
         space_id: impl Into<String>,
         space_version: Option<String>,
@@ -78,6 +80,49 @@ impl Entity {
             .await?
             .map(|node| Entity::new(node, space_id, space_version, strict)))
     }
+
+    /// Same as [`Entity::load`], but batched with the other entities loaded
+    /// during the same GraphQL request.
+    pub async fn load_batched(
+        kg: &KnowledgeGraph,
+        id: impl Into<String>,
+        space_id: impl Into<String>,
+        space_version: Option<String>,
+        strict: bool,
+    ) -> FieldResult<Option<Self>> {
+        let space_id = space_id.into();
+
+        Ok(kg
+            .loaders
+            .entities
+            .load(EntityKey::new(id, space_id.clone(), space_version.clone()))
+            .await?
+            .map(|node| Entity::new(node, space_id, space_version, strict)))
+    }
+
+    /// Load the value of the attribute `attribute_id` of the entity. The value is
+    /// batched with the other attribute values loaded during the same GraphQL request.
+    async fn load_attribute(
+        &self,
+        kg: &KnowledgeGraph,
+        attribute_id: &str,
+    ) -> FieldResult<Option<String>> {
+        Ok(kg
+            .loaders
+            .attributes
+            .load(AttributeKey {
+                entity_id: self.node.id.clone(),
+                attribute_id: attribute_id.to_string(),
+                space_id: self.space_id.clone(),
+                space_version: self.space_version.clone(),
+                hierarchy: if self.strict {
+                    None
+                } else {
+                    Some(self.parent_spaces.clone())
+                },
+            })
+            .await?)
+    }
 }
 
 #[graphql_object]
@@ -89,6 +134,28 @@ impl Entity {
         &self.node.id
     }
 
+    /// ID of the canonical entity, i.e.: the entity this entity was merged into,
+    /// or the entity itself if it was not merged. Entities are always resolved to
+    /// their canonical entity when queried, so this is the entity ID.
+    pub fn canonical_id(&self) -> &str {
+        &self.node.id
+    }
+
+    /// IDs of the entities merged into this entity. Their original triples and
+    /// relations can still be queried using these IDs.
+    pub async fn merged_ids<'a, S: ScalarValue>(
+        &'a self,
+        executor: &'a Executor<'_, '_, KnowledgeGraph, S>,
+    ) -> FieldResult<Vec<String>> {
+        Ok(executor
+            .context()
+            .loaders
+            .merged_ids
+            .load(self.node.id.clone())
+            .await?
+            .unwrap_or_default())
+    }
+
     /// The space ID of the entity (note: the same entity can exist in multiple spaces)
     pub fn space_id(&self) -> &str {
         &self.space_id
@@ -115,21 +182,8 @@ impl Entity {
         &'a self,
         executor: &'a Executor<'_, '_, KnowledgeGraph, S>,
     ) -> FieldResult<Option<String>> {
-        Ok(triple::find_one(
-            &executor.context().neo4j,
-            system_ids::NAME_ATTRIBUTE,
-            &self.node.id,
-            &self.space_id,
-            self.space_version.clone(),
-        )
-        .pluralism(if self.strict {
-            Pluralism::None
-        } else {
-            Pluralism::Hierarchy(self.parent_spaces.clone())
-        })
-        .send()
-        .await?
-        .map(|triple| triple.value.value))
+        self.load_attribute(executor.context(), system_ids::NAME_ATTRIBUTE)
+            .await
     }
 
     /// Entity description (if available)
@@ -137,21 +191,8 @@ impl Entity {
         &'a self,
         executor: &'a Executor<'_, '_, KnowledgeGraph, S>,
     ) -> FieldResult<Option<String>> {
-        Ok(triple::find_one(
-            &executor.context().neo4j,
-            system_ids::DESCRIPTION_ATTRIBUTE,
-            &self.node.id,
-            &self.space_id,
-            self.space_version.clone(),
-        )
-        .pluralism(if self.strict {
-            Pluralism::None
-        } else {
-            Pluralism::Hierarchy(self.parent_spaces.clone())
-        })
-        .send()
-        .await?
-        .map(|triple| triple.value.value))
+        self.load_attribute(executor.context(), system_ids::DESCRIPTION_ATTRIBUTE)
+            .await
     }
 
     /// Entity cover (if available)
@@ -159,21 +200,8 @@ impl Entity {
         &'a self,
         executor: &'a Executor<'_, '_, KnowledgeGraph, S>,
     ) -> FieldResult<Option<String>> {
-        Ok(triple::find_one(
-            &executor.context().neo4j,
-            system_ids::COVER_ATTRIBUTE,
-            &self.node.id,
-            &self.space_id,
-            self.space_version.clone(),
-        )
-        .pluralism(if self.strict {
-            Pluralism::None
-        } else {
-            Pluralism::Hierarchy(self.parent_spaces.clone())
-        })
-        .send()
-        .await?
-        .map(|triple| triple.value.value))
+        self.load_attribute(executor.context(), system_ids::COVER_ATTRIBUTE)
+            .await
     }
 
     /// Entity blocks (if available)
@@ -214,26 +242,23 @@ impl Entity {
         &'a self,
         executor: &'a Executor<'_, '_, KnowledgeGraph, S>,
     ) -> FieldResult<Vec<Entity>> {
-        let types_rel = self
-            .node
-            .get_outbound_relations::<RelationEdge<EntityNode>>(
-                &executor.context().neo4j,
+        let types = executor
+            .context()
+            .loaders
+            .types
+            .load(EntityKey::new(
+                &self.node.id,
                 &self.space_id,
                 self.space_version.clone(),
-            )
-            .filter(relation::RelationFilter::default().relation_type(
-                entity::EntityFilter::default().id(prop_filter::value(system_ids::TYPES_ATTRIBUTE)),
             ))
-            .send()
             .await?
-            .try_collect::<Vec<_>>()
-            .await?;
+            .unwrap_or_default();
 
-        Ok(types_rel
+        Ok(types
             .into_iter()
-            .map(|rel| {
+            .map(|node| {
                 Entity::new(
-                    rel.to,
+                    node,
                     self.space_id.clone(),
                     self.space_version.clone(),
                     self.strict,
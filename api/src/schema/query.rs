@@ -14,7 +14,9 @@ use grc20_sdk::models::{account, property, space};
 
 use crate::{
     context::KnowledgeGraph,
-    schema::{Account, AccountFilter, Entity, Relation, RelationFilter, Space, SpaceFilter},
+    schema::{
+        Account, AccountFilter, Entity, Relation, RelationFilter, SchemaType, Space, SpaceFilter,
+    },
 };
 
 use super::{entity_order_by::OrderDirection, EntityFilter, Triple};
@@ -184,6 +186,30 @@ impl RootQuery {
         .await
     }
 
+    /// Returns a single type identified by its ID and space ID, along with its properties
+    /// (and their value types) and example entities
+    async fn r#type<'a, S: ScalarValue>(
+        &'a self,
+        executor: &'a Executor<'_, '_, KnowledgeGraph, S>,
+        id: String,
+        space_id: String,
+        version_id: Option<String>,
+        #[graphql(default = true)] strict: bool,
+    ) -> FieldResult<Option<SchemaType>> {
+        let version_index = if let Some(version_id) = version_id {
+            mapping::get_version_index(&executor.context().neo4j, version_id).await?
+        } else {
+            None
+        };
+
+        let type_ = space::r#type(&executor.context().neo4j, &space_id, &id)
+            .strict(strict)
+            .send()
+            .await?;
+
+        Ok(type_.map(|node| SchemaType::new(node, space_id, version_index, strict)))
+    }
+
     #[allow(clippy::too_many_arguments)]
     /// Returns multiple entities according to the provided space ID and filter
     async fn entities<'a, S: ScalarValue>(
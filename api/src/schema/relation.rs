@@ -1,8 +1,8 @@
 use juniper::{graphql_object, Executor, FieldResult, ScalarValue};
 
 use grc20_core::{
+    connection::Graph,
     mapping::{query_utils::Query, relation, EntityNode, RelationEdge},
-    neo4rs,
 };
 
 use crate::context::KnowledgeGraph;
@@ -33,7 +33,7 @@ impl Relation {
     }
 
     pub async fn load(
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         id: impl Into<String>,
         space_id: impl Into<String>,
         space_version: Option<String>,
@@ -63,13 +63,18 @@ impl Relation {
         &self.node.id
     }
 
+    /// Fractional index of the relation. Relation lists are ordered by this key.
+    fn index(&self) -> &str {
+        &self.node.index
+    }
+
     /// Entity of the relation
     async fn entity<'a, S: ScalarValue>(
         &'a self,
         executor: &'a Executor<'_, '_, KnowledgeGraph, S>,
     ) -> FieldResult<Entity> {
-        Ok(Entity::load(
-            &executor.context().neo4j,
+        Ok(Entity::load_batched(
+            executor.context(),
             &self.node.id,
             self.space_id.clone(),
             self.space_version.clone(),
@@ -84,8 +89,8 @@ impl Relation {
         &'a self,
         executor: &'a Executor<'_, '_, KnowledgeGraph, S>,
     ) -> FieldResult<Entity> {
-        Ok(Entity::load(
-            &executor.context().neo4j,
+        Ok(Entity::load_batched(
+            executor.context(),
             &self.node.relation_type,
             self.space_id.clone(),
             self.space_version.clone(),
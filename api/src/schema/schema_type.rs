@@ -3,7 +3,7 @@ use grc20_core::{
     mapping::{aggregation::SpaceRanking, query_utils::QueryStream, EntityNode, RelationEdge},
     system_ids,
 };
-use grc20_sdk::models::property;
+use grc20_sdk::models::{property, type_schema};
 use juniper::{graphql_object, Executor, FieldResult, ScalarValue};
 
 use crate::context::KnowledgeGraph;
@@ -188,4 +188,39 @@ impl SchemaType {
             })
             .collect())
     }
+
+    /// Entities of the Type in the space (e.g.: to illustrate how the Type is used)
+    async fn example_entities<'a, S: ScalarValue>(
+        &'a self,
+        executor: &'a Executor<'_, '_, KnowledgeGraph, S>,
+        #[graphql(default = 10)] first: i32,
+        #[graphql(default = 0)] skip: i32,
+    ) -> FieldResult<Vec<Entity>> {
+        if first > 1000 {
+            return Err("Cannot query more than 1000 entities at once".into());
+        }
+
+        Ok(type_schema::example_entities::<EntityNode>(
+            &executor.context().neo4j,
+            self.entity.id(),
+            self.space_id(),
+            self.entity.space_version.clone(),
+        )
+        .limit(first as usize)
+        .skip(skip as usize)
+        .send()
+        .await?
+        .map_ok(|node| {
+            Entity::with_hierarchy(
+                node,
+                self.space_id().to_string(),
+                self.entity.parent_spaces.clone(),
+                self.entity.subspaces.clone(),
+                self.entity.space_version.clone(),
+                self.entity.strict,
+            )
+        })
+        .try_collect()
+        .await?)
+    }
 }
@@ -2,6 +2,7 @@ use futures::{StreamExt, TryStreamExt};
 use juniper::{graphql_object, Executor, FieldResult, GraphQLEnum, ScalarValue};
 
 use grc20_core::{
+    connection::Graph,
     entity::EntityNode,
     error::DatabaseError,
     indexer_ids,
@@ -11,11 +12,10 @@ use grc20_core::{
         entity, prop_filter,
         query_utils::{Query, QueryStream},
     },
-    neo4rs,
 };
 use grc20_sdk::models::{self, space, Space as SdkSpace};
 
-use crate::context::KnowledgeGraph;
+use crate::{context::KnowledgeGraph, loader::SpaceTypesKey};
 
 use super::{entity_order_by::OrderDirection, Account, Entity, EntityFilter, SchemaType};
 
@@ -42,7 +42,7 @@ impl Space {
     }
 
     pub async fn from_entity(
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         entity: mapping::Entity<SdkSpace>,
         version: Option<String>,
     ) -> Result<Self, DatabaseError> {
@@ -64,7 +64,7 @@ impl Space {
     }
 
     pub async fn load(
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         id: impl Into<String>,
         version: Option<String>,
     ) -> Result<Option<Self>, DatabaseError> {
@@ -304,15 +304,30 @@ impl Space {
         #[graphql(default = 0)] skip: i32,
         #[graphql(default = true)] strict: bool,
     ) -> FieldResult<Vec<SchemaType>> {
-        let types = models::space::types(&executor.context().neo4j, self.entity.id())
-            .strict(strict)
-            .limit(first as usize)
-            .skip(skip as usize)
-            .send()
-            .await?;
+        let mut space_ids = vec![self.entity.id().to_string()];
+        if !strict {
+            space_ids.extend(
+                self.parent_spaces
+                    .iter()
+                    .map(|ranking| ranking.space_id.clone()),
+            );
+        }
+
+        let types = executor
+            .context()
+            .loaders
+            .space_types
+            .load(SpaceTypesKey {
+                space_ids,
+                first: first as usize,
+                skip: skip as usize,
+            })
+            .await?
+            .unwrap_or_default();
 
         Ok(types
-            .map_ok(|node| {
+            .into_iter()
+            .map(|node| {
                 SchemaType::with_hierarchy(
                     node,
                     self.entity.id().to_string(),
@@ -322,8 +337,7 @@ impl Space {
                     strict,
                 )
             })
-            .try_collect()
-            .await?)
+            .collect())
     }
 
     async fn r#type<'a, S: ScalarValue>(
@@ -0,0 +1,437 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+};
+
+use grc20_core::mapping::ValueType;
+use heck::{ToShoutySnakeCase, ToSnakeCase, ToUpperCamelCase};
+
+use crate::schema::{PropertyKind, PropertySchema, SpaceSchema};
+
+/// Keywords which cannot be used as identifiers
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Names used by the code generated by the `entity` macro
+const RESERVED_FIELDS: &[&str] = &[
+    "id",
+    "limit",
+    "neo4j",
+    "new",
+    "order_by",
+    "send",
+    "skip",
+    "space_id",
+    "version",
+    "version_opt",
+];
+
+const RESERVED_STRUCTS: &[&str] = &["FindManyQuery", "FindOneQuery", "Self"];
+
+/// Generates the Rust code of the models of a space schema:
+/// - an `ids` module with the IDs of the types and properties (e.g.: `PERSON_TYPE`,
+///   `NAME_ATTRIBUTE`, `WORKS_AT_RELATION`)
+/// - one module per type containing a `#[grc20_core::entity]` struct (e.g.: `person::Person`),
+///   re-exported at the top level
+/// - a `relations` module with one relation marker struct per relation property
+pub fn generate(schema: &SpaceSchema) -> String {
+    let names = Names::new(schema);
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        "// This file is generated by grc20-codegen from the schema of space {}. Do not edit manually.",
+        schema.space_id
+    )
+    .unwrap();
+
+    // IDs
+    writeln!(out).unwrap();
+    writeln!(out, "pub mod ids {{").unwrap();
+    writeln!(out, "    // Types").unwrap();
+    for type_ in &schema.types {
+        writeln!(
+            out,
+            "    pub const {}: &str = {:?};",
+            names.type_consts[&type_.id], type_.id
+        )
+        .unwrap();
+    }
+    if !names.properties.is_empty() {
+        writeln!(out).unwrap();
+        writeln!(out, "    // Properties").unwrap();
+    }
+    for property in &names.properties {
+        writeln!(
+            out,
+            "    pub const {}: &str = {:?};",
+            names.property_consts[&property.id], property.id
+        )
+        .unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+
+    // Entities
+    for (type_, (struct_name, module_name)) in schema.types.iter().zip(&names.structs) {
+        let mut field_names = UniqueNames::new(RESERVED_FIELDS, "_");
+
+        writeln!(out).unwrap();
+        writeln!(out, "pub mod {module_name} {{").unwrap();
+        writeln!(out, "    use super::ids;").unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "    /// {}", doc(type_.name.as_deref(), &type_.id)).unwrap();
+        writeln!(out, "    #[grc20_core::entity]").unwrap();
+        writeln!(
+            out,
+            "    #[grc20(schema_type = ids::{})]",
+            names.type_consts[&type_.id]
+        )
+        .unwrap();
+
+        if type_.properties.is_empty() {
+            writeln!(out, "    pub struct {struct_name} {{}}").unwrap();
+        } else {
+            writeln!(out, "    pub struct {struct_name} {{").unwrap();
+            for (idx, property) in type_.properties.iter().enumerate() {
+                let field_name = field_names.insert(snake_case_ident(
+                    property.name.as_deref(),
+                    "property",
+                    &property.id,
+                ));
+                let property_const = &names.property_consts[&property.id];
+
+                if idx > 0 {
+                    writeln!(out).unwrap();
+                }
+                writeln!(
+                    out,
+                    "        /// {}",
+                    doc(property.name.as_deref(), &property.id)
+                )
+                .unwrap();
+                match &property.kind {
+                    PropertyKind::Attribute { value_type } => {
+                        writeln!(out, "        #[grc20(attribute = ids::{property_const})]")
+                            .unwrap();
+                        writeln!(
+                            out,
+                            "        pub {field_name}: Option<{}>,",
+                            rust_type(value_type.as_ref())
+                        )
+                        .unwrap();
+                    }
+                    PropertyKind::Relation { .. } => {
+                        writeln!(out, "        #[grc20(relation = ids::{property_const})]")
+                            .unwrap();
+                        writeln!(
+                            out,
+                            "        pub {field_name}: Vec<grc20_core::mapping::EntityNodeRef>,"
+                        )
+                        .unwrap();
+                    }
+                }
+            }
+            writeln!(out, "    }}").unwrap();
+        }
+        writeln!(out, "}}").unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "pub use {module_name}::{struct_name};").unwrap();
+    }
+
+    // Relation markers
+    let relations = names
+        .properties
+        .iter()
+        .filter(|property| matches!(property.kind, PropertyKind::Relation { .. }))
+        .collect::<Vec<_>>();
+
+    if !relations.is_empty() {
+        writeln!(out).unwrap();
+        writeln!(out, "pub mod relations {{").unwrap();
+        writeln!(out, "    use super::ids;").unwrap();
+        for property in relations {
+            let marker = &names.relation_markers[&property.id];
+            let property_const = &names.property_consts[&property.id];
+
+            writeln!(out).unwrap();
+            writeln!(
+                out,
+                "    /// {}",
+                doc(property.name.as_deref(), &property.id)
+            )
+            .unwrap();
+            if let PropertyKind::Relation {
+                target_type: Some(target_type),
+            } = &property.kind
+            {
+                writeln!(out, "    ///").unwrap();
+                match names.type_consts.get(target_type) {
+                    Some(type_const) => {
+                        writeln!(out, "    /// Target type: [`ids::{type_const}`]").unwrap()
+                    }
+                    None => writeln!(out, "    /// Target type: `{target_type}`").unwrap(),
+                }
+            }
+            writeln!(out, "    #[grc20_core::relation]").unwrap();
+            writeln!(out, "    #[grc20(relation_type = ids::{property_const})]").unwrap();
+            writeln!(out, "    pub struct {marker} {{}}").unwrap();
+            writeln!(out).unwrap();
+            writeln!(out, "    impl {marker} {{").unwrap();
+            writeln!(
+                out,
+                "        pub const RELATION_TYPE: &'static str = ids::{property_const};"
+            )
+            .unwrap();
+            writeln!(out).unwrap();
+            writeln!(out, "        pub fn new(").unwrap();
+            writeln!(out, "            id: impl Into<String>,").unwrap();
+            writeln!(out, "            from: impl Into<String>,").unwrap();
+            writeln!(out, "            to: impl Into<String>,").unwrap();
+            writeln!(
+                out,
+                "            index: impl Into<grc20_core::mapping::Value>,"
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "        ) -> grc20_core::mapping::Relation<Self, grc20_core::mapping::EntityNodeRef> {{"
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "            grc20_core::mapping::Relation::new(id, from, to, Self::RELATION_TYPE, index, Self {{}})"
+            )
+            .unwrap();
+            writeln!(out, "        }}").unwrap();
+            writeln!(out, "    }}").unwrap();
+        }
+        writeln!(out, "}}").unwrap();
+    }
+
+    out
+}
+
+/// Identifiers of the generated items
+struct Names<'a> {
+    /// Properties of all types, deduplicated by ID
+    properties: Vec<&'a PropertySchema>,
+    type_consts: HashMap<String, String>,
+    property_consts: HashMap<String, String>,
+    /// Struct and module names of the types
+    structs: Vec<(String, String)>,
+    relation_markers: HashMap<String, String>,
+}
+
+impl<'a> Names<'a> {
+    fn new(schema: &'a SpaceSchema) -> Self {
+        let mut consts = UniqueNames::new(&[], "_");
+        let mut structs = UniqueNames::new(RESERVED_STRUCTS, "");
+        let mut modules = UniqueNames::new(&["ids", "relations"], "_");
+        let mut markers = UniqueNames::new(&[], "");
+
+        let mut names = Names {
+            properties: vec![],
+            type_consts: HashMap::new(),
+            property_consts: HashMap::new(),
+            structs: vec![],
+            relation_markers: HashMap::new(),
+        };
+
+        for type_ in &schema.types {
+            let name = ident_words(type_.name.as_deref(), "type", &type_.id);
+            names.type_consts.insert(
+                type_.id.clone(),
+                consts.insert(format!("{}_TYPE", name.to_shouty_snake_case())),
+            );
+
+            let struct_name = structs.insert(name.to_upper_camel_case());
+            let module_name = modules.insert(snake_case_ident(Some(&struct_name), "type", ""));
+            names.structs.push((struct_name, module_name));
+        }
+
+        let mut seen = HashSet::new();
+        for property in schema.types.iter().flat_map(|type_| &type_.properties) {
+            if !seen.insert(&property.id) {
+                continue;
+            }
+
+            let name = ident_words(property.name.as_deref(), "property", &property.id);
+            let suffix = match property.kind {
+                PropertyKind::Attribute { .. } => "ATTRIBUTE",
+                PropertyKind::Relation { .. } => {
+                    names.relation_markers.insert(
+                        property.id.clone(),
+                        markers.insert(name.to_upper_camel_case()),
+                    );
+                    "RELATION"
+                }
+            };
+            names.property_consts.insert(
+                property.id.clone(),
+                consts.insert(format!("{}_{suffix}", name.to_shouty_snake_case())),
+            );
+            names.properties.push(property);
+        }
+
+        names
+    }
+}
+
+/// Set of identifiers in which a numeric suffix is added to duplicate identifiers
+struct UniqueNames {
+    names: HashSet<String>,
+    separator: &'static str,
+}
+
+impl UniqueNames {
+    fn new(reserved: &[&str], separator: &'static str) -> Self {
+        Self {
+            names: reserved.iter().map(|name| name.to_string()).collect(),
+            separator,
+        }
+    }
+
+    fn insert(&mut self, name: String) -> String {
+        let mut unique = name.clone();
+        let mut idx = 2;
+        while self.names.contains(&unique) {
+            unique = format!("{name}{}{idx}", self.separator);
+            idx += 1;
+        }
+        self.names.insert(unique.clone());
+        unique
+    }
+}
+
+/// Returns the words of the identifier of an item named `name`, falling back to the
+/// item ID if the name is missing or has no ASCII alphanumeric characters.
+fn ident_words(name: Option<&str>, kind: &str, id: &str) -> String {
+    let words = name
+        .unwrap_or_default()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { ' ' })
+        .collect::<String>();
+
+    match words.trim() {
+        "" => format!("{kind} {}", id.to_lowercase()),
+        words if words.starts_with(|c: char| c.is_ascii_digit()) => format!("{kind} {words}"),
+        words => words.to_string(),
+    }
+}
+
+fn snake_case_ident(name: Option<&str>, kind: &str, id: &str) -> String {
+    let ident = ident_words(name, kind, id).to_snake_case();
+    if KEYWORDS.contains(&ident.as_str()) || RESERVED_FIELDS.contains(&ident.as_str()) {
+        format!("{ident}_")
+    } else {
+        ident
+    }
+}
+
+fn rust_type(value_type: Option<&ValueType>) -> &'static str {
+    match value_type {
+        Some(ValueType::Number) => "f64",
+        Some(ValueType::Checkbox) => "bool",
+        Some(ValueType::Time) => "grc20_core::chrono::DateTime<grc20_core::chrono::Utc>",
+        Some(ValueType::Text | ValueType::Url | ValueType::Point) | None => "String",
+    }
+}
+
+fn doc(name: Option<&str>, id: &str) -> String {
+    match name {
+        Some(name) => format!("{name} (`{id}`)"),
+        None => format!("`{id}`"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::TypeSchema;
+
+    fn property(id: &str, name: Option<&str>, kind: PropertyKind) -> PropertySchema {
+        PropertySchema {
+            id: id.to_string(),
+            name: name.map(str::to_string),
+            kind,
+        }
+    }
+
+    #[test]
+    fn test_ident_words() {
+        assert_eq!(
+            ident_words(Some("Date of birth"), "property", "x"),
+            "Date of birth"
+        );
+        assert_eq!(ident_words(Some("3D model"), "type", "x"), "type 3D model");
+        assert_eq!(ident_words(Some("日本"), "type", "AbC"), "type abc");
+        assert_eq!(ident_words(None, "property", "AbC"), "property abc");
+    }
+
+    #[test]
+    fn test_snake_case_ident() {
+        assert_eq!(snake_case_ident(Some("Type"), "property", "x"), "type_");
+        assert_eq!(snake_case_ident(Some("ID"), "property", "x"), "id_");
+        assert_eq!(
+            snake_case_ident(Some("Works at"), "property", "x"),
+            "works_at"
+        );
+    }
+
+    #[test]
+    fn test_duplicate_names() {
+        let schema = SpaceSchema {
+            space_id: "space".to_string(),
+            types: vec![
+                TypeSchema {
+                    id: "person_1".to_string(),
+                    name: Some("Person".to_string()),
+                    properties: vec![
+                        property(
+                            "name",
+                            Some("Name"),
+                            PropertyKind::Attribute { value_type: None },
+                        ),
+                        property(
+                            "other_name",
+                            Some("Name"),
+                            PropertyKind::Attribute { value_type: None },
+                        ),
+                    ],
+                },
+                TypeSchema {
+                    id: "person_2".to_string(),
+                    name: Some("Person".to_string()),
+                    properties: vec![property(
+                        "name",
+                        Some("Name"),
+                        PropertyKind::Attribute { value_type: None },
+                    )],
+                },
+            ],
+        };
+
+        let names = Names::new(&schema);
+        assert_eq!(names.type_consts["person_1"], "PERSON_TYPE");
+        assert_eq!(names.type_consts["person_2"], "PERSON_TYPE_2");
+        assert_eq!(names.property_consts["name"], "NAME_ATTRIBUTE");
+        assert_eq!(names.property_consts["other_name"], "NAME_ATTRIBUTE_2");
+        assert_eq!(names.properties.len(), 2);
+        assert_eq!(
+            names.structs,
+            vec![
+                ("Person".to_string(), "person".to_string()),
+                ("Person2".to_string(), "person2".to_string())
+            ]
+        );
+
+        let code = generate(&schema);
+        assert!(code.contains("pub name: Option<String>,"));
+        assert!(code.contains("pub name_2: Option<String>,"));
+    }
+}
@@ -0,0 +1,84 @@
+//! Generates Rust models (`#[grc20_core::entity]` structs, relation markers and ID constants)
+//! from the schema of a space.
+//!
+//! The schema is either loaded from the knowledge graph with [`load_schema`] or read from a
+//! schema file exported with the `grc20-codegen export` command. Models can be regenerated
+//! with the `grc20-codegen generate` command or from a build script:
+//!
+//! ```rust,ignore
+//! // build.rs
+//! fn main() {
+//!     grc20_codegen::Builder::new("schema/space.json")
+//!         .generate()
+//!         .expect("Failed to generate models");
+//! }
+//!
+//! // src/models.rs
+//! include!(concat!(env!("OUT_DIR"), "/grc20_models.rs"));
+//! ```
+//!
+//! The generated code depends on the `grc20-core` crate.
+use std::path::{Path, PathBuf};
+
+pub mod generator;
+pub mod loader;
+pub mod schema;
+
+pub use generator::generate;
+pub use loader::load_schema;
+pub use schema::{PropertyKind, PropertySchema, SpaceSchema, TypeSchema};
+
+#[derive(Debug, thiserror::Error)]
+pub enum CodegenError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid schema file: {0}")]
+    InvalidSchema(#[from] serde_json::Error),
+    #[error("Database error: {0}")]
+    Database(#[from] grc20_core::error::DatabaseError),
+    #[error("OUT_DIR not set, the output file must be set explicitly")]
+    MissingOutDir,
+}
+
+/// Generates models from a schema file, e.g.: in a build script
+pub struct Builder {
+    schema_file: PathBuf,
+    out_file: Option<PathBuf>,
+}
+
+impl Builder {
+    pub fn new(schema_file: impl Into<PathBuf>) -> Self {
+        Self {
+            schema_file: schema_file.into(),
+            out_file: None,
+        }
+    }
+
+    /// Path of the generated file (default: `$OUT_DIR/grc20_models.rs`)
+    pub fn out_file(mut self, out_file: impl Into<PathBuf>) -> Self {
+        self.out_file = Some(out_file.into());
+        self
+    }
+
+    /// Generates the models and writes them to the output file. When run from a build
+    /// script, the build script is rerun whenever the schema file changes.
+    pub fn generate(self) -> Result<PathBuf, CodegenError> {
+        let out_dir = std::env::var_os("OUT_DIR");
+
+        let out_file = match (self.out_file, &out_dir) {
+            (Some(out_file), _) => out_file,
+            (None, Some(out_dir)) => Path::new(out_dir).join("grc20_models.rs"),
+            (None, None) => return Err(CodegenError::MissingOutDir),
+        };
+
+        // OUT_DIR is only set when running a build script
+        if out_dir.is_some() {
+            println!("cargo:rerun-if-changed={}", self.schema_file.display());
+        }
+
+        let schema = SpaceSchema::from_json(&std::fs::read_to_string(&self.schema_file)?)?;
+        std::fs::write(&out_file, generate(&schema))?;
+
+        Ok(out_file)
+    }
+}
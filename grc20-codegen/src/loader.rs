@@ -0,0 +1,151 @@
+use futures::{pin_mut, StreamExt, TryStreamExt};
+use grc20_core::{
+    connection::Graph,
+    error::DatabaseError,
+    mapping::{entity::EntityNodeRef, query_utils::QueryStream, RelationEdge, ValueType},
+    system_ids,
+};
+use grc20_sdk::models::{property, space};
+
+use crate::schema::{PropertyKind, PropertySchema, SpaceSchema, TypeSchema};
+
+/// Loads the schema of the space `space_id` (including the types inherited from
+/// its parent spaces) from the knowledge graph.
+pub async fn load_schema(neo4j: &Graph, space_id: &str) -> Result<SpaceSchema, DatabaseError> {
+    let types = space::types(neo4j, space_id).strict(false).send().await?;
+
+    pin_mut!(types);
+
+    let mut schema = SpaceSchema {
+        space_id: space_id.to_string(),
+        types: vec![],
+    };
+
+    while let Some(type_) = types.next().await {
+        let type_ = type_?;
+
+        let properties = property::get_outbound_relations::<RelationEdge<EntityNodeRef>>(
+            neo4j,
+            system_ids::PROPERTIES,
+            &type_.id,
+            space_id,
+            None,
+            None,
+            None,
+            false,
+        )
+        .await?
+        .send()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+
+        let mut type_schema = TypeSchema {
+            name: name(neo4j, &type_.id, space_id).await?,
+            id: type_.id,
+            properties: vec![],
+        };
+
+        for property in properties {
+            type_schema
+                .properties
+                .push(load_property(neo4j, &property.to.0, space_id).await?);
+        }
+
+        schema.types.push(type_schema);
+    }
+
+    schema.types.sort_by(|a, b| a.id.cmp(&b.id));
+
+    Ok(schema)
+}
+
+async fn load_property(
+    neo4j: &Graph,
+    property_id: &str,
+    space_id: &str,
+) -> Result<PropertySchema, DatabaseError> {
+    let value_type = first_target(
+        neo4j,
+        system_ids::VALUE_TYPE_ATTRIBUTE,
+        property_id,
+        space_id,
+    )
+    .await?;
+
+    let kind = match value_type.as_deref() {
+        Some(system_ids::RELATION_SCHEMA_TYPE) => PropertyKind::Relation {
+            target_type: first_target(
+                neo4j,
+                system_ids::RELATION_VALUE_RELATIONSHIP_TYPE,
+                property_id,
+                space_id,
+            )
+            .await?,
+        },
+        value_type => PropertyKind::Attribute {
+            value_type: value_type.and_then(value_type_from_id),
+        },
+    };
+
+    Ok(PropertySchema {
+        id: property_id.to_string(),
+        name: name(neo4j, property_id, space_id).await?,
+        kind,
+    })
+}
+
+async fn name(
+    neo4j: &Graph,
+    entity_id: &str,
+    space_id: &str,
+) -> Result<Option<String>, DatabaseError> {
+    Ok(property::get_triple(
+        neo4j,
+        system_ids::NAME_ATTRIBUTE,
+        entity_id,
+        space_id,
+        None,
+        false,
+    )
+    .await?
+    .map(|triple| triple.value.value))
+}
+
+/// Returns the target of the first relation of type `relation_type` outgoing from `entity_id`
+async fn first_target(
+    neo4j: &Graph,
+    relation_type: &str,
+    entity_id: &str,
+    space_id: &str,
+) -> Result<Option<String>, DatabaseError> {
+    let relations = property::get_outbound_relations::<RelationEdge<EntityNodeRef>>(
+        neo4j,
+        relation_type,
+        entity_id,
+        space_id,
+        None,
+        Some(1),
+        None,
+        false,
+    )
+    .await?
+    .send()
+    .await?
+    .try_collect::<Vec<_>>()
+    .await?;
+
+    Ok(relations.into_iter().next().map(|relation| relation.to.0))
+}
+
+fn value_type_from_id(id: &str) -> Option<ValueType> {
+    match id {
+        system_ids::TEXT => Some(ValueType::Text),
+        system_ids::NUMBER => Some(ValueType::Number),
+        system_ids::CHECKBOX => Some(ValueType::Checkbox),
+        system_ids::URL => Some(ValueType::Url),
+        system_ids::TIME => Some(ValueType::Time),
+        system_ids::POINT => Some(ValueType::Point),
+        _ => None,
+    }
+}
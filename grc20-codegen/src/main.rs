@@ -0,0 +1,99 @@
+use std::path::PathBuf;
+
+use anyhow::Error;
+use clap::{Parser, Subcommand};
+use grc20_codegen::SpaceSchema;
+use grc20_core::connection::Neo4jArgs;
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let args = AppArgs::parse();
+
+    match args.command {
+        Command::Export {
+            neo4j_args,
+            space_id,
+            output,
+        } => {
+            let schema = load_schema(&neo4j_args, &space_id).await?;
+            write_output(output, schema.to_json()?)?;
+        }
+        Command::Generate {
+            schema_file,
+            neo4j_args,
+            space_id,
+            output,
+        } => {
+            let schema = match (schema_file, space_id) {
+                (Some(schema_file), _) => {
+                    SpaceSchema::from_json(&std::fs::read_to_string(schema_file)?)?
+                }
+                (None, Some(space_id)) => match neo4j_args {
+                    Some(neo4j_args) => load_schema(&neo4j_args, &space_id).await?,
+                    None => anyhow::bail!("The Neo4j connection must be set with --space-id"),
+                },
+                (None, None) => anyhow::bail!("Either --schema-file or --space-id must be set"),
+            };
+            write_output(output, grc20_codegen::generate(&schema))?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn load_schema(neo4j_args: &Neo4jArgs, space_id: &str) -> Result<SpaceSchema, Error> {
+    let neo4j = neo4j_args.connect().await?;
+
+    Ok(grc20_codegen::load_schema(&neo4j, space_id).await?)
+}
+
+/// Writes to the output file if set, to stdout otherwise
+fn write_output(output: Option<PathBuf>, content: String) -> Result<(), Error> {
+    match output {
+        Some(output) => std::fs::write(output, content)?,
+        None => print!("{content}"),
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Parser)]
+#[command(name = "grc20-codegen", version, about, arg_required_else_help = true)]
+struct AppArgs {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Export the schema of a space to a JSON schema file
+    Export {
+        #[clap(flatten)]
+        neo4j_args: Neo4jArgs,
+
+        /// ID of the space
+        #[arg(long)]
+        space_id: String,
+
+        /// Output file (default: stdout)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Generate the Rust models of a space from a schema file or from the knowledge graph
+    Generate {
+        /// Schema file exported with the `export` command
+        #[arg(long, conflicts_with_all = ["space_id", "Neo4jArgs"])]
+        schema_file: Option<PathBuf>,
+
+        #[clap(flatten)]
+        neo4j_args: Option<Neo4jArgs>,
+
+        /// ID of the space (the schema is loaded from the knowledge graph)
+        #[arg(long)]
+        space_id: Option<String>,
+
+        /// Output file (default: stdout)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+}
@@ -0,0 +1,55 @@
+use grc20_core::mapping::ValueType;
+use serde::{Deserialize, Serialize};
+
+/// Schema of a space, i.e.: the types defined in the space and their properties.
+/// This is the format of the exported schema files.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct SpaceSchema {
+    pub space_id: String,
+    pub types: Vec<TypeSchema>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct TypeSchema {
+    pub id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub properties: Vec<PropertySchema>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct PropertySchema {
+    pub id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub kind: PropertyKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PropertyKind {
+    /// Property holding a value. Properties which do not declare a value type
+    /// are treated as text properties.
+    Attribute {
+        #[serde(default)]
+        value_type: Option<ValueType>,
+    },
+    /// Property holding relations to other entities, optionally restricted to
+    /// entities of the type `target_type`
+    Relation {
+        #[serde(default)]
+        target_type: Option<String>,
+    },
+}
+
+impl SpaceSchema {
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
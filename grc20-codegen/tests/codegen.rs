@@ -0,0 +1,68 @@
+use grc20_codegen::SpaceSchema;
+use grc20_core::mapping::{EntityNodeRef, IntoAttributes};
+
+// The generated code is compiled as part of the tests
+#[allow(dead_code, unused_imports)]
+#[path = "generated/library.rs"]
+mod library;
+
+#[test]
+fn test_generated_code_is_up_to_date() {
+    let schema = SpaceSchema::from_json(include_str!("schemas/library.json"))
+        .expect("Failed to parse schema");
+
+    // NOTE: Regenerate with `cargo run -p grc20-codegen -- generate --schema-file
+    // grc20-codegen/tests/schemas/library.json -o grc20-codegen/tests/generated/library.rs`
+    assert_eq!(
+        grc20_codegen::generate(&schema),
+        include_str!("generated/library.rs")
+    );
+}
+
+#[test]
+fn test_schema_roundtrip() {
+    let schema = SpaceSchema::from_json(include_str!("schemas/library.json"))
+        .expect("Failed to parse schema");
+
+    assert_eq!(
+        SpaceSchema::from_json(&schema.to_json().expect("Failed to serialize schema"))
+            .expect("Failed to parse schema"),
+        schema
+    );
+}
+
+#[test]
+fn test_generated_models() {
+    assert_eq!(library::Book::SCHEMA_TYPE, library::ids::BOOK_TYPE);
+    assert_eq!(
+        library::relations::Authors::RELATION_TYPE,
+        library::ids::AUTHORS_RELATION
+    );
+
+    let book = library::Book {
+        name: Some("Dune".to_string()),
+        page_count: Some(412.0),
+        publication_date: None,
+        website: None,
+        available: Some(true),
+        type_: None,
+        authors: vec![EntityNodeRef("frank_herbert".to_string())],
+        genres: vec![],
+    };
+
+    let attrs = book.into_attributes().expect("Failed to convert book");
+    assert_eq!(
+        attrs.get::<String>(library::ids::NAME_ATTRIBUTE).unwrap(),
+        "Dune"
+    );
+    assert_eq!(
+        attrs
+            .get::<f64>(library::ids::PAGE_COUNT_ATTRIBUTE)
+            .unwrap(),
+        412.0
+    );
+    assert!(attrs
+        .get_opt::<String>(library::ids::WEBSITE_ATTRIBUTE)
+        .unwrap()
+        .is_none());
+}
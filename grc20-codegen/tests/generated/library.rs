@@ -0,0 +1,139 @@
+// This file is generated by grc20-codegen from the schema of space LibrarySpace. Do not edit manually.
+
+pub mod ids {
+    // Types
+    pub const BOOK_TYPE: &str = "BookType";
+    pub const PERSON_TYPE: &str = "PersonType";
+    pub const GENRE_TYPE: &str = "GenreType";
+
+    // Properties
+    pub const NAME_ATTRIBUTE: &str = "NameAttr";
+    pub const PAGE_COUNT_ATTRIBUTE: &str = "PagesAttr";
+    pub const PUBLICATION_DATE_ATTRIBUTE: &str = "PublishedAttr";
+    pub const WEBSITE_ATTRIBUTE: &str = "WebsiteAttr";
+    pub const AVAILABLE_ATTRIBUTE: &str = "AvailableAttr";
+    pub const TYPE_ATTRIBUTE: &str = "FormatAttr";
+    pub const AUTHORS_RELATION: &str = "AuthorsRel";
+    pub const GENRES_RELATION: &str = "GenresRel";
+    pub const ID_ATTRIBUTE: &str = "IdAttr";
+    pub const DATE_OF_BIRTH_ATTRIBUTE: &str = "BirthAttr";
+}
+
+pub mod book {
+    use super::ids;
+
+    /// Book (`BookType`)
+    #[grc20_core::entity]
+    #[grc20(schema_type = ids::BOOK_TYPE)]
+    pub struct Book {
+        /// Name (`NameAttr`)
+        #[grc20(attribute = ids::NAME_ATTRIBUTE)]
+        pub name: Option<String>,
+
+        /// Page count (`PagesAttr`)
+        #[grc20(attribute = ids::PAGE_COUNT_ATTRIBUTE)]
+        pub page_count: Option<f64>,
+
+        /// Publication date (`PublishedAttr`)
+        #[grc20(attribute = ids::PUBLICATION_DATE_ATTRIBUTE)]
+        pub publication_date: Option<grc20_core::chrono::DateTime<grc20_core::chrono::Utc>>,
+
+        /// Website (`WebsiteAttr`)
+        #[grc20(attribute = ids::WEBSITE_ATTRIBUTE)]
+        pub website: Option<String>,
+
+        /// Available (`AvailableAttr`)
+        #[grc20(attribute = ids::AVAILABLE_ATTRIBUTE)]
+        pub available: Option<bool>,
+
+        /// Type (`FormatAttr`)
+        #[grc20(attribute = ids::TYPE_ATTRIBUTE)]
+        pub type_: Option<String>,
+
+        /// Authors (`AuthorsRel`)
+        #[grc20(relation = ids::AUTHORS_RELATION)]
+        pub authors: Vec<grc20_core::mapping::EntityNodeRef>,
+
+        /// Genres (`GenresRel`)
+        #[grc20(relation = ids::GENRES_RELATION)]
+        pub genres: Vec<grc20_core::mapping::EntityNodeRef>,
+    }
+}
+
+pub use book::Book;
+
+pub mod person {
+    use super::ids;
+
+    /// Person (`PersonType`)
+    #[grc20_core::entity]
+    #[grc20(schema_type = ids::PERSON_TYPE)]
+    pub struct Person {
+        /// Name (`NameAttr`)
+        #[grc20(attribute = ids::NAME_ATTRIBUTE)]
+        pub name: Option<String>,
+
+        /// ID (`IdAttr`)
+        #[grc20(attribute = ids::ID_ATTRIBUTE)]
+        pub id_: Option<String>,
+
+        /// Date of birth (`BirthAttr`)
+        #[grc20(attribute = ids::DATE_OF_BIRTH_ATTRIBUTE)]
+        pub date_of_birth: Option<grc20_core::chrono::DateTime<grc20_core::chrono::Utc>>,
+    }
+}
+
+pub use person::Person;
+
+pub mod genre {
+    use super::ids;
+
+    /// Genre (`GenreType`)
+    #[grc20_core::entity]
+    #[grc20(schema_type = ids::GENRE_TYPE)]
+    pub struct Genre {}
+}
+
+pub use genre::Genre;
+
+pub mod relations {
+    use super::ids;
+
+    /// Authors (`AuthorsRel`)
+    ///
+    /// Target type: [`ids::PERSON_TYPE`]
+    #[grc20_core::relation]
+    #[grc20(relation_type = ids::AUTHORS_RELATION)]
+    pub struct Authors {}
+
+    impl Authors {
+        pub const RELATION_TYPE: &'static str = ids::AUTHORS_RELATION;
+
+        pub fn new(
+            id: impl Into<String>,
+            from: impl Into<String>,
+            to: impl Into<String>,
+            index: impl Into<grc20_core::mapping::Value>,
+        ) -> grc20_core::mapping::Relation<Self, grc20_core::mapping::EntityNodeRef> {
+            grc20_core::mapping::Relation::new(id, from, to, Self::RELATION_TYPE, index, Self {})
+        }
+    }
+
+    /// Genres (`GenresRel`)
+    #[grc20_core::relation]
+    #[grc20(relation_type = ids::GENRES_RELATION)]
+    pub struct Genres {}
+
+    impl Genres {
+        pub const RELATION_TYPE: &'static str = ids::GENRES_RELATION;
+
+        pub fn new(
+            id: impl Into<String>,
+            from: impl Into<String>,
+            to: impl Into<String>,
+            index: impl Into<grc20_core::mapping::Value>,
+        ) -> grc20_core::mapping::Relation<Self, grc20_core::mapping::EntityNodeRef> {
+            grc20_core::mapping::Relation::new(id, from, to, Self::RELATION_TYPE, index, Self {})
+        }
+    }
+}
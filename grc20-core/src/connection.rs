@@ -0,0 +1,413 @@
+//! Neo4j connection configuration shared by the sink, the API and the MCP server.
+//!
+//! ```rust,ignore
+//! use grc20_core::connection::{Neo4jConfig, RetryPolicy};
+//!
+//! let neo4j = Neo4jConfig::new("neo4j+s://localhost:7687", "neo4j", "password")
+//!     .database("neo4j")
+//!     .max_connections(32)
+//!     .execute_timeout(Duration::from_secs(30))
+//!     .retry_policy(RetryPolicy::default().max_retries(5))
+//!     .connect()
+//!     .await?;
+//! ```
+//!
+//! The retry policy and execute timeout are stored with the returned [`Graph`] and apply
+//! to all the queries of the [`mapping`](crate::mapping) module, which are sent with
+//! [`with_query_policy`].
+use std::{future::Future, ops::Deref, path::PathBuf, time::Duration};
+
+use neo4rs::Neo4jErrorKind;
+
+use crate::error::DatabaseError;
+
+/// Neo4j connection along with the [`QueryPolicy`] of its queries. Dereferences to the
+/// underlying [`neo4rs::Graph`]. Cloning the graph shares the connection pool.
+#[derive(Clone)]
+pub struct Graph {
+    inner: neo4rs::Graph,
+    policy: QueryPolicy,
+}
+
+impl Graph {
+    /// Connects to the database with the default configuration and query policy
+    pub async fn new(
+        uri: impl Into<String>,
+        user: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Result<Self, neo4rs::Error> {
+        Ok(neo4rs::Graph::new(uri, user, password).await?.into())
+    }
+
+    pub fn with_policy(mut self, policy: QueryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn policy(&self) -> QueryPolicy {
+        self.policy
+    }
+}
+
+impl From<neo4rs::Graph> for Graph {
+    fn from(inner: neo4rs::Graph) -> Self {
+        Self {
+            inner,
+            policy: QueryPolicy::default(),
+        }
+    }
+}
+
+impl Deref for Graph {
+    type Target = neo4rs::Graph;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+/// Builder for Neo4j connections. TLS is enabled with the `neo4j+s` and `bolt+s`
+/// URI schemes (or `neo4j+ssc` and `bolt+ssc` to accept self-signed certificates).
+#[derive(Clone, Debug)]
+pub struct Neo4jConfig {
+    uri: String,
+    user: String,
+    password: String,
+    database: Option<String>,
+    max_connections: Option<usize>,
+    fetch_size: Option<usize>,
+    tls_ca_certificate: Option<PathBuf>,
+    policy: QueryPolicy,
+}
+
+impl Neo4jConfig {
+    pub fn new(
+        uri: impl Into<String>,
+        user: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        Self {
+            uri: uri.into(),
+            user: user.into(),
+            password: password.into(),
+            database: None,
+            max_connections: None,
+            fetch_size: None,
+            tls_ca_certificate: None,
+            policy: QueryPolicy::default(),
+        }
+    }
+
+    /// Name of the database (default: `neo4j`)
+    pub fn database(mut self, database: impl Into<String>) -> Self {
+        self.database = Some(database.into());
+        self
+    }
+
+    /// Size of the connection pool (default: 16)
+    pub fn max_connections(mut self, max_connections: usize) -> Self {
+        self.max_connections = Some(max_connections);
+        self
+    }
+
+    /// Number of rows fetched from the server in one request (default: 200)
+    pub fn fetch_size(mut self, fetch_size: usize) -> Self {
+        self.fetch_size = Some(fetch_size);
+        self
+    }
+
+    /// CA certificate used to verify the server certificate when TLS is enabled
+    pub fn tls_ca_certificate(mut self, path: impl Into<PathBuf>) -> Self {
+        self.tls_ca_certificate = Some(path.into());
+        self
+    }
+
+    pub fn retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.policy.retry = retry;
+        self
+    }
+
+    /// Maximum duration of the execution of a query attempt, i.e.: until the first
+    /// rows are received. The consumption of the rows is not bounded (default: no
+    /// timeout).
+    pub fn execute_timeout(mut self, timeout: Duration) -> Self {
+        self.policy.execute_timeout = Some(timeout);
+        self
+    }
+
+    pub fn execute_timeout_opt(mut self, timeout: Option<Duration>) -> Self {
+        self.policy.execute_timeout = timeout;
+        self
+    }
+
+    /// Connects to the database. The retry policy and query timeout of the configuration
+    /// apply to the queries sent through the returned graph.
+    pub async fn connect(self) -> Result<Graph, DatabaseError> {
+        let mut builder = neo4rs::ConfigBuilder::new()
+            .uri(self.uri)
+            .user(self.user)
+            .password(self.password);
+
+        if let Some(database) = self.database {
+            builder = builder.db(database);
+        }
+        if let Some(max_connections) = self.max_connections {
+            builder = builder.max_connections(max_connections);
+        }
+        if let Some(fetch_size) = self.fetch_size {
+            builder = builder.fetch_size(fetch_size);
+        }
+        if let Some(tls_ca_certificate) = self.tls_ca_certificate {
+            builder = builder.with_client_certificate(tls_ca_certificate);
+        }
+
+        let neo4j = neo4rs::Graph::connect(builder.build()?).await?;
+
+        Ok(Graph::from(neo4j).with_policy(self.policy))
+    }
+}
+
+/// Command line arguments of the Neo4j connection, shared by the binaries
+#[cfg(feature = "clap")]
+#[derive(Clone, Debug, clap::Args)]
+pub struct Neo4jArgs {
+    /// Neo4j database host
+    #[arg(long, env = "NEO4J_URI")]
+    pub neo4j_uri: String,
+
+    /// Neo4j database user name
+    #[arg(long, env = "NEO4J_USER")]
+    pub neo4j_user: String,
+
+    /// Neo4j database user password
+    #[arg(long, env = "NEO4J_PASS")]
+    pub neo4j_pass: String,
+
+    /// Neo4j database name (default: neo4j)
+    #[arg(long)]
+    pub neo4j_database: Option<String>,
+
+    /// Maximum number of connections in the Neo4j connection pool (default: 16)
+    #[arg(long)]
+    pub neo4j_max_connections: Option<usize>,
+
+    /// Number of rows fetched from Neo4j in one request (default: 200)
+    #[arg(long)]
+    pub neo4j_fetch_size: Option<usize>,
+
+    /// CA certificate of the Neo4j server (TLS is enabled with the neo4j+s:// scheme)
+    #[arg(long)]
+    pub neo4j_tls_ca_cert: Option<PathBuf>,
+
+    /// Timeout in seconds of the execution of Neo4j queries, the consumption of their
+    /// results is not bounded (default: no timeout)
+    #[arg(long)]
+    pub neo4j_execute_timeout: Option<u64>,
+
+    /// Maximum number of retries of Neo4j queries failing with a transient error
+    /// (default: 3)
+    #[arg(long)]
+    pub neo4j_max_retries: Option<u32>,
+}
+
+#[cfg(feature = "clap")]
+impl Neo4jArgs {
+    pub fn config(&self) -> Neo4jConfig {
+        let mut config = Neo4jConfig::new(&self.neo4j_uri, &self.neo4j_user, &self.neo4j_pass)
+            .execute_timeout_opt(self.neo4j_execute_timeout.map(Duration::from_secs));
+
+        if let Some(database) = &self.neo4j_database {
+            config = config.database(database);
+        }
+        if let Some(max_connections) = self.neo4j_max_connections {
+            config = config.max_connections(max_connections);
+        }
+        if let Some(fetch_size) = self.neo4j_fetch_size {
+            config = config.fetch_size(fetch_size);
+        }
+        if let Some(tls_ca_cert) = &self.neo4j_tls_ca_cert {
+            config = config.tls_ca_certificate(tls_ca_cert);
+        }
+        if let Some(max_retries) = self.neo4j_max_retries {
+            config = config.retry_policy(RetryPolicy::default().max_retries(max_retries));
+        }
+
+        config
+    }
+
+    pub async fn connect(&self) -> Result<Graph, DatabaseError> {
+        self.config().connect().await
+    }
+}
+
+/// Retry policy for transient errors (see [`is_transient`]). Retries are delayed
+/// with an exponential backoff.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    const DEFAULT: Self = Self {
+        max_retries: 3,
+        initial_backoff: Duration::from_millis(100),
+        max_backoff: Duration::from_secs(5),
+    };
+
+    /// Policy which never retries
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::DEFAULT
+        }
+    }
+
+    /// Maximum number of retries (default: 3)
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Delay before the first retry (default: 100ms)
+    pub fn initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// Maximum delay between two retries (default: 5s)
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Delay before the retry number `retry` (starting at 0)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Retry policy and execute timeout applied to the queries sent with [`with_query_policy`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QueryPolicy {
+    pub retry: RetryPolicy,
+    pub execute_timeout: Option<Duration>,
+}
+
+/// Returns true if the error is transient, i.e.: the query may succeed if retried
+/// (e.g.: deadlocks, leader switches or connection resets).
+pub fn is_transient(error: &DatabaseError) -> bool {
+    match error {
+        DatabaseError::Neo4jError(neo4rs::Error::Neo4j(error)) => matches!(
+            error.kind(),
+            Neo4jErrorKind::Transient
+                | Neo4jErrorKind::Client(neo4rs::Neo4jClientErrorKind::SessionExpired)
+        ),
+        DatabaseError::Neo4jError(neo4rs::Error::IOError { .. })
+        | DatabaseError::Neo4jError(neo4rs::Error::ConnectionError) => true,
+        _ => false,
+    }
+}
+
+/// Sends a query with the [`QueryPolicy`] of the graph: each attempt is bounded by the
+/// execute timeout of the policy and transient errors are retried. Timeouts are not retried since
+/// the query may have been applied.
+///
+/// ```rust,ignore
+/// let stream = with_query_policy(&neo4j, || neo4j.execute(query.clone())).await?;
+/// ```
+///
+/// NOTE: The timeout only applies to the execution of the query, not to the
+/// consumption of the returned stream (hence its name).
+pub async fn with_query_policy<T, F, Fut>(neo4j: &Graph, attempt: F) -> Result<T, DatabaseError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, neo4rs::Error>>,
+{
+    with_policy(neo4j.policy(), attempt).await
+}
+
+async fn with_policy<T, F, Fut>(policy: QueryPolicy, mut attempt: F) -> Result<T, DatabaseError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, neo4rs::Error>>,
+{
+    let mut retry = 0;
+
+    loop {
+        let result = match policy.execute_timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, attempt()).await {
+                Ok(result) => result.map_err(DatabaseError::from),
+                Err(_) => Err(DatabaseError::Timeout(timeout)),
+            },
+            None => attempt().await.map_err(DatabaseError::from),
+        };
+
+        match result {
+            Err(error) if retry < policy.retry.max_retries && is_transient(&error) => {
+                let backoff = policy.retry.backoff(retry);
+                tracing::warn!("Retrying query in {backoff:?} due to transient error: {error}");
+                tokio::time::sleep(backoff).await;
+                retry += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::default()
+            .initial_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_secs(1));
+
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(400));
+        assert_eq!(policy.backoff(4), Duration::from_secs(1));
+        assert_eq!(policy.backoff(100), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_is_transient() {
+        assert!(is_transient(&DatabaseError::Neo4jError(
+            neo4rs::Error::ConnectionError
+        )));
+        assert!(!is_transient(&DatabaseError::Timeout(Duration::from_secs(
+            1
+        ))));
+        assert!(!is_transient(&DatabaseError::NotFound("abc".into())));
+    }
+
+    #[tokio::test]
+    async fn test_retry_transient_errors() {
+        let mut attempts = 0;
+
+        let result = with_policy(QueryPolicy::default(), || {
+            attempts += 1;
+            let attempt = attempts;
+            async move {
+                if attempt < 3 {
+                    Err(neo4rs::Error::ConnectionError)
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), 3);
+    }
+}
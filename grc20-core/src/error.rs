@@ -16,4 +16,10 @@ pub enum DatabaseError {
     Infaillible(#[from] std::convert::Infallible),
     #[error("Not found: {0}")]
     NotFound(String),
+    #[error("Invalid merge: {0}")]
+    InvalidMerge(String),
+    #[error("Query execution timed out after {0:?}")]
+    Timeout(std::time::Duration),
+    #[error("Fractional index error: {0}")]
+    FractionalIndex(#[from] mapping::relation::fractional_index::FractionalIndexError),
 }
@@ -74,3 +74,18 @@ pub const CURSOR_ATTRIBUTE: &str = "cursor-attribute";
 pub const BLOCK_NUMBER_ATTRIBUTE: &str = "block-number-attribute";
 pub const BLOCK_TIMESTAMP_ATTRIBUTE: &str = "block-timestamp-attribute";
 pub const VERSION_ATTRIBUTE: &str = "version-attribute";
+
+// Validation
+/// Validation diagnostic type ID
+pub const VALIDATION_DIAGNOSTIC_TYPE: &str = "NzET9veHduwmfbFFiRj75d";
+pub const DIAGNOSTIC_KIND_ATTRIBUTE: &str = "diagnostic_kind";
+pub const DIAGNOSTIC_SPACE_ATTRIBUTE: &str = "diagnostic_space";
+pub const DIAGNOSTIC_ENTITY_ATTRIBUTE: &str = "diagnostic_entity";
+pub const DIAGNOSTIC_PROPERTY_ATTRIBUTE: &str = "diagnostic_property";
+pub const DIAGNOSTIC_EXPECTED_ATTRIBUTE: &str = "diagnostic_expected";
+pub const DIAGNOSTIC_ACTUAL_ATTRIBUTE: &str = "diagnostic_actual";
+pub const DIAGNOSTIC_REJECTED_ATTRIBUTE: &str = "diagnostic_rejected";
+
+// Merges
+/// MERGED_ENTITY > REDIRECTS_TO > CANONICAL_ENTITY
+pub const REDIRECTS_TO: &str = "7pKeRfMWqGe2dRwHAoTzWA";
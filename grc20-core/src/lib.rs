@@ -1,10 +1,12 @@
 pub mod block;
+pub mod connection;
 pub mod error;
 pub mod graph_uri;
 pub mod ids;
 pub mod mapping;
 pub mod neo4j_utils;
 pub mod pb;
+pub mod storage;
 
 pub use ids::indexer_ids;
 pub use ids::network_ids;
@@ -13,12 +15,14 @@ pub use ids::system_ids;
 pub use mapping::entity;
 pub use mapping::relation;
 
+pub use chrono;
 pub use neo4rs;
 
-pub use grc20_macros::{entity, relation};
+pub use grc20_macros::{entity, relation, Grc20Value};
 
 #[cfg(test)]
 pub(crate) mod test_utils {
+    use crate::connection::Graph;
     use testcontainers::{
         core::{IntoContainerPort, WaitFor},
         runners::AsyncRunner,
@@ -28,7 +32,7 @@ pub(crate) mod test_utils {
     const BOLT_PORT: u16 = 7687;
     const HTTP_PORT: u16 = 7474;
 
-    pub async fn setup_neo4j() -> (testcontainers::ContainerAsync<GenericImage>, neo4rs::Graph) {
+    pub async fn setup_neo4j() -> (testcontainers::ContainerAsync<GenericImage>, Graph) {
         // Setup a local Neo4J container for testing
         let container = GenericImage::new("neo4j", "2025.01.0-community")
             .with_wait_for(WaitFor::Duration {
@@ -44,7 +48,7 @@ pub(crate) mod test_utils {
         let port = container.get_host_port_ipv4(BOLT_PORT).await.unwrap();
         let host = container.get_host().await.unwrap().to_string();
 
-        let neo4j = neo4rs::Graph::new(format!("neo4j://{host}:{port}"), "", "")
+        let neo4j = Graph::new(format!("neo4j://{host}:{port}"), "", "")
             .await
             .unwrap();
 
@@ -37,7 +37,7 @@ impl TryFrom<Value> for AggregationDirection {
     }
 }
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Deserialize)]
 pub struct SpaceRanking {
     pub space_id: String,
     pub depth: usize,
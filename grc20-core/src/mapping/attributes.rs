@@ -4,14 +4,20 @@ use futures::{Stream, StreamExt, TryStreamExt};
 use neo4rs::{BoltList, BoltMap, BoltType};
 use serde::Deserialize;
 
-use crate::{block::BlockMetadata, error::DatabaseError, indexer_ids};
+use crate::{
+    block::BlockMetadata,
+    connection::{with_query_policy, Graph},
+    error::DatabaseError,
+    indexer_ids,
+};
 
 use super::{
     query_utils::{
         query_builder::{MatchQuery, QueryBuilder, Subquery},
         Query, QueryStream, VersionFilter,
     },
-    AttributeFilter, AttributeNode, PropFilter, Triple, TriplesConversionError, Value,
+    AttributeFilter, AttributeNode, EntityNodeRef, PropFilter, RelationEdge, Triple,
+    TriplesConversionError, Value,
 };
 
 /// Group of attributes belonging to the same entity.
@@ -82,7 +88,7 @@ impl Attributes {
 
     pub fn insert(
         self,
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         block: &BlockMetadata,
         entity_id: impl Into<String>,
         space_id: impl Into<String>,
@@ -138,7 +144,7 @@ impl From<Vec<AttributeNode>> for Attributes {
 }
 
 pub fn find_one(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     entity_id: impl Into<String>,
     space_id: impl Into<String>,
     space_version: Option<String>,
@@ -146,12 +152,12 @@ pub fn find_one(
     FindOneQuery::new(neo4j, entity_id.into(), space_id.into(), space_version)
 }
 
-pub fn find_many(neo4j: &neo4rs::Graph) -> FindManyQuery {
+pub fn find_many(neo4j: &Graph) -> FindManyQuery {
     FindManyQuery::new(neo4j)
 }
 
 pub fn insert_many(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     block: &BlockMetadata,
     space_id: impl Into<String>,
     space_version: impl Into<String>,
@@ -160,7 +166,7 @@ pub fn insert_many(
 }
 
 pub fn insert_one<T>(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     block: &BlockMetadata,
     entity_id: impl Into<String>,
     space_id: impl Into<String>,
@@ -195,7 +201,7 @@ pub fn insert_one<T>(
 // }
 
 pub struct InsertOneQuery<T> {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     block: BlockMetadata,
     entity_id: String,
     space_id: String,
@@ -205,7 +211,7 @@ pub struct InsertOneQuery<T> {
 
 impl<T> InsertOneQuery<T> {
     pub fn new(
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         block: &BlockMetadata,
         entity_id: String,
         space_id: String,
@@ -266,14 +272,14 @@ impl<T: IntoAttributes> Query<()> for InsertOneQuery<T> {
             .param("block_number", self.block.block_number.to_string())
             .param("block_timestamp", self.block.timestamp.to_rfc3339());
 
-        self.neo4j.run(query).await?;
+        with_query_policy(&self.neo4j, || self.neo4j.run(query.clone())).await?;
 
         Ok(())
     }
 }
 
 pub struct InsertManyQuery {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     block: BlockMetadata,
     space_id: String,
     space_version: String,
@@ -282,7 +288,7 @@ pub struct InsertManyQuery {
 
 impl InsertManyQuery {
     pub fn new(
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         block: &BlockMetadata,
         space_id: String,
         space_version: String,
@@ -368,14 +374,14 @@ impl Query<()> for InsertManyQuery {
             .param("block_number", self.block.block_number.to_string())
             .param("block_timestamp", self.block.timestamp.to_rfc3339());
 
-        self.neo4j.run(query).await?;
+        with_query_policy(&self.neo4j, || self.neo4j.run(query.clone())).await?;
 
         Ok(())
     }
 }
 
 pub struct FindOneQuery {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     entity_id: String,
     space_id: String,
     space_version: VersionFilter,
@@ -383,7 +389,7 @@ pub struct FindOneQuery {
 
 impl FindOneQuery {
     pub fn new(
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         entity_id: String,
         space_id: String,
         space_version: Option<String>,
@@ -429,8 +435,7 @@ where
             attrs: Vec<AttributeNode>,
         }
 
-        let result = neo4j
-            .execute(query.build())
+        let result = with_query_policy(&neo4j, || neo4j.execute(query.build()))
             .await?
             .next()
             .await?
@@ -447,7 +452,7 @@ where
 }
 
 pub struct FindManyQuery {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     id: Option<PropFilter<String>>,
     attributes: Vec<AttributeFilter>,
 
@@ -456,7 +461,7 @@ pub struct FindManyQuery {
 }
 
 impl FindManyQuery {
-    fn new(neo4j: &neo4rs::Graph) -> Self {
+    fn new(neo4j: &Graph) -> Self {
         Self {
             neo4j: neo4j.clone(),
             id: None,
@@ -533,8 +538,7 @@ where
             attributes: Vec<AttributeNode>,
         }
 
-        let stream = neo4j
-            .execute(query)
+        let stream = with_query_policy(&neo4j, || neo4j.execute(query.clone()))
             .await?
             .into_stream_as::<RowResult>()
             .map_err(DatabaseError::from)
@@ -561,6 +565,23 @@ impl FromAttributes for Attributes {
 /// Trait to convert a type into Triples
 pub trait IntoAttributes {
     fn into_attributes(self) -> Result<Attributes, TriplesConversionError>;
+
+    /// Relations outgoing from the entity `entity_id` that are part of the value
+    /// (e.g.: fields declared with `#[grc20(relation = ...)]` in the `entity` macro).
+    /// They are inserted alongside the attributes of the entity.
+    fn relations(
+        &self,
+        _entity_id: &str,
+        _space_id: &str,
+    ) -> Result<Vec<RelationEdge<EntityNodeRef>>, TriplesConversionError> {
+        Ok(vec![])
+    }
+
+    /// Types of the relations returned by [`IntoAttributes::relations`]. The existing
+    /// relations of these types outgoing from the entity are replaced on insert.
+    fn relation_types(&self) -> Vec<String> {
+        vec![]
+    }
 }
 
 impl IntoAttributes for Attributes {
@@ -1,7 +1,13 @@
-use crate::{block::BlockMetadata, error::DatabaseError, indexer_ids, mapping::Query};
+use crate::{
+    block::BlockMetadata,
+    connection::{with_query_policy, Graph},
+    error::DatabaseError,
+    indexer_ids,
+    mapping::Query,
+};
 
 pub struct DeleteOneQuery {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     block: BlockMetadata,
     id: String,
     space_id: String,
@@ -10,7 +16,7 @@ pub struct DeleteOneQuery {
 
 impl DeleteOneQuery {
     pub fn new(
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         block: &BlockMetadata,
         id: String,
         space_id: String,
@@ -48,7 +54,7 @@ impl Query<()> for DeleteOneQuery {
             .param("block_timestamp", self.block.timestamp.to_rfc3339())
             .param("block_number", self.block.block_number.to_string());
 
-        self.neo4j.run(query).await?;
+        with_query_policy(&self.neo4j, || self.neo4j.run(query.clone())).await?;
 
         Ok(())
     }
@@ -1,6 +1,7 @@
 use futures::{Stream, StreamExt, TryStreamExt};
 
 use crate::{
+    connection::{with_query_policy, Graph},
     entity::utils::MatchEntity,
     error::DatabaseError,
     mapping::{
@@ -13,10 +14,10 @@ use crate::{
     },
 };
 
-use super::{Entity, EntityNode};
+use super::{merge::not_merged, Entity, EntityNode};
 
 pub struct FindManyQuery<T> {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     filter: EntityFilter,
     order_by: Option<FieldOrderBy>,
     limit: usize,
@@ -29,7 +30,7 @@ pub struct FindManyQuery<T> {
 }
 
 impl<T> FindManyQuery<T> {
-    pub(super) fn new(neo4j: &neo4rs::Graph) -> Self {
+    pub(super) fn new(neo4j: &Graph) -> Self {
         Self {
             neo4j: neo4j.clone(),
             filter: EntityFilter::default(),
@@ -107,7 +108,8 @@ impl<T> FindManyQuery<T> {
 
     fn subquery(&self) -> QueryBuilder {
         QueryBuilder::default()
-            .subquery(MatchQuery::new("(e:Entity)"))
+            // Merged entities are represented by their canonical entity
+            .subquery(MatchQuery::new("(e:Entity)").r#where(not_merged("e")))
             .subquery(self.filter.subquery("e"))
             .subquery_opt(self.order_by.as_ref().map(|o| o.subquery("e")))
             .limit(self.limit)
@@ -136,8 +138,7 @@ impl QueryStream<EntityNode> for FindManyQuery<EntityNode> {
             e: EntityNode,
         }
 
-        Ok(neo4j
-            .execute(query.build())
+        Ok(with_query_policy(&neo4j, || neo4j.execute(query.build()))
             .await?
             .into_stream_as::<RowResult>()
             .map_err(DatabaseError::from)
@@ -178,9 +179,7 @@ impl<T: FromAttributes> QueryStream<Entity<T>> for FindManyQuery<Entity<T>> {
             types: Vec<EntityNode>,
         }
 
-        let stream = self
-            .neo4j
-            .execute(query.build())
+        let stream = with_query_policy(&self.neo4j, || self.neo4j.execute(query.build()))
             .await?
             .into_stream_as::<RowResult>()
             .map_err(DatabaseError::from)
@@ -0,0 +1,179 @@
+use futures::TryStreamExt;
+
+use crate::{
+    connection::{with_query_policy, Graph},
+    entity::utils::MatchEntity,
+    error::DatabaseError,
+    mapping::{
+        prop_filter,
+        query_utils::{
+            query_builder::{QueryBuilder, Subquery},
+            VersionFilter,
+        },
+        AttributeNode, FromAttributes, Query,
+    },
+};
+
+use super::{Entity, EntityNode};
+
+/// Query to find multiple entities by their IDs in a single round trip. Entities
+/// that do not exist are omitted from the result, and the order of the result is
+/// not guaranteed to match the order of the IDs.
+pub struct FindManyByIdsQuery<T> {
+    neo4j: Graph,
+    ids: Vec<String>,
+    space_id: Option<String>,
+    version: VersionFilter,
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<T> FindManyByIdsQuery<T> {
+    pub(super) fn new(neo4j: &Graph, ids: Vec<String>) -> Self {
+        Self {
+            neo4j: neo4j.clone(),
+            ids,
+            space_id: None,
+            version: VersionFilter::default(),
+            _phantom: std::marker::PhantomData,
+        }
+    }
+
+    pub fn space_id(mut self, space_id: impl Into<String>) -> Self {
+        self.space_id = Some(space_id.into());
+        self
+    }
+
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version.version_mut(version.into());
+        self
+    }
+
+    pub fn version_opt(mut self, version: Option<String>) -> Self {
+        self.version.version_opt(version);
+        self
+    }
+}
+
+impl Query<Vec<EntityNode>> for FindManyByIdsQuery<EntityNode> {
+    async fn send(self) -> Result<Vec<EntityNode>, DatabaseError> {
+        if self.ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        const QUERY: &str = r#"
+            UNWIND $ids AS id
+            MATCH (e:Entity {id: id})
+            RETURN DISTINCT e
+        "#;
+
+        let query = neo4rs::query(QUERY).param("ids", self.ids);
+
+        #[derive(Debug, serde::Deserialize)]
+        struct RowResult {
+            e: EntityNode,
+        }
+
+        with_query_policy(&self.neo4j, || self.neo4j.execute(query.clone()))
+            .await?
+            .into_stream_as::<RowResult>()
+            .map_err(DatabaseError::from)
+            .map_ok(|row| row.e)
+            .try_collect()
+            .await
+    }
+}
+
+impl<T: FromAttributes> Query<Vec<Entity<T>>> for FindManyByIdsQuery<Entity<T>> {
+    async fn send(self) -> Result<Vec<Entity<T>>, DatabaseError> {
+        if self.ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let space_filter = self.space_id.map(prop_filter::value);
+        let match_entity = MatchEntity::new(&space_filter, &self.version);
+
+        let query = QueryBuilder::default()
+            .subquery("UNWIND $ids AS id")
+            .subquery("MATCH (e:Entity {id: id})")
+            .with(
+                vec!["DISTINCT e".to_string()],
+                match_entity.chain(
+                    "e",
+                    "attrs",
+                    "types",
+                    None,
+                    "RETURN e{.*, attrs: attrs, types: types}",
+                ),
+            )
+            .params("ids", self.ids);
+
+        if cfg!(debug_assertions) || cfg!(test) {
+            println!(
+                "entity::FindManyByIdsQuery::<Entity<T>>:\n{}\nparams:{:?}",
+                query.compile(),
+                query.params
+            );
+        };
+
+        #[derive(Debug, serde::Deserialize)]
+        struct RowResult {
+            #[serde(flatten)]
+            node: EntityNode,
+            attrs: Vec<AttributeNode>,
+            types: Vec<EntityNode>,
+        }
+
+        with_query_policy(&self.neo4j, || self.neo4j.execute(query.build()))
+            .await?
+            .into_stream_as::<RowResult>()
+            .map_err(DatabaseError::from)
+            .and_then(|row| async move {
+                Ok(Entity {
+                    node: row.node,
+                    attributes: T::from_attributes(row.attrs.into())?,
+                    types: row.types.into_iter().map(|t| t.id).collect(),
+                })
+            })
+            .try_collect()
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        block::BlockMetadata,
+        mapping::{entity::find_many_by_ids, triple, EntityNode, Query, Triple},
+    };
+
+    #[tokio::test]
+    async fn test_find_many_by_ids() {
+        // Setup a local Neo 4J container for testing. NOTE: docker service must be running.
+        let (_container, neo4j) = crate::test_utils::setup_neo4j().await;
+
+        triple::insert_many(&neo4j, &BlockMetadata::default(), "ROOT", "0")
+            .triples(vec![
+                Triple::new("abc", "name", "Alice"),
+                Triple::new("def", "name", "Bob"),
+                Triple::new("ghi", "name", "Charlie"),
+            ])
+            .send()
+            .await
+            .expect("Failed to insert triples");
+
+        let mut found = find_many_by_ids::<EntityNode>(
+            &neo4j,
+            vec!["abc".to_string(), "ghi".to_string(), "unknown".to_string()],
+        )
+        .send()
+        .await
+        .expect("Failed to find entities")
+        .into_iter()
+        .map(|node| node.id)
+        .collect::<Vec<_>>();
+
+        found.sort();
+
+        assert_eq!(found, vec!["abc".to_string(), "ghi".to_string()]);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::{
+    connection::{with_query_policy, Graph},
     entity::utils::MatchEntity,
     error::DatabaseError,
     mapping::{
@@ -11,10 +12,10 @@ use crate::{
     },
 };
 
-use super::{Entity, EntityNode};
+use super::{merge::canonical_entity, Entity, EntityNode};
 
 pub struct FindOneQuery<T> {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     id: String,
     space_id: Option<String>,
     version: VersionFilter,
@@ -22,7 +23,7 @@ pub struct FindOneQuery<T> {
 }
 
 impl<T> FindOneQuery<T> {
-    pub(super) fn new(neo4j: &neo4rs::Graph, id: String) -> Self {
+    pub(super) fn new(neo4j: &Graph, id: String) -> Self {
         Self {
             neo4j: neo4j.clone(),
             id,
@@ -50,20 +51,22 @@ impl<T> FindOneQuery<T> {
 
 impl Query<Option<EntityNode>> for FindOneQuery<EntityNode> {
     async fn send(self) -> Result<Option<EntityNode>, DatabaseError> {
-        const QUERY: &str = r#"
-            MATCH (e:Entity {id: $id})
-            RETURN e
-        "#;
-
-        let query = neo4rs::query(QUERY).param("id", self.id);
+        // Merged entities resolve to their canonical entity
+        let query = neo4rs::query(&format!(
+            r#"
+            MATCH (source:Entity {{id: $id}})
+            RETURN {} AS e
+            "#,
+            canonical_entity("source")
+        ))
+        .param("id", self.id);
 
         #[derive(Debug, serde::Deserialize)]
         struct RowResult {
             e: EntityNode,
         }
 
-        self.neo4j
-            .execute(query)
+        with_query_policy(&self.neo4j, || self.neo4j.execute(query.clone()))
             .await?
             .next()
             .await?
@@ -81,9 +84,10 @@ impl<T: FromAttributes> Query<Option<Entity<T>>> for FindOneQuery<Entity<T>> {
         let match_entity = MatchEntity::new(&space_filter, &self.version);
 
         let query = QueryBuilder::default()
-            .subquery(MatchQuery::new("(e:Entity {id: $id})"))
+            .subquery(MatchQuery::new("(source:Entity {id: $id})"))
+            // Merged entities resolve to their canonical entity
             .with(
-                vec!["e".to_string()],
+                vec![format!("{} AS e", canonical_entity("source"))],
                 match_entity.chain(
                     "e",
                     "attrs",
@@ -110,8 +114,7 @@ impl<T: FromAttributes> Query<Option<Entity<T>>> for FindOneQuery<Entity<T>> {
             types: Vec<EntityNode>,
         }
 
-        self.neo4j
-            .execute(query.build())
+        with_query_policy(&self.neo4j, || self.neo4j.execute(query.build()))
             .await?
             .next()
             .await?
@@ -1,6 +1,7 @@
 use neo4rs::Path;
 
 use crate::{
+    connection::{with_query_policy, Graph},
     entity::EntityFilter,
     error::DatabaseError,
     mapping::{
@@ -20,7 +21,7 @@ pub struct Relation {
 }
 
 pub struct FindPathQuery {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     id1: String,
     id2: String,
     filter: EntityFilter,
@@ -32,7 +33,7 @@ pub struct FindPathQuery {
 }
 
 impl FindPathQuery {
-    pub(super) fn new(neo4j: &neo4rs::Graph, id1: String, id2: String) -> Self {
+    pub(super) fn new(neo4j: &Graph, id1: String, id2: String) -> Self {
         Self {
             neo4j: neo4j.clone(),
             id1,
@@ -132,7 +133,8 @@ impl Query<Vec<Relation>> for FindPathQuery {
             );
         }
 
-        let mut result = self.neo4j.execute(query.build()).await?;
+        let mut result =
+            with_query_policy(&self.neo4j, || self.neo4j.execute(query.build())).await?;
         let mut all_relationship_data = Vec::new();
 
         // Process each row
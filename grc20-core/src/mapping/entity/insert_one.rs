@@ -1,7 +1,8 @@
 use crate::{
     block::BlockMetadata,
+    connection::{with_query_policy, Graph},
     error::DatabaseError,
-    ids,
+    ids, indexer_ids,
     mapping::{attributes, IntoAttributes, Query, RelationEdge},
     relation, system_ids,
 };
@@ -9,7 +10,7 @@ use crate::{
 use super::Entity;
 
 pub struct InsertOneQuery<T> {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     block: BlockMetadata,
     entity: T,
     space_id: String,
@@ -18,7 +19,7 @@ pub struct InsertOneQuery<T> {
 
 impl<T> InsertOneQuery<T> {
     pub(super) fn new(
-        neo4j: neo4rs::Graph,
+        neo4j: Graph,
         block: BlockMetadata,
         entity: T,
         space_id: String,
@@ -36,6 +37,12 @@ impl<T> InsertOneQuery<T> {
 
 impl<T: IntoAttributes> Query<()> for InsertOneQuery<Entity<T>> {
     async fn send(self) -> Result<(), DatabaseError> {
+        let relations = self
+            .entity
+            .attributes
+            .relations(&self.entity.node.id, &self.space_id)?;
+        let relation_types = self.entity.attributes.relation_types();
+
         // Insert the entity data
         attributes::insert_one(
             &self.neo4j,
@@ -70,9 +77,38 @@ impl<T: IntoAttributes> Query<()> for InsertOneQuery<Entity<T>> {
             })
             .collect::<Vec<_>>();
 
+        // Retire the existing relations of the relation fields, which are replaced
+        // by the inserted ones
+        if !relation_types.is_empty() {
+            const RETIRE_RELATIONS_QUERY: &str = const_format::formatcp!(
+                r#"
+                MATCH (:Entity {{id: $entity_id}}) -[r:RELATION {{space_id: $space_id}}]-> (:Entity)
+                WHERE r.relation_type IN $relation_types AND r.max_version IS NULL
+                SET r.max_version = $space_version
+                SET r += {{
+                    `{UPDATED_AT}`: datetime($block_timestamp),
+                    `{UPDATED_AT_BLOCK}`: $block_number
+                }}
+                "#,
+                UPDATED_AT = indexer_ids::UPDATED_AT_TIMESTAMP,
+                UPDATED_AT_BLOCK = indexer_ids::UPDATED_AT_BLOCK,
+            );
+
+            let query = neo4rs::query(RETIRE_RELATIONS_QUERY)
+                .param("entity_id", self.entity.node.id.clone())
+                .param("space_id", self.space_id.clone())
+                .param("space_version", self.space_version.clone())
+                .param("relation_types", relation_types)
+                .param("block_timestamp", self.block.timestamp.to_rfc3339())
+                .param("block_number", self.block.block_number.to_string());
+
+            with_query_policy(&self.neo4j, || self.neo4j.run(query.clone())).await?;
+        }
+
         // Insert the relations
         relation::insert_many(&self.neo4j, &self.block, &self.space_id, self.space_version)
             .relations(types_relations)
+            .relations(relations)
             .send()
             .await?;
 
@@ -0,0 +1,545 @@
+use std::collections::HashMap;
+
+use crate::{
+    block::BlockMetadata,
+    connection::{with_query_policy, Graph},
+    error::DatabaseError,
+    ids, indexer_ids,
+    mapping::{
+        EntityNode, EntityNodeRef, MatchQuery, PropFilter, Query, QueryBuilder, RelationEdge,
+    },
+};
+
+/// Cypher expression evaluating to the canonical entity of `node_var`, i.e.: the
+/// entity it was merged into, or the entity itself if it was not merged.
+pub(crate) fn canonical_entity(node_var: &str) -> String {
+    format!(
+        r#"HEAD([({node_var}) -[{node_var}_redirect:RELATION {{relation_type: "{}", space_id: "{}"}}]-> ({node_var}_canonical:Entity) WHERE {node_var}_redirect.max_version IS NULL | {node_var}_canonical] + [{node_var}])"#,
+        indexer_ids::REDIRECTS_TO,
+        indexer_ids::INDEXER_SPACE_ID,
+    )
+}
+
+/// Cypher predicate that is true if the entity `node_var` was not merged into
+/// another entity.
+pub fn not_merged(node_var: &str) -> String {
+    format!(
+        r#"NOT EXISTS {{ ({node_var}) -[{node_var}_redirect:RELATION {{relation_type: "{}", space_id: "{}"}}]-> (:Entity) WHERE {node_var}_redirect.max_version IS NULL }}"#,
+        indexer_ids::REDIRECTS_TO,
+        indexer_ids::INDEXER_SPACE_ID,
+    )
+}
+
+/// Cypher expression evaluating to the list made of the canonical entity `node_var`
+/// followed by the entities merged into it.
+pub(crate) fn merged_entities(node_var: &str) -> String {
+    format!(
+        r#"[{node_var}] + COLLECT {{ MATCH ({node_var}_merged:Entity) -[{node_var}_merged_redirect:RELATION {{relation_type: "{}", space_id: "{}"}}]-> ({node_var}) WHERE {node_var}_merged_redirect.max_version IS NULL RETURN {node_var}_merged }}"#,
+        indexer_ids::REDIRECTS_TO,
+        indexer_ids::INDEXER_SPACE_ID,
+    )
+}
+
+/// Subquery binding `node_var` to the entities whose canonical entity has an ID matching
+/// `id_filter`: the canonical entities and the entities merged into them. An ID of a
+/// merged entity is resolved to its canonical entity first, so that filtering the
+/// endpoints of relations by the ID of any of the merged entities returns the relations
+/// of all of them.
+pub(crate) fn match_merged(node_var: &str, id_filter: &PropFilter<String>) -> QueryBuilder {
+    let filtered_var = format!("{node_var}_filtered");
+    let resolved_var = format!("{node_var}_resolved");
+
+    QueryBuilder::default()
+        .subquery("CALL {")
+        .subquery(
+            MatchQuery::new(format!("({filtered_var}:Entity)")).r#where(id_filter.subquery(
+                &filtered_var,
+                "id",
+                None,
+            )),
+        )
+        .subquery(format!(
+            "WITH DISTINCT {} AS {resolved_var}",
+            canonical_entity(&filtered_var)
+        ))
+        .subquery(format!(
+            "UNWIND {} AS {node_var}",
+            merged_entities(&resolved_var)
+        ))
+        .subquery(format!("RETURN {node_var}"))
+        .subquery("}")
+}
+
+/// Query merging a source entity into a canonical entity. The merge is recorded
+/// as a `REDIRECTS_TO` relation in the indexer space, the triples and relations
+/// of the source entity are left untouched.
+///
+/// Redirects never chain: if the canonical entity was itself merged, the source is
+/// redirected to the entity it was merged into, and entities previously merged into
+/// the source are redirected to the new canonical entity.
+///
+/// Returns the ID of the canonical entity.
+pub struct MergeQuery {
+    neo4j: Graph,
+    block: BlockMetadata,
+    source_id: String,
+    canonical_id: String,
+}
+
+impl MergeQuery {
+    pub(super) fn new(
+        neo4j: &Graph,
+        block: &BlockMetadata,
+        source_id: String,
+        canonical_id: String,
+    ) -> Self {
+        Self {
+            neo4j: neo4j.clone(),
+            block: block.clone(),
+            source_id,
+            canonical_id,
+        }
+    }
+}
+
+impl Query<String> for MergeQuery {
+    async fn send(self) -> Result<String, DatabaseError> {
+        if self.source_id == self.canonical_id {
+            return Err(DatabaseError::InvalidMerge(format!(
+                "cannot merge entity {} into itself",
+                self.source_id
+            )));
+        }
+
+        // If the canonical entity was merged into the source entity, the previous
+        // merge is reversed. Otherwise, follow the redirect of the canonical entity.
+        let canonical_id = match super::resolve_redirect(&self.neo4j, &self.canonical_id)
+            .send()
+            .await?
+        {
+            Some(resolved) if resolved != self.source_id => resolved,
+            _ => self.canonical_id.clone(),
+        };
+
+        let found = super::find_many_by_ids::<EntityNode>(
+            &self.neo4j,
+            vec![self.source_id.clone(), canonical_id.clone()],
+        )
+        .send()
+        .await?;
+
+        for id in [&self.source_id, &canonical_id] {
+            if !found.iter().any(|node| &node.id == id) {
+                return Err(DatabaseError::NotFound(format!("Entity {id}")));
+            }
+        }
+
+        let mut source_ids = super::merged_ids(&self.neo4j, &self.source_id)
+            .send()
+            .await?;
+        source_ids.retain(|id| id != &canonical_id);
+        source_ids.push(self.source_id.clone());
+
+        // Remove the current redirects of the source entities (and of the canonical
+        // entity, in case the merge is reversed) and redirect the source entities to
+        // the canonical entity. Both are done in a single statement so that a failure
+        // cannot leave a source entity retired without a redirect.
+        const MERGE_QUERY: &str = const_format::formatcp!(
+            r#"
+                CALL {{
+                    MATCH (e:Entity) -[r:RELATION {{relation_type: "{REDIRECTS_TO}", space_id: "{INDEXER_SPACE_ID}"}}]-> (:Entity)
+                    WHERE e.id IN $retire_ids
+                    AND r.max_version IS NULL
+                    SET r.max_version = $space_version
+                    SET r += {{
+                        `{UPDATED_AT}`: datetime($block_timestamp),
+                        `{UPDATED_AT_BLOCK}`: $block_number
+                    }}
+                }}
+                UNWIND $relations as relation
+                MATCH (from:Entity {{id: relation.from}})
+                MATCH (to:Entity {{id: relation.to}})
+                CREATE (from) -[r:RELATION]-> (to)
+                SET r += {{
+                    id: relation.id,
+                    space_id: "{INDEXER_SPACE_ID}",
+                    index: relation.index,
+                    min_version: $space_version,
+                    relation_type: relation.relation_type,
+                    `{CREATED_AT}`: datetime($block_timestamp),
+                    `{CREATED_AT_BLOCK}`: $block_number,
+                    `{UPDATED_AT}`: datetime($block_timestamp),
+                    `{UPDATED_AT_BLOCK}`: $block_number
+                }}
+            "#,
+            REDIRECTS_TO = indexer_ids::REDIRECTS_TO,
+            INDEXER_SPACE_ID = indexer_ids::INDEXER_SPACE_ID,
+            CREATED_AT = indexer_ids::CREATED_AT_TIMESTAMP,
+            CREATED_AT_BLOCK = indexer_ids::CREATED_AT_BLOCK,
+            UPDATED_AT = indexer_ids::UPDATED_AT_TIMESTAMP,
+            UPDATED_AT_BLOCK = indexer_ids::UPDATED_AT_BLOCK,
+        );
+
+        let retire_ids = source_ids
+            .iter()
+            .cloned()
+            .chain(std::iter::once(canonical_id.clone()))
+            .collect::<Vec<_>>();
+
+        let relations = source_ids
+            .iter()
+            .map(|source_id| {
+                RelationEdge::<EntityNodeRef>::new(
+                    ids::create_id_from_unique_string(format!(
+                        "{source_id}:{}:{canonical_id}:{}",
+                        indexer_ids::REDIRECTS_TO,
+                        self.block.block_number
+                    )),
+                    source_id,
+                    &canonical_id,
+                    indexer_ids::REDIRECTS_TO,
+                    "a0",
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let query = neo4rs::query(MERGE_QUERY)
+            .param("retire_ids", retire_ids)
+            .param("relations", relations)
+            .param("space_version", "0")
+            .param("block_timestamp", self.block.timestamp.to_rfc3339())
+            .param("block_number", self.block.block_number.to_string());
+
+        with_query_policy(&self.neo4j, || self.neo4j.run(query.clone())).await?;
+
+        Ok(canonical_id)
+    }
+}
+
+/// Query returning the ID of the entity that an entity was merged into (if any)
+pub struct ResolveRedirectQuery {
+    neo4j: Graph,
+    id: String,
+}
+
+impl ResolveRedirectQuery {
+    pub(super) fn new(neo4j: &Graph, id: String) -> Self {
+        Self {
+            neo4j: neo4j.clone(),
+            id,
+        }
+    }
+}
+
+impl Query<Option<String>> for ResolveRedirectQuery {
+    async fn send(self) -> Result<Option<String>, DatabaseError> {
+        const QUERY: &str = const_format::formatcp!(
+            r#"
+                MATCH (e:Entity {{id: $id}}) -[r:RELATION {{relation_type: "{REDIRECTS_TO}", space_id: "{INDEXER_SPACE_ID}"}}]-> (canonical:Entity)
+                WHERE r.max_version IS NULL
+                RETURN canonical.id AS id
+                LIMIT 1
+            "#,
+            REDIRECTS_TO = indexer_ids::REDIRECTS_TO,
+            INDEXER_SPACE_ID = indexer_ids::INDEXER_SPACE_ID,
+        );
+
+        let query = neo4rs::query(QUERY).param("id", self.id);
+
+        #[derive(Debug, serde::Deserialize)]
+        struct RowResult {
+            id: String,
+        }
+
+        with_query_policy(&self.neo4j, || self.neo4j.execute(query.clone()))
+            .await?
+            .next()
+            .await?
+            .map(|row| {
+                let row = row.to::<RowResult>()?;
+                Result::<_, DatabaseError>::Ok(row.id)
+            })
+            .transpose()
+    }
+}
+
+/// Query returning the IDs of the entities merged into an entity
+pub struct MergedIdsQuery {
+    neo4j: Graph,
+    id: String,
+}
+
+impl MergedIdsQuery {
+    pub(super) fn new(neo4j: &Graph, id: String) -> Self {
+        Self {
+            neo4j: neo4j.clone(),
+            id,
+        }
+    }
+}
+
+impl Query<Vec<String>> for MergedIdsQuery {
+    async fn send(self) -> Result<Vec<String>, DatabaseError> {
+        const QUERY: &str = const_format::formatcp!(
+            r#"
+                MATCH (merged:Entity) -[r:RELATION {{relation_type: "{REDIRECTS_TO}", space_id: "{INDEXER_SPACE_ID}"}}]-> (e:Entity {{id: $id}})
+                WHERE r.max_version IS NULL
+                RETURN COLLECT(DISTINCT merged.id) AS ids
+            "#,
+            REDIRECTS_TO = indexer_ids::REDIRECTS_TO,
+            INDEXER_SPACE_ID = indexer_ids::INDEXER_SPACE_ID,
+        );
+
+        let query = neo4rs::query(QUERY).param("id", self.id);
+
+        #[derive(Debug, serde::Deserialize)]
+        struct RowResult {
+            ids: Vec<String>,
+        }
+
+        let mut ids = with_query_policy(&self.neo4j, || self.neo4j.execute(query.clone()))
+            .await?
+            .next()
+            .await?
+            .map(|row| row.to::<RowResult>())
+            .transpose()?
+            .map(|row| row.ids)
+            .unwrap_or_default();
+        ids.sort();
+
+        Ok(ids)
+    }
+}
+
+/// Query returning the IDs of the entities merged into each of a set of entities
+pub struct MergedIdsByIdsQuery {
+    neo4j: Graph,
+    ids: Vec<String>,
+}
+
+impl MergedIdsByIdsQuery {
+    pub(super) fn new(neo4j: &Graph, ids: Vec<String>) -> Self {
+        Self {
+            neo4j: neo4j.clone(),
+            ids,
+        }
+    }
+}
+
+impl Query<HashMap<String, Vec<String>>> for MergedIdsByIdsQuery {
+    async fn send(self) -> Result<HashMap<String, Vec<String>>, DatabaseError> {
+        if self.ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        const QUERY: &str = const_format::formatcp!(
+            r#"
+                MATCH (merged:Entity) -[r:RELATION {{relation_type: "{REDIRECTS_TO}", space_id: "{INDEXER_SPACE_ID}"}}]-> (e:Entity)
+                WHERE e.id IN $ids AND r.max_version IS NULL
+                RETURN e.id AS id, COLLECT(DISTINCT merged.id) AS ids
+            "#,
+            REDIRECTS_TO = indexer_ids::REDIRECTS_TO,
+            INDEXER_SPACE_ID = indexer_ids::INDEXER_SPACE_ID,
+        );
+
+        let query = neo4rs::query(QUERY).param("ids", self.ids);
+
+        #[derive(Debug, serde::Deserialize)]
+        struct RowResult {
+            id: String,
+            ids: Vec<String>,
+        }
+
+        let mut result =
+            with_query_policy(&self.neo4j, || self.neo4j.execute(query.clone())).await?;
+        let mut merged_ids = HashMap::new();
+
+        while let Some(row) = result.next().await? {
+            let mut row = row.to::<RowResult>()?;
+            row.ids.sort();
+            merged_ids.insert(row.id, row.ids);
+        }
+
+        Ok(merged_ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use futures::TryStreamExt;
+
+    use crate::{
+        block::BlockMetadata,
+        error::DatabaseError,
+        mapping::{
+            entity::{self, TraversalHop},
+            prop_filter, relation, triple, EntityFilter, EntityNode, EntityNodeRef, Query,
+            QueryStream, RelationEdge, Triple,
+        },
+    };
+
+    #[tokio::test]
+    async fn test_merge() {
+        // Setup a local Neo 4J container for testing. NOTE: docker service must be running.
+        let (_container, neo4j) = crate::test_utils::setup_neo4j().await;
+        let block = BlockMetadata::default();
+
+        triple::insert_many(&neo4j, &block, "ROOT", "0")
+            .triples(vec![
+                Triple::new("alice", "name", "Alice"),
+                Triple::new("alice_dup", "name", "Alice (duplicate)"),
+                Triple::new("alice_dup_2", "name", "Alice (second duplicate)"),
+                Triple::new("bob", "name", "Bob"),
+            ])
+            .send()
+            .await
+            .expect("Failed to insert triples");
+
+        relation::insert_one::<RelationEdge<EntityNodeRef>>(
+            &neo4j,
+            &block,
+            "ROOT",
+            "0",
+            RelationEdge::new("knows", "bob", "alice_dup", "KNOWS", "a0"),
+        )
+        .send()
+        .await
+        .expect("Failed to insert relation");
+
+        relation::insert_one::<RelationEdge<EntityNodeRef>>(
+            &neo4j,
+            &block,
+            "ROOT",
+            "0",
+            RelationEdge::new("likes", "alice_dup_2", "bob", "LIKES", "a0"),
+        )
+        .send()
+        .await
+        .expect("Failed to insert relation");
+
+        // Merge the second duplicate into the first one, then the first one into
+        // the canonical entity: redirects must not chain.
+        entity::merge(&neo4j, &block, "alice_dup_2", "alice_dup")
+            .send()
+            .await
+            .expect("Failed to merge entities");
+        let canonical_id = entity::merge(&neo4j, &block, "alice_dup", "alice")
+            .send()
+            .await
+            .expect("Failed to merge entities");
+        assert_eq!(canonical_id, "alice");
+
+        assert_eq!(
+            entity::merged_ids(&neo4j, "alice").send().await.unwrap(),
+            vec!["alice_dup".to_string(), "alice_dup_2".to_string()]
+        );
+        assert_eq!(
+            entity::resolve_redirect(&neo4j, "alice_dup_2")
+                .send()
+                .await
+                .unwrap(),
+            Some("alice".to_string())
+        );
+        assert_eq!(
+            entity::merged_ids_by_ids(&neo4j, vec!["alice".to_string(), "bob".to_string()])
+                .send()
+                .await
+                .unwrap(),
+            HashMap::from([(
+                "alice".to_string(),
+                vec!["alice_dup".to_string(), "alice_dup_2".to_string()]
+            )])
+        );
+
+        // Merging an entity into itself is rejected
+        assert!(matches!(
+            entity::merge(&neo4j, &block, "alice_dup", "alice_dup")
+                .send()
+                .await,
+            Err(DatabaseError::InvalidMerge(_))
+        ));
+
+        // find_one follows redirects
+        let found = entity::find_one::<EntityNode>(&neo4j, "alice_dup_2")
+            .send()
+            .await
+            .expect("Failed to find entity")
+            .expect("Entity not found");
+        assert_eq!(found.id, "alice");
+
+        // find_many only returns canonical entities
+        let mut ids = entity::find_many::<EntityNode>(&neo4j)
+            .send()
+            .await
+            .expect("Failed to find entities")
+            .map_ok(|node| node.id)
+            .try_collect::<Vec<_>>()
+            .await
+            .expect("Failed to collect entities");
+        ids.retain(|id| id.starts_with("alice") || id == "bob");
+        ids.sort();
+        assert_eq!(ids, vec!["alice".to_string(), "bob".to_string()]);
+
+        // Traversals follow redirects while the path keeps the original relation
+        let reached = entity::traverse::<EntityNode>(
+            &neo4j,
+            EntityFilter::default().id(prop_filter::value("bob")),
+        )
+        .hop(TraversalHop::outbound("KNOWS"))
+        .send()
+        .await
+        .expect("Failed to traverse")
+        .try_collect::<Vec<_>>()
+        .await
+        .expect("Failed to collect results");
+        assert_eq!(reached.len(), 1);
+        assert_eq!(reached[0].entity.id, "alice");
+        assert_eq!(reached[0].path.nodes, vec!["bob", "alice_dup"]);
+
+        // Relations of merged entities are found from the canonical entity and
+        // resolve both endpoints to their canonical entities
+        let outgoing = relation::find_many::<RelationEdge<EntityNodeRef>>(&neo4j)
+            .filter(
+                relation::RelationFilter::default()
+                    .relation_type(EntityFilter::default().id(prop_filter::value("LIKES")))
+                    .from_(EntityFilter::default().id(prop_filter::value("alice"))),
+            )
+            .send()
+            .await
+            .expect("Failed to find relations")
+            .try_collect::<Vec<_>>()
+            .await
+            .expect("Failed to collect relations");
+        assert_eq!(outgoing.len(), 1);
+        assert_eq!(outgoing[0].id, "likes");
+        assert_eq!(outgoing[0].from, EntityNodeRef("alice".to_string()));
+
+        // Filtering by the ID of a merged entity matches the relations of all the
+        // entities merged together
+        let incoming = relation::find_many::<RelationEdge<EntityNodeRef>>(&neo4j)
+            .filter(
+                relation::RelationFilter::default()
+                    .relation_type(EntityFilter::default().id(prop_filter::value("KNOWS")))
+                    .to_(EntityFilter::default().id(prop_filter::value("alice_dup_2"))),
+            )
+            .send()
+            .await
+            .expect("Failed to find relations")
+            .try_collect::<Vec<_>>()
+            .await
+            .expect("Failed to collect relations");
+        assert_eq!(incoming.len(), 1);
+        assert_eq!(incoming[0].id, "knows");
+        assert_eq!(incoming[0].to, EntityNodeRef("alice".to_string()));
+
+        // The triples of the merged entity are left untouched
+        let original = triple::find_one(&neo4j, "name", "alice_dup", "ROOT", None)
+            .send()
+            .await
+            .expect("Failed to find triple")
+            .expect("Triple not found");
+        assert_eq!(original.value.value, "Alice (duplicate)");
+    }
+}
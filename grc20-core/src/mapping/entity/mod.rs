@@ -1,31 +1,38 @@
 pub mod delete_many;
 pub mod delete_one;
 pub mod find_many;
+pub mod find_many_by_ids;
 pub mod find_one;
 pub mod find_path;
 pub mod insert_many;
 pub mod insert_one;
+pub mod merge;
 pub mod models;
 pub mod prefiltered_semantic_search;
 pub mod search_with_traversals;
 pub mod semantic_search;
+pub mod traverse;
 pub mod utils;
 
 pub use delete_one::DeleteOneQuery;
 pub use find_many::FindManyQuery;
+pub use find_many_by_ids::FindManyByIdsQuery;
 pub use find_one::FindOneQuery;
 pub use find_path::FindPathQuery;
 pub use insert_one::InsertOneQuery;
-pub use models::{Entity, EntityNode, EntityNodeRef, SystemProperties};
+pub use merge::{MergeQuery, MergedIdsByIdsQuery, MergedIdsQuery, ResolveRedirectQuery};
+pub use models::{Entity, EntityNode, EntityNodeRef, RelationField, SystemProperties};
 pub use prefiltered_semantic_search::PrefilteredSemanticSearchQuery;
 pub use search_with_traversals::SearchWithTraversals;
 pub use semantic_search::SemanticSearchQuery;
+pub use traverse::{TraversalHop, TraversalPath, TraverseQuery, TraverseResult};
 pub use utils::{EntityFilter, EntityRelationFilter, TypesFilter};
 
 use crate::block::BlockMetadata;
+use crate::connection::Graph;
 
 pub fn delete_one(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     block: &BlockMetadata,
     entity_id: impl Into<String>,
     space_id: impl Into<String>,
@@ -57,7 +64,7 @@ pub fn delete_one(
 ///     .send()
 ///     .await?;
 /// ```
-pub fn find_one<T>(neo4j: &neo4rs::Graph, id: impl Into<String>) -> FindOneQuery<T> {
+pub fn find_one<T>(neo4j: &Graph, id: impl Into<String>) -> FindOneQuery<T> {
     FindOneQuery::new(neo4j, id.into())
 }
 
@@ -101,10 +108,26 @@ pub fn find_one<T>(neo4j: &neo4rs::Graph, id: impl Into<String>) -> FindOneQuery
 ///     .send()
 ///     .await?;
 /// ```
-pub fn find_many<T>(neo4j: &neo4rs::Graph) -> FindManyQuery<T> {
+pub fn find_many<T>(neo4j: &Graph) -> FindManyQuery<T> {
     FindManyQuery::new(neo4j)
 }
 
+/// Creates a query to find multiple entities by their IDs in a single round trip.
+/// Entities that do not exist are omitted from the result.
+/// ```rust
+/// use grc20_core::mapping::entity;
+///
+/// let entities = entity::find_many_by_ids::<EntityNode>(
+///     &neo4j,
+///     vec!["entity_id_1".to_string(), "entity_id_2".to_string()],
+/// )
+///     .send()
+///     .await?;
+/// ```
+pub fn find_many_by_ids<T>(neo4j: &Graph, ids: Vec<String>) -> FindManyByIdsQuery<T> {
+    FindManyByIdsQuery::new(neo4j, ids)
+}
+
 /// Create a query to search for entities using semantic search based on a vector. The query
 /// supports the same filtering options as `find_many`, allowing you to filter results by
 /// attributes, relations, and other properties.
@@ -130,31 +153,108 @@ pub fn find_many<T>(neo4j: &neo4rs::Graph) -> FindManyQuery<T> {
 ///     .send()
 ///     .await?;
 /// ```
-pub fn search<T>(neo4j: &neo4rs::Graph, vector: Vec<f64>) -> SemanticSearchQuery<T> {
+pub fn search<T>(neo4j: &Graph, vector: Vec<f64>) -> SemanticSearchQuery<T> {
     SemanticSearchQuery::new(neo4j, vector)
 }
 
-pub fn prefiltered_search<T>(
-    neo4j: &neo4rs::Graph,
-    vector: Vec<f64>,
-) -> PrefilteredSemanticSearchQuery<T> {
+pub fn prefiltered_search<T>(neo4j: &Graph, vector: Vec<f64>) -> PrefilteredSemanticSearchQuery<T> {
     PrefilteredSemanticSearchQuery::new(neo4j, vector)
 }
 
-pub fn search_from_restictions<T>(
-    neo4j: &neo4rs::Graph,
-    vector: Vec<f64>,
-) -> SearchWithTraversals<T> {
+pub fn search_from_restictions<T>(neo4j: &Graph, vector: Vec<f64>) -> SearchWithTraversals<T> {
     SearchWithTraversals::new(neo4j, vector)
 }
 
+/// Creates a query that starts from the entities matching `start` and follows the
+/// hops added with [`TraverseQuery::hop`] in order. Each hop follows relations of
+/// a given type in a given direction, optionally over a variable number of relations.
+/// The entities reached at the end of the traversal are returned together with the
+/// path that was followed to reach them.
+///
+/// ```rust
+/// use grc20_core::mapping::{entity, prop_filter};
+/// use grc20_core::mapping::entity::{EntityFilter, TraversalHop};
+///
+/// // Find the papers authored by people working at "org_id"
+/// let papers = entity::traverse::<EntityNode>(
+///     &neo4j,
+///     EntityFilter::default().id(prop_filter::value("org_id")),
+/// )
+///     // People working at the org
+///     .hop(TraversalHop::inbound("WORKS_AT"))
+///     // Papers authored by these people
+///     .hop(TraversalHop::inbound("AUTHORED_BY"))
+///     // Only keep entities of type "Paper"
+///     .filter(EntityFilter::default()
+///         .relations(TypesFilter::default().r#type("Paper".to_string())))
+///     .send()
+///     .await?;
+///
+/// // Find all ancestors of "entity_id" up to 5 levels up
+/// let ancestors = entity::traverse::<EntityNode>(
+///     &neo4j,
+///     EntityFilter::default().id(prop_filter::value("entity_id")),
+/// )
+///     .hop(TraversalHop::outbound("PARENT").length(1, 5))
+///     .send()
+///     .await?;
+/// ```
+pub fn traverse<T>(neo4j: &Graph, start: EntityFilter) -> TraverseQuery<T> {
+    TraverseQuery::new(neo4j, start)
+}
+
+/// Creates a query merging the entity `source_id` into the entity `canonical_id`.
+/// The merge is recorded as a redirect in the indexer space: the triples and
+/// relations of the source entity are kept (and can still be queried by its ID),
+/// but [`find_one`], [`find_many`], [`traverse`] and relation queries resolve the
+/// source entity to the canonical entity.
+///
+/// The query returns the ID of the canonical entity, which differs from
+/// `canonical_id` if that entity was itself merged into another entity.
+/// ```rust
+/// use grc20_core::mapping::entity;
+///
+/// let canonical_id = entity::merge(&neo4j, &block, "duplicate_id", "entity_id")
+///     .send()
+///     .await?;
+///
+/// // Returns the canonical entity
+/// let maybe_entity = entity::find_one::<EntityNode>(&neo4j, "duplicate_id")
+///     .send()
+///     .await?;
+/// ```
+pub fn merge(
+    neo4j: &Graph,
+    block: &BlockMetadata,
+    source_id: impl Into<String>,
+    canonical_id: impl Into<String>,
+) -> MergeQuery {
+    MergeQuery::new(neo4j, block, source_id.into(), canonical_id.into())
+}
+
+/// Creates a query returning the ID of the entity that `id` was merged into, if any.
+pub fn resolve_redirect(neo4j: &Graph, id: impl Into<String>) -> ResolveRedirectQuery {
+    ResolveRedirectQuery::new(neo4j, id.into())
+}
+
+/// Creates a query returning the IDs of the entities merged into `id`.
+pub fn merged_ids(neo4j: &Graph, id: impl Into<String>) -> MergedIdsQuery {
+    MergedIdsQuery::new(neo4j, id.into())
+}
+
+/// Creates a query returning the IDs of the entities merged into each of `ids`.
+/// Entities into which no entity was merged are omitted from the result.
+pub fn merged_ids_by_ids(neo4j: &Graph, ids: Vec<String>) -> MergedIdsByIdsQuery {
+    MergedIdsByIdsQuery::new(neo4j, ids)
+}
+
 // TODO: add docs for use via GraphQL
-pub fn find_path(neo4j: &neo4rs::Graph, id1: String, id2: String) -> FindPathQuery {
+pub fn find_path(neo4j: &Graph, id1: String, id2: String) -> FindPathQuery {
     FindPathQuery::new(neo4j, id1, id2)
 }
 
 pub fn insert_one<T>(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     block: &BlockMetadata,
     entity: T,
     space_id: impl Into<String>,
@@ -4,6 +4,7 @@ use chrono::{DateTime, Utc};
 
 use crate::{
     block::BlockMetadata,
+    connection::Graph,
     mapping::{
         attributes, entity_version, prop_filter, triple, AttributeNode, EntityFilter, Triple,
     },
@@ -25,7 +26,7 @@ pub struct EntityNode {
 impl EntityNode {
     pub fn delete(
         self,
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         block: &BlockMetadata,
         space_id: impl Into<String>,
         space_version: impl Into<String>,
@@ -35,7 +36,7 @@ impl EntityNode {
 
     pub fn get_attributes(
         &self,
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         space_id: impl Into<String>,
         space_version: Option<String>,
     ) -> attributes::FindOneQuery {
@@ -44,7 +45,7 @@ impl EntityNode {
 
     pub fn get_outbound_relations<T>(
         &self,
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         space_id: impl Into<String>,
         space_version: Option<String>,
     ) -> relation::FindManyQuery<T> {
@@ -59,7 +60,7 @@ impl EntityNode {
 
     pub fn get_inbound_relations<T>(
         &self,
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         space_id: impl Into<String>,
         space_version: Option<String>,
     ) -> relation::FindManyQuery<T> {
@@ -74,7 +75,7 @@ impl EntityNode {
 
     pub fn set_attribute(
         &self,
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         block: &BlockMetadata,
         space_id: impl Into<String>,
         space_version: impl Into<String>,
@@ -91,7 +92,7 @@ impl EntityNode {
 
     pub fn set_attributes<T>(
         &self,
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         block: &BlockMetadata,
         space_id: impl Into<String>,
         space_version: impl Into<String>,
@@ -108,7 +109,7 @@ impl EntityNode {
     }
 
     /// Get all the versions that have been applied to this entity
-    pub fn versions(&self, neo4j: &neo4rs::Graph) -> entity_version::FindManyQuery {
+    pub fn versions(&self, neo4j: &Graph) -> entity_version::FindManyQuery {
         entity_version::FindManyQuery::new(neo4j.clone(), self.id.clone())
     }
 }
@@ -154,6 +155,35 @@ impl Display for EntityNodeRef {
     }
 }
 
+/// Type of an entity field backed by relations (i.e.: a field declared with
+/// `#[grc20(relation = ...)]` in the `entity` macro). The targets of the relations
+/// are ordered by relation index.
+pub trait RelationField: Default {
+    fn from_targets(targets: Vec<EntityNodeRef>) -> Self;
+
+    fn targets(&self) -> Vec<&EntityNodeRef>;
+}
+
+impl RelationField for Vec<EntityNodeRef> {
+    fn from_targets(targets: Vec<EntityNodeRef>) -> Self {
+        targets
+    }
+
+    fn targets(&self) -> Vec<&EntityNodeRef> {
+        self.iter().collect()
+    }
+}
+
+impl RelationField for Option<EntityNodeRef> {
+    fn from_targets(targets: Vec<EntityNodeRef>) -> Self {
+        targets.into_iter().next()
+    }
+
+    fn targets(&self) -> Vec<&EntityNodeRef> {
+        self.iter().collect()
+    }
+}
+
 /// High level model encapsulating an entity with its attributes and types.
 #[derive(Clone, Debug, PartialEq)]
 pub struct Entity<T> {
@@ -194,7 +224,7 @@ impl<T> Entity<T> {
 
     pub fn get_outbound_relations<U>(
         &self,
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         space_id: impl Into<String>,
         space_version: Option<String>,
     ) -> relation::FindManyQuery<U> {
@@ -209,7 +239,7 @@ impl<T> Entity<T> {
 
     pub fn get_inbound_relations<U>(
         &self,
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         space_id: impl Into<String>,
         space_version: Option<String>,
     ) -> relation::FindManyQuery<U> {
@@ -224,7 +254,7 @@ impl<T> Entity<T> {
 
     pub fn insert(
         self,
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         block: &BlockMetadata,
         space_id: impl Into<String>,
         space_version: impl Into<String>,
@@ -1,6 +1,7 @@
 use futures::{Stream, StreamExt, TryStreamExt};
 
 use crate::{
+    connection::{with_query_policy, Graph},
     entity::utils::MatchEntity,
     error::DatabaseError,
     mapping::{
@@ -12,7 +13,7 @@ use crate::{
 use super::{Entity, EntityFilter, EntityNode};
 
 pub struct PrefilteredSemanticSearchQuery<T> {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     vector: Vec<f64>,
     filter: EntityFilter,
     space_id: Option<PropFilter<String>>,
@@ -25,7 +26,7 @@ pub struct PrefilteredSemanticSearchQuery<T> {
 }
 
 impl<T> PrefilteredSemanticSearchQuery<T> {
-    pub fn new(neo4j: &neo4rs::Graph, vector: Vec<f64>) -> Self {
+    pub fn new(neo4j: &Graph, vector: Vec<f64>) -> Self {
         Self {
             neo4j: neo4j.clone(),
             vector,
@@ -132,18 +133,18 @@ impl QueryStream<SemanticSearchResult<EntityNode>> for PrefilteredSemanticSearch
             score: f64,
         }
 
-        Ok(self
-            .neo4j
-            .execute(query.build())
-            .await?
-            .into_stream_as::<RowResult>()
-            .map_err(DatabaseError::from)
-            .and_then(|row| async move {
-                Ok(SemanticSearchResult {
-                    entity: row.e,
-                    score: row.score,
-                })
-            }))
+        Ok(
+            with_query_policy(&self.neo4j, || self.neo4j.execute(query.build()))
+                .await?
+                .into_stream_as::<RowResult>()
+                .map_err(DatabaseError::from)
+                .and_then(|row| async move {
+                    Ok(SemanticSearchResult {
+                        entity: row.e,
+                        score: row.score,
+                    })
+                }),
+        )
     }
 }
 
@@ -186,9 +187,7 @@ impl<T: FromAttributes> QueryStream<SemanticSearchResult<Entity<T>>>
             score: f64,
         }
 
-        let stream = self
-            .neo4j
-            .execute(query.build())
+        let stream = with_query_policy(&self.neo4j, || self.neo4j.execute(query.build()))
             .await?
             .into_stream_as::<RowResult>()
             .map_err(DatabaseError::from)
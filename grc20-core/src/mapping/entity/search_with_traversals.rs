@@ -1,6 +1,7 @@
 use futures::{Stream, StreamExt, TryStreamExt};
 
 use crate::{
+    connection::{with_query_policy, Graph},
     entity::utils::MatchEntity,
     error::DatabaseError,
     mapping::{
@@ -12,7 +13,7 @@ use crate::{
 use super::{Entity, EntityFilter, EntityNode};
 
 pub struct SearchWithTraversals<T> {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     vector: Vec<f64>,
     filters: Vec<EntityFilter>,
     space_id: Option<PropFilter<String>>,
@@ -25,7 +26,7 @@ pub struct SearchWithTraversals<T> {
 }
 
 impl<T> SearchWithTraversals<T> {
-    pub fn new(neo4j: &neo4rs::Graph, vector: Vec<f64>) -> Self {
+    pub fn new(neo4j: &Graph, vector: Vec<f64>) -> Self {
         Self {
             neo4j: neo4j.clone(),
             vector,
@@ -133,13 +134,13 @@ impl QueryStream<SearchWithTraversalsResult<EntityNode>> for SearchWithTraversal
             e: EntityNode,
         }
 
-        Ok(self
-            .neo4j
-            .execute(query.build())
-            .await?
-            .into_stream_as::<RowResult>()
-            .map_err(DatabaseError::from)
-            .and_then(|row| async move { Ok(SearchWithTraversalsResult { entity: row.e }) }))
+        Ok(
+            with_query_policy(&self.neo4j, || self.neo4j.execute(query.build()))
+                .await?
+                .into_stream_as::<RowResult>()
+                .map_err(DatabaseError::from)
+                .and_then(|row| async move { Ok(SearchWithTraversalsResult { entity: row.e }) }),
+        )
     }
 }
 
@@ -181,9 +182,7 @@ impl<T: FromAttributes> QueryStream<SearchWithTraversalsResult<Entity<T>>>
             types: Vec<EntityNode>,
         }
 
-        let stream = self
-            .neo4j
-            .execute(query.build())
+        let stream = with_query_policy(&self.neo4j, || self.neo4j.execute(query.build()))
             .await?
             .into_stream_as::<RowResult>()
             .map_err(DatabaseError::from)
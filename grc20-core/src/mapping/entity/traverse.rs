@@ -0,0 +1,623 @@
+use futures::{Stream, StreamExt, TryStreamExt};
+
+use crate::{
+    connection::{with_query_policy, Graph},
+    entity::utils::MatchEntity,
+    error::DatabaseError,
+    mapping::{
+        query_utils::{
+            query_builder::{MatchQuery, QueryBuilder, Subquery, WhereClause},
+            RelationDirection, VersionFilter,
+        },
+        AttributeNode, EntityNodeRef, FromAttributes, PropFilter, QueryStream, RelationEdge,
+    },
+};
+
+use super::{merge::canonical_entity, Entity, EntityFilter, EntityNode};
+
+/// A single hop of a traversal. A hop follows one or more relations (see
+/// [`TraversalHop::length`]) matching the relation type filter in the given
+/// direction.
+#[derive(Clone, Debug)]
+pub struct TraversalHop {
+    relation_type: Option<PropFilter<String>>,
+    direction: RelationDirection,
+    min_length: usize,
+    max_length: usize,
+    space_id: Option<PropFilter<String>>,
+    /// Filter applied to the node reached at the end of the hop
+    filter: Option<EntityFilter>,
+}
+
+impl Default for TraversalHop {
+    fn default() -> Self {
+        Self {
+            relation_type: None,
+            direction: RelationDirection::From,
+            min_length: 1,
+            max_length: 1,
+            space_id: None,
+            filter: None,
+        }
+    }
+}
+
+impl TraversalHop {
+    /// Follow outbound relations of type `relation_type`
+    pub fn outbound(relation_type: impl Into<PropFilter<String>>) -> Self {
+        Self::default()
+            .relation_type(relation_type)
+            .direction(RelationDirection::From)
+    }
+
+    /// Follow inbound relations of type `relation_type`
+    pub fn inbound(relation_type: impl Into<PropFilter<String>>) -> Self {
+        Self::default()
+            .relation_type(relation_type)
+            .direction(RelationDirection::To)
+    }
+
+    pub fn relation_type(mut self, relation_type: impl Into<PropFilter<String>>) -> Self {
+        self.relation_type = Some(relation_type.into());
+        self
+    }
+
+    /// Direction of the relations to follow. `RelationDirection::From` follows
+    /// outbound relations (i.e.: the current node is the `from` of the relation)
+    /// while `RelationDirection::To` follows inbound relations.
+    pub fn direction(mut self, direction: RelationDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Make the hop variable length, following between `min` and `max` relations
+    /// (inclusive). By default, a hop follows exactly one relation.
+    pub fn length(mut self, min: usize, max: usize) -> Self {
+        self.min_length = min;
+        self.max_length = max.max(min);
+        self
+    }
+
+    pub fn space_id(mut self, space_id: impl Into<PropFilter<String>>) -> Self {
+        self.space_id = Some(space_id.into());
+        self
+    }
+
+    /// Filter the nodes reached by this hop. For variable length hops, only the
+    /// last node of the hop is filtered.
+    pub fn filter(mut self, filter: EntityFilter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    fn subquery(
+        &self,
+        idx: usize,
+        default_space_id: &Option<PropFilter<String>>,
+        version: &VersionFilter,
+        terminal_filter: Option<&EntityFilter>,
+    ) -> QueryBuilder {
+        let path_var = format!("p{idx}");
+        let curr_node_var = format!("n{idx}");
+        let next_node_var = format!("n{}", idx + 1);
+        let rel_var = format!("r{idx}");
+
+        let pattern = match self.direction {
+            RelationDirection::From => format!(
+                "({curr_node_var}) -[:RELATION*{}..{}]-> ({next_node_var}:Entity)",
+                self.min_length, self.max_length
+            ),
+            RelationDirection::To => format!(
+                "({curr_node_var}) <-[:RELATION*{}..{}]- ({next_node_var}:Entity)",
+                self.min_length, self.max_length
+            ),
+        };
+
+        // Filters applied to every relation of the hop
+        let rel_filters = std::iter::once(version.subquery(&rel_var))
+            .chain(
+                self.relation_type
+                    .as_ref()
+                    .map(|relation_type| relation_type.subquery(&rel_var, "relation_type", None)),
+            )
+            .chain(
+                self.space_id
+                    .as_ref()
+                    .or(default_space_id.as_ref())
+                    .map(|space_id| space_id.subquery(&rel_var, "space_id", None)),
+            )
+            .collect::<Vec<_>>();
+
+        let rel_clause = WhereClause {
+            clauses: vec![format!(
+                "ALL({rel_var} IN relationships({path_var}) WHERE {})",
+                rel_filters
+                    .iter()
+                    .flat_map(|filter| filter.clauses.iter().cloned())
+                    .collect::<Vec<_>>()
+                    .join(" AND ")
+            )],
+            params: rel_filters
+                .into_iter()
+                .flat_map(|filter| filter.params)
+                .collect(),
+        };
+
+        // The terminal filter uses its own parameter names so that it does not clash
+        // with the hop filter when both filter the reached node by id.
+        let terminal_id_expr = format!("{next_node_var}.id");
+
+        let match_query = MatchQuery::new(format!("{path_var} = {pattern}"))
+            .r#where(rel_clause)
+            // Apply the id filters on the reached node (if any)
+            .where_opt(
+                self.filter
+                    .as_ref()
+                    .and_then(|filter| filter.id.as_ref())
+                    .map(|id| id.subquery(&next_node_var, "id", None)),
+            )
+            .where_opt(
+                terminal_filter
+                    .and_then(|filter| filter.id.as_ref())
+                    .map(|id| {
+                        id.subquery(
+                            &format!("{next_node_var}_terminal"),
+                            "id",
+                            Some(&terminal_id_expr),
+                        )
+                    }),
+            );
+
+        QueryBuilder::default()
+            .subquery(match_query)
+            .subquery_opt(
+                self.filter
+                    .as_ref()
+                    .map(|filter| filter.subquery(&next_node_var)),
+            )
+            .subquery_opt(terminal_filter.map(|filter| filter.subquery(&next_node_var)))
+    }
+}
+
+/// Path followed by a traversal to reach an entity
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize)]
+pub struct TraversalPath {
+    /// IDs of the entities along the path, starting with the start entity and
+    /// ending with the reached entity
+    pub nodes: Vec<String>,
+    /// Relations followed along the path, in traversal order
+    pub relations: Vec<RelationEdge<EntityNodeRef>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraverseResult<T> {
+    pub entity: T,
+    pub path: TraversalPath,
+}
+
+pub struct TraverseQuery<T> {
+    neo4j: Graph,
+    start: EntityFilter,
+    hops: Vec<TraversalHop>,
+    filter: Option<EntityFilter>,
+    limit: usize,
+    skip: Option<usize>,
+
+    space_id: Option<PropFilter<String>>,
+    version: VersionFilter,
+
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<T> TraverseQuery<T> {
+    pub(super) fn new(neo4j: &Graph, start: EntityFilter) -> Self {
+        Self {
+            neo4j: neo4j.clone(),
+            start,
+            hops: Vec::new(),
+            filter: None,
+            limit: 100,
+            skip: None,
+            space_id: None,
+            version: VersionFilter::default(),
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Add a hop to the traversal. Hops are followed in the order they are added.
+    pub fn hop(mut self, hop: TraversalHop) -> Self {
+        self.hops.push(hop);
+        self
+    }
+
+    pub fn hop_mut(&mut self, hop: TraversalHop) {
+        self.hops.push(hop);
+    }
+
+    pub fn hops(mut self, hops: impl IntoIterator<Item = TraversalHop>) -> Self {
+        self.hops.extend(hops);
+        self
+    }
+
+    /// Filter the entities reached at the end of the traversal
+    pub fn filter(mut self, filter: EntityFilter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub fn skip(mut self, skip: usize) -> Self {
+        self.skip = Some(skip);
+        self
+    }
+
+    /// Restrict the traversal to relations (and attributes of the reached entities)
+    /// in the given space(s). Can be overridden per hop with [`TraversalHop::space_id`].
+    pub fn space_id(mut self, space_id: impl Into<PropFilter<String>>) -> Self {
+        self.space_id = Some(space_id.into());
+        self
+    }
+
+    pub fn version(mut self, space_version: String) -> Self {
+        self.version.version_mut(space_version);
+        self
+    }
+
+    pub fn version_opt(mut self, space_version: Option<String>) -> Self {
+        self.version.version_opt(space_version);
+        self
+    }
+
+    fn terminal_var(&self) -> String {
+        format!("n{}", self.hops.len())
+    }
+
+    fn subquery(&self) -> QueryBuilder {
+        let last_hop = self.hops.len().saturating_sub(1);
+
+        let query = QueryBuilder::default()
+            .subquery(
+                MatchQuery::new("(n0:Entity)")
+                    .where_opt(
+                        self.start
+                            .id
+                            .as_ref()
+                            .map(|id| id.subquery("n0", "id", None)),
+                    )
+                    .where_opt(
+                        self.filter
+                            .as_ref()
+                            .filter(|_| self.hops.is_empty())
+                            .and_then(|filter| filter.id.as_ref())
+                            .map(|id| id.subquery("n0_terminal", "id", Some("n0.id"))),
+                    ),
+            )
+            .subquery(self.start.subquery("n0"))
+            // Without hops, the start entities are the terminal entities
+            .subquery_opt(
+                self.filter
+                    .as_ref()
+                    .filter(|_| self.hops.is_empty())
+                    .map(|filter| filter.subquery("n0")),
+            );
+
+        let query = self
+            .hops
+            .iter()
+            .enumerate()
+            .fold(query, |query, (idx, hop)| {
+                query.subquery(hop.subquery(
+                    idx,
+                    &self.space_id,
+                    &self.version,
+                    if idx == last_hop {
+                        self.filter.as_ref()
+                    } else {
+                        None
+                    },
+                ))
+            });
+
+        // Without hops, the traversal simply returns the start entities with an
+        // empty path.
+        let (path_nodes, path_relations) = if self.hops.is_empty() {
+            ("[n0.id]".to_string(), "[]".to_string())
+        } else {
+            (
+                std::iter::once("[n IN nodes(p0) | n.id]".to_string())
+                    .chain(
+                        (1..self.hops.len())
+                            .map(|idx| format!("[n IN tail(nodes(p{idx})) | n.id]")),
+                    )
+                    .collect::<Vec<_>>()
+                    .join(" + "),
+                (0..self.hops.len())
+                    .map(|idx| {
+                        format!("[r IN relationships(p{idx}) | r{{.*, from: startNode(r).id, to: endNode(r).id}}]")
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" + "),
+            )
+        };
+
+        query
+            // Reached entities that were merged resolve to their canonical entity,
+            // the path keeps the entities and relations that were followed
+            .with(
+                vec![
+                    format!(
+                        "{} AS {}",
+                        canonical_entity(&self.terminal_var()),
+                        self.terminal_var()
+                    ),
+                    format!("{path_nodes} AS path_nodes"),
+                    format!("{path_relations} AS path_relations"),
+                ],
+                QueryBuilder::default(),
+            )
+            .skip_opt(self.skip)
+            .limit(self.limit)
+    }
+}
+
+impl QueryStream<TraverseResult<EntityNode>> for TraverseQuery<EntityNode> {
+    async fn send(
+        self,
+    ) -> Result<impl Stream<Item = Result<TraverseResult<EntityNode>, DatabaseError>>, DatabaseError>
+    {
+        let query = self.subquery().r#return(format!(
+            "{} AS e, path_nodes, path_relations",
+            self.terminal_var()
+        ));
+
+        if cfg!(debug_assertions) || cfg!(test) {
+            tracing::info!(
+                "entity_node::TraverseQuery::<EntityNode>:\n{}\nparams:{:?}",
+                query.compile(),
+                query.params()
+            );
+        };
+
+        #[derive(Debug, serde::Deserialize)]
+        struct RowResult {
+            e: EntityNode,
+            path_nodes: Vec<String>,
+            path_relations: Vec<RelationEdge<EntityNodeRef>>,
+        }
+
+        Ok(
+            with_query_policy(&self.neo4j, || self.neo4j.execute(query.build()))
+                .await?
+                .into_stream_as::<RowResult>()
+                .map_err(DatabaseError::from)
+                .and_then(|row| async move {
+                    Ok(TraverseResult {
+                        entity: row.e,
+                        path: TraversalPath {
+                            nodes: row.path_nodes,
+                            relations: row.path_relations,
+                        },
+                    })
+                }),
+        )
+    }
+}
+
+impl<T: FromAttributes> QueryStream<TraverseResult<Entity<T>>> for TraverseQuery<Entity<T>> {
+    async fn send(
+        self,
+    ) -> Result<impl Stream<Item = Result<TraverseResult<Entity<T>>, DatabaseError>>, DatabaseError>
+    {
+        let match_entity = MatchEntity::new(&self.space_id, &self.version);
+        let terminal_var = self.terminal_var();
+
+        let query = self.subquery().with(
+            vec![
+                terminal_var.clone(),
+                "path_nodes".to_string(),
+                "path_relations".to_string(),
+            ],
+            match_entity.chain(
+                &terminal_var,
+                "attrs",
+                "types",
+                Some(vec!["path_nodes".to_string(), "path_relations".to_string()]),
+                format!("RETURN {terminal_var}{{.*, attrs: attrs, types: types, path_nodes: path_nodes, path_relations: path_relations}}"),
+            ),
+        );
+
+        if cfg!(debug_assertions) || cfg!(test) {
+            tracing::info!(
+                "entity_node::TraverseQuery::<Entity<T>>:\n{}\nparams:{:?}",
+                query.compile(),
+                query.params
+            );
+        };
+
+        #[derive(Debug, serde::Deserialize)]
+        struct RowResult {
+            #[serde(flatten)]
+            node: EntityNode,
+            attrs: Vec<AttributeNode>,
+            types: Vec<EntityNode>,
+            path_nodes: Vec<String>,
+            path_relations: Vec<RelationEdge<EntityNodeRef>>,
+        }
+
+        let stream = with_query_policy(&self.neo4j, || self.neo4j.execute(query.build()))
+            .await?
+            .into_stream_as::<RowResult>()
+            .map_err(DatabaseError::from)
+            .map(|row_result| {
+                row_result.and_then(|row| {
+                    T::from_attributes(row.attrs.into())
+                        .map(|data| TraverseResult {
+                            entity: Entity {
+                                node: row.node,
+                                attributes: data,
+                                types: row.types.into_iter().map(|t| t.id).collect(),
+                            },
+                            path: TraversalPath {
+                                nodes: row.path_nodes,
+                                relations: row.path_relations,
+                            },
+                        })
+                        .map_err(DatabaseError::from)
+                })
+            });
+
+        Ok(stream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::TryStreamExt;
+
+    use crate::{
+        block::BlockMetadata,
+        mapping::{
+            entity::{traverse, TraversalHop},
+            prop_filter, relation, triple, EntityFilter, EntityNode, EntityNodeRef, Query,
+            QueryStream, RelationEdge, Triple,
+        },
+    };
+
+    #[tokio::test]
+    async fn test_traverse() {
+        // Setup a local Neo 4J container for testing. NOTE: docker service must be running.
+        let (_container, neo4j) = crate::test_utils::setup_neo4j().await;
+
+        let block = &BlockMetadata::default();
+
+        triple::insert_many(&neo4j, block, "ROOT", "0")
+            .triples(vec![
+                Triple::new("org", "name", "Org"),
+                Triple::new("alice", "name", "Alice"),
+                Triple::new("bob", "name", "Bob"),
+                Triple::new("paper1", "name", "Paper 1"),
+                Triple::new("paper2", "name", "Paper 2"),
+                Triple::new("paper3", "name", "Paper 3"),
+            ])
+            .send()
+            .await
+            .expect("Failed to insert triples");
+
+        relation::insert_many::<RelationEdge<EntityNodeRef>>(&neo4j, block, "ROOT", "0")
+            .relations(vec![
+                RelationEdge::new("r1", "alice", "org", "works_at", "a0"),
+                RelationEdge::new("r2", "paper1", "alice", "authored_by", "a0"),
+                RelationEdge::new("r3", "paper2", "alice", "authored_by", "a0"),
+                RelationEdge::new("r4", "paper3", "bob", "authored_by", "a0"),
+            ])
+            .send()
+            .await
+            .expect("Failed to insert relations");
+
+        // Papers authored by people working at `org`
+        let mut results = traverse::<EntityNode>(
+            &neo4j,
+            EntityFilter::default().id(prop_filter::value("org")),
+        )
+        .hop(TraversalHop::inbound("works_at"))
+        .hop(TraversalHop::inbound("authored_by"))
+        .send()
+        .await
+        .expect("Failed to traverse")
+        .try_collect::<Vec<_>>()
+        .await
+        .expect("Failed to collect results");
+
+        results.sort_by(|a, b| a.entity.id.cmp(&b.entity.id));
+
+        assert_eq!(
+            results
+                .iter()
+                .map(|result| result.entity.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["paper1", "paper2"]
+        );
+        assert_eq!(results[0].path.nodes, vec!["org", "alice", "paper1"]);
+        assert_eq!(
+            results[0]
+                .path
+                .relations
+                .iter()
+                .map(|rel| rel.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["r1", "r2"]
+        );
+
+        // Terminal filter
+        let results = traverse::<EntityNode>(
+            &neo4j,
+            EntityFilter::default().id(prop_filter::value("org")),
+        )
+        .hop(TraversalHop::inbound("works_at"))
+        .hop(TraversalHop::inbound("authored_by"))
+        .filter(EntityFilter::default().id(prop_filter::value("paper2")))
+        .send()
+        .await
+        .expect("Failed to traverse")
+        .try_collect::<Vec<_>>()
+        .await
+        .expect("Failed to collect results");
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].entity.id, "paper2");
+    }
+
+    #[tokio::test]
+    async fn test_traverse_variable_length() {
+        // Setup a local Neo 4J container for testing. NOTE: docker service must be running.
+        let (_container, neo4j) = crate::test_utils::setup_neo4j().await;
+
+        let block = &BlockMetadata::default();
+
+        triple::insert_many(&neo4j, block, "ROOT", "0")
+            .triples(vec![
+                Triple::new("a", "name", "A"),
+                Triple::new("b", "name", "B"),
+                Triple::new("c", "name", "C"),
+                Triple::new("d", "name", "D"),
+            ])
+            .send()
+            .await
+            .expect("Failed to insert triples");
+
+        relation::insert_many::<RelationEdge<EntityNodeRef>>(&neo4j, block, "ROOT", "0")
+            .relations(vec![
+                RelationEdge::new("r1", "a", "b", "parent", "a0"),
+                RelationEdge::new("r2", "b", "c", "parent", "a0"),
+                RelationEdge::new("r3", "c", "d", "parent", "a0"),
+            ])
+            .send()
+            .await
+            .expect("Failed to insert relations");
+
+        let mut results =
+            traverse::<EntityNode>(&neo4j, EntityFilter::default().id(prop_filter::value("a")))
+                .hop(TraversalHop::outbound("parent").length(1, 2))
+                .send()
+                .await
+                .expect("Failed to traverse")
+                .try_collect::<Vec<_>>()
+                .await
+                .expect("Failed to collect results");
+
+        results.sort_by(|a, b| a.entity.id.cmp(&b.entity.id));
+
+        assert_eq!(
+            results
+                .iter()
+                .map(|result| result.entity.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["b", "c"]
+        );
+        assert_eq!(results[1].path.nodes, vec!["a", "b", "c"]);
+    }
+}
@@ -1,7 +1,11 @@
 use futures::TryStreamExt;
 use serde::Deserialize;
 
-use crate::{error::DatabaseError, indexer_ids};
+use crate::{
+    connection::{with_query_policy, Graph},
+    error::DatabaseError,
+    indexer_ids,
+};
 
 use super::{
     query_utils::query_builder::{MatchQuery, QueryBuilder, Subquery},
@@ -16,17 +20,17 @@ pub struct EntityVersion {
 }
 
 pub struct FindManyQuery {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     entity_id: String,
     space_id: Option<PropFilter<String>>,
 }
 
-pub fn find_many(neo4j: neo4rs::Graph, entity_id: impl Into<String>) -> FindManyQuery {
+pub fn find_many(neo4j: Graph, entity_id: impl Into<String>) -> FindManyQuery {
     FindManyQuery::new(neo4j, entity_id.into())
 }
 
 impl FindManyQuery {
-    pub fn new(neo4j: neo4rs::Graph, entity_id: String) -> Self {
+    pub fn new(neo4j: Graph, entity_id: String) -> Self {
         Self {
             neo4j,
             entity_id,
@@ -78,8 +82,7 @@ impl Query<Vec<EntityVersion>> for FindManyQuery {
             );
         }
 
-        self.neo4j
-            .execute(query.build())
+        with_query_policy(&self.neo4j, || self.neo4j.execute(query.build()))
             .await?
             .into_stream_as::<EntityVersion>()
             .map_err(DatabaseError::from)
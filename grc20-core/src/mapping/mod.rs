@@ -13,7 +13,9 @@ pub mod value;
 pub use aggregation::AggregationDirection;
 pub use attribute_node::AttributeNode;
 pub use attributes::{Attributes, FromAttributes, IntoAttributes};
-pub use entity::{Entity, EntityFilter, EntityNode, EntityNodeRef, EntityRelationFilter};
+pub use entity::{
+    Entity, EntityFilter, EntityNode, EntityNodeRef, EntityRelationFilter, RelationField,
+};
 pub use entity_version::EntityVersion;
 pub use error::TriplesConversionError;
 pub use pluralism::Pluralism;
@@ -24,9 +26,9 @@ pub use query_utils::{
 };
 pub use relation::{Relation, RelationEdge};
 pub use triple::Triple;
-pub use value::{Options, Value, ValueType};
+pub use value::{Grc20Value, Options, Value, ValueType};
 
-use crate::{error::DatabaseError, indexer_ids};
+use crate::{connection::Graph, error::DatabaseError, indexer_ids};
 
 pub const EFFECTIVE_SEARCH_RATIO: f64 = 1000000.0;
 
@@ -34,8 +36,14 @@ pub fn new_version_index(block_number: u64, idx: usize) -> String {
     format!("{block_number:016}:{idx:04}")
 }
 
+/// Version index following all the edits of the block, i.e.: the version at which to read
+/// the knowledge graph to get its state at the end of the block
+pub fn block_version_index(block_number: u64) -> String {
+    new_version_index(block_number, 9999)
+}
+
 pub async fn get_version_index(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     version_id: impl Into<String>,
 ) -> Result<Option<String>, DatabaseError> {
     Ok(triple::find_one(
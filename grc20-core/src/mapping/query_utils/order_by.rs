@@ -21,6 +21,13 @@ pub fn desc(field_name: impl Into<String>) -> FieldOrderBy {
 }
 
 impl FieldOrderBy {
+    pub fn new(field_name: impl Into<String>, order_direction: OrderDirection) -> Self {
+        FieldOrderBy {
+            field_name: field_name.into(),
+            order_direction,
+        }
+    }
+
     pub(crate) fn subquery(&self, node_var: impl Into<String>) -> impl Subquery {
         let node_var = node_var.into();
         let mut query = QueryBuilder::default().subquery(MatchQuery::new(format!(
@@ -28,12 +35,19 @@ impl FieldOrderBy {
             self.field_name
         )));
 
+        // Values are stored as strings, numbers are cast so that they are not ordered
+        // lexicographically (e.g.: "9" after "30"). Other value types are ordered as
+        // strings, which is chronological for times with the same offset.
+        let value = format!(
+            r#"CASE {node_var}_order_by.value_type WHEN "NUMBER" THEN toFloat({node_var}_order_by.value) ELSE {node_var}_order_by.value END"#
+        );
+
         match self.order_direction {
             OrderDirection::Asc => {
-                query = query.subquery(format!("ORDER BY {node_var}_order_by.value"));
+                query = query.subquery(format!("ORDER BY {value}"));
             }
             OrderDirection::Desc => {
-                query = query.subquery(format!("ORDER BY {node_var}_order_by.value DESC"));
+                query = query.subquery(format!("ORDER BY {value} DESC"));
             }
         }
 
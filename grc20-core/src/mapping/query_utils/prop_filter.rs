@@ -157,6 +157,27 @@ impl<T> PropFilter<T> {
     }
 }
 
+impl<T: PartialOrd> PropFilter<T> {
+    /// Returns true if `value` satisfies all the conditions of the filter. This is
+    /// the in-memory equivalent of the [WhereClause] returned by [PropFilter::subquery].
+    pub fn matches(&self, value: &T) -> bool {
+        self.value.as_ref().is_none_or(|v| value == v)
+            && self.value_gt.as_ref().is_none_or(|v| value > v)
+            && self.value_gte.as_ref().is_none_or(|v| value >= v)
+            && self.value_lt.as_ref().is_none_or(|v| value < v)
+            && self.value_lte.as_ref().is_none_or(|v| value <= v)
+            && self.value_not.as_ref().is_none_or(|v| value != v)
+            && self
+                .value_in
+                .as_ref()
+                .is_none_or(|values| values.contains(value))
+            && self
+                .value_not_in
+                .as_ref()
+                .is_none_or(|values| !values.contains(value))
+    }
+}
+
 impl<T: Clone + Into<BoltType>> PropFilter<T> {
     /// Compiles the attribute filter into a [WhereClause] Neo4j subquery that will apply
     /// a filter on the `key` field of the `node_var` node(s) (i.e.: `{node_var}.{key}`).
@@ -1,7 +1,13 @@
-use crate::{block::BlockMetadata, error::DatabaseError, indexer_ids, mapping::Query};
+use crate::{
+    block::BlockMetadata,
+    connection::{with_query_policy, Graph},
+    error::DatabaseError,
+    indexer_ids,
+    mapping::Query,
+};
 
 pub struct DeleteManyQuery {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     block: BlockMetadata,
     space_id: String,
     space_version: String,
@@ -10,7 +16,7 @@ pub struct DeleteManyQuery {
 
 impl DeleteManyQuery {
     pub(super) fn new(
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         block: &BlockMetadata,
         space_id: String,
         space_version: String,
@@ -71,6 +77,6 @@ impl Query<()> for DeleteManyQuery {
             .param("block_timestamp", self.block.timestamp.to_rfc3339())
             .param("block_number", self.block.block_number.to_string());
 
-        Ok(self.neo4j.run(query).await?)
+        with_query_policy(&self.neo4j, || self.neo4j.run(query.clone())).await
     }
 }
@@ -1,6 +1,8 @@
 use futures::{Stream, StreamExt, TryStreamExt};
 
 use crate::{
+    connection::{with_query_policy, Graph},
+    entity::merge::{canonical_entity, match_merged},
     error::DatabaseError,
     mapping::{
         query_utils::{
@@ -14,7 +16,7 @@ use crate::{
 use super::{utils::RelationFilter, FindManyToQuery, Relation, RelationEdge};
 
 pub struct FindManyQuery<T> {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     filter: RelationFilter,
 
     space_id: Option<PropFilter<String>>,
@@ -27,7 +29,7 @@ pub struct FindManyQuery<T> {
 }
 
 impl<T> FindManyQuery<T> {
-    pub(super) fn new(neo4j: &neo4rs::Graph) -> Self {
+    pub(super) fn new(neo4j: &Graph) -> Self {
         Self {
             neo4j: neo4j.clone(),
             filter: RelationFilter::default(),
@@ -80,6 +82,23 @@ impl<T> FindManyQuery<T> {
 
     fn relation_edge_subquery(&self) -> QueryBuilder {
         QueryBuilder::default()
+            // Apply from.id filter (ids of merged entities match their canonical entity
+            // and vice versa)
+            .subquery_opt(
+                self.filter
+                    .from_
+                    .as_ref()
+                    .and_then(|from_filter| from_filter.id.as_ref())
+                    .map(|from_id| match_merged("from", from_id)),
+            )
+            // Apply to.id filter
+            .subquery_opt(
+                self.filter
+                    .to_
+                    .as_ref()
+                    .and_then(|to_filter| to_filter.id.as_ref())
+                    .map(|to_id| match_merged("to", to_id)),
+            )
             .subquery(
                 MatchQuery::new("(from:Entity) -[r:RELATION]-> (to:Entity)")
                     // Apply edge id filter
@@ -89,22 +108,6 @@ impl<T> FindManyQuery<T> {
                             .as_ref()
                             .map(|id| id.subquery("r", "id", None)),
                     )
-                    // Apply from.id filter
-                    .where_opt(
-                        self.filter
-                            .from_
-                            .as_ref()
-                            .and_then(|from_filter| from_filter.id.clone())
-                            .map(|from_id| from_id.subquery("from", "id", None)),
-                    )
-                    // Apply to.id filter
-                    .where_opt(
-                        self.filter
-                            .to_
-                            .as_ref()
-                            .and_then(|to_filter| to_filter.id.clone())
-                            .map(|to_id| to_id.subquery("to", "id", None)),
-                    )
                     // Apply edge relation_type filter
                     .where_opt(
                         self.filter
@@ -122,10 +125,25 @@ impl<T> FindManyQuery<T> {
                     // Apply edge version filter
                     .r#where(self.version.subquery("r")),
             )
+            // Relations from or to merged entities resolve to the canonical entities
+            .with(
+                vec![
+                    "r".to_string(),
+                    format!("{} AS from", canonical_entity("from")),
+                    format!("{} AS to", canonical_entity("to")),
+                ],
+                QueryBuilder::default(),
+            )
             .subquery(self.filter.subquery("r", "from", "to"))
-            .subquery("ORDER BY r.index")
-            .limit(self.limit)
+            .with(
+                vec!["r".to_string(), "from".to_string(), "to".to_string()],
+                QueryBuilder::default(),
+            )
+            // Relations are ordered by their fractional index (ties are broken by id
+            // so that pagination is stable)
+            .subquery("ORDER BY r.index, r.id")
             .skip_opt(self.skip)
+            .limit(self.limit)
     }
 
     fn full_relation_subquery(&self) -> QueryBuilder {
@@ -165,8 +183,7 @@ impl QueryStream<RelationEdge<EntityNodeRef>> for FindManyQuery<RelationEdge<Ent
             );
         };
 
-        Ok(neo4j
-            .execute(query.build())
+        Ok(with_query_policy(&neo4j, || neo4j.execute(query.build()))
             .await?
             .into_stream_as::<RelationEdge<EntityNodeRef>>()
             .map_err(DatabaseError::from))
@@ -190,8 +207,7 @@ impl QueryStream<RelationEdge<EntityNode>> for FindManyQuery<RelationEdge<Entity
             );
         };
 
-        Ok(neo4j
-            .execute(query.build())
+        Ok(with_query_policy(&neo4j, || neo4j.execute(query.build()))
             .await?
             .into_stream_as::<RelationEdge<EntityNode>>()
             .map_err(DatabaseError::from))
@@ -213,7 +229,11 @@ impl<T: FromAttributes> QueryStream<Relation<T, EntityNodeRef>>
                 "to".to_string(),
                 "COLLECT(DISTINCT n{.*}) AS attrs".to_string(),
             ],
-            "RETURN r{.*, from: from.id, to: to.id, attributes: attrs} as r",
+            vec![
+                // Aggregating the attributes does not preserve the order of the relations
+                "ORDER BY r.index, r.id".to_string(),
+                "RETURN r{.*, from: from.id, to: to.id, attributes: attrs} as r".to_string(),
+            ],
         );
 
         if cfg!(debug_assertions) || cfg!(test) {
@@ -230,9 +250,7 @@ impl<T: FromAttributes> QueryStream<Relation<T, EntityNodeRef>>
             attributes: Vec<AttributeNode>,
         }
 
-        let stream = self
-            .neo4j
-            .execute(query.build())
+        let stream = with_query_policy(&self.neo4j, || self.neo4j.execute(query.build()))
             .await?
             .into_stream_as::<RowResult>()
             .map_err(DatabaseError::from)
@@ -266,7 +284,11 @@ impl<T: FromAttributes> QueryStream<Relation<T, EntityNode>>
                 "to".to_string(),
                 "COLLECT(DISTINCT n{.*}) AS attrs".to_string(),
             ],
-            "RETURN r{.*, from: from, to: to, attributes: attrs} as r",
+            vec![
+                // Aggregating the attributes does not preserve the order of the relations
+                "ORDER BY r.index, r.id".to_string(),
+                "RETURN r{.*, from: from, to: to, attributes: attrs} as r".to_string(),
+            ],
         );
 
         if cfg!(debug_assertions) || cfg!(test) {
@@ -283,9 +305,7 @@ impl<T: FromAttributes> QueryStream<Relation<T, EntityNode>>
             attributes: Vec<AttributeNode>,
         }
 
-        let stream = self
-            .neo4j
-            .execute(query.build())
+        let stream = with_query_policy(&self.neo4j, || self.neo4j.execute(query.build()))
             .await?
             .into_stream_as::<RowResult>()
             .map_err(DatabaseError::from)
@@ -313,7 +333,7 @@ mod tests {
         indexer_ids,
         mapping::{
             self, prop_filter,
-            relation::{find_many, insert_many, RelationEdge},
+            relation::{find_many, fractional_index, insert_many, RelationEdge},
             triple, EntityFilter, EntityNode, EntityNodeRef, Query, QueryStream, Relation, Triple,
         },
         relation::utils::RelationFilter,
@@ -480,6 +500,71 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_find_many_ordered_by_index() {
+        // Setup a local Neo 4J container for testing. NOTE: docker service must be running.
+        let (_container, neo4j) = crate::test_utils::setup_neo4j().await;
+
+        triple::insert_many(&neo4j, &BlockMetadata::default(), "ROOT", "0")
+            .triples(vec![
+                Triple::new("collection", "name", "Collection"),
+                Triple::new("item", "name", "Item"),
+                Triple::new("item1", "name", "Item 1"),
+                Triple::new("item2", "name", "Item 2"),
+                Triple::new("item3", "name", "Item 3"),
+            ])
+            .send()
+            .await
+            .expect("Failed to insert triples");
+
+        // Item inserted between the first and last items
+        let first = fractional_index::first_key();
+        let last = fractional_index::key_between(Some(&first), None).unwrap();
+        let middle = fractional_index::key_between(Some(&first), Some(&last)).unwrap();
+
+        insert_many(&neo4j, &BlockMetadata::default(), "ROOT", "0")
+            .relations(vec![
+                RelationEdge::new("r3", "collection", "item3", "item", last.as_str()),
+                RelationEdge::new("r1", "collection", "item1", "item", first.as_str()),
+                RelationEdge::new("r2", "collection", "item2", "item", middle.as_str()),
+            ])
+            .send()
+            .await
+            .expect("Failed to insert relations");
+
+        let query = || {
+            find_many::<RelationEdge<EntityNodeRef>>(&neo4j).filter(
+                RelationFilter::default()
+                    .relation_type(EntityFilter::default().id(prop_filter::value("item")))
+                    .from_(EntityFilter::default().id(prop_filter::value("collection"))),
+            )
+        };
+
+        let found_ids = query()
+            .send()
+            .await
+            .expect("Failed to find relations")
+            .map_ok(|relation| relation.id)
+            .try_collect::<Vec<_>>()
+            .await
+            .expect("Failed to collect relations");
+
+        assert_eq!(found_ids, vec!["r1", "r2", "r3"]);
+
+        let found_ids = query()
+            .skip(1)
+            .limit(1)
+            .send()
+            .await
+            .expect("Failed to find relations")
+            .map_ok(|relation| relation.id)
+            .try_collect::<Vec<_>>()
+            .await
+            .expect("Failed to collect relations");
+
+        assert_eq!(found_ids, vec!["r2"]);
+    }
+
     #[tokio::test]
     async fn test_insert_find_many_relations() {
         // Setup a local Neo 4J container for testing. NOTE: docker service must be running.
@@ -1,6 +1,8 @@
 use futures::{Stream, StreamExt, TryStreamExt};
 
 use crate::{
+    connection::{with_query_policy, Graph},
+    entity::merge::{canonical_entity, match_merged},
     entity::utils::MatchEntity,
     error::DatabaseError,
     mapping::{
@@ -15,7 +17,7 @@ use crate::{
 use super::utils::RelationFilter;
 
 pub struct FindManyToQuery<T> {
-    pub(super) neo4j: neo4rs::Graph,
+    pub(super) neo4j: Graph,
     pub(super) filter: RelationFilter,
 
     pub(super) space_id: Option<PropFilter<String>>,
@@ -28,7 +30,7 @@ pub struct FindManyToQuery<T> {
 }
 
 impl<T> FindManyToQuery<T> {
-    pub(super) fn new(neo4j: &neo4rs::Graph) -> Self {
+    pub(super) fn new(neo4j: &Graph) -> Self {
         Self {
             neo4j: neo4j.clone(),
             filter: RelationFilter::default(),
@@ -69,6 +71,23 @@ impl<T> FindManyToQuery<T> {
 
     fn subquery(&self) -> QueryBuilder {
         QueryBuilder::default()
+            // Apply from.id filter (ids of merged entities match their canonical entity
+            // and vice versa)
+            .subquery_opt(
+                self.filter
+                    .from_
+                    .as_ref()
+                    .and_then(|from_filter| from_filter.id.as_ref())
+                    .map(|from_id| match_merged("from", from_id)),
+            )
+            // Apply to.id filter
+            .subquery_opt(
+                self.filter
+                    .to_
+                    .as_ref()
+                    .and_then(|to_filter| to_filter.id.as_ref())
+                    .map(|to_id| match_merged("to", to_id)),
+            )
             .subquery(
                 MatchQuery::new("(from:Entity) -[r:RELATION]-> (to:Entity)")
                     // Apply edge id filter
@@ -78,22 +97,6 @@ impl<T> FindManyToQuery<T> {
                             .as_ref()
                             .map(|id| id.subquery("r", "id", None)),
                     )
-                    // Apply from.id filter
-                    .where_opt(
-                        self.filter
-                            .from_
-                            .as_ref()
-                            .and_then(|from_filter| from_filter.id.clone())
-                            .map(|from_id| from_id.subquery("from", "id", None)),
-                    )
-                    // Apply to.id filter
-                    .where_opt(
-                        self.filter
-                            .to_
-                            .as_ref()
-                            .and_then(|to_filter| to_filter.id.clone())
-                            .map(|to_id| to_id.subquery("to", "id", None)),
-                    )
                     // Apply edge relation_type filter
                     .where_opt(
                         self.filter
@@ -111,10 +114,25 @@ impl<T> FindManyToQuery<T> {
                     // Apply edge version filter
                     .r#where(self.version.subquery("r")),
             )
+            // Relations from or to merged entities resolve to the canonical entities
+            .with(
+                vec![
+                    "r".to_string(),
+                    format!("{} AS from", canonical_entity("from")),
+                    format!("{} AS to", canonical_entity("to")),
+                ],
+                QueryBuilder::default(),
+            )
             .subquery(self.filter.subquery("r", "from", "to"))
-            .subquery("ORDER BY r.index")
-            .limit(self.limit)
+            .with(
+                vec!["r".to_string(), "from".to_string(), "to".to_string()],
+                QueryBuilder::default(),
+            )
+            // Relations are ordered by their fractional index (ties are broken by id
+            // so that pagination is stable)
+            .subquery("ORDER BY r.index, r.id")
             .skip_opt(self.skip)
+            .limit(self.limit)
     }
 }
 
@@ -128,12 +146,12 @@ impl QueryStream<EntityNode> for FindManyToQuery<EntityNode> {
             println!("relation_node::FindManyToQuery:\n{}", query.compile());
         };
 
-        Ok(self
-            .neo4j
-            .execute(query.build())
-            .await?
-            .into_stream_as::<EntityNode>()
-            .map_err(DatabaseError::from))
+        Ok(
+            with_query_policy(&self.neo4j, || self.neo4j.execute(query.build()))
+                .await?
+                .into_stream_as::<EntityNode>()
+                .map_err(DatabaseError::from),
+        )
     }
 }
 
@@ -144,13 +162,14 @@ impl<T: FromAttributes> QueryStream<Entity<T>> for FindManyToQuery<Entity<T>> {
         let match_entity = MatchEntity::new(&self.space_id, &self.version);
 
         let query = self.subquery().with(
-            vec!["to".to_string()],
+            vec!["r".to_string(), "to".to_string()],
             match_entity.chain(
                 "to",
                 "attrs",
                 "types",
-                None,
-                "RETURN to{.*, attrs: attrs, types: types}",
+                Some(vec!["r".to_string()]),
+                // Aggregating the attributes does not preserve the order of the relations
+                "RETURN to{.*, attrs: attrs, types: types} ORDER BY r.index, r.id",
             ),
         );
 
@@ -166,9 +185,7 @@ impl<T: FromAttributes> QueryStream<Entity<T>> for FindManyToQuery<Entity<T>> {
             types: Vec<EntityNode>,
         }
 
-        let stream = self
-            .neo4j
-            .execute(query.build())
+        let stream = with_query_policy(&self.neo4j, || self.neo4j.execute(query.build()))
             .await?
             .into_stream_as::<RowResult>()
             .map_err(DatabaseError::from)
@@ -1,4 +1,5 @@
 use crate::{
+    connection::{with_query_policy, Graph},
     error::DatabaseError,
     mapping::{
         query_utils::{
@@ -12,7 +13,7 @@ use crate::{
 use super::{FindOneToQuery, Relation, RelationEdge};
 
 pub struct FindOneQuery<T> {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     id: String,
     space_id: String,
     version: VersionFilter,
@@ -21,7 +22,7 @@ pub struct FindOneQuery<T> {
 
 impl<T> FindOneQuery<T> {
     pub(super) fn new(
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         id: String,
         space_id: String,
         space_version: Option<String>,
@@ -61,8 +62,7 @@ impl Query<Option<RelationEdge<EntityNodeRef>>> for FindOneQuery<RelationEdge<En
             .subquery("ORDER BY r.index")
             .r#return("r{.*, from: from.id, to: to.id} as r");
 
-        neo4j
-            .execute(query.build())
+        with_query_policy(&neo4j, || neo4j.execute(query.build()))
             .await?
             .next()
             .await?
@@ -86,8 +86,7 @@ impl Query<Option<RelationEdge<EntityNode>>> for FindOneQuery<RelationEdge<Entit
             .subquery("ORDER BY r.index")
             .r#return("r{.*, from: from, to: to} as r");
 
-        neo4j
-            .execute(query.build())
+        with_query_policy(&neo4j, || neo4j.execute(query.build()))
             .await?
             .next()
             .await?
@@ -139,8 +138,7 @@ impl<T: FromAttributes> Query<Option<Relation<T, EntityNodeRef>>>
             attributes: Vec<AttributeNode>,
         }
 
-        self.neo4j
-            .execute(query.build())
+        with_query_policy(&self.neo4j, || self.neo4j.execute(query.build()))
             .await?
             .next()
             .await?
@@ -198,8 +196,7 @@ impl<T: FromAttributes> Query<Option<Relation<T, EntityNode>>>
             attributes: Vec<AttributeNode>,
         }
 
-        self.neo4j
-            .execute(query.build())
+        with_query_policy(&self.neo4j, || self.neo4j.execute(query.build()))
             .await?
             .next()
             .await?
@@ -1,4 +1,5 @@
 use crate::{
+    connection::{with_query_policy, Graph},
     entity::utils::MatchEntity,
     error::DatabaseError,
     mapping::{
@@ -13,7 +14,7 @@ use crate::{
 };
 
 pub struct FindOneToQuery<T> {
-    pub(super) neo4j: neo4rs::Graph,
+    pub(super) neo4j: Graph,
     pub(super) id: String,
     pub(super) space_id: String,
     pub(super) version: VersionFilter,
@@ -22,7 +23,7 @@ pub struct FindOneToQuery<T> {
 
 impl<T> FindOneToQuery<T> {
     pub(super) fn new(
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         id: String,
         space_id: String,
         version: Option<String>,
@@ -55,8 +56,7 @@ impl Query<Option<EntityNode>> for FindOneToQuery<EntityNode> {
             to: EntityNode,
         }
 
-        self.neo4j
-            .execute(query.build())
+        with_query_policy(&self.neo4j, || self.neo4j.execute(query.build()))
             .await?
             .next()
             .await?
@@ -97,8 +97,7 @@ impl<T: FromAttributes> Query<Option<Entity<T>>> for FindOneToQuery<Entity<T>> {
             types: Vec<EntityNode>,
         }
 
-        self.neo4j
-            .execute(query.build())
+        with_query_policy(&self.neo4j, || self.neo4j.execute(query.build()))
             .await?
             .next()
             .await?
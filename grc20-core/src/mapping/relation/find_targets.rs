@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+
+use crate::{
+    connection::{with_query_policy, Graph},
+    entity::merge::{canonical_entity, merged_entities},
+    error::DatabaseError,
+    mapping::{
+        query_utils::{
+            query_builder::{MatchQuery, QueryBuilder, Subquery},
+            VersionFilter,
+        },
+        EntityNodeRef, Query,
+    },
+};
+
+/// Query to find the targets of the relations of given types outgoing from a set
+/// of entities. Targets are grouped by `(from entity id, relation type)` and ordered
+/// by relation index. Unlike [`super::FindManyQuery`], the number of targets is not
+/// limited.
+pub struct FindTargetsQuery {
+    neo4j: Graph,
+    from_ids: Vec<String>,
+    relation_types: Vec<String>,
+    space_id: String,
+    version: VersionFilter,
+}
+
+impl FindTargetsQuery {
+    pub(super) fn new(
+        neo4j: &Graph,
+        from_ids: Vec<String>,
+        relation_types: Vec<String>,
+        space_id: String,
+    ) -> Self {
+        Self {
+            neo4j: neo4j.clone(),
+            from_ids,
+            relation_types,
+            space_id,
+            version: VersionFilter::default(),
+        }
+    }
+
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version.version_mut(version.into());
+        self
+    }
+
+    pub fn version_opt(mut self, version: Option<String>) -> Self {
+        self.version.version_opt(version);
+        self
+    }
+}
+
+impl Query<HashMap<(String, String), Vec<EntityNodeRef>>> for FindTargetsQuery {
+    async fn send(self) -> Result<HashMap<(String, String), Vec<EntityNodeRef>>, DatabaseError> {
+        if self.from_ids.is_empty() || self.relation_types.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        // Relations of the entities merged into the requested entities (or into the
+        // entity the requested entities were merged into) are included, but results
+        // are keyed by the requested IDs
+        let query = QueryBuilder::default()
+            .subquery("UNWIND $from_ids AS from_id")
+            .subquery(MatchQuery::new("(requested:Entity {id: from_id})"))
+            .with(
+                vec![
+                    "from_id".to_string(),
+                    format!("{} AS canonical", canonical_entity("requested")),
+                ],
+                format!("UNWIND {} AS from", merged_entities("canonical")),
+            )
+            .subquery(
+                MatchQuery::new("(from) -[r:RELATION]-> (to:Entity)")
+                    .r#where("r.relation_type IN $relation_types")
+                    .r#where("r.space_id = $space_id")
+                    .r#where(self.version.subquery("r")),
+            )
+            // Relations to merged entities resolve to the canonical entity
+            .with(
+                vec![
+                    "from_id".to_string(),
+                    "r".to_string(),
+                    format!("{} AS to", canonical_entity("to")),
+                ],
+                "ORDER BY r.index, r.id",
+            )
+            .params("from_ids", self.from_ids)
+            .params("relation_types", self.relation_types)
+            .params("space_id", self.space_id)
+            .r#return(
+                "from_id AS from, r.relation_type AS relation_type, COLLECT(to.id) AS targets",
+            );
+
+        if cfg!(debug_assertions) || cfg!(test) {
+            tracing::info!(
+                "relation::FindTargetsQuery:\n{}\nparams:{:?}",
+                query.compile(),
+                query.params()
+            );
+        };
+
+        #[derive(Debug, serde::Deserialize)]
+        struct RowResult {
+            from: String,
+            relation_type: String,
+            targets: Vec<EntityNodeRef>,
+        }
+
+        let mut result =
+            with_query_policy(&self.neo4j, || self.neo4j.execute(query.build())).await?;
+        let mut targets = HashMap::new();
+
+        while let Some(row) = result.next().await? {
+            let row = row.to::<RowResult>()?;
+            targets.insert((row.from, row.relation_type), row.targets);
+        }
+
+        Ok(targets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        block::BlockMetadata,
+        mapping::{
+            relation::{self, RelationEdge},
+            triple, EntityNodeRef, Query, Triple,
+        },
+    };
+
+    #[tokio::test]
+    async fn test_find_targets() {
+        // Setup a local Neo 4J container for testing. NOTE: docker service must be running.
+        let (_container, neo4j) = crate::test_utils::setup_neo4j().await;
+        let block = BlockMetadata::default();
+
+        triple::insert_many(&neo4j, &block, "ROOT", "0")
+            .triples(vec![
+                Triple::new("paper", "name", "Paper"),
+                Triple::new("alice", "name", "Alice"),
+                Triple::new("bob", "name", "Bob"),
+                Triple::new("journal", "name", "Journal"),
+            ])
+            .send()
+            .await
+            .expect("Failed to insert triples");
+
+        relation::insert_many::<RelationEdge<EntityNodeRef>>(&neo4j, &block, "ROOT", "0")
+            .relations(vec![
+                RelationEdge::new("author_2", "paper", "alice", "AUTHORS", "a1"),
+                RelationEdge::new("author_1", "paper", "bob", "AUTHORS", "a0"),
+                RelationEdge::new("published_in", "paper", "journal", "PUBLISHED_IN", "a0"),
+                RelationEdge::new("cites", "paper", "journal", "CITES", "a0"),
+            ])
+            .send()
+            .await
+            .expect("Failed to insert relations");
+
+        let targets = relation::find_targets(
+            &neo4j,
+            vec!["paper".to_string()],
+            vec!["AUTHORS".to_string(), "PUBLISHED_IN".to_string()],
+            "ROOT",
+        )
+        .send()
+        .await
+        .expect("Failed to find targets");
+
+        assert_eq!(targets.len(), 2);
+        assert_eq!(
+            targets[&("paper".to_string(), "AUTHORS".to_string())],
+            vec![
+                EntityNodeRef("bob".to_string()),
+                EntityNodeRef("alice".to_string())
+            ]
+        );
+        assert_eq!(
+            targets[&("paper".to_string(), "PUBLISHED_IN".to_string())],
+            vec![EntityNodeRef("journal".to_string())]
+        );
+    }
+}
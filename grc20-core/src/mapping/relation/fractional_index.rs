@@ -0,0 +1,446 @@
+//! Fractional index keys used to order relations (e.g.: the blocks of an entity or
+//! the items of a collection).
+//!
+//! Keys are base62 strings compatible with the `fractional-indexing` algorithm used
+//! by the GRC-20 authoring tools: they sort lexicographically and a new key can
+//! always be generated between two existing keys without updating the others.
+//! A key is made of an integer part (whose first character encodes its length) and
+//! an optional fractional part which never ends with `0`.
+
+const DIGITS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Smallest integer part. Keys cannot be generated before it.
+const SMALLEST_INTEGER: &str = "A00000000000000000000000000";
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum FractionalIndexError {
+    #[error("Invalid fractional index key: {0}")]
+    InvalidKey(String),
+
+    #[error("Fractional index keys out of order: {0} >= {1}")]
+    OutOfOrder(String, String),
+
+    #[error("Fractional index key space exhausted")]
+    Exhausted,
+}
+
+/// Returns the first key of a list (i.e.: a key with no neighbours)
+pub fn first_key() -> String {
+    "a0".to_string()
+}
+
+/// Returns a key that sorts strictly between `before` and `after`. `None` means
+/// that there is no neighbour on that side (i.e.: inserting at the start or at the
+/// end of the list).
+///
+/// ```
+/// use grc20_core::mapping::relation::fractional_index::key_between;
+///
+/// let first = key_between(None, None).unwrap();
+/// let last = key_between(Some(&first), None).unwrap();
+/// let middle = key_between(Some(&first), Some(&last)).unwrap();
+///
+/// assert!(first < middle && middle < last);
+/// ```
+pub fn key_between(
+    before: Option<&str>,
+    after: Option<&str>,
+) -> Result<String, FractionalIndexError> {
+    if let Some(before) = before {
+        validate_key(before)?;
+    }
+    if let Some(after) = after {
+        validate_key(after)?;
+    }
+
+    match (before, after) {
+        (Some(before), Some(after)) if before >= after => Err(FractionalIndexError::OutOfOrder(
+            before.to_string(),
+            after.to_string(),
+        )),
+        (None, None) => Ok(first_key()),
+        (None, Some(after)) => {
+            let int = integer_part(after)?;
+            let frac = &after[int.len()..];
+
+            if int == SMALLEST_INTEGER {
+                return Ok(format!("{int}{}", midpoint("", Some(frac))?));
+            }
+
+            if int < after {
+                return Ok(int.to_string());
+            }
+
+            decrement_integer(int).ok_or(FractionalIndexError::Exhausted)
+        }
+        (Some(before), None) => {
+            let int = integer_part(before)?;
+            let frac = &before[int.len()..];
+
+            match increment_integer(int) {
+                Some(next) => Ok(next),
+                None => Ok(format!("{int}{}", midpoint(frac, None)?)),
+            }
+        }
+        (Some(before), Some(after)) => {
+            let int_before = integer_part(before)?;
+            let frac_before = &before[int_before.len()..];
+            let int_after = integer_part(after)?;
+            let frac_after = &after[int_after.len()..];
+
+            if int_before == int_after {
+                return Ok(format!(
+                    "{int_before}{}",
+                    midpoint(frac_before, Some(frac_after))?
+                ));
+            }
+
+            let next = increment_integer(int_before).ok_or(FractionalIndexError::Exhausted)?;
+
+            if next.as_str() < after {
+                Ok(next)
+            } else {
+                Ok(format!("{int_before}{}", midpoint(frac_before, None)?))
+            }
+        }
+    }
+}
+
+/// Returns `n` ordered keys that sort strictly between `before` and `after`, spread
+/// so that the keys stay short.
+pub fn n_keys_between(
+    before: Option<&str>,
+    after: Option<&str>,
+    n: usize,
+) -> Result<Vec<String>, FractionalIndexError> {
+    match n {
+        0 => Ok(vec![]),
+        1 => Ok(vec![key_between(before, after)?]),
+        _ => match (before, after) {
+            (_, None) => {
+                let mut keys = Vec::with_capacity(n);
+                let mut key = key_between(before, None)?;
+                for _ in 1..n {
+                    let next = key_between(Some(&key), None)?;
+                    keys.push(key);
+                    key = next;
+                }
+                keys.push(key);
+                Ok(keys)
+            }
+            (None, Some(after)) => {
+                let mut keys = Vec::with_capacity(n);
+                let mut key = key_between(None, Some(after))?;
+                for _ in 1..n {
+                    let prev = key_between(None, Some(&key))?;
+                    keys.push(key);
+                    key = prev;
+                }
+                keys.push(key);
+                keys.reverse();
+                Ok(keys)
+            }
+            (Some(before), Some(after)) => {
+                let mid = n / 2;
+                let key = key_between(Some(before), Some(after))?;
+
+                let mut keys = n_keys_between(Some(before), Some(&key), mid)?;
+                let after_keys = n_keys_between(Some(&key), Some(after), n - mid - 1)?;
+                keys.push(key);
+                keys.extend(after_keys);
+                Ok(keys)
+            }
+        },
+    }
+}
+
+/// Returns `n` fresh evenly spaced keys. Used to re-balance a list whose keys grew
+/// long after many insertions at the same position: assign the returned keys to
+/// the items of the list in order.
+pub fn rebalance(n: usize) -> Vec<String> {
+    n_keys_between(None, None, n).expect("Generating keys without neighbours cannot fail")
+}
+
+/// Checks that `key` is a valid fractional index key
+pub fn validate_key(key: &str) -> Result<(), FractionalIndexError> {
+    if key == SMALLEST_INTEGER || !key.bytes().all(|c| DIGITS.contains(&c)) {
+        return Err(FractionalIndexError::InvalidKey(key.to_string()));
+    }
+
+    let int = integer_part(key)?;
+    if key[int.len()..].ends_with('0') {
+        return Err(FractionalIndexError::InvalidKey(key.to_string()));
+    }
+
+    Ok(())
+}
+
+fn digit_value(c: u8) -> usize {
+    DIGITS
+        .iter()
+        .position(|d| *d == c)
+        .expect("Key characters are validated")
+}
+
+fn integer_length(head: u8) -> Result<usize, FractionalIndexError> {
+    match head {
+        b'a'..=b'z' => Ok((head - b'a') as usize + 2),
+        b'A'..=b'Z' => Ok((b'Z' - head) as usize + 2),
+        _ => Err(FractionalIndexError::InvalidKey(
+            char::from(head).to_string(),
+        )),
+    }
+}
+
+fn integer_part(key: &str) -> Result<&str, FractionalIndexError> {
+    let head = *key
+        .as_bytes()
+        .first()
+        .ok_or_else(|| FractionalIndexError::InvalidKey(key.to_string()))?;
+    let len = integer_length(head)?;
+
+    key.get(..len)
+        .ok_or_else(|| FractionalIndexError::InvalidKey(key.to_string()))
+}
+
+/// Returns the next integer part, or `None` if `int` is the largest integer part
+fn increment_integer(int: &str) -> Option<String> {
+    let (head, digits) = (int.as_bytes()[0], &int.as_bytes()[1..]);
+    let mut digits = digits.to_vec();
+
+    let mut carry = true;
+    for digit in digits.iter_mut().rev() {
+        let value = digit_value(*digit) + 1;
+        if value == DIGITS.len() {
+            *digit = DIGITS[0];
+        } else {
+            *digit = DIGITS[value];
+            carry = false;
+            break;
+        }
+    }
+
+    if carry {
+        if head == b'Z' {
+            return Some(format!("a{}", char::from(DIGITS[0])));
+        }
+        if head == b'z' {
+            return None;
+        }
+
+        let head = head + 1;
+        if head > b'a' {
+            digits.push(DIGITS[0]);
+        } else {
+            digits.pop();
+        }
+        return Some(to_key(head, &digits));
+    }
+
+    Some(to_key(head, &digits))
+}
+
+/// Returns the previous integer part, or `None` if `int` is the smallest integer part
+fn decrement_integer(int: &str) -> Option<String> {
+    let (head, digits) = (int.as_bytes()[0], &int.as_bytes()[1..]);
+    let mut digits = digits.to_vec();
+    let last_digit = DIGITS[DIGITS.len() - 1];
+
+    let mut borrow = true;
+    for digit in digits.iter_mut().rev() {
+        let value = digit_value(*digit);
+        if value == 0 {
+            *digit = last_digit;
+        } else {
+            *digit = DIGITS[value - 1];
+            borrow = false;
+            break;
+        }
+    }
+
+    if borrow {
+        if head == b'a' {
+            return Some(format!("Z{}", char::from(last_digit)));
+        }
+        if head == b'A' {
+            return None;
+        }
+
+        let head = head - 1;
+        if head < b'Z' {
+            digits.push(last_digit);
+        } else {
+            digits.pop();
+        }
+        return Some(to_key(head, &digits));
+    }
+
+    Some(to_key(head, &digits))
+}
+
+/// Returns a fractional part that sorts strictly between `before` and `after`.
+/// `None` means that there is no upper bound.
+fn midpoint(before: &str, after: Option<&str>) -> Result<String, FractionalIndexError> {
+    if let Some(after) = after {
+        if before >= after {
+            return Err(FractionalIndexError::OutOfOrder(
+                before.to_string(),
+                after.to_string(),
+            ));
+        }
+    }
+
+    if before.ends_with('0') || after.is_some_and(|after| after.ends_with('0')) {
+        return Err(FractionalIndexError::InvalidKey(before.to_string()));
+    }
+
+    let (before, after) = (before.as_bytes(), after.map(str::as_bytes));
+
+    if let Some(after) = after {
+        // Length of the common prefix (missing digits of `before` count as zeros)
+        let prefix_len = after
+            .iter()
+            .enumerate()
+            .take_while(|(i, c)| before.get(*i).copied().unwrap_or(DIGITS[0]) == **c)
+            .count();
+
+        if prefix_len > 0 {
+            let prefix = String::from_utf8_lossy(&after[..prefix_len]);
+            let before_rest = std::str::from_utf8(before.get(prefix_len..).unwrap_or_default())
+                .expect("Key characters are ASCII");
+            let after_rest =
+                std::str::from_utf8(&after[prefix_len..]).expect("Key characters are ASCII");
+            return Ok(format!(
+                "{prefix}{}",
+                midpoint(before_rest, Some(after_rest))?
+            ));
+        }
+    }
+
+    let digit_before = before.first().map(|c| digit_value(*c)).unwrap_or(0);
+    let digit_after = after
+        .and_then(|after| after.first())
+        .map(|c| digit_value(*c))
+        .unwrap_or(DIGITS.len());
+
+    if digit_after - digit_before > 1 {
+        let mid = (digit_before + digit_after).div_ceil(2);
+        Ok(char::from(DIGITS[mid]).to_string())
+    } else if let Some(after) = after.filter(|after| after.len() > 1) {
+        Ok(char::from(after[0]).to_string())
+    } else {
+        let before_rest = std::str::from_utf8(before.get(1..).unwrap_or_default())
+            .expect("Key characters are ASCII");
+        Ok(format!(
+            "{}{}",
+            char::from(DIGITS[digit_before]),
+            midpoint(before_rest, None)?
+        ))
+    }
+}
+
+fn to_key(head: u8, digits: &[u8]) -> String {
+    let mut key = String::with_capacity(digits.len() + 1);
+    key.push(char::from(head));
+    key.extend(digits.iter().map(|c| char::from(*c)));
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_between() {
+        assert_eq!(key_between(None, None).unwrap(), "a0");
+        assert_eq!(key_between(None, Some("a0")).unwrap(), "Zz");
+        assert_eq!(key_between(Some("a0"), None).unwrap(), "a1");
+        assert_eq!(key_between(Some("a0"), Some("a1")).unwrap(), "a0V");
+        assert_eq!(key_between(Some("a0V"), Some("a1")).unwrap(), "a0l");
+        assert_eq!(key_between(Some("Zz"), Some("a0")).unwrap(), "ZzV");
+        assert_eq!(key_between(Some("a1"), Some("a2")).unwrap(), "a1V");
+        assert_eq!(key_between(Some("az"), None).unwrap(), "b00");
+        assert_eq!(key_between(Some("b00"), Some("b01")).unwrap(), "b00V");
+    }
+
+    #[test]
+    fn test_key_between_errors() {
+        assert_eq!(
+            key_between(Some("a1"), Some("a0")),
+            Err(FractionalIndexError::OutOfOrder(
+                "a1".to_string(),
+                "a0".to_string()
+            ))
+        );
+        assert_eq!(
+            key_between(Some("a0"), Some("a0")),
+            Err(FractionalIndexError::OutOfOrder(
+                "a0".to_string(),
+                "a0".to_string()
+            ))
+        );
+        assert!(matches!(
+            key_between(Some("a00"), None),
+            Err(FractionalIndexError::InvalidKey(_))
+        ));
+        assert!(matches!(
+            key_between(Some("a"), None),
+            Err(FractionalIndexError::InvalidKey(_))
+        ));
+        assert!(matches!(
+            key_between(Some("a_"), None),
+            Err(FractionalIndexError::InvalidKey(_))
+        ));
+    }
+
+    #[test]
+    fn test_repeated_insertions_stay_ordered() {
+        // Always insert right after the first key
+        let mut keys = vec![first_key(), key_between(Some("a0"), None).unwrap()];
+        for _ in 0..100 {
+            let key = key_between(Some(&keys[0]), Some(&keys[1])).unwrap();
+            keys.insert(1, key);
+        }
+
+        let mut sorted = keys.clone();
+        sorted.sort();
+        assert_eq!(keys, sorted);
+
+        // Always insert before the first key
+        let mut keys = vec![first_key()];
+        for _ in 0..100 {
+            keys.insert(0, key_between(None, Some(&keys[0])).unwrap());
+        }
+
+        let mut sorted = keys.clone();
+        sorted.sort();
+        assert_eq!(keys, sorted);
+    }
+
+    #[test]
+    fn test_n_keys_between() {
+        assert_eq!(n_keys_between(None, None, 0).unwrap(), Vec::<String>::new());
+        assert_eq!(
+            n_keys_between(None, None, 3).unwrap(),
+            vec!["a0", "a1", "a2"]
+        );
+        assert_eq!(
+            n_keys_between(None, Some("a0"), 3).unwrap(),
+            vec!["Zx", "Zy", "Zz"]
+        );
+
+        let keys = n_keys_between(Some("a0"), Some("a1"), 10).unwrap();
+        assert_eq!(keys.len(), 10);
+        assert!(keys.first().unwrap().as_str() > "a0");
+        assert!(keys.last().unwrap().as_str() < "a1");
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_rebalance() {
+        let keys = rebalance(100);
+        assert_eq!(keys.len(), 100);
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(keys.iter().all(|key| validate_key(key).is_ok()));
+    }
+}
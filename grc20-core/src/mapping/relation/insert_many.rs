@@ -1,5 +1,6 @@
 use crate::{
     block::BlockMetadata,
+    connection::{with_query_policy, Graph},
     error::DatabaseError,
     indexer_ids,
     mapping::{EntityNodeRef, Query},
@@ -8,7 +9,7 @@ use crate::{
 use super::RelationEdge;
 
 pub struct InsertManyQuery<T> {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     block: BlockMetadata,
     space_id: String,
     space_version: String,
@@ -17,7 +18,7 @@ pub struct InsertManyQuery<T> {
 
 impl<T> InsertManyQuery<T> {
     pub(super) fn new(
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         block: &BlockMetadata,
         space_id: String,
         space_version: String,
@@ -92,7 +93,7 @@ impl Query<()> for InsertManyQuery<RelationEdge<EntityNodeRef>> {
             .param("block_number", self.block.block_number.to_string())
             .param("block_timestamp", self.block.timestamp.to_rfc3339());
 
-        self.neo4j.run(query).await?;
+        with_query_policy(&self.neo4j, || self.neo4j.run(query.clone())).await?;
 
         Ok(())
     }
@@ -4,12 +4,15 @@ pub mod find_many;
 pub mod find_many_to;
 pub mod find_one;
 pub mod find_one_to;
+pub mod find_targets;
+pub mod fractional_index;
 pub mod insert_many;
 pub mod insert_one;
 pub mod models;
 pub mod utils;
 
 use crate::block::BlockMetadata;
+use crate::connection::Graph;
 
 pub use delete_many::DeleteManyQuery;
 pub use delete_one::DeleteOneQuery;
@@ -17,13 +20,14 @@ pub use find_many::FindManyQuery;
 pub use find_many_to::FindManyToQuery;
 pub use find_one::FindOneQuery;
 pub use find_one_to::FindOneToQuery;
+pub use find_targets::FindTargetsQuery;
 pub use insert_many::InsertManyQuery;
 pub use insert_one::InsertOneQuery;
 pub use models::{Relation, RelationEdge};
 pub use utils::RelationFilter;
 
 pub fn delete_many(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     block: &BlockMetadata,
     space_id: impl Into<String>,
     space_version: impl Into<String>,
@@ -32,7 +36,7 @@ pub fn delete_many(
 }
 
 pub fn delete_one(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     block: &BlockMetadata,
     relation_id: impl Into<String>,
     space_id: impl Into<String>,
@@ -64,7 +68,7 @@ pub fn delete_one(
 ///     .await?;
 /// ```
 pub fn find_one<T>(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     relation_id: impl Into<String>,
     space_id: impl Into<String>,
     space_version: Option<String>,
@@ -100,14 +104,14 @@ pub fn find_one<T>(
 ///     .send()
 ///     .await?;
 /// ```
-pub fn find_many<T>(neo4j: &neo4rs::Graph) -> FindManyQuery<T> {
+pub fn find_many<T>(neo4j: &Graph) -> FindManyQuery<T> {
     FindManyQuery::new(neo4j)
 }
 
 /// Same as `find_one`, but it returns the `to` entity of the relation instead of the
 /// relation itself.
 pub fn find_one_to<T>(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     relation_id: impl Into<String>,
     space_id: impl Into<String>,
     space_version: Option<String>,
@@ -118,12 +122,40 @@ pub fn find_one_to<T>(
 /// Same as `find_many`, but it returns the `to` entities of the relations instead of the
 /// relations themselves. This is useful when you want to retrieve the target entities of
 /// a set of relations without fetching the relations themselves.
-pub fn find_many_to<T>(neo4j: &neo4rs::Graph) -> FindManyToQuery<T> {
+pub fn find_many_to<T>(neo4j: &Graph) -> FindManyToQuery<T> {
     FindManyToQuery::new(neo4j)
 }
 
+/// Creates a query to find the targets of the relations of the given types outgoing
+/// from the entities `from_ids`, grouped by `(from entity id, relation type)` and
+/// ordered by relation index. This is used to load the relation fields of entities
+/// (see the `relation` option of the `entity` macro).
+///
+/// ```rust
+/// use grc20_core::mapping::relation;
+///
+/// let targets = relation::find_targets(
+///     &neo4j,
+///     vec!["paper_id".to_string()],
+///     vec!["AUTHORS".to_string()],
+///     "space_id",
+/// )
+///     .send()
+///     .await?;
+///
+/// let authors = targets.get(&("paper_id".to_string(), "AUTHORS".to_string()));
+/// ```
+pub fn find_targets(
+    neo4j: &Graph,
+    from_ids: Vec<String>,
+    relation_types: Vec<String>,
+    space_id: impl Into<String>,
+) -> FindTargetsQuery {
+    FindTargetsQuery::new(neo4j, from_ids, relation_types, space_id.into())
+}
+
 pub fn insert_one<T>(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     block: &BlockMetadata,
     space_id: impl Into<String>,
     space_version: impl Into<String>,
@@ -139,7 +171,7 @@ pub fn insert_one<T>(
 }
 
 pub fn insert_many<T>(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     block: &BlockMetadata,
     space_id: impl Into<String>,
     space_version: impl Into<String>,
@@ -4,6 +4,7 @@ use neo4rs::BoltType;
 
 use crate::{
     block::BlockMetadata,
+    connection::Graph,
     mapping::{
         attributes,
         entity::{self, EntityNodeRef, SystemProperties},
@@ -60,7 +61,7 @@ impl RelationEdge<EntityNodeRef> {
 
     pub fn insert(
         self,
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         block: &BlockMetadata,
         space_id: impl Into<String>,
         space_version: impl Into<String>,
@@ -70,7 +71,7 @@ impl RelationEdge<EntityNodeRef> {
 
     pub fn get_attributes(
         &self,
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         space_id: impl Into<String>,
         space_version: Option<String>,
     ) -> attributes::FindOneQuery {
@@ -79,7 +80,7 @@ impl RelationEdge<EntityNodeRef> {
 
     pub fn set_attribute(
         &self,
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         block: &BlockMetadata,
         space_id: impl Into<String>,
         space_version: impl Into<String>,
@@ -96,7 +97,7 @@ impl RelationEdge<EntityNodeRef> {
 
     pub fn set_attributes(
         &self,
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         block: &BlockMetadata,
         space_id: impl Into<String>,
         space_version: impl Into<String>,
@@ -112,19 +113,19 @@ impl RelationEdge<EntityNodeRef> {
         )
     }
 
-    pub fn to<T>(&self, neo4j: &neo4rs::Graph) -> entity::FindOneQuery<T> {
+    pub fn to<T>(&self, neo4j: &Graph) -> entity::FindOneQuery<T> {
         entity::find_one(neo4j, &self.to)
     }
 
-    pub fn from<T>(&self, neo4j: &neo4rs::Graph) -> entity::FindOneQuery<T> {
+    pub fn from<T>(&self, neo4j: &Graph) -> entity::FindOneQuery<T> {
         entity::find_one(neo4j, &self.from)
     }
 
-    pub fn relation_type<T>(&self, neo4j: &neo4rs::Graph) -> entity::FindOneQuery<T> {
+    pub fn relation_type<T>(&self, neo4j: &Graph) -> entity::FindOneQuery<T> {
         entity::find_one(neo4j, &self.relation_type)
     }
 
-    pub fn entity<T>(&self, neo4j: &neo4rs::Graph) -> entity::FindOneQuery<T> {
+    pub fn entity<T>(&self, neo4j: &Graph) -> entity::FindOneQuery<T> {
         entity::find_one(neo4j, &self.id)
     }
 
@@ -207,7 +208,7 @@ impl<T> Relation<T, EntityNodeRef> {
 
     pub fn insert(
         self,
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         block: &BlockMetadata,
         space_id: impl Into<String>,
         space_version: impl Into<String>,
@@ -2,17 +2,23 @@ use futures::{Stream, StreamExt, TryStreamExt};
 
 use crate::{
     block::BlockMetadata,
+    connection::Graph,
     error::DatabaseError,
     indexer_ids,
     mapping::{AttributeNode, EntityNode},
 };
 
 use super::{
-    attributes::IntoAttributes, entity_node::{self, EntityNodeRef}, prop_filter, query_utils::{query_part, Query, QueryPart, VersionFilter}, relation_edge, Entity, FromAttributes, PropFilter, QueryStream, RelationEdge, RelationFilter, Value
+    attributes::IntoAttributes,
+    entity_node::{self, EntityNodeRef},
+    prop_filter,
+    query_utils::{query_part, Query, QueryPart, VersionFilter},
+    relation_edge, Entity, FromAttributes, PropFilter, QueryStream, RelationEdge, RelationFilter,
+    Value,
 };
 
 pub fn find_one<T>(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     id: impl Into<String>,
     space_id: impl Into<String>,
     version: Option<String>,
@@ -20,12 +26,12 @@ pub fn find_one<T>(
     FindOneQuery::new(neo4j, id.into(), space_id.into(), version)
 }
 
-pub fn find_many<T>(neo4j: &neo4rs::Graph) -> FindManyQuery<T> {
+pub fn find_many<T>(neo4j: &Graph) -> FindManyQuery<T> {
     FindManyQuery::new(neo4j)
 }
 
 pub fn delete_one(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     block: &BlockMetadata,
     relation_id: impl Into<String>,
     space_id: impl Into<String>,
@@ -41,7 +47,7 @@ pub fn delete_one(
 }
 
 pub struct FindManyQuery<T> {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     id: Option<PropFilter<String>>,
     filter: RelationFilter,
 
@@ -55,7 +61,7 @@ pub struct FindManyQuery<T> {
 }
 
 impl<T> FindManyQuery<T> {
-    pub fn new(neo4j: &neo4rs::Graph) -> Self {
+    pub fn new(neo4j: &Graph) -> Self {
         Self {
             neo4j: neo4j.clone(),
             id: None,
@@ -107,12 +113,10 @@ impl<T> FindManyQuery<T> {
             .order_by_clause("r.index")
             .limit(self.limit);
 
-        query_part = query_part
-            .merge(self.version.clone().into_query_part("r"));
+        query_part = query_part.merge(self.version.clone().into_query_part("r"));
 
         if let Some(space_id) = &self.space_id {
-            query_part = query_part
-                .merge(space_id.clone().into_query_part("r", "space_id", None));
+            query_part = query_part.merge(space_id.clone().into_query_part("r", "space_id", None));
         }
 
         if let Some(skip) = self.skip {
@@ -129,13 +133,11 @@ impl<T> FindManyQuery<T> {
             .order_by_clause("r.index")
             .limit(self.limit);
 
-            query_part = query_part
-                .merge(self.version.clone().into_query_part("r"));
+        query_part = query_part.merge(self.version.clone().into_query_part("r"));
 
-            if let Some(space_id) = &self.space_id {
-                query_part = query_part
-                    .merge(space_id.clone().into_query_part("r", "space_id", None));
-            }
+        if let Some(space_id) = &self.space_id {
+            query_part = query_part.merge(space_id.clone().into_query_part("r", "space_id", None));
+        }
 
         if let Some(skip) = self.skip {
             query_part = query_part.skip(skip);
@@ -162,10 +164,8 @@ impl<T> FindManyQuery<T> {
     }
 }
 
-
-
 pub struct FindManyToQuery {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     query_part: QueryPart,
 }
 
@@ -211,7 +211,7 @@ impl<T: FromAttributes> QueryStream<Entity<T>> for FindManyToQuery {
 }
 
 pub struct DeleteOneQuery {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     block: BlockMetadata,
     relation_id: String,
     space_id: String,
@@ -220,7 +220,7 @@ pub struct DeleteOneQuery {
 
 impl DeleteOneQuery {
     fn new(
-        neo4j: neo4rs::Graph,
+        neo4j: Graph,
         block: BlockMetadata,
         relation_id: String,
         space_id: String,
@@ -260,10 +260,12 @@ impl Query<()> for DeleteOneQuery {
     }
 }
 
-
 #[cfg(test)]
 mod tests {
-    use crate::{mapping::{self, triple, EntityFilter, Triple}, system_ids};
+    use crate::{
+        mapping::{self, triple, EntityFilter, Triple},
+        system_ids,
+    };
 
     use super::*;
 
@@ -301,8 +303,4 @@ mod tests {
             })
         }
     }
-
-
-
-
 }
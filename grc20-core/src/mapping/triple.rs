@@ -6,6 +6,7 @@ use serde::Deserialize;
 
 use crate::{
     block::BlockMetadata,
+    connection::{with_query_policy, Graph},
     error::DatabaseError,
     ids, indexer_ids,
     mapping::{query_utils::query_builder::Subquery, EFFECTIVE_SEARCH_RATIO},
@@ -64,7 +65,7 @@ impl Triple {
 
     pub fn insert(
         self,
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         block: &BlockMetadata,
         space_id: impl Into<String>,
         space_version: impl Into<String>,
@@ -74,7 +75,7 @@ impl Triple {
 }
 
 pub fn delete_one(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     block: &BlockMetadata,
     attribute_id: impl Into<String>,
     entity_id: impl Into<String>,
@@ -92,7 +93,7 @@ pub fn delete_one(
 }
 
 pub fn delete_many(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     block: &BlockMetadata,
     space_id: impl Into<String>,
     space_version: impl Into<String>,
@@ -101,7 +102,7 @@ pub fn delete_many(
 }
 
 pub fn insert_one(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     block: &BlockMetadata,
     space_id: impl Into<String>,
     space_version: impl Into<String>,
@@ -111,7 +112,7 @@ pub fn insert_one(
 }
 
 pub fn insert_many(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     block: &BlockMetadata,
     space_id: impl Into<String>,
     space_version: impl Into<String>,
@@ -120,7 +121,7 @@ pub fn insert_many(
 }
 
 pub fn find_one(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     attribute_id: impl Into<String>,
     entity_id: impl Into<String>,
     space_id: impl Into<String>,
@@ -135,11 +136,30 @@ pub fn find_one(
     )
 }
 
-pub fn find_many(neo4j: &neo4rs::Graph) -> FindManyQuery {
+pub fn find_many(neo4j: &Graph) -> FindManyQuery {
     FindManyQuery::new(neo4j)
 }
 
-pub fn search(neo4j: &neo4rs::Graph, vector: Vec<f64>) -> SemanticSearchQuery {
+/// Creates a query to find the value of `attribute_id` for multiple entities in a
+/// single round trip. At most one triple is returned per entity (following the same
+/// resolution rules as [`find_one`] when a pluralism is set).
+pub fn find_many_by_ids(
+    neo4j: &Graph,
+    attribute_id: impl Into<String>,
+    entity_ids: Vec<String>,
+    space_id: impl Into<String>,
+    space_version: Option<String>,
+) -> FindManyByIdsQuery {
+    FindManyByIdsQuery::new(
+        neo4j,
+        attribute_id.into(),
+        entity_ids,
+        space_id.into(),
+        space_version,
+    )
+}
+
+pub fn search(neo4j: &Graph, vector: Vec<f64>) -> SemanticSearchQuery {
     SemanticSearchQuery::new(neo4j, vector)
 }
 
@@ -226,7 +246,7 @@ impl From<Triple> for BoltType {
 }
 
 pub struct InsertOneQuery {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     block: BlockMetadata,
     space_id: String,
     space_version: String,
@@ -235,7 +255,7 @@ pub struct InsertOneQuery {
 
 impl InsertOneQuery {
     pub(crate) fn new(
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         block: &BlockMetadata,
         space_id: String,
         space_version: String,
@@ -289,14 +309,14 @@ impl Query<()> for InsertOneQuery {
             .param("block_number", self.block.block_number.to_string())
             .param("block_timestamp", self.block.timestamp.to_rfc3339());
 
-        self.neo4j.run(query).await?;
+        with_query_policy(&self.neo4j, || self.neo4j.run(query.clone())).await?;
 
         Ok(())
     }
 }
 
 pub struct InsertManyQuery {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     block: BlockMetadata,
     space_id: String,
     space_version: String,
@@ -304,12 +324,7 @@ pub struct InsertManyQuery {
 }
 
 impl InsertManyQuery {
-    fn new(
-        neo4j: &neo4rs::Graph,
-        block: &BlockMetadata,
-        space_id: String,
-        space_version: String,
-    ) -> Self {
+    fn new(neo4j: &Graph, block: &BlockMetadata, space_id: String, space_version: String) -> Self {
         Self {
             neo4j: neo4j.clone(),
             block: block.clone(),
@@ -377,14 +392,14 @@ impl Query<()> for InsertManyQuery {
             .param("block_number", self.block.block_number.to_string())
             .param("block_timestamp", self.block.timestamp.to_rfc3339());
 
-        self.neo4j.run(query).await?;
+        with_query_policy(&self.neo4j, || self.neo4j.run(query.clone())).await?;
 
         Ok(())
     }
 }
 
 pub struct FindOneQuery {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     attribute_id: String,
     entity_id: String,
     space_id: String,
@@ -394,7 +409,7 @@ pub struct FindOneQuery {
 
 impl FindOneQuery {
     fn new(
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         attribute_id: String,
         entity_id: String,
         space_id: String,
@@ -502,8 +517,7 @@ impl Query<Option<Triple>> for FindOneQuery {
             println!("triple::FindOneQuery:\n{}", query.compile());
         }
 
-        self.neo4j
-            .execute(query.build())
+        with_query_policy(&self.neo4j, || self.neo4j.execute(query.build()))
             .await?
             .next()
             .await?
@@ -519,8 +533,136 @@ impl Query<Option<Triple>> for FindOneQuery {
     }
 }
 
+pub struct FindManyByIdsQuery {
+    neo4j: Graph,
+    attribute_id: String,
+    entity_ids: Vec<String>,
+    space_id: String,
+    version: VersionFilter,
+    pluralism: Pluralism,
+}
+
+impl FindManyByIdsQuery {
+    fn new(
+        neo4j: &Graph,
+        attribute_id: String,
+        entity_ids: Vec<String>,
+        space_id: String,
+        version: Option<String>,
+    ) -> Self {
+        Self {
+            neo4j: neo4j.clone(),
+            attribute_id,
+            entity_ids,
+            space_id,
+            version: VersionFilter::new(version),
+            pluralism: Pluralism::None,
+        }
+    }
+
+    pub fn pluralism(mut self, pluralism_config: Pluralism) -> Self {
+        self.pluralism = pluralism_config;
+        self
+    }
+
+    fn subquery(&self) -> impl Subquery {
+        match &self.pluralism {
+            Pluralism::None | Pluralism::Direction(AggregationDirection::Bidirectional) => {
+                if let Pluralism::Direction(_) = &self.pluralism {
+                    tracing::warn!("Bidirectional aggregation direction is not implemented yet! Defaulting to None.");
+                }
+
+                QueryBuilder::default()
+                    .subquery("UNWIND $entity_ids AS entity_id")
+                    .subquery(
+                        MatchQuery::new("(e:Entity {id: entity_id}) -[r:ATTRIBUTE {space_id: $space_id}]-> (attr:Attribute {id: $attribute_id})")
+                            .r#where(self.version.subquery("r"))
+                    )
+                    .params("attribute_id", self.attribute_id.clone())
+                    .params("entity_ids", self.entity_ids.clone())
+                    .params("space_id", self.space_id.clone())
+                    .r#return("attr{.*, entity: e.id} AS triple")
+            }
+            Pluralism::Direction(AggregationDirection::Up) => {
+                QueryBuilder::default()
+                    .subquery(format!(
+                        r#"MATCH (start:Entity {{id: $space_id}}) (() <-[r:RELATION {{relation_type: "{}", space_id: "{}"}}]- (s:Entity)){{,}}"#,
+                        indexer_ids::PARENT_SPACE,
+                        indexer_ids::INDEXER_SPACE_ID,
+                    ))
+                    .subquery("WHERE size(s) = size(COLLECT { WITH s UNWIND s AS _ RETURN DISTINCT _ })")
+                    .subquery("WITH COLLECT({space_id: LAST([start] + s).id, depth: SIZE(s)}) AS subspaces")
+                    .subquery("UNWIND subspaces AS subspace")
+                    .subquery("UNWIND $entity_ids AS entity_id")
+                    .subquery(r#"MATCH (e:Entity {id: entity_id}) -[r_attr:ATTRIBUTE {space_id: subspace.space_id}]-> (attr:Attribute {id: $attribute_id})"#)
+                    .subquery(self.version.subquery("r_attr"))
+                    .subquery("WITH e, attr, subspace ORDER BY subspace.depth")
+                    .subquery("WITH e, COLLECT(attr)[0] AS attr")
+                    .params("attribute_id", self.attribute_id.clone())
+                    .params("entity_ids", self.entity_ids.clone())
+                    .params("space_id", self.space_id.clone())
+                    .r#return("attr{.*, entity: e.id} AS triple")
+            }
+            Pluralism::Direction(AggregationDirection::Down) => {
+                QueryBuilder::default()
+                    .subquery(format!(
+                        r#"MATCH (start:Entity {{id: $space_id}}) (() -[r:RELATION {{relation_type: "{}", space_id: "{}"}}]-> (s:Entity)){{,}}"#,
+                        indexer_ids::PARENT_SPACE,
+                        indexer_ids::INDEXER_SPACE_ID,
+                    ))
+                    .subquery("WHERE size(s) = size(COLLECT { WITH s UNWIND s AS _ RETURN DISTINCT _ })")
+                    .subquery("WITH COLLECT({space_id: LAST([start] + s).id, depth: SIZE(s)}) AS parent_spaces")
+                    .subquery("UNWIND parent_spaces AS parent_space")
+                    .subquery("UNWIND $entity_ids AS entity_id")
+                    .subquery(r#"MATCH (e:Entity {id: entity_id}) -[r_attr:ATTRIBUTE {space_id: parent_space.space_id}]-> (attr:Attribute {id: $attribute_id})"#)
+                    .subquery(self.version.subquery("r_attr"))
+                    .subquery("WITH e, attr, parent_space ORDER BY parent_space.depth")
+                    .subquery("WITH e, COLLECT(attr)[0] AS attr")
+                    .params("attribute_id", self.attribute_id.clone())
+                    .params("entity_ids", self.entity_ids.clone())
+                    .params("space_id", self.space_id.clone())
+                    .r#return("attr{.*, entity: e.id} AS triple")
+            }
+            Pluralism::Hierarchy(spaces) => {
+                QueryBuilder::default()
+                    .subquery("UNWIND $spaces AS space")
+                    .subquery("UNWIND $entity_ids AS entity_id")
+                    .subquery(r#"MATCH (e:Entity {id: entity_id}) -[r_attr:ATTRIBUTE {space_id: space.space_id}]-> (attr:Attribute {id: $attribute_id})"#)
+                    .subquery(self.version.subquery("r_attr"))
+                    .subquery("WITH e, attr, space ORDER BY space.depth")
+                    .subquery("WITH e, COLLECT(attr)[0] AS attr")
+                    .params("attribute_id", self.attribute_id.clone())
+                    .params("entity_ids", self.entity_ids.clone())
+                    .params("spaces", spaces.clone())
+                    .r#return("attr{.*, entity: e.id} AS triple")
+            }
+        }
+    }
+}
+
+impl Query<Vec<Triple>> for FindManyByIdsQuery {
+    async fn send(self) -> Result<Vec<Triple>, DatabaseError> {
+        if self.entity_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let query = self.subquery();
+
+        if cfg!(debug_assertions) || cfg!(test) {
+            println!("triple::FindManyByIdsQuery:\n{}", query.compile());
+        }
+
+        with_query_policy(&self.neo4j, || self.neo4j.execute(query.build()))
+            .await?
+            .into_stream_as::<Triple>()
+            .map_err(DatabaseError::from)
+            .try_collect()
+            .await
+    }
+}
+
 pub struct FindManyQuery {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     attribute_id: Option<PropFilter<String>>,
     value: Option<PropFilter<String>>,
     value_type: Option<PropFilter<String>>,
@@ -530,7 +672,7 @@ pub struct FindManyQuery {
 }
 
 impl FindManyQuery {
-    pub fn new(neo4j: &neo4rs::Graph) -> Self {
+    pub fn new(neo4j: &Graph) -> Self {
         Self {
             neo4j: neo4j.clone(),
             attribute_id: None,
@@ -609,17 +751,17 @@ impl QueryStream<Triple> for FindManyQuery {
             println!("triple::FindManyQuery:\n{}", query.compile());
         }
 
-        Ok(self
-            .neo4j
-            .execute(query.build())
-            .await?
-            .into_stream_as::<Triple>()
-            .map_err(DatabaseError::from))
+        Ok(
+            with_query_policy(&self.neo4j, || self.neo4j.execute(query.build()))
+                .await?
+                .into_stream_as::<Triple>()
+                .map_err(DatabaseError::from),
+        )
     }
 }
 
 pub struct SemanticSearchQuery {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     vector: Vec<f64>,
     // space_id: Option<PropFilter<String>>,
     // space_version: VersionFilter,
@@ -628,7 +770,7 @@ pub struct SemanticSearchQuery {
 }
 
 impl SemanticSearchQuery {
-    pub fn new(neo4j: &neo4rs::Graph, vector: Vec<f64>) -> Self {
+    pub fn new(neo4j: &Graph, vector: Vec<f64>) -> Self {
         Self {
             neo4j: neo4j.clone(),
             vector,
@@ -714,17 +856,17 @@ impl QueryStream<SemanticSearchResult> for SemanticSearchQuery {
             .param("limit", self.limit as i64)
             .param("effective_search_ratio", EFFECTIVE_SEARCH_RATIO);
 
-        Ok(self
-            .neo4j
-            .execute(query)
-            .await?
-            .into_stream_as::<SemanticSearchResult>()
-            .map_err(DatabaseError::from))
+        Ok(
+            with_query_policy(&self.neo4j, || self.neo4j.execute(query.clone()))
+                .await?
+                .into_stream_as::<SemanticSearchResult>()
+                .map_err(DatabaseError::from),
+        )
     }
 }
 
 pub struct DeleteOneQuery {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     block: BlockMetadata,
     attribute_id: String,
     entity_id: String,
@@ -734,7 +876,7 @@ pub struct DeleteOneQuery {
 
 impl DeleteOneQuery {
     pub fn new(
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         block: &BlockMetadata,
         attribute_id: String,
         entity_id: String,
@@ -776,14 +918,14 @@ impl Query<()> for DeleteOneQuery {
             .param("block_number", self.block.block_number.to_string())
             .param("block_timestamp", self.block.timestamp.to_rfc3339());
 
-        self.neo4j.run(query).await?;
+        with_query_policy(&self.neo4j, || self.neo4j.run(query.clone())).await?;
 
         Ok(())
     }
 }
 
 pub struct DeleteManyQuery {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     block: BlockMetadata,
     space_id: String,
     space_version: String,
@@ -792,7 +934,7 @@ pub struct DeleteManyQuery {
 
 impl DeleteManyQuery {
     pub fn new(
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         block: &BlockMetadata,
         space_id: String,
         space_version: String,
@@ -872,7 +1014,7 @@ impl Query<()> for DeleteManyQuery {
             .param("block_number", self.block.block_number.to_string())
             .param("block_timestamp", self.block.timestamp.to_rfc3339());
 
-        self.neo4j.run(query).await?;
+        with_query_policy(&self.neo4j, || self.neo4j.run(query.clone())).await?;
 
         Ok(())
     }
@@ -927,6 +1069,40 @@ mod tests {
         assert_eq!(triple, found_triple);
     }
 
+    #[tokio::test]
+    async fn test_find_many_by_ids() {
+        // Setup a local Neo 4J container for testing. NOTE: docker service must be running.
+        let (_container, neo4j) = crate::test_utils::setup_neo4j().await;
+
+        let triple = Triple::new("abc", "name", "Alice");
+        let other_triple = Triple::new("def", "name", "Bob");
+
+        insert_many(&neo4j, &BlockMetadata::default(), "ROOT", "0")
+            .triples(vec![
+                triple.clone(),
+                other_triple.clone(),
+                Triple::new("ghi", "name", "Charlie"),
+            ])
+            .send()
+            .await
+            .expect("Failed to insert triples");
+
+        let mut found_triples = find_many_by_ids(
+            &neo4j,
+            "name",
+            vec!["abc".to_string(), "def".to_string(), "unknown".to_string()],
+            "ROOT",
+            None,
+        )
+        .send()
+        .await
+        .expect("Failed to find triples");
+
+        found_triples.sort_by(|a, b| a.entity.cmp(&b.entity));
+
+        assert_eq!(vec![triple, other_triple], found_triples);
+    }
+
     #[tokio::test]
     pub async fn test_insert_many() {
         // Setup a local Neo 4J container for testing. NOTE: docker service must be running.
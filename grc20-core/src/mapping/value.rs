@@ -2,7 +2,7 @@ use std::{collections::HashMap, fmt::Display};
 
 use chrono::{DateTime, Utc};
 use neo4rs::BoltType;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::pb;
 
@@ -269,7 +269,7 @@ pub struct Options {
     pub language: Option<String>,
 }
 
-#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ValueType {
     #[default]
@@ -309,3 +309,66 @@ impl TryFrom<pb::ipfs::ValueType> for ValueType {
         }
     }
 }
+
+impl From<ValueType> for pb::ipfs::ValueType {
+    fn from(value: ValueType) -> Self {
+        match value {
+            ValueType::Text => pb::ipfs::ValueType::Text,
+            ValueType::Number => pb::ipfs::ValueType::Number,
+            ValueType::Checkbox => pb::ipfs::ValueType::Checkbox,
+            ValueType::Url => pb::ipfs::ValueType::Url,
+            ValueType::Time => pb::ipfs::ValueType::Time,
+            ValueType::Point => pb::ipfs::ValueType::Point,
+        }
+    }
+}
+
+/// Types which can be used as attribute values with a known value type and options.
+///
+/// This trait is implemented for the primitive types supported by [`Value`] and can be
+/// derived for unit enums and newtypes with `#[derive(Grc20Value)]` (see [`crate::Grc20Value`]).
+pub trait Grc20Value: Into<Value> + TryFrom<Value, Error = TriplesConversionError> {
+    /// The value type of the attribute values of this type
+    fn value_type() -> ValueType;
+
+    /// The options (e.g.: unit, format) of the attribute values of this type
+    fn options() -> Options {
+        Options::default()
+    }
+}
+
+impl Grc20Value for String {
+    fn value_type() -> ValueType {
+        ValueType::Text
+    }
+}
+
+impl Grc20Value for i64 {
+    fn value_type() -> ValueType {
+        ValueType::Number
+    }
+}
+
+impl Grc20Value for u64 {
+    fn value_type() -> ValueType {
+        ValueType::Number
+    }
+}
+
+impl Grc20Value for f64 {
+    fn value_type() -> ValueType {
+        ValueType::Number
+    }
+}
+
+impl Grc20Value for bool {
+    fn value_type() -> ValueType {
+        ValueType::Checkbox
+    }
+}
+
+impl Grc20Value for DateTime<Utc> {
+    fn value_type() -> ValueType {
+        ValueType::Time
+    }
+}
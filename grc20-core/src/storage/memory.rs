@@ -0,0 +1,427 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use crate::{
+    block::BlockMetadata,
+    error::DatabaseError,
+    mapping::{
+        entity::SystemProperties, query_utils::VersionFilter, AttributeNode, Entity, EntityNode,
+        EntityNodeRef, FromAttributes, RelationEdge, Triple,
+    },
+    system_ids,
+};
+
+use super::{RelationEdgeFilter, Storage, TripleFilter};
+
+/// Pure in-memory [`Storage`] implementation following the same versioning rules
+/// as the Neo4j implementation. Cloning the storage returns a handle to the same data.
+///
+/// NOTE: Entity merges are not supported, i.e.: relations and entities are never
+/// resolved to their canonical entity.
+#[derive(Clone, Default)]
+pub struct InMemoryStorage {
+    state: Arc<RwLock<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    entities: HashMap<String, SystemProperties>,
+    attributes: Vec<AttributeRecord>,
+    relations: Vec<RelationRecord>,
+}
+
+/// Equivalent of an `ATTRIBUTE` edge and its attribute node
+struct AttributeRecord {
+    space_id: String,
+    min_version: String,
+    max_version: Option<String>,
+    triple: Triple,
+}
+
+/// Equivalent of a `RELATION` edge
+struct RelationRecord {
+    space_id: String,
+    min_version: String,
+    max_version: Option<String>,
+    edge: RelationEdge<EntityNodeRef>,
+}
+
+impl AttributeRecord {
+    fn is_current(&self) -> bool {
+        self.max_version.is_none()
+    }
+
+    fn matches(&self, version: &VersionFilter) -> bool {
+        version.matches(&self.min_version, self.max_version.as_deref())
+    }
+}
+
+impl RelationRecord {
+    fn is_current(&self) -> bool {
+        self.max_version.is_none()
+    }
+
+    fn matches(&self, version: &VersionFilter) -> bool {
+        version.matches(&self.min_version, self.max_version.as_deref())
+    }
+}
+
+impl State {
+    fn touch_entity(&mut self, entity_id: &str, block: &BlockMetadata) {
+        if let Some(system_properties) = self.entities.get_mut(entity_id) {
+            system_properties.updated_at = block.timestamp;
+            system_properties.updated_at_block = block.block_number.to_string();
+        }
+    }
+}
+
+impl InMemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, State> {
+        self.state.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, State> {
+        self.state.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Storage for InMemoryStorage {
+    async fn insert_triples(
+        &self,
+        block: &BlockMetadata,
+        space_id: &str,
+        space_version: &str,
+        triples: Vec<Triple>,
+    ) -> Result<(), DatabaseError> {
+        let mut state = self.write();
+
+        for triple in triples {
+            state
+                .entities
+                .entry(triple.entity.clone())
+                .or_insert_with(|| block.clone().into());
+            state.touch_entity(&triple.entity, block);
+
+            // Close the current value (if it was set in a previous version)
+            state
+                .attributes
+                .iter_mut()
+                .filter(|record| {
+                    record.triple.entity == triple.entity
+                        && record.triple.attribute == triple.attribute
+                        && record.space_id == space_id
+                        && record.is_current()
+                        && record.min_version != space_version
+                })
+                .for_each(|record| record.max_version = Some(space_version.to_string()));
+
+            // Values set multiple times in the same version are overwritten
+            match state.attributes.iter_mut().find(|record| {
+                record.triple.entity == triple.entity
+                    && record.triple.attribute == triple.attribute
+                    && record.space_id == space_id
+                    && record.min_version == space_version
+            }) {
+                Some(record) => record.triple = triple,
+                None => state.attributes.push(AttributeRecord {
+                    space_id: space_id.to_string(),
+                    min_version: space_version.to_string(),
+                    max_version: None,
+                    triple,
+                }),
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn delete_triples(
+        &self,
+        block: &BlockMetadata,
+        space_id: &str,
+        space_version: &str,
+        triples: Vec<(String, String)>,
+    ) -> Result<(), DatabaseError> {
+        let mut state = self.write();
+
+        for (entity_id, attribute_id) in triples {
+            let mut deleted = false;
+
+            state
+                .attributes
+                .iter_mut()
+                .filter(|record| {
+                    record.triple.entity == entity_id
+                        && record.triple.attribute == attribute_id
+                        && record.space_id == space_id
+                        && record.is_current()
+                })
+                .for_each(|record| {
+                    record.max_version = Some(space_version.to_string());
+                    deleted = true;
+                });
+
+            if deleted {
+                state.touch_entity(&entity_id, block);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn find_triple(
+        &self,
+        attribute_id: &str,
+        entity_id: &str,
+        space_id: &str,
+        space_version: Option<String>,
+    ) -> Result<Option<Triple>, DatabaseError> {
+        let version = VersionFilter::new(space_version);
+
+        Ok(self
+            .read()
+            .attributes
+            .iter()
+            .find(|record| {
+                record.triple.entity == entity_id
+                    && record.triple.attribute == attribute_id
+                    && record.space_id == space_id
+                    && record.matches(&version)
+            })
+            .map(|record| record.triple.clone()))
+    }
+
+    async fn find_triples(&self, filter: TripleFilter) -> Result<Vec<Triple>, DatabaseError> {
+        let version = VersionFilter::new(filter.version);
+
+        Ok(self
+            .read()
+            .attributes
+            .iter()
+            .filter(|record| {
+                let triple = &record.triple;
+
+                record.matches(&version)
+                    && filter
+                        .entity_id
+                        .as_ref()
+                        .is_none_or(|f| f.matches(&triple.entity))
+                    && filter
+                        .attribute_id
+                        .as_ref()
+                        .is_none_or(|f| f.matches(&triple.attribute))
+                    && filter
+                        .value
+                        .as_ref()
+                        .is_none_or(|f| f.matches(&triple.value.value))
+                    && filter
+                        .value_type
+                        .as_ref()
+                        .is_none_or(|f| f.matches(&triple.value.value_type.to_string()))
+                    && filter
+                        .space_id
+                        .as_ref()
+                        .is_none_or(|f| f.matches(&record.space_id))
+            })
+            .map(|record| record.triple.clone())
+            .collect())
+    }
+
+    async fn insert_relations(
+        &self,
+        block: &BlockMetadata,
+        space_id: &str,
+        space_version: &str,
+        relations: Vec<RelationEdge<EntityNodeRef>>,
+    ) -> Result<(), DatabaseError> {
+        let mut state = self.write();
+
+        for relation in relations {
+            if !state.entities.contains_key(&relation.from.0)
+                || !state.entities.contains_key(&relation.to.0)
+            {
+                continue;
+            }
+
+            state.relations.push(RelationRecord {
+                space_id: space_id.to_string(),
+                min_version: space_version.to_string(),
+                max_version: None,
+                edge: RelationEdge {
+                    system_properties: block.clone().into(),
+                    ..relation
+                },
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn delete_relations(
+        &self,
+        block: &BlockMetadata,
+        space_id: &str,
+        space_version: &str,
+        relation_ids: Vec<String>,
+    ) -> Result<(), DatabaseError> {
+        let mut state = self.write();
+
+        state
+            .relations
+            .iter_mut()
+            .filter(|record| {
+                relation_ids.contains(&record.edge.id)
+                    && record.space_id == space_id
+                    && record.is_current()
+            })
+            .for_each(|record| {
+                record.max_version = Some(space_version.to_string());
+                record.edge.system_properties.updated_at = block.timestamp;
+                record.edge.system_properties.updated_at_block = block.block_number.to_string();
+            });
+
+        Ok(())
+    }
+
+    async fn find_relations(
+        &self,
+        filter: RelationEdgeFilter,
+    ) -> Result<Vec<RelationEdge<EntityNodeRef>>, DatabaseError> {
+        let version = VersionFilter::new(filter.version);
+
+        let mut relations = self
+            .read()
+            .relations
+            .iter()
+            .filter(|record| {
+                let edge = &record.edge;
+
+                record.matches(&version)
+                    && filter.id.as_ref().is_none_or(|f| f.matches(&edge.id))
+                    && filter
+                        .from_id
+                        .as_ref()
+                        .is_none_or(|f| f.matches(&edge.from.0))
+                    && filter.to_id.as_ref().is_none_or(|f| f.matches(&edge.to.0))
+                    && filter
+                        .relation_type
+                        .as_ref()
+                        .is_none_or(|f| f.matches(&edge.relation_type))
+                    && filter
+                        .space_id
+                        .as_ref()
+                        .is_none_or(|f| f.matches(&record.space_id))
+            })
+            .map(|record| record.edge.clone())
+            .collect::<Vec<_>>();
+
+        relations.sort_by(|a, b| a.index.cmp(&b.index).then_with(|| a.id.cmp(&b.id)));
+
+        Ok(relations
+            .into_iter()
+            .skip(filter.skip.unwrap_or(0))
+            .take(filter.limit)
+            .collect())
+    }
+
+    async fn delete_entity(
+        &self,
+        block: &BlockMetadata,
+        entity_id: &str,
+        space_id: &str,
+        space_version: &str,
+    ) -> Result<(), DatabaseError> {
+        let mut state = self.write();
+        let mut deleted = false;
+
+        state
+            .attributes
+            .iter_mut()
+            .filter(|record| {
+                record.triple.entity == entity_id
+                    && record.space_id == space_id
+                    && record.is_current()
+            })
+            .for_each(|record| {
+                record.max_version = Some(space_version.to_string());
+                deleted = true;
+            });
+
+        if deleted {
+            state.touch_entity(entity_id, block);
+        }
+
+        Ok(())
+    }
+
+    async fn find_entity_node(&self, id: &str) -> Result<Option<EntityNode>, DatabaseError> {
+        Ok(self
+            .read()
+            .entities
+            .get(id)
+            .map(|system_properties| EntityNode {
+                id: id.to_string(),
+                system_properties: system_properties.clone(),
+            }))
+    }
+
+    async fn find_entity<T: FromAttributes + Send>(
+        &self,
+        id: &str,
+        space_id: Option<String>,
+        space_version: Option<String>,
+    ) -> Result<Option<Entity<T>>, DatabaseError> {
+        let version = VersionFilter::new(space_version);
+        let in_space = |record_space_id: &str| {
+            space_id
+                .as_deref()
+                .is_none_or(|space_id| space_id == record_space_id)
+        };
+
+        let state = self.read();
+
+        let Some(system_properties) = state.entities.get(id) else {
+            return Ok(None);
+        };
+
+        let attributes = state
+            .attributes
+            .iter()
+            .filter(|record| {
+                record.triple.entity == id && in_space(&record.space_id) && record.matches(&version)
+            })
+            .map(|record| AttributeNode::from(record.triple.clone()))
+            .collect::<Vec<_>>();
+
+        let mut types: Vec<String> = vec![];
+        state
+            .relations
+            .iter()
+            .filter(|record| {
+                record.edge.from.0 == id
+                    && record.edge.relation_type == system_ids::TYPES_ATTRIBUTE
+                    && in_space(&record.space_id)
+                    && record.matches(&version)
+            })
+            .for_each(|record| {
+                if !types.contains(&record.edge.to.0) {
+                    types.push(record.edge.to.0.clone());
+                }
+            });
+
+        Ok(Some(Entity {
+            node: EntityNode {
+                id: id.to_string(),
+                system_properties: system_properties.clone(),
+            },
+            attributes: T::from_attributes(attributes.into())?,
+            types,
+        }))
+    }
+}
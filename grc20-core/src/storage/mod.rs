@@ -0,0 +1,470 @@
+//! Storage abstraction over the entity, triple and relation operations of the
+//! knowledge graph.
+//!
+//! [`Neo4jStorage`] delegates to the Neo4j queries of the [`mapping`](crate::mapping)
+//! module while [`InMemoryStorage`] is a pure in-memory implementation following the
+//! same versioning rules, which makes it possible to test code written against the
+//! [`Storage`] trait without a Neo4j instance.
+//!
+//! ```rust,ignore
+//! use grc20_core::storage::{InMemoryStorage, Storage, TripleFilter};
+//!
+//! let storage = InMemoryStorage::new();
+//!
+//! storage
+//!     .insert_triples(&block, "space_id", "0", vec![Triple::new("abc", "name", "Alice")])
+//!     .await?;
+//!
+//! let triples = storage
+//!     .find_triples(TripleFilter::default().entity_id(prop_filter::value("abc")))
+//!     .await?;
+//! ```
+use std::future::Future;
+
+use crate::{
+    block::BlockMetadata,
+    error::DatabaseError,
+    mapping::{
+        Entity, EntityNode, EntityNodeRef, FromAttributes, PropFilter, RelationEdge, Triple,
+    },
+};
+
+pub mod memory;
+pub mod neo4j;
+
+pub use memory::InMemoryStorage;
+pub use neo4j::Neo4jStorage;
+
+pub trait Storage: Clone + Send + Sync {
+    /// Inserts the triples in the space `space_id` at version `space_version`. The
+    /// current values of the triples (if any) are closed at `space_version`.
+    fn insert_triples(
+        &self,
+        block: &BlockMetadata,
+        space_id: &str,
+        space_version: &str,
+        triples: Vec<Triple>,
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    /// Deletes the triples identified by their `(entity_id, attribute_id)` pair in the
+    /// space `space_id` at version `space_version`.
+    fn delete_triples(
+        &self,
+        block: &BlockMetadata,
+        space_id: &str,
+        space_version: &str,
+        triples: Vec<(String, String)>,
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    /// Returns the value of the attribute `attribute_id` of the entity `entity_id` in
+    /// the space `space_id` (current value if `space_version` is not set).
+    fn find_triple(
+        &self,
+        attribute_id: &str,
+        entity_id: &str,
+        space_id: &str,
+        space_version: Option<String>,
+    ) -> impl Future<Output = Result<Option<Triple>, DatabaseError>> + Send;
+
+    fn find_triples(
+        &self,
+        filter: TripleFilter,
+    ) -> impl Future<Output = Result<Vec<Triple>, DatabaseError>> + Send;
+
+    /// Inserts the relations in the space `space_id` at version `space_version`.
+    /// Relations whose `from` or `to` entity does not exist are ignored.
+    fn insert_relations(
+        &self,
+        block: &BlockMetadata,
+        space_id: &str,
+        space_version: &str,
+        relations: Vec<RelationEdge<EntityNodeRef>>,
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    fn delete_relations(
+        &self,
+        block: &BlockMetadata,
+        space_id: &str,
+        space_version: &str,
+        relation_ids: Vec<String>,
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    /// Returns the relations matching the filter, ordered by index (and id)
+    fn find_relations(
+        &self,
+        filter: RelationEdgeFilter,
+    ) -> impl Future<Output = Result<Vec<RelationEdge<EntityNodeRef>>, DatabaseError>> + Send;
+
+    /// Deletes all the attributes of the entity `entity_id` in the space `space_id`
+    /// at version `space_version`.
+    fn delete_entity(
+        &self,
+        block: &BlockMetadata,
+        entity_id: &str,
+        space_id: &str,
+        space_version: &str,
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    fn find_entity_node(
+        &self,
+        id: &str,
+    ) -> impl Future<Output = Result<Option<EntityNode>, DatabaseError>> + Send;
+
+    /// Returns the entity `id` with its attributes and types, optionally restricted
+    /// to the space `space_id` (current version if `space_version` is not set).
+    fn find_entity<T: FromAttributes + Send>(
+        &self,
+        id: &str,
+        space_id: Option<String>,
+        space_version: Option<String>,
+    ) -> impl Future<Output = Result<Option<Entity<T>>, DatabaseError>> + Send;
+}
+
+/// Filter of [`Storage::find_triples`]. Only current triples are returned unless
+/// a version is set.
+#[derive(Clone, Debug, Default)]
+pub struct TripleFilter {
+    pub(crate) entity_id: Option<PropFilter<String>>,
+    pub(crate) attribute_id: Option<PropFilter<String>>,
+    pub(crate) value: Option<PropFilter<String>>,
+    pub(crate) value_type: Option<PropFilter<String>>,
+    pub(crate) space_id: Option<PropFilter<String>>,
+    pub(crate) version: Option<String>,
+}
+
+impl TripleFilter {
+    pub fn entity_id(mut self, entity_id: impl Into<PropFilter<String>>) -> Self {
+        self.entity_id = Some(entity_id.into());
+        self
+    }
+
+    pub fn attribute_id(mut self, attribute_id: impl Into<PropFilter<String>>) -> Self {
+        self.attribute_id = Some(attribute_id.into());
+        self
+    }
+
+    pub fn value(mut self, value: impl Into<PropFilter<String>>) -> Self {
+        self.value = Some(value.into());
+        self
+    }
+
+    pub fn value_type(mut self, value_type: impl Into<PropFilter<String>>) -> Self {
+        self.value_type = Some(value_type.into());
+        self
+    }
+
+    pub fn space_id(mut self, space_id: impl Into<PropFilter<String>>) -> Self {
+        self.space_id = Some(space_id.into());
+        self
+    }
+
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    pub fn version_opt(mut self, version: Option<String>) -> Self {
+        self.version = version;
+        self
+    }
+}
+
+/// Filter of [`Storage::find_relations`]. Only current relations are returned unless
+/// a version is set. At most 100 relations are returned by default.
+#[derive(Clone, Debug)]
+pub struct RelationEdgeFilter {
+    pub(crate) id: Option<PropFilter<String>>,
+    pub(crate) from_id: Option<PropFilter<String>>,
+    pub(crate) to_id: Option<PropFilter<String>>,
+    pub(crate) relation_type: Option<PropFilter<String>>,
+    pub(crate) space_id: Option<PropFilter<String>>,
+    pub(crate) version: Option<String>,
+    pub(crate) limit: usize,
+    pub(crate) skip: Option<usize>,
+}
+
+impl Default for RelationEdgeFilter {
+    fn default() -> Self {
+        Self {
+            id: None,
+            from_id: None,
+            to_id: None,
+            relation_type: None,
+            space_id: None,
+            version: None,
+            limit: 100,
+            skip: None,
+        }
+    }
+}
+
+impl RelationEdgeFilter {
+    pub fn id(mut self, id: impl Into<PropFilter<String>>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    pub fn from_id(mut self, from_id: impl Into<PropFilter<String>>) -> Self {
+        self.from_id = Some(from_id.into());
+        self
+    }
+
+    pub fn to_id(mut self, to_id: impl Into<PropFilter<String>>) -> Self {
+        self.to_id = Some(to_id.into());
+        self
+    }
+
+    pub fn relation_type(mut self, relation_type: impl Into<PropFilter<String>>) -> Self {
+        self.relation_type = Some(relation_type.into());
+        self
+    }
+
+    pub fn space_id(mut self, space_id: impl Into<PropFilter<String>>) -> Self {
+        self.space_id = Some(space_id.into());
+        self
+    }
+
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    pub fn version_opt(mut self, version: Option<String>) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub fn skip(mut self, skip: usize) -> Self {
+        self.skip = Some(skip);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::TryFutureExt;
+
+    use super::*;
+    use crate::{
+        mapping::{prop_filter, Attributes},
+        system_ids,
+    };
+
+    /// Runs the same scenario against a storage implementation so that the
+    /// in-memory implementation is checked against the Neo4j one.
+    async fn scenario(storage: impl Storage) {
+        let block = BlockMetadata::default();
+
+        // Triples
+        storage
+            .insert_triples(
+                &block,
+                "ROOT",
+                "0",
+                vec![
+                    Triple::new("abc", "name", "Alice"),
+                    Triple::new("def", "name", "Bob"),
+                    Triple::new("type", "name", "Person"),
+                ],
+            )
+            .await
+            .expect("Failed to insert triples");
+
+        storage
+            .insert_triples(
+                &block,
+                "ROOT",
+                "1",
+                vec![Triple::new("abc", "name", "Alicia")],
+            )
+            .await
+            .expect("Failed to insert triples");
+
+        storage
+            .insert_triples(&block, "OTHER", "1", vec![Triple::new("abc", "name", "Al")])
+            .await
+            .expect("Failed to insert triples");
+
+        let find_name = |version: Option<&str>| {
+            storage
+                .find_triple("name", "abc", "ROOT", version.map(String::from))
+                .map_ok(|triple| triple.map(|triple| triple.value.value))
+        };
+
+        assert_eq!(find_name(None).await.unwrap(), Some("Alicia".to_string()));
+        assert_eq!(
+            find_name(Some("0")).await.unwrap(),
+            Some("Alice".to_string())
+        );
+        assert_eq!(
+            find_name(Some("1")).await.unwrap(),
+            Some("Alicia".to_string())
+        );
+
+        let mut triples = storage
+            .find_triples(
+                TripleFilter::default()
+                    .attribute_id(prop_filter::value("name"))
+                    .space_id(prop_filter::value("ROOT"))
+                    .version("0"),
+            )
+            .await
+            .expect("Failed to find triples");
+        triples.sort_by(|a, b| a.entity.cmp(&b.entity));
+
+        assert_eq!(
+            triples,
+            vec![
+                Triple::new("abc", "name", "Alice"),
+                Triple::new("def", "name", "Bob"),
+                Triple::new("type", "name", "Person"),
+            ]
+        );
+
+        storage
+            .delete_triples(&block, "ROOT", "2", vec![("def".into(), "name".into())])
+            .await
+            .expect("Failed to delete triples");
+
+        assert_eq!(
+            storage
+                .find_triple("name", "def", "ROOT", None)
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            storage
+                .find_triple("name", "def", "ROOT", Some("1".into()))
+                .await
+                .unwrap(),
+            Some(Triple::new("def", "name", "Bob"))
+        );
+
+        // Relations
+        storage
+            .insert_relations(
+                &block,
+                "ROOT",
+                "2",
+                vec![
+                    RelationEdge::new("r2", "abc", "def", "knows", "b"),
+                    RelationEdge::new("r1", "abc", "type", "knows", "a"),
+                    RelationEdge::new_types("r3", "abc", "type", "a"),
+                    RelationEdge::new("r4", "abc", "unknown", "knows", "c"),
+                ],
+            )
+            .await
+            .expect("Failed to insert relations");
+
+        let relation_ids = |filter: RelationEdgeFilter| {
+            storage.find_relations(filter).map_ok(|relations| {
+                relations
+                    .into_iter()
+                    .map(|relation| relation.id)
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        let knows = RelationEdgeFilter::default()
+            .from_id(prop_filter::value("abc"))
+            .relation_type(prop_filter::value("knows"))
+            .space_id(prop_filter::value("ROOT"));
+
+        assert_eq!(relation_ids(knows.clone()).await.unwrap(), vec!["r1", "r2"]);
+        assert_eq!(
+            relation_ids(knows.clone().skip(1)).await.unwrap(),
+            vec!["r2"]
+        );
+        assert_eq!(
+            relation_ids(knows.clone().version("1")).await.unwrap(),
+            Vec::<String>::new()
+        );
+
+        storage
+            .delete_relations(&block, "ROOT", "3", vec!["r1".into()])
+            .await
+            .expect("Failed to delete relations");
+
+        assert_eq!(relation_ids(knows.clone()).await.unwrap(), vec!["r2"]);
+        assert_eq!(
+            relation_ids(knows.version("2")).await.unwrap(),
+            vec!["r1", "r2"]
+        );
+
+        // Entities
+        assert_eq!(
+            storage
+                .find_entity_node("abc")
+                .await
+                .unwrap()
+                .map(|node| node.id),
+            Some("abc".to_string())
+        );
+        assert_eq!(storage.find_entity_node("unknown").await.unwrap(), None);
+
+        let entity = storage
+            .find_entity::<Attributes>("abc", Some("ROOT".into()), None)
+            .await
+            .expect("Failed to find entity")
+            .expect("Entity not found");
+
+        assert_eq!(entity.id(), "abc");
+        assert_eq!(entity.types, vec!["type".to_string()]);
+        assert_eq!(
+            entity.attributes.get::<String>("name").unwrap(),
+            "Alicia".to_string()
+        );
+
+        storage
+            .delete_entity(&block, "abc", "ROOT", "4")
+            .await
+            .expect("Failed to delete entity");
+
+        let entity = storage
+            .find_entity::<Attributes>("abc", Some("ROOT".into()), None)
+            .await
+            .expect("Failed to find entity")
+            .expect("Entity not found");
+
+        assert!(entity.attributes.0.is_empty());
+        assert_eq!(
+            storage
+                .find_triple("name", "abc", "OTHER", None)
+                .await
+                .unwrap()
+                .map(|triple| triple.value.value),
+            Some("Al".to_string())
+        );
+
+        assert_eq!(
+            storage
+                .find_relations(
+                    RelationEdgeFilter::default()
+                        .relation_type(prop_filter::value(system_ids::TYPES_ATTRIBUTE))
+                )
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_storage() {
+        scenario(InMemoryStorage::new()).await;
+    }
+
+    #[tokio::test]
+    async fn test_neo4j_storage() {
+        // Setup a local Neo 4J container for testing. NOTE: docker service must be running.
+        let (_container, neo4j) = crate::test_utils::setup_neo4j().await;
+
+        scenario(Neo4jStorage::new(neo4j)).await;
+    }
+}
@@ -0,0 +1,203 @@
+use futures::TryStreamExt;
+
+use crate::{
+    block::BlockMetadata,
+    connection::Graph,
+    error::DatabaseError,
+    mapping::{
+        entity, relation, relation::RelationFilter, triple, Entity, EntityFilter, EntityNode,
+        EntityNodeRef, FromAttributes, Query, QueryStream, RelationEdge, Triple,
+    },
+};
+
+use super::{RelationEdgeFilter, Storage, TripleFilter};
+
+/// [`Storage`] implementation backed by Neo4j
+#[derive(Clone)]
+pub struct Neo4jStorage {
+    neo4j: Graph,
+}
+
+impl Neo4jStorage {
+    pub fn new(neo4j: Graph) -> Self {
+        Self { neo4j }
+    }
+
+    pub fn graph(&self) -> &Graph {
+        &self.neo4j
+    }
+}
+
+impl From<Graph> for Neo4jStorage {
+    fn from(neo4j: Graph) -> Self {
+        Self::new(neo4j)
+    }
+}
+
+impl Storage for Neo4jStorage {
+    async fn insert_triples(
+        &self,
+        block: &BlockMetadata,
+        space_id: &str,
+        space_version: &str,
+        triples: Vec<Triple>,
+    ) -> Result<(), DatabaseError> {
+        triple::insert_many(&self.neo4j, block, space_id, space_version)
+            .triples(triples)
+            .send()
+            .await
+    }
+
+    async fn delete_triples(
+        &self,
+        block: &BlockMetadata,
+        space_id: &str,
+        space_version: &str,
+        triples: Vec<(String, String)>,
+    ) -> Result<(), DatabaseError> {
+        triple::delete_many(&self.neo4j, block, space_id, space_version)
+            .triples(triples)
+            .send()
+            .await
+    }
+
+    async fn find_triple(
+        &self,
+        attribute_id: &str,
+        entity_id: &str,
+        space_id: &str,
+        space_version: Option<String>,
+    ) -> Result<Option<Triple>, DatabaseError> {
+        triple::find_one(
+            &self.neo4j,
+            attribute_id,
+            entity_id,
+            space_id,
+            space_version,
+        )
+        .send()
+        .await
+    }
+
+    async fn find_triples(&self, filter: TripleFilter) -> Result<Vec<Triple>, DatabaseError> {
+        let mut query = triple::find_many(&self.neo4j);
+
+        if let Some(entity_id) = filter.entity_id {
+            query = query.entity_id(entity_id);
+        }
+        if let Some(attribute_id) = filter.attribute_id {
+            query = query.attribute_id(attribute_id);
+        }
+        if let Some(value) = filter.value {
+            query = query.value(value);
+        }
+        if let Some(value_type) = filter.value_type {
+            query = query.value_type(value_type);
+        }
+        if let Some(space_id) = filter.space_id {
+            query = query.space_id(space_id);
+        }
+        if let Some(version) = filter.version {
+            query = query.space_version(version);
+        }
+
+        query.send().await?.try_collect().await
+    }
+
+    async fn insert_relations(
+        &self,
+        block: &BlockMetadata,
+        space_id: &str,
+        space_version: &str,
+        relations: Vec<RelationEdge<EntityNodeRef>>,
+    ) -> Result<(), DatabaseError> {
+        relation::insert_many::<RelationEdge<EntityNodeRef>>(
+            &self.neo4j,
+            block,
+            space_id,
+            space_version,
+        )
+        .relations(relations)
+        .send()
+        .await
+    }
+
+    async fn delete_relations(
+        &self,
+        block: &BlockMetadata,
+        space_id: &str,
+        space_version: &str,
+        relation_ids: Vec<String>,
+    ) -> Result<(), DatabaseError> {
+        relation::delete_many(&self.neo4j, block, space_id, space_version)
+            .relations(relation_ids)
+            .send()
+            .await
+    }
+
+    async fn find_relations(
+        &self,
+        filter: RelationEdgeFilter,
+    ) -> Result<Vec<RelationEdge<EntityNodeRef>>, DatabaseError> {
+        let mut relation_filter = RelationFilter::default();
+
+        if let Some(id) = filter.id {
+            relation_filter = relation_filter.id(id);
+        }
+        if let Some(from_id) = filter.from_id {
+            relation_filter = relation_filter.from_(EntityFilter::default().id(from_id));
+        }
+        if let Some(to_id) = filter.to_id {
+            relation_filter = relation_filter.to_(EntityFilter::default().id(to_id));
+        }
+        if let Some(relation_type) = filter.relation_type {
+            relation_filter =
+                relation_filter.relation_type(EntityFilter::default().id(relation_type));
+        }
+
+        let mut query = relation::find_many::<RelationEdge<EntityNodeRef>>(&self.neo4j)
+            .filter(relation_filter)
+            .version(filter.version)
+            .limit(filter.limit);
+
+        if let Some(space_id) = filter.space_id {
+            query = query.space_id(space_id);
+        }
+        if let Some(skip) = filter.skip {
+            query = query.skip(skip);
+        }
+
+        query.send().await?.try_collect().await
+    }
+
+    async fn delete_entity(
+        &self,
+        block: &BlockMetadata,
+        entity_id: &str,
+        space_id: &str,
+        space_version: &str,
+    ) -> Result<(), DatabaseError> {
+        entity::delete_one(&self.neo4j, block, entity_id, space_id, space_version)
+            .send()
+            .await
+    }
+
+    async fn find_entity_node(&self, id: &str) -> Result<Option<EntityNode>, DatabaseError> {
+        entity::find_one::<EntityNode>(&self.neo4j, id).send().await
+    }
+
+    async fn find_entity<T: FromAttributes + Send>(
+        &self,
+        id: &str,
+        space_id: Option<String>,
+        space_version: Option<String>,
+    ) -> Result<Option<Entity<T>>, DatabaseError> {
+        let mut query = entity::find_one::<Entity<T>>(&self.neo4j, id).version_opt(space_version);
+
+        if let Some(space_id) = space_id {
+            query = query.space_id(space_id);
+        }
+
+        query.send().await
+    }
+}
@@ -70,6 +70,33 @@ pub(crate) struct EntityFieldOpts {
     ty: Type,
     #[darling(default)]
     attribute: Option<StringOrPath>,
+    /// Relation type of a field backed by relations (e.g.: `Vec<EntityNodeRef>`)
+    #[darling(default)]
+    relation: Option<StringOrPath>,
+}
+
+impl EntityOpts {
+    /// Check the field attributes that darling cannot validate on its own
+    pub(crate) fn validate(&self) -> syn::Result<()> {
+        let fields = self.data.as_ref().take_struct().expect("Expected struct");
+
+        for field in fields.iter() {
+            if field.relation.is_some() && field.attribute.is_some() {
+                return Err(syn::Error::new_spanned(
+                    &field.ident,
+                    "Field cannot be both an attribute and a relation",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl EntityFieldOpts {
+    fn is_relation(&self) -> bool {
+        self.relation.is_some()
+    }
 }
 
 pub(crate) fn generate_from_attributes_impl(opts: &EntityOpts) -> TokenStream2 {
@@ -79,6 +106,14 @@ pub(crate) fn generate_from_attributes_impl(opts: &EntityOpts) -> TokenStream2 {
     let field_assignments = fields.iter().map(|field| {
         let field_name = field.ident.as_ref().expect("Expected named field");
         let field_type = &field.ty;
+
+        // Relation fields are loaded separately by the generated queries
+        if field.is_relation() {
+            return quote! {
+                #field_name: Default::default(),
+            };
+        }
+
         let attribute_name = field
             .attribute
             .as_ref()
@@ -200,8 +235,6 @@ pub(crate) fn generate_builder_impl(opts: &EntityOpts) -> TokenStream2 {
         quote! {}
     };
 
-impl ToTokens for StringOrPath
-
     quote! {
         impl #struct_name {
             pub fn new(id: impl Into<String>) -> #builder_name {
@@ -249,7 +282,7 @@ pub(crate) fn generate_query_impls(opts: &EntityOpts) -> TokenStream2 {
     let find_one_fn = quote! {
         /// Find a person by its id
         pub fn find_one(
-            neo4j: &grc20_core::neo4rs::Graph,
+            neo4j: &grc20_core::connection::Graph,
             id: impl Into<String>,
             space_id: impl Into<String>,
         ) -> FindOneQuery {
@@ -259,22 +292,112 @@ pub(crate) fn generate_query_impls(opts: &EntityOpts) -> TokenStream2 {
 
     let find_many_fn = quote! {
         /// Find multiple persons with filters
-        pub fn find_many(neo4j: &grc20_core::neo4rs::Graph, space_id: impl Into<String>) -> FindManyQuery {
+        pub fn find_many(neo4j: &grc20_core::connection::Graph, space_id: impl Into<String>) -> FindManyQuery {
             FindManyQuery::new(neo4j.clone(), space_id.into())
         }
     };
 
+    // Relation fields are loaded with a single query once the entities are found
+    let relation_fields = fields
+        .iter()
+        .filter(|field| field.is_relation())
+        .map(|field| {
+            let field_name = field.ident.as_ref().expect("Expected named field");
+            let relation_type = field.relation.as_ref().map(|s| quote!(#s));
+            (field_name, relation_type)
+        })
+        .collect::<Vec<_>>();
+    let relation_field_names = relation_fields
+        .iter()
+        .map(|(field_name, _)| field_name)
+        .collect::<Vec<_>>();
+    let relation_types = relation_fields
+        .iter()
+        .map(|(_, relation_type)| relation_type)
+        .collect::<Vec<_>>();
+
+    let load_relations = quote! {
+        let mut targets = grc20_core::mapping::relation::find_targets(
+            &neo4j,
+            entities.iter().map(|entity| entity.id().to_string()).collect(),
+            vec![#(#relation_types.to_string()),*],
+            space_id,
+        )
+            .version_opt(version)
+            .send()
+            .await?;
+
+        for entity in &mut entities {
+            #(
+                entity.attributes.#relation_field_names = grc20_core::mapping::RelationField::from_targets(
+                    targets
+                        .remove(&(entity.id().to_string(), #relation_types.to_string()))
+                        .unwrap_or_default(),
+                );
+            )*
+        }
+    };
+
+    let find_one_send = if relation_fields.is_empty() {
+        quote! {
+            grc20_core::entity::find_one::<grc20_core::mapping::Entity<#struct_name>>(
+                &self.neo4j,
+                self.id,
+            )
+                .space_id(self.space_id)
+                .version_opt(self.version)
+                .send()
+                .await
+        }
+    } else {
+        quote! {
+            let Self { neo4j, id, space_id, version } = self;
+
+            let Some(entity) = grc20_core::entity::find_one::<grc20_core::mapping::Entity<#struct_name>>(
+                &neo4j,
+                id,
+            )
+                .space_id(space_id.clone())
+                .version_opt(version.clone())
+                .send()
+                .await? else {
+                return Ok(None);
+            };
+
+            let mut entities = vec![entity];
+            #load_relations
+
+            Ok(entities.pop())
+        }
+    };
+
+    let find_many_send = if relation_fields.is_empty() {
+        quote! {
+            query.send().await
+        }
+    } else {
+        quote! {
+            let mut entities = futures::TryStreamExt::try_collect::<Vec<_>>(query.send().await?).await?;
+            let neo4j = self.neo4j;
+            let space_id = self.space_id;
+            let version = self.version;
+            #load_relations
+
+            Ok(futures::stream::iter(entities.into_iter().map(Ok)))
+        }
+    };
+
     let find_one_query_struct = quote! {
         /// Query to find a single person
         pub struct FindOneQuery {
-            neo4j: grc20_core::neo4rs::Graph,
+            neo4j: grc20_core::connection::Graph,
             id: String,
             space_id: String,
             version: Option<String>,
         }
 
         impl FindOneQuery {
-            fn new(neo4j: grc20_core::neo4rs::Graph, id: String, space_id: String) -> Self {
+            fn new(neo4j: grc20_core::connection::Graph, id: String, space_id: String) -> Self {
                 Self {
                     neo4j,
                     id,
@@ -296,24 +419,22 @@ pub(crate) fn generate_query_impls(opts: &EntityOpts) -> TokenStream2 {
 
         impl grc20_core::mapping::query_utils::Query<Option<grc20_core::mapping::Entity<#struct_name>>> for FindOneQuery {
             async fn send(self) -> Result<Option<grc20_core::mapping::Entity<#struct_name>>, grc20_core::error::DatabaseError> {
-                grc20_core::entity::find_one::<grc20_core::mapping::Entity<#struct_name>>(
-                    &self.neo4j,
-                    self.id,
-                )
-                    .space_id(self.space_id)
-                    .version_opt(self.version)
-                    .send()
-                    .await
+                #find_one_send
             }
         }
     };
 
-    // Collect field names and types
+    // Collect field names and types (relation fields cannot be filtered on)
     let field_names: Vec<_> = fields
         .iter()
+        .filter(|f| !f.is_relation())
         .map(|f| f.ident.as_ref().expect("Expected named field"))
         .collect();
-    let field_types: Vec<_> = fields.iter().map(|f| &f.ty).collect();
+    let field_types: Vec<_> = fields
+        .iter()
+        .filter(|f| !f.is_relation())
+        .map(|f| &f.ty)
+        .collect();
 
     // Generate fields for FindManyQuery
     let find_many_fields = field_names.iter().zip(field_types.iter())
@@ -388,6 +509,7 @@ pub(crate) fn generate_query_impls(opts: &EntityOpts) -> TokenStream2 {
     // Generate attribute filter applications for QueryStream implementation
     let find_many_filters = fields
         .iter()
+        .filter(|field| !field.is_relation())
         .map(|field| {
             let field_name = field.ident.as_ref().expect("Expected named field");
             let attribute_name = field
@@ -407,21 +529,62 @@ pub(crate) fn generate_query_impls(opts: &EntityOpts) -> TokenStream2 {
         })
         .collect::<Vec<_>>();
 
+    // Generate order by methods for FindManyQuery
+    let order_by_methods = fields
+        .iter()
+        .filter(|field| !field.is_relation())
+        .map(|field| {
+            let field_name = field.ident.as_ref().expect("Expected named field");
+            let method_name = Ident::new(&format!("order_by_{field_name}"), Span::call_site());
+            let doc_comment = format!("Order by {field_name}");
+            let attribute_name = field
+                .attribute
+                .as_ref()
+                .map(|s| quote!(#s))
+                .unwrap_or_else(|| {
+                    let name = LitStr::new(&field_name.to_string(), Span::call_site());
+                    quote!(#name)
+                });
+
+            quote! {
+                #[doc = #doc_comment]
+                pub fn #method_name(mut self, direction: grc20_core::mapping::query_utils::OrderDirection) -> Self {
+                    self.order_by = Some(grc20_core::mapping::query_utils::FieldOrderBy::new(#attribute_name, direction));
+                    self
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
     let schema_type = opts.schema_type.as_ref().map(|s| quote!(#s));
-    let type_filter = if let Some(schema_type) = schema_type {
-        quote! {
-            .relations(grc20_core::mapping::entity::TypesFilter::default().r#type(#schema_type.to_string()))
-        }
+    let (schema_type_impl, type_filter) = if let Some(schema_type) = schema_type {
+        (
+            quote! {
+                impl #struct_name {
+                    /// ID of the type of the entities
+                    pub const SCHEMA_TYPE: &'static str = #schema_type;
+
+                    /// Filter matching the entities with the type [`Self::SCHEMA_TYPE`]
+                    pub fn type_filter() -> grc20_core::mapping::entity::TypesFilter {
+                        grc20_core::mapping::entity::TypesFilter::default().r#type(Self::SCHEMA_TYPE.to_string())
+                    }
+                }
+            },
+            quote! {
+                .relations(#struct_name::type_filter())
+            },
+        )
     } else {
-        quote! {}
+        (quote! {}, quote! {})
     };
 
     let find_many_query_struct = quote! {
         /// Query to find multiple persons with filters
         pub struct FindManyQuery {
-            neo4j: grc20_core::neo4rs::Graph,
+            neo4j: grc20_core::connection::Graph,
             id: Option<grc20_core::mapping::query_utils::PropFilter<String>>,
             #(#find_many_fields)*
+            order_by: Option<grc20_core::mapping::query_utils::FieldOrderBy>,
             space_id: String,
             version: Option<String>,
             limit: usize,
@@ -429,13 +592,14 @@ pub(crate) fn generate_query_impls(opts: &EntityOpts) -> TokenStream2 {
         }
 
         impl FindManyQuery {
-            fn new(neo4j: grc20_core::neo4rs::Graph, space_id: String) -> Self {
+            fn new(neo4j: grc20_core::connection::Graph, space_id: String) -> Self {
                 let mut query = Self {
                     neo4j,
                     id: None,
                     #(
                         #field_names: None,
                     )*
+                    order_by: None,
                     space_id,
                     version: None,
                     limit: 100,
@@ -452,6 +616,8 @@ pub(crate) fn generate_query_impls(opts: &EntityOpts) -> TokenStream2 {
 
             #(#find_many_methods)*
 
+            #(#order_by_methods)*
+
             pub fn version(mut self, version: impl Into<String>) -> Self {
                 self.version = Some(version.into());
                 self
@@ -482,8 +648,8 @@ pub(crate) fn generate_query_impls(opts: &EntityOpts) -> TokenStream2 {
                 let mut query = grc20_core::entity::find_many::<grc20_core::mapping::Entity<#struct_name>>(
                     &self.neo4j,
                 )
-                    .space_id(self.space_id)
-                    .version_opt(self.version)
+                    .space_id(self.space_id.clone())
+                    .version_opt(self.version.clone())
                     .limit(self.limit)
                     .with_filter(
                         grc20_core::mapping::EntityFilter::default()
@@ -492,11 +658,15 @@ pub(crate) fn generate_query_impls(opts: &EntityOpts) -> TokenStream2 {
 
                 #(#find_many_filters)*
 
+                if let Some(order_by) = self.order_by {
+                    query = query.order_by(order_by);
+                }
+
                 if let Some(skip) = self.skip {
                     query = query.skip(skip);
                 }
 
-                query.send().await
+                #find_many_send
             }
         }
     };
@@ -508,6 +678,8 @@ pub(crate) fn generate_query_impls(opts: &EntityOpts) -> TokenStream2 {
             },
         };
 
+        #schema_type_impl
+
         #find_one_fn
         #find_many_fn
         #find_one_query_struct
@@ -519,35 +691,91 @@ pub(crate) fn generate_into_attributes_impl(opts: &EntityOpts) -> TokenStream2 {
     let struct_name = &opts.ident;
     let fields = opts.data.as_ref().take_struct().expect("Expected struct");
 
-    let field_conversions = fields.iter().map(|field| {
+    let field_conversions = fields
+        .iter()
+        .filter(|field| !field.is_relation())
+        .map(|field| {
+            let field_name = field.ident.as_ref().expect("Expected named field");
+            let attribute_name = field
+                .attribute
+                .as_ref()
+                .map(|s| quote!(#s))
+                .unwrap_or_else(|| quote!(#field_name.to_string()));
+
+            // Check if field type is Option<T>
+            if let syn::Type::Path(type_path) = &field.ty {
+                if type_path
+                    .path
+                    .segments
+                    .last()
+                    .map(|s| s.ident == "Option")
+                    .unwrap_or(false)
+                {
+                    return quote! {
+                        if let Some(value) = self.#field_name {
+                            attributes = attributes.attribute((#attribute_name, value));
+                        }
+                    };
+                }
+            }
+
+            quote! {
+                attributes = attributes.attribute((#attribute_name, self.#field_name));
+            }
+        });
+
+    // Relations are created in order so that their index preserves the order of the targets.
+    // The position is part of the relation ID so that repeated targets get distinct relations.
+    let relation_conversions = fields.iter().filter(|field| field.is_relation()).map(|field| {
         let field_name = field.ident.as_ref().expect("Expected named field");
-        let attribute_name = field
-            .attribute
-            .as_ref()
-            .map(|s| quote!(#s))
-            .unwrap_or_else(|| quote!(#field_name.to_string()));
+        let relation_type = field.relation.as_ref().map(|s| quote!(#s));
 
-        // Check if field type is Option<T>
-        if let syn::Type::Path(type_path) = &field.ty {
-            if type_path
-                .path
-                .segments
-                .last()
-                .map(|s| s.ident == "Option")
-                .unwrap_or(false)
-            {
-                return quote! {
-                    if let Some(value) = self.#field_name {
-                        attributes = attributes.attribute((#attribute_name, value));
-                    }
-                };
+        quote! {
+            let targets = grc20_core::mapping::RelationField::targets(&self.#field_name);
+            let indexes = grc20_core::mapping::relation::fractional_index::rebalance(targets.len());
+            for (position, (target, index)) in targets.into_iter().zip(indexes).enumerate() {
+                relations.push(grc20_core::mapping::RelationEdge::new(
+                    grc20_core::ids::create_id_from_unique_string(format!(
+                        "{}:{}:{}:{}:{}",
+                        space_id,
+                        entity_id,
+                        #relation_type,
+                        position,
+                        target,
+                    )),
+                    entity_id,
+                    target,
+                    #relation_type,
+                    index,
+                ));
             }
         }
+    }).collect::<Vec<_>>();
+
+    let relation_types = fields
+        .iter()
+        .filter_map(|field| field.relation.as_ref().map(|s| quote!(#s)))
+        .collect::<Vec<_>>();
 
+    let relations_fn = if relation_conversions.is_empty() {
+        quote! {}
+    } else {
         quote! {
-            attributes = attributes.attribute((#attribute_name, self.#field_name));
+            fn relations(
+                &self,
+                entity_id: &str,
+                space_id: &str,
+            ) -> Result<Vec<grc20_core::mapping::RelationEdge<grc20_core::mapping::EntityNodeRef>>, grc20_core::mapping::TriplesConversionError> {
+                let mut relations = vec![];
+                #(#relation_conversions)*
+                Ok(relations)
+            }
+
+            fn relation_types(&self) -> Vec<String> {
+                vec![#(#relation_types.to_string()),*]
+            }
         }
-    });
+    };
 
     quote! {
         impl grc20_core::mapping::IntoAttributes for #struct_name {
@@ -558,6 +786,8 @@ pub(crate) fn generate_into_attributes_impl(opts: &EntityOpts) -> TokenStream2 {
                 #(#field_conversions)*
                 Ok(attributes)
             }
+
+            #relations_fn
         }
     }
 }
@@ -1,11 +1,13 @@
 mod entity;
 mod relation;
+mod value;
 
 use darling::FromDeriveInput;
 use proc_macro::TokenStream;
 use quote::quote;
 use relation::RelationOpts;
 use syn::{parse_macro_input, DeriveInput};
+use value::ValueOpts;
 
 use entity::EntityOpts;
 
@@ -17,7 +19,16 @@ use entity::EntityOpts;
 /// - Automatic implementation of `FromAttributes` and `IntoAttributes`
 /// - Optional fields using `Option<T>`
 /// - Field renaming with `#[grc20(attribute = "...")]`
-/// - Type tagging with `#[grc20(schema_type = "...")]` (placeholder for future use)
+/// - Fields backed by relations with `#[grc20(relation = "...")]`. The field must be a
+///   `Vec<EntityNodeRef>` or an `Option<EntityNodeRef>`. Relations are written on insert
+///   and loaded by the generated `find_one`/`find_many` queries, ordered by relation index.
+/// - Type tagging with `#[grc20(schema_type = "...")]`. The generated `find_many` query only
+///   returns entities with that type (see the generated `type_filter` function).
+///
+/// The generated `find_many` query has a filter method and an `order_by_*` method per
+/// attribute field, e.g.: `find_many(&neo4j, space_id).name(prop_filter::value("Alice"))`
+/// or `find_many(&neo4j, space_id).order_by_age(OrderDirection::Desc)`. Number values are
+/// ordered by value, other values are ordered as strings.
 ///
 /// # Example
 ///
@@ -33,6 +44,8 @@ use entity::EntityOpts;
 ///     nickname: Option<String>,
 ///     #[grc20(attribute = system_ids::AGE_ATTRIBUTE)]
 ///     age: u64,
+///     #[grc20(relation = system_ids::WORKS_AT_RELATION)]
+///     works_at: Vec<EntityNodeRef>,
 /// }
 /// ```
 #[proc_macro_attribute]
@@ -40,6 +53,10 @@ pub fn entity(_args: TokenStream, input: TokenStream) -> TokenStream {
     let mut input = parse_macro_input!(input as DeriveInput);
     let opts = EntityOpts::from_derive_input(&input).expect("Failed to parse input");
 
+    if let Err(err) = opts.validate() {
+        return err.to_compile_error().into();
+    }
+
     let impl_from_attributes = entity::generate_from_attributes_impl(&opts);
     let impl_into_attributes = entity::generate_into_attributes_impl(&opts);
     let impl_query = entity::generate_query_impls(&opts);
@@ -126,3 +143,53 @@ pub fn relation(_args: TokenStream, input: TokenStream) -> TokenStream {
     }
     .into()
 }
+
+/// Derives the conversions between a type and attribute values (`From<T> for Value`,
+/// `TryFrom<Value> for T` and `Grc20Value`) so that it can be used as a field type in
+/// `#[entity]` and `#[relation]` structs.
+///
+/// Supported types are:
+///
+/// - Unit enums, stored as text values. The value of a variant is its name, which can be
+///   changed with `#[grc20(rename = "...")]` on the variant or with
+///   `#[grc20(rename_all = "...")]` on the enum (one of `lowercase`, `UPPERCASE`,
+///   `snake_case`, `SCREAMING_SNAKE_CASE`, `kebab-case` or `camelCase`).
+/// - Newtypes over a type implementing `Grc20Value` (e.g.: `String`, `u64`, `f64`,
+///   `DateTime<Utc>`). The value type defaults to the one of the inner type and can be
+///   changed with `#[grc20(value_type = "url")]`. The value options can be set with
+///   `#[grc20(unit = "...")]` and `#[grc20(format = "...")]`.
+///
+/// # Example
+///
+/// ```ignore
+/// use grc20_core::Grc20Value;
+///
+/// #[derive(Grc20Value)]
+/// #[grc20(rename_all = "UPPERCASE")]
+/// enum VoteType {
+///     Accept,
+///     Reject,
+/// }
+///
+/// #[derive(Grc20Value)]
+/// #[grc20(unit = "USD", format = "0.00")]
+/// struct Price(f64);
+///
+/// #[derive(Grc20Value)]
+/// #[grc20(value_type = "url")]
+/// struct Website(String);
+/// ```
+#[proc_macro_derive(Grc20Value, attributes(grc20))]
+pub fn derive_grc20_value(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let opts = match ValueOpts::from_derive_input(&input) {
+        Ok(opts) => opts,
+        Err(err) => return err.write_errors().into(),
+    };
+
+    if let Err(err) = opts.validate() {
+        return err.to_compile_error().into();
+    }
+
+    value::generate_value_impl(&opts).into()
+}
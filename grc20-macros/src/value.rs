@@ -0,0 +1,277 @@
+use darling::{util::SpannedValue, FromDeriveInput, FromField, FromVariant};
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{Ident, LitStr, Type};
+
+use crate::entity::StringOrPath;
+
+#[derive(FromDeriveInput)]
+#[darling(attributes(grc20), supports(enum_unit, struct_newtype))]
+pub(crate) struct ValueOpts {
+    ident: Ident,
+    data: darling::ast::Data<ValueVariantOpts, ValueFieldOpts>,
+    /// Case convention of the values of the enum variants
+    #[darling(default)]
+    rename_all: Option<SpannedValue<String>>,
+    /// Value type of the values of the newtype (defaults to the value type of the inner type)
+    #[darling(default)]
+    value_type: Option<SpannedValue<String>>,
+    #[darling(default)]
+    unit: Option<StringOrPath>,
+    #[darling(default)]
+    format: Option<StringOrPath>,
+}
+
+#[derive(FromVariant)]
+#[darling(attributes(grc20))]
+pub(crate) struct ValueVariantOpts {
+    ident: Ident,
+    #[darling(default)]
+    rename: Option<String>,
+}
+
+#[derive(FromField)]
+#[darling(attributes(grc20))]
+pub(crate) struct ValueFieldOpts {
+    ty: Type,
+}
+
+impl ValueOpts {
+    pub(crate) fn validate(&self) -> syn::Result<()> {
+        match &self.data {
+            darling::ast::Data::Enum(_) => {
+                if let Some(value_type) = &self.value_type {
+                    return Err(syn::Error::new(
+                        value_type.span(),
+                        "`value_type` is only supported on newtypes",
+                    ));
+                }
+
+                if self.unit.is_some() || self.format.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        &self.ident,
+                        "`unit` and `format` are only supported on newtypes",
+                    ));
+                }
+
+                if let Some(rename_all) = &self.rename_all {
+                    if !RENAME_RULES.contains(&rename_all.as_str()) {
+                        return Err(syn::Error::new(
+                            rename_all.span(),
+                            format!(
+                                "Invalid rename_all value: {}, expected one of: {}",
+                                rename_all.as_str(),
+                                RENAME_RULES.join(", ")
+                            ),
+                        ));
+                    }
+                }
+            }
+            darling::ast::Data::Struct(_) => {
+                if let Some(rename_all) = &self.rename_all {
+                    return Err(syn::Error::new(
+                        rename_all.span(),
+                        "`rename_all` is only supported on enums",
+                    ));
+                }
+
+                if let Some(value_type) = &self.value_type {
+                    if value_type_variant(value_type).is_none() {
+                        return Err(syn::Error::new(
+                            value_type.span(),
+                            format!("Invalid value type: {}", value_type.as_str()),
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Case conventions supported by `rename_all`
+const RENAME_RULES: &[&str] = &[
+    "lowercase",
+    "UPPERCASE",
+    "snake_case",
+    "SCREAMING_SNAKE_CASE",
+    "kebab-case",
+    "camelCase",
+];
+
+pub(crate) fn generate_value_impl(opts: &ValueOpts) -> TokenStream2 {
+    match &opts.data {
+        darling::ast::Data::Enum(variants) => generate_enum_impl(opts, variants),
+        darling::ast::Data::Struct(fields) => {
+            let field = fields.fields.first().expect("Expected newtype struct");
+            generate_newtype_impl(opts, &field.ty)
+        }
+    }
+}
+
+fn generate_enum_impl(opts: &ValueOpts, variants: &[ValueVariantOpts]) -> TokenStream2 {
+    let enum_name = &opts.ident;
+
+    let variant_values = variants
+        .iter()
+        .map(|variant| {
+            let value = variant.rename.clone().unwrap_or_else(|| {
+                rename_variant(
+                    &variant.ident.to_string(),
+                    opts.rename_all
+                        .as_ref()
+                        .map(|rename_all| rename_all.as_str()),
+                )
+            });
+            (&variant.ident, LitStr::new(&value, Span::call_site()))
+        })
+        .collect::<Vec<_>>();
+
+    let into_value_arms = variant_values.iter().map(|(ident, value)| {
+        quote! {
+            #enum_name::#ident => grc20_core::mapping::Value::text(#value)
+        }
+    });
+
+    let try_from_value_arms = variant_values.iter().map(|(ident, value)| {
+        quote! {
+            (grc20_core::mapping::ValueType::Text, #value) => Ok(#enum_name::#ident)
+        }
+    });
+
+    let error_message = format!("Invalid {enum_name} value: {{}} ({{:?}})");
+
+    quote! {
+        impl From<#enum_name> for grc20_core::mapping::Value {
+            fn from(value: #enum_name) -> Self {
+                match value {
+                    #(#into_value_arms),*
+                }
+            }
+        }
+
+        impl TryFrom<grc20_core::mapping::Value> for #enum_name {
+            type Error = grc20_core::mapping::TriplesConversionError;
+
+            fn try_from(value: grc20_core::mapping::Value) -> Result<Self, Self::Error> {
+                match (&value.value_type, value.value.as_str()) {
+                    #(#try_from_value_arms,)*
+                    (value_type, _) => Err(grc20_core::mapping::TriplesConversionError::InvalidValue(format!(
+                        #error_message,
+                        value.value,
+                        value_type
+                    ))),
+                }
+            }
+        }
+
+        impl grc20_core::mapping::Grc20Value for #enum_name {
+            fn value_type() -> grc20_core::mapping::ValueType {
+                grc20_core::mapping::ValueType::Text
+            }
+        }
+    }
+}
+
+fn generate_newtype_impl(opts: &ValueOpts, inner_type: &Type) -> TokenStream2 {
+    let struct_name = &opts.ident;
+
+    // Invalid value types are rejected by `ValueOpts::validate`
+    let value_type = match opts.value_type.as_ref().and_then(value_type_variant) {
+        Some(variant) => quote!(grc20_core::mapping::ValueType::#variant),
+        None => quote!(<#inner_type as grc20_core::mapping::Grc20Value>::value_type()),
+    };
+
+    let unit = opts.unit.as_ref().map(|unit| {
+        quote! {
+            options.unit = Some(#unit.to_string());
+        }
+    });
+
+    let format = opts.format.as_ref().map(|format| {
+        quote! {
+            options.format = Some(#format.to_string());
+        }
+    });
+
+    quote! {
+        impl From<#struct_name> for grc20_core::mapping::Value {
+            fn from(value: #struct_name) -> Self {
+                grc20_core::mapping::Value {
+                    value_type: <#struct_name as grc20_core::mapping::Grc20Value>::value_type(),
+                    options: <#struct_name as grc20_core::mapping::Grc20Value>::options(),
+                    ..grc20_core::mapping::Value::from(value.0)
+                }
+            }
+        }
+
+        impl TryFrom<grc20_core::mapping::Value> for #struct_name {
+            type Error = grc20_core::mapping::TriplesConversionError;
+
+            fn try_from(value: grc20_core::mapping::Value) -> Result<Self, Self::Error> {
+                Ok(#struct_name(<#inner_type>::try_from(value)?))
+            }
+        }
+
+        impl grc20_core::mapping::Grc20Value for #struct_name {
+            fn value_type() -> grc20_core::mapping::ValueType {
+                #value_type
+            }
+
+            #[allow(unused_mut)]
+            fn options() -> grc20_core::mapping::Options {
+                let mut options = <#inner_type as grc20_core::mapping::Grc20Value>::options();
+                #unit
+                #format
+                options
+            }
+        }
+    }
+}
+
+fn value_type_variant(value_type: &SpannedValue<String>) -> Option<Ident> {
+    let variant = match value_type.to_lowercase().as_str() {
+        "text" => "Text",
+        "number" => "Number",
+        "checkbox" => "Checkbox",
+        "url" => "Url",
+        "time" => "Time",
+        "point" => "Point",
+        _ => return None,
+    };
+
+    Some(Ident::new(variant, value_type.span()))
+}
+
+/// Converts a PascalCase variant name to the given case convention (one of
+/// [`RENAME_RULES`], as checked by `ValueOpts::validate`)
+fn rename_variant(name: &str, rename_all: Option<&str>) -> String {
+    let Some(rename_all) = rename_all else {
+        return name.to_string();
+    };
+
+    let mut words: Vec<String> = vec![];
+    for c in name.chars() {
+        match words.last_mut() {
+            Some(word) if !c.is_uppercase() => word.push(c),
+            _ => words.push(c.to_string()),
+        }
+    }
+
+    match rename_all {
+        "lowercase" => name.to_lowercase(),
+        "UPPERCASE" => name.to_uppercase(),
+        "snake_case" => words.join("_").to_lowercase(),
+        "SCREAMING_SNAKE_CASE" => words.join("_").to_uppercase(),
+        "kebab-case" => words.join("-").to_lowercase(),
+        "camelCase" => {
+            let mut chars = name.chars();
+            chars
+                .next()
+                .map(|first| first.to_lowercase().chain(chars).collect())
+                .unwrap_or_default()
+        }
+        _ => name.to_string(),
+    }
+}
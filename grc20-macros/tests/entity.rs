@@ -1,6 +1,10 @@
 use grc20_core::{
-    mapping::{triple, Attributes, FromAttributes, IntoAttributes, PropFilter, Query, QueryStream},
-    neo4rs, system_ids,
+    connection::Graph,
+    mapping::{
+        query_utils::OrderDirection, triple, Attributes, EntityNodeRef, FromAttributes,
+        IntoAttributes, PropFilter, Query, QueryStream,
+    },
+    system_ids,
 };
 
 mod test_ids {
@@ -8,6 +12,8 @@ mod test_ids {
     pub const NAME_ATTR: &str = "name";
     pub const AGE_ATTR: &str = "age";
     pub const NICKNAME_ATTRIBUTE: &str = "nickname";
+    pub const AUTHORS_RELATION: &str = "authors";
+    pub const PUBLISHED_IN_RELATION: &str = "published_in";
 }
 
 #[test]
@@ -83,6 +89,64 @@ fn test_entity_macro_with_paths() {
     assert_eq!(person.name, "Bob");
     assert_eq!(person.nickname, None);
     assert_eq!(person.age, "25");
+
+    assert_eq!(Person::SCHEMA_TYPE, test_ids::PERSON_TYPE);
+}
+
+#[test]
+fn test_entity_macro_with_relations() {
+    #[grc20_core::entity]
+    struct Paper {
+        #[grc20(attribute = "name")]
+        name: String,
+        #[grc20(relation = "authors")]
+        authors: Vec<EntityNodeRef>,
+        #[grc20(relation = test_ids::PUBLISHED_IN_RELATION)]
+        published_in: Option<EntityNodeRef>,
+    }
+
+    let paper = Paper {
+        name: "Paper".to_string(),
+        authors: vec!["bob".to_string().into(), "alice".to_string().into()],
+        published_in: Some("journal".to_string().into()),
+    };
+
+    // Relation fields are written as relations, in order
+    let relations = paper.relations("paper", "ROOT").unwrap();
+    assert_eq!(relations.len(), 3);
+    assert_eq!(relations[0].to, EntityNodeRef("bob".to_string()));
+    assert_eq!(relations[1].to, EntityNodeRef("alice".to_string()));
+    assert!(relations[0].index < relations[1].index);
+    assert_eq!(relations[2].relation_type, test_ids::PUBLISHED_IN_RELATION);
+    assert!(relations
+        .iter()
+        .all(|relation| relation.from == EntityNodeRef("paper".to_string())));
+    assert_eq!(
+        paper.relation_types(),
+        vec![
+            "authors".to_string(),
+            test_ids::PUBLISHED_IN_RELATION.to_string()
+        ]
+    );
+
+    // Repeated targets get distinct relations
+    let repeated = Paper {
+        name: "Paper".to_string(),
+        authors: vec!["bob".to_string().into(), "bob".to_string().into()],
+        published_in: None,
+    };
+    let relations = repeated.relations("paper", "ROOT").unwrap();
+    assert_eq!(relations.len(), 2);
+    assert_ne!(relations[0].id, relations[1].id);
+
+    // ... and not as attributes
+    let attrs = paper.into_attributes().unwrap();
+    assert_eq!(attrs.0.len(), 1);
+
+    let paper = Paper::from_attributes(attrs).unwrap();
+    assert_eq!(paper.name, "Paper");
+    assert!(paper.authors.is_empty());
+    assert_eq!(paper.published_in, None);
 }
 
 use futures::{pin_mut, StreamExt};
@@ -124,7 +188,7 @@ async fn test_find_one() {
     let port = container.get_host_port_ipv4(BOLT_PORT).await.unwrap();
     let host = container.get_host().await.unwrap().to_string();
 
-    let neo4j = neo4rs::Graph::new(format!("neo4j://{host}:{port}"), "user", "password")
+    let neo4j = Graph::new(format!("neo4j://{host}:{port}"), "user", "password")
         .await
         .unwrap();
 
@@ -179,7 +243,7 @@ async fn test_find_many() {
     let port = container.get_host_port_ipv4(BOLT_PORT).await.unwrap();
     let host = container.get_host().await.unwrap().to_string();
 
-    let neo4j = neo4rs::Graph::new(format!("neo4j://{host}:{port}"), "user", "password")
+    let neo4j = Graph::new(format!("neo4j://{host}:{port}"), "user", "password")
         .await
         .unwrap();
 
@@ -251,4 +315,142 @@ async fn test_find_many() {
     assert_eq!(found_entity.attributes.name, person.name);
     assert_eq!(found_entity.attributes.nickname, person.nickname);
     assert_eq!(found_entity.attributes.age, person.age);
+
+    let other_person = Person {
+        name: "Bob".into(),
+        nickname: None,
+        age: 9,
+    };
+
+    grc20_core::mapping::Entity::new("def", other_person)
+        .with_type(test_ids::PERSON_TYPE)
+        .insert(
+            &neo4j,
+            &grc20_core::block::BlockMetadata::default(),
+            "ROOT",
+            "0",
+        )
+        .send()
+        .await
+        .expect("Failed to insert entity");
+
+    let names = find_many(&neo4j, "ROOT")
+        .order_by_name(OrderDirection::Desc)
+        .send()
+        .await
+        .expect("Failed to find entities")
+        .map(|entity| entity.expect("Failed to get entity").attributes.name)
+        .collect::<Vec<_>>()
+        .await;
+
+    assert_eq!(names, vec!["Bob".to_string(), "Alice".to_string()]);
+
+    // Numbers are ordered by value, not lexicographically
+    let names = find_many(&neo4j, "ROOT")
+        .order_by_age(OrderDirection::Asc)
+        .send()
+        .await
+        .expect("Failed to find entities")
+        .map(|entity| entity.expect("Failed to get entity").attributes.name)
+        .collect::<Vec<_>>()
+        .await;
+
+    assert_eq!(names, vec!["Bob".to_string(), "Alice".to_string()]);
+}
+
+mod paper {
+    use super::*;
+
+    #[derive(Clone)]
+    #[grc20_core::entity]
+    pub struct Paper {
+        #[grc20(attribute = test_ids::NAME_ATTR)]
+        pub name: String,
+        #[grc20(relation = test_ids::AUTHORS_RELATION)]
+        pub authors: Vec<EntityNodeRef>,
+        #[grc20(relation = test_ids::PUBLISHED_IN_RELATION)]
+        pub published_in: Option<EntityNodeRef>,
+    }
+}
+
+#[tokio::test]
+async fn test_find_with_relations() {
+    // Setup a local Neo 4J container for testing. NOTE: docker service must be running.
+    let container = GenericImage::new("neo4j", "2025.01.0-community")
+        .with_wait_for(WaitFor::Duration {
+            length: std::time::Duration::from_secs(5),
+        })
+        .with_exposed_port(BOLT_PORT.tcp())
+        .with_exposed_port(HTTP_PORT.tcp())
+        .with_env_var("NEO4J_AUTH", "none")
+        .start()
+        .await
+        .expect("Failed to start Neo 4J container");
+
+    let port = container.get_host_port_ipv4(BOLT_PORT).await.unwrap();
+    let host = container.get_host().await.unwrap().to_string();
+
+    let neo4j = Graph::new(format!("neo4j://{host}:{port}"), "user", "password")
+        .await
+        .unwrap();
+
+    // Create the targets of the relations
+    triple::insert_many(
+        &neo4j,
+        &grc20_core::block::BlockMetadata::default(),
+        "ROOT",
+        "0",
+    )
+    .triples(vec![
+        triple::Triple::new("alice", system_ids::NAME_ATTRIBUTE, "Alice"),
+        triple::Triple::new("bob", system_ids::NAME_ATTRIBUTE, "Bob"),
+        triple::Triple::new("journal", system_ids::NAME_ATTRIBUTE, "Journal"),
+    ])
+    .send()
+    .await
+    .expect("Failed to insert triples");
+
+    let paper = paper::Paper {
+        name: "Paper".into(),
+        authors: vec!["bob".to_string().into(), "alice".to_string().into()],
+        published_in: Some("journal".to_string().into()),
+    };
+
+    grc20_core::mapping::Entity::new("paper", paper.clone())
+        .insert(
+            &neo4j,
+            &grc20_core::block::BlockMetadata::default(),
+            "ROOT",
+            "0",
+        )
+        .send()
+        .await
+        .expect("Failed to insert entity");
+
+    let found_entity = paper::find_one(&neo4j, "paper", "ROOT")
+        .send()
+        .await
+        .expect("Failed to find entity")
+        .expect("Entity not found");
+
+    assert_eq!(found_entity.attributes.name, paper.name);
+    assert_eq!(found_entity.attributes.authors, paper.authors);
+    assert_eq!(found_entity.attributes.published_in, paper.published_in);
+
+    let stream = paper::find_many(&neo4j, "ROOT")
+        .name(PropFilter::default().value("Paper"))
+        .send()
+        .await
+        .expect("Failed to find entities");
+
+    pin_mut!(stream);
+
+    let found_entity = stream
+        .next()
+        .await
+        .expect("Failed to get next entity")
+        .expect("Entity not found");
+
+    assert_eq!(found_entity.attributes.authors, paper.authors);
+    assert_eq!(found_entity.attributes.published_in, paper.published_in);
 }
@@ -0,0 +1,133 @@
+use chrono::{DateTime, Utc};
+use grc20_core::{
+    mapping::{Grc20Value, IntoAttributes, Value, ValueType},
+    Grc20Value,
+};
+
+mod test_ids {
+    pub const USD_UNIT: &str = "USD";
+}
+
+#[derive(Clone, Debug, PartialEq, Grc20Value)]
+enum Status {
+    Proposed,
+    #[grc20(rename = "DONE")]
+    Executed,
+}
+
+#[derive(Clone, Debug, PartialEq, Grc20Value)]
+#[grc20(rename_all = "SCREAMING_SNAKE_CASE")]
+enum Kind {
+    ValueTypeMismatch,
+    Other,
+}
+
+#[derive(Clone, Debug, PartialEq, Grc20Value)]
+#[grc20(unit = test_ids::USD_UNIT, format = "0.00")]
+struct Price(f64);
+
+#[derive(Clone, Debug, PartialEq, Grc20Value)]
+#[grc20(value_type = "url")]
+struct Website(String);
+
+#[derive(Clone, Debug, PartialEq, Grc20Value)]
+struct PublishedAt(DateTime<Utc>);
+
+#[test]
+fn test_enum_value() {
+    assert_eq!(Status::value_type(), ValueType::Text);
+
+    let value = Value::from(Status::Proposed);
+    assert_eq!(value, Value::text("Proposed"));
+    assert_eq!(Status::try_from(value).unwrap(), Status::Proposed);
+
+    let value = Value::from(Status::Executed);
+    assert_eq!(value, Value::text("DONE"));
+    assert_eq!(Status::try_from(value).unwrap(), Status::Executed);
+
+    assert!(Status::try_from(Value::text("Executed")).is_err());
+}
+
+#[test]
+fn test_enum_value_type_mismatch() {
+    let value = Value {
+        value_type: ValueType::Number,
+        ..Value::text("Proposed")
+    };
+    assert!(Status::try_from(value).is_err());
+
+    let value = Value {
+        value_type: ValueType::Url,
+        ..Value::text("OTHER")
+    };
+    assert!(Kind::try_from(value).is_err());
+}
+
+#[test]
+fn test_enum_value_rename_all() {
+    assert_eq!(
+        Value::from(Kind::ValueTypeMismatch),
+        Value::text("VALUE_TYPE_MISMATCH")
+    );
+    assert_eq!(Kind::try_from(Value::text("OTHER")).unwrap(), Kind::Other);
+    assert!(Kind::try_from(Value::text("Other")).is_err());
+}
+
+#[test]
+fn test_newtype_value() {
+    assert_eq!(Price::value_type(), ValueType::Number);
+    assert_eq!(Price::options().unit.as_deref(), Some("USD"));
+    assert_eq!(Price::options().format.as_deref(), Some("0.00"));
+
+    let value = Value::from(Price(9.5));
+    assert_eq!(value.value, "9.5");
+    assert_eq!(value.value_type, ValueType::Number);
+    assert_eq!(value.options, Price::options());
+    assert_eq!(Price::try_from(value).unwrap(), Price(9.5));
+
+    assert!(Price::try_from(Value::text("not a number")).is_err());
+}
+
+#[test]
+fn test_newtype_value_type() {
+    let value = Value::from(Website("https://geobrowser.io".to_string()));
+    assert_eq!(value, Value::url("https://geobrowser.io".to_string()));
+    assert_eq!(
+        Website::try_from(value).unwrap(),
+        Website("https://geobrowser.io".to_string())
+    );
+
+    let published_at = DateTime::parse_from_rfc3339("2025-01-01T00:00:00Z")
+        .unwrap()
+        .with_timezone(&Utc);
+    let value = Value::from(PublishedAt(published_at));
+    assert_eq!(value, Value::time(published_at));
+    assert_eq!(
+        PublishedAt::try_from(value).unwrap(),
+        PublishedAt(published_at)
+    );
+}
+
+#[test]
+fn test_entity_with_value_fields() {
+    #[grc20_core::entity]
+    struct Product {
+        #[grc20(attribute = "status")]
+        status: Status,
+        #[grc20(attribute = "price")]
+        price: Price,
+        #[grc20(attribute = "website")]
+        website: Option<Website>,
+    }
+
+    let product = Product {
+        status: Status::Executed,
+        price: Price(12.0),
+        website: None,
+    };
+
+    let attrs = product.into_attributes().unwrap();
+    assert_eq!(attrs.get::<Status>("status").unwrap(), Status::Executed);
+    assert_eq!(attrs.get::<Price>("price").unwrap(), Price(12.0));
+    assert!(attrs.get_opt::<Website>("website").unwrap().is_none());
+}
@@ -1,7 +1,7 @@
 use futures::{pin_mut, StreamExt};
 use grc20_core::{
+    connection::Graph,
     mapping::{aggregation::SpaceRanking, query_utils::QueryStream},
-    neo4rs,
 };
 use grc20_sdk::models::space;
 
@@ -13,7 +13,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .expect("Please provide a space ID as the first argument");
 
     // Initialize Neo4j connection
-    let neo4j = neo4rs::Graph::new("bolt://localhost:7687", "neo4j", "password").await?;
+    let neo4j = Graph::new("bolt://localhost:7687", "neo4j", "password").await?;
 
     // Create and execute subspaces query using Space helper
     let query = space::subspaces(&neo4j, &space_id)
@@ -1,7 +1,8 @@
 use futures::{pin_mut, StreamExt, TryStreamExt};
 use grc20_core::{
+    connection::Graph,
     mapping::{entity::EntityNodeRef, query_utils::QueryStream, RelationEdge},
-    neo4rs, system_ids,
+    system_ids,
 };
 use grc20_sdk::models::{property, space};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
@@ -22,7 +23,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .expect("Please provide a space ID as the first argument");
 
     // Initialize Neo4j connection
-    let neo4j = neo4rs::Graph::new("bolt://localhost:7687", "neo4j", "password").await?;
+    let neo4j = Graph::new("bolt://localhost:7687", "neo4j", "password").await?;
 
     let types = space::types(&neo4j, &space_id).strict(false).send().await?;
 
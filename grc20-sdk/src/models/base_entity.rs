@@ -1,9 +1,9 @@
 use futures::TryStreamExt;
 use grc20_core::{
+    connection::Graph,
     entity::{Entity, EntityNodeRef},
     error::DatabaseError,
     mapping::{prop_filter, EntityFilter, RelationEdge},
-    neo4rs,
     relation::{self, RelationFilter},
     system_ids,
 };
@@ -18,7 +18,7 @@ pub struct BaseEntity {
 }
 
 pub async fn blocks(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     entity_id: impl Into<String>,
     space_id: impl Into<String>,
     version: Option<String>,
@@ -40,8 +40,34 @@ pub async fn blocks(
         .await
 }
 
+/// Items of a collection, ordered by their relation index
+pub async fn collection_items(
+    neo4j: &Graph,
+    collection_id: impl Into<String>,
+    space_id: impl Into<String>,
+    version: Option<String>,
+    _strict: bool,
+) -> Result<Vec<Entity<BaseEntity>>, DatabaseError> {
+    // TODO: Implement aggregation
+    relation::find_many::<RelationEdge<EntityNodeRef>>(neo4j)
+        .filter(
+            RelationFilter::default()
+                .from_(EntityFilter::default().id(prop_filter::value(collection_id.into())))
+                .relation_type(EntityFilter::default().id(prop_filter::value(
+                    system_ids::COLLECTION_ITEM_RELATION_TYPE,
+                ))),
+        )
+        .space_id(prop_filter::value(space_id.into()))
+        .version(version)
+        .select_to::<Entity<BaseEntity>>()
+        .send()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await
+}
+
 pub async fn types(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     entity_id: impl Into<String>,
     space_id: impl Into<String>,
     version: Option<String>,
@@ -2,11 +2,11 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use grc20_core::{
+    connection::Graph,
     entity,
     error::DatabaseError,
     indexer_ids,
     mapping::{Entity, Query},
-    neo4rs,
 };
 
 #[derive(Clone, Default, Deserialize, Serialize)]
@@ -41,7 +41,7 @@ impl Cursor {
         .with_type(indexer_ids::CURSOR_TYPE)
     }
 
-    pub async fn load(neo4j: &neo4rs::Graph) -> Result<Option<Entity<Self>>, DatabaseError> {
+    pub async fn load(neo4j: &Graph) -> Result<Option<Entity<Self>>, DatabaseError> {
         entity::find_one::<Entity<Self>>(neo4j, indexer_ids::CURSOR_ID)
             .space_id(indexer_ids::INDEXER_SPACE_ID)
             .send()
@@ -0,0 +1,433 @@
+//! Drafts of edits: structured changes to the knowledge graph turned into the ops of an
+//! edit and validated against the schema of the space in which the edit is proposed.
+//!
+//! Drafting does not publish anything: the drafted edit is meant to be reviewed, signed
+//! and published (i.e.: uploaded to IPFS and proposed on-chain) by a member of the space.
+use std::collections::HashMap;
+
+use futures::TryStreamExt;
+use grc20_core::{
+    connection::{with_query_policy, Graph},
+    entity,
+    error::DatabaseError,
+    ids,
+    mapping::{
+        query_utils::Query, relation::fractional_index, triple, Entity, QueryBuilder, Subquery,
+        ValueType,
+    },
+    pb, system_ids,
+};
+use prost::Message;
+
+use super::{
+    property,
+    validation::{self, Violation},
+    BaseEntity,
+};
+
+/// Version of the edits format produced by the drafts
+pub const EDIT_VERSION: &str = "1.0.0";
+
+/// Change to the knowledge graph
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    /// Set the value of an attribute of an entity. The value type defaults to the value
+    /// type of the attribute (or text if the attribute does not declare one).
+    SetAttribute {
+        entity_id: String,
+        attribute_id: String,
+        value: String,
+        value_type: Option<ValueType>,
+    },
+    /// Add a relation from an entity to another
+    AddRelation {
+        from_id: String,
+        relation_type_id: String,
+        to_id: String,
+    },
+    /// Create an entity with the given types. A new ID is generated if none is given.
+    CreateEntity {
+        id: Option<String>,
+        name: String,
+        description: Option<String>,
+        types: Vec<String>,
+    },
+}
+
+/// Edit drafted from a list of changes
+#[derive(Debug)]
+pub struct DraftedEdit {
+    pub edit: pb::ipfs::Edit,
+    /// IDs of the entities created by the edit, in the order of the changes
+    pub created_entities: Vec<String>,
+    /// Human readable description of the ops of the edit, one line per op
+    pub diff: Vec<String>,
+    /// Ops violating the schema of the space. An edit with violations can still be
+    /// published but the violating ops may be rejected when indexed.
+    pub violations: Vec<Violation>,
+}
+
+impl DraftedEdit {
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Protobuf encoding of the edit, i.e.: the content to upload to IPFS
+    pub fn encode(&self) -> Vec<u8> {
+        self.edit.encode_to_vec()
+    }
+}
+
+/// Query drafting an edit from a list of changes. See [`draft`].
+pub struct DraftEditQuery {
+    neo4j: Graph,
+    space_id: String,
+    name: String,
+    authors: Vec<String>,
+    changes: Vec<Change>,
+}
+
+impl DraftEditQuery {
+    pub(crate) fn new(neo4j: Graph, space_id: String, name: String) -> Self {
+        Self {
+            neo4j,
+            space_id,
+            name,
+            authors: vec![],
+            changes: vec![],
+        }
+    }
+
+    /// Add an author (i.e.: the address of an account) to the edit
+    pub fn author(mut self, author: impl Into<String>) -> Self {
+        self.authors.push(author.into());
+        self
+    }
+
+    pub fn change(mut self, change: Change) -> Self {
+        self.changes.push(change);
+        self
+    }
+
+    pub fn changes(mut self, changes: impl IntoIterator<Item = Change>) -> Self {
+        self.changes.extend(changes);
+        self
+    }
+
+    /// Value type of the values of an attribute, according to the schema of the space
+    async fn attribute_value_type(&self, attribute_id: &str) -> Result<ValueType, DatabaseError> {
+        let value_type =
+            property::value_type(&self.neo4j, attribute_id, &self.space_id, None, false)
+                .await?
+                .and_then(|value_type| validation::value_type_from_id(value_type.id()));
+
+        Ok(value_type.unwrap_or(ValueType::Text))
+    }
+}
+
+impl Query<DraftedEdit> for DraftEditQuery {
+    async fn send(self) -> Result<DraftedEdit, DatabaseError> {
+        let mut created_entities = vec![];
+        let mut set_triples = vec![];
+        let mut create_relations = vec![];
+
+        for change in &self.changes {
+            match change {
+                Change::SetAttribute {
+                    entity_id,
+                    attribute_id,
+                    value,
+                    value_type,
+                } => {
+                    let value_type = match value_type {
+                        Some(value_type) => value_type.clone(),
+                        None => self.attribute_value_type(attribute_id).await?,
+                    };
+                    set_triples.push(new_triple(entity_id, attribute_id, value, value_type));
+                }
+                Change::AddRelation {
+                    from_id,
+                    relation_type_id,
+                    to_id,
+                } => {
+                    create_relations.push(new_relation(from_id, relation_type_id, to_id));
+                }
+                Change::CreateEntity {
+                    id,
+                    name,
+                    description,
+                    types,
+                } => {
+                    let id = id.clone().unwrap_or_else(ids::create_geo_id);
+
+                    set_triples.push(new_triple(
+                        &id,
+                        system_ids::NAME_ATTRIBUTE,
+                        name,
+                        ValueType::Text,
+                    ));
+                    if let Some(description) = description {
+                        set_triples.push(new_triple(
+                            &id,
+                            system_ids::DESCRIPTION_ATTRIBUTE,
+                            description,
+                            ValueType::Text,
+                        ));
+                    }
+                    for r#type in types {
+                        create_relations.push(new_relation(
+                            &id,
+                            system_ids::TYPES_ATTRIBUTE,
+                            r#type,
+                        ));
+                    }
+
+                    created_entities.push(id);
+                }
+            }
+        }
+
+        self.assign_indexes(&mut create_relations).await?;
+
+        let violations =
+            validation::validate_ops(&self.neo4j, &self.space_id, &set_triples, &create_relations)
+                .await?;
+
+        let diff = self.diff(&set_triples, &create_relations).await?;
+
+        let ops = set_triples
+            .into_iter()
+            .map(|triple| pb::ipfs::Op {
+                r#type: pb::ipfs::OpType::SetTriple.into(),
+                triple: Some(triple),
+                ..Default::default()
+            })
+            .chain(create_relations.into_iter().map(|relation| pb::ipfs::Op {
+                r#type: pb::ipfs::OpType::CreateRelation.into(),
+                relation: Some(relation),
+                ..Default::default()
+            }))
+            .collect();
+
+        Ok(DraftedEdit {
+            edit: pb::ipfs::Edit {
+                version: EDIT_VERSION.to_string(),
+                r#type: pb::ipfs::ActionType::AddEdit.into(),
+                id: ids::create_geo_id(),
+                name: self.name,
+                ops,
+                authors: self.authors,
+            },
+            created_entities,
+            diff,
+            violations,
+        })
+    }
+}
+
+impl DraftEditQuery {
+    /// Assigns the fractional indexes of the created relations: relations are appended,
+    /// in the order of the changes, after the existing relations of the same type
+    /// outgoing from the same entity.
+    async fn assign_indexes(
+        &self,
+        relations: &mut [pb::ipfs::Relation],
+    ) -> Result<(), DatabaseError> {
+        if relations.is_empty() {
+            return Ok(());
+        }
+
+        #[derive(serde::Deserialize)]
+        struct ExistingIndexes {
+            from_id: String,
+            relation_type: String,
+            indexes: Vec<String>,
+        }
+
+        let query = QueryBuilder::default()
+            .subquery("MATCH (from:Entity) -[r:RELATION]-> (:Entity)")
+            .subquery("WHERE from.id IN $from_ids AND r.relation_type IN $relation_types")
+            .subquery("AND r.space_id = $space_id AND r.max_version IS NULL")
+            .params(
+                "from_ids",
+                relations
+                    .iter()
+                    .map(|relation| relation.from_entity.clone())
+                    .collect::<Vec<_>>(),
+            )
+            .params(
+                "relation_types",
+                relations
+                    .iter()
+                    .map(|relation| relation.r#type.clone())
+                    .collect::<Vec<_>>(),
+            )
+            .params("space_id", self.space_id.clone())
+            .r#return(
+                "from.id AS from_id, r.relation_type AS relation_type, COLLECT(r.index) AS indexes",
+            );
+
+        // Last valid index of the existing relations (relations indexed before fractional
+        // indexes were used are ignored)
+        let last_indexes: HashMap<(String, String), String> =
+            with_query_policy(&self.neo4j, || self.neo4j.execute(query.build()))
+                .await?
+                .into_stream_as::<ExistingIndexes>()
+                .map_err(DatabaseError::from)
+                .try_filter_map(|row| async move {
+                    Ok(row
+                        .indexes
+                        .into_iter()
+                        .filter(|index| fractional_index::validate_key(index).is_ok())
+                        .max()
+                        .map(|index| ((row.from_id, row.relation_type), index)))
+                })
+                .try_collect()
+                .await?;
+
+        let mut groups: HashMap<(String, String), Vec<usize>> = HashMap::new();
+        for (position, relation) in relations.iter().enumerate() {
+            groups
+                .entry((relation.from_entity.clone(), relation.r#type.clone()))
+                .or_default()
+                .push(position);
+        }
+
+        for (key, positions) in groups {
+            let indexes = fractional_index::n_keys_between(
+                last_indexes.get(&key).map(String::as_str),
+                None,
+                positions.len(),
+            )?;
+
+            for (position, index) in positions.into_iter().zip(indexes) {
+                relations[position].index = index;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Describes the ops with the names of the entities they refer to. Attributes which
+    /// already have a value in the space are shown with their current value.
+    async fn diff(
+        &self,
+        set_triples: &[pb::ipfs::Triple],
+        create_relations: &[pb::ipfs::Relation],
+    ) -> Result<Vec<String>, DatabaseError> {
+        let mut ids = set_triples
+            .iter()
+            .flat_map(|triple| [triple.entity.clone(), triple.attribute.clone()])
+            .chain(create_relations.iter().flat_map(|relation| {
+                [
+                    relation.from_entity.clone(),
+                    relation.r#type.clone(),
+                    relation.to_entity.clone(),
+                ]
+            }))
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids.dedup();
+
+        let mut names = entity::find_many_by_ids::<Entity<BaseEntity>>(&self.neo4j, ids)
+            .space_id(&self.space_id)
+            .send()
+            .await?
+            .into_iter()
+            .filter_map(|entity| {
+                let id = entity.id().to_string();
+                entity.attributes.name.map(|name| (id, name))
+            })
+            .collect::<HashMap<_, _>>();
+
+        // Names of the entities created (or renamed) by the edit
+        for triple in set_triples {
+            if let (system_ids::NAME_ATTRIBUTE, Some(value)) =
+                (triple.attribute.as_str(), &triple.value)
+            {
+                names.insert(triple.entity.clone(), value.value.clone());
+            }
+        }
+
+        let label = |id: &str| match names.get(id) {
+            Some(name) => format!("{name} ({id})"),
+            None => id.to_string(),
+        };
+
+        let mut diff = vec![];
+        for triple in set_triples {
+            let Some(value) = &triple.value else {
+                continue;
+            };
+
+            let current = triple::find_one(
+                &self.neo4j,
+                &triple.attribute,
+                &triple.entity,
+                &self.space_id,
+                None,
+            )
+            .send()
+            .await?;
+
+            let line = format!(
+                "{}.{} = {:?} ({})",
+                label(&triple.entity),
+                label(&triple.attribute),
+                value.value,
+                value.r#type().as_str_name(),
+            );
+            diff.push(match current {
+                Some(current) => format!("~ {line}, was {:?}", current.value.value),
+                None => format!("+ {line}"),
+            });
+        }
+
+        for relation in create_relations {
+            diff.push(format!(
+                "+ {} -[{}]-> {}",
+                label(&relation.from_entity),
+                label(&relation.r#type),
+                label(&relation.to_entity),
+            ));
+        }
+
+        Ok(diff)
+    }
+}
+
+/// Drafts an edit named `name` to be proposed in the space `space_id`
+pub fn draft(
+    neo4j: &Graph,
+    space_id: impl Into<String>,
+    name: impl Into<String>,
+) -> DraftEditQuery {
+    DraftEditQuery::new(neo4j.clone(), space_id.into(), name.into())
+}
+
+fn new_triple(
+    entity_id: &str,
+    attribute_id: &str,
+    value: &str,
+    value_type: ValueType,
+) -> pb::ipfs::Triple {
+    pb::ipfs::Triple {
+        entity: entity_id.to_string(),
+        attribute: attribute_id.to_string(),
+        value: Some(pb::ipfs::Value {
+            r#type: pb::ipfs::ValueType::from(value_type).into(),
+            value: value.to_string(),
+        }),
+    }
+}
+
+/// Builds a relation. Its index is assigned by [`DraftEditQuery::assign_indexes`].
+fn new_relation(from_id: &str, relation_type_id: &str, to_id: &str) -> pb::ipfs::Relation {
+    pb::ipfs::Relation {
+        id: ids::create_geo_id(),
+        r#type: relation_type_id.to_string(),
+        from_entity: from_id.to_string(),
+        to_entity: to_id.to_string(),
+        index: String::new(),
+    }
+}
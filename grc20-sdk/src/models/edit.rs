@@ -1,6 +1,14 @@
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
 use grc20_core::{
+    connection::{with_query_policy, Graph},
+    error::DatabaseError,
     ids, indexer_ids,
-    mapping::{entity::EntityNodeRef, Entity, Relation},
+    mapping::{
+        entity::EntityNodeRef,
+        query_utils::query_builder::{QueryBuilder, Subquery},
+        Entity, Relation,
+    },
     system_ids,
 };
 
@@ -90,3 +98,35 @@ impl ProposedEdit {
         )
     }
 }
+
+/// Returns the version index of the last edit published at or before `time`, i.e.: the
+/// version at which to read the knowledge graph to get its state at that time
+pub async fn version_index_at(
+    neo4j: &Graph,
+    time: DateTime<Utc>,
+) -> Result<Option<String>, DatabaseError> {
+    #[derive(serde::Deserialize)]
+    struct VersionIndex {
+        index: String,
+    }
+
+    let query = QueryBuilder::default()
+        .subquery("MATCH (e:Entity) -[r:ATTRIBUTE]-> (n:Attribute {id: $EDIT_INDEX_ATTR})")
+        .subquery(format!(
+            "WHERE r.max_version IS NULL AND e.`{CREATED_AT}` <= datetime($time)",
+            CREATED_AT = indexer_ids::CREATED_AT_TIMESTAMP,
+        ))
+        .params("EDIT_INDEX_ATTR", indexer_ids::EDIT_INDEX_ATTRIBUTE)
+        .params("time", time.to_rfc3339())
+        .r#return("n.value AS index ORDER BY index DESC LIMIT 1");
+
+    let mut indexes = with_query_policy(neo4j, || neo4j.execute(query.build()))
+        .await?
+        .into_stream_as::<VersionIndex>()
+        .map_ok(|row| row.index)
+        .map_err(DatabaseError::from)
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    Ok(indexes.pop())
+}
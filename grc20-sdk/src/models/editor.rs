@@ -1,9 +1,9 @@
 use grc20_core::{
     block::BlockMetadata,
+    connection::Graph,
     error::DatabaseError,
     ids, indexer_ids,
     mapping::{entity::EntityNodeRef, query_utils::Query, relation, Relation},
-    neo4rs,
 };
 
 /// Space editor relation.
@@ -31,7 +31,7 @@ impl SpaceEditor {
 
     /// Delete a relation between an editor and a space.
     pub async fn remove(
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         block: &BlockMetadata,
         editor_id: &str,
         space_id: &str,
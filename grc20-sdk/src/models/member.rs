@@ -1,9 +1,9 @@
 use grc20_core::{
     block::BlockMetadata,
+    connection::Graph,
     error::DatabaseError,
     ids, indexer_ids,
     mapping::{entity::EntityNodeRef, query_utils::Query, relation, Relation},
-    neo4rs,
 };
 
 /// Space member relation.
@@ -31,7 +31,7 @@ impl SpaceMember {
 
     /// Delete a relation between an member and a space.
     pub async fn remove(
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         block: &BlockMetadata,
         member_id: &str,
         space_id: &str,
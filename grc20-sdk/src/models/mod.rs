@@ -1,17 +1,22 @@
 pub mod account;
 pub mod base_entity;
 pub mod cursor;
+pub mod draft;
 pub mod edit;
 pub mod editor;
 pub mod member;
 pub mod property;
 pub mod proposal;
 pub mod space;
+pub mod tentative_edit;
+pub mod type_schema;
+pub mod validation;
 pub mod vote;
 
 pub use account::Account;
 pub use base_entity::BaseEntity;
 pub use cursor::Cursor;
+pub use draft::{Change, DraftedEdit};
 pub use edit::Edit;
 pub use editor::SpaceEditor;
 pub use member::SpaceMember;
@@ -21,4 +26,7 @@ pub use proposal::{
     ProposalCreator, Proposals, RemoveEditorProposal, RemoveMemberProposal, RemoveSubspaceProposal,
 };
 pub use space::{Space, SpaceBuilder, SpaceGovernanceType};
+pub use tentative_edit::TentativeEdit;
+pub use type_schema::PropertySchema;
+pub use validation::{DiagnosticKind, ValidationDiagnostic};
 pub use vote::{VoteCast, VoteType};
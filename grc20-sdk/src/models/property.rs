@@ -1,5 +1,8 @@
+use std::collections::HashMap;
+
 use futures::TryStreamExt;
 use grc20_core::{
+    connection::Graph,
     entity::{self, Entity},
     error::DatabaseError,
     mapping::{
@@ -7,7 +10,7 @@ use grc20_core::{
         entity::EntityNodeRef,
         prop_filter, triple, QueryStream, RelationEdge,
     },
-    neo4rs, relation, system_ids,
+    relation, system_ids,
 };
 
 use crate::models::space::ParentSpacesQuery;
@@ -31,7 +34,7 @@ pub struct Property {
 }
 
 pub async fn value_type(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     property_id: impl Into<String>,
     space_id: impl Into<String>,
     space_version: Option<String>,
@@ -66,7 +69,7 @@ pub async fn value_type(
 }
 
 pub async fn relation_value_type(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     property_id: impl Into<String>,
     space_id: impl Into<String>,
     space_version: Option<String>,
@@ -100,8 +103,96 @@ pub async fn relation_value_type(
     }
 }
 
+/// Returns the IDs of the value types of the given properties, keyed by property ID.
+/// Batched version of [`value_type`] which only looks up the relations declaring the
+/// value types (properties without value type are not in the map).
+pub async fn value_type_ids(
+    neo4j: &Graph,
+    property_ids: Vec<String>,
+    space_id: impl Into<String>,
+    space_version: Option<String>,
+    strict: bool,
+) -> Result<HashMap<String, String>, DatabaseError> {
+    outbound_relation_targets(
+        neo4j,
+        system_ids::VALUE_TYPE_ATTRIBUTE,
+        property_ids,
+        space_id,
+        space_version,
+        strict,
+    )
+    .await
+}
+
+/// Returns the IDs of the relation value types of the given relation properties, keyed
+/// by property ID. Batched version of [`relation_value_type`].
+pub async fn relation_value_type_ids(
+    neo4j: &Graph,
+    property_ids: Vec<String>,
+    space_id: impl Into<String>,
+    space_version: Option<String>,
+    strict: bool,
+) -> Result<HashMap<String, String>, DatabaseError> {
+    outbound_relation_targets(
+        neo4j,
+        system_ids::RELATION_VALUE_RELATIONSHIP_TYPE,
+        property_ids,
+        space_id,
+        space_version,
+        strict,
+    )
+    .await
+}
+
+/// Returns the target of the `relation_type` relation of each of the entities in a single
+/// query. As with [`get_outbound_relations`], the first relation found is used.
+async fn outbound_relation_targets(
+    neo4j: &Graph,
+    relation_type: &str,
+    entity_ids: Vec<String>,
+    space_id: impl Into<String>,
+    space_version: Option<String>,
+    strict: bool,
+) -> Result<HashMap<String, String>, DatabaseError> {
+    if entity_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let spaces = spaces_for_property(neo4j, relation_type, space_id, strict)
+        .await?
+        .into_iter()
+        .map(|ranking| ranking.space_id)
+        .collect::<Vec<_>>();
+
+    // At most one relation per entity and space
+    let limit = entity_ids.len() * spaces.len();
+
+    let relations = relation::find_many::<RelationEdge<EntityNodeRef>>(neo4j)
+        .filter(
+            relation::RelationFilter::default()
+                .from_(entity::EntityFilter::default().id(prop_filter::value_in(entity_ids)))
+                .relation_type(
+                    entity::EntityFilter::default().id(prop_filter::value(relation_type)),
+                ),
+        )
+        .space_id(prop_filter::value_in(spaces))
+        .version(space_version)
+        .limit(limit)
+        .send()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    let mut targets = HashMap::new();
+    for relation in relations {
+        targets.entry(relation.from.0).or_insert(relation.to.0);
+    }
+
+    Ok(targets)
+}
+
 async fn attribute_aggregation_direction(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     space_id: &str,
     attribute_id: &str,
 ) -> Result<Option<AggregationDirection>, DatabaseError> {
@@ -165,7 +256,7 @@ async fn attribute_aggregation_direction(
 
 // TODO: Find a better place for this function
 pub async fn get_triple(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     property_id: impl Into<String>,
     entity_id: impl Into<String>,
     space_id: impl Into<String>,
@@ -201,7 +292,7 @@ pub async fn get_triple(
 
 #[allow(clippy::too_many_arguments)]
 pub async fn get_outbound_relations<T>(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     property_id: impl Into<String>,
     entity_id: impl Into<String>,
     space_id: impl Into<String>,
@@ -259,7 +350,7 @@ pub async fn get_outbound_relations<T>(
 
 /// Returns the spaces from which the property is inherited
 async fn spaces_for_property(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     property_id: impl Into<String>,
     space_id: impl Into<String>,
     strict: bool,
@@ -5,6 +5,7 @@ use serde::{Deserialize, Serialize};
 use web3_utils::checksum_address;
 
 use grc20_core::{
+    connection::Graph,
     entity,
     error::DatabaseError,
     ids, indexer_ids,
@@ -13,9 +14,9 @@ use grc20_core::{
         attributes::{FromAttributes, IntoAttributes},
         entity::EntityNodeRef,
         query_utils::{AttributeFilter, PropFilter, QueryStream},
-        Entity, Relation, TriplesConversionError, Value,
+        Entity, Relation, Value,
     },
-    neo4rs, pb,
+    pb, Grc20Value,
 };
 
 /// Common fields for all proposals
@@ -39,7 +40,7 @@ impl Proposal {
 
     /// Finds a proposal by its onchain ID and plugin address
     pub async fn find_by_id_and_address(
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         proposal_id: &str,
         plugin_address: &str,
     ) -> Result<Option<Entity<Self>>, DatabaseError> {
@@ -63,7 +64,7 @@ impl Proposal {
 
     // /// Returns a query to set the status of a proposal given its ID
     // pub async fn set_status(
-    //     neo4j: &neo4rs::Graph,
+    //     neo4j: &Graph,
     //     block: &BlockMetadata,
     //     proposal_id: &str,
     //     status: ProposalStatus,
@@ -144,7 +145,7 @@ impl TryFrom<pb::ipfs::ActionType> for ProposalType {
     }
 }
 
-#[derive(Clone, Deserialize, Serialize)]
+#[derive(Clone, Deserialize, Serialize, Grc20Value)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ProposalStatus {
     Proposed,
@@ -154,36 +155,6 @@ pub enum ProposalStatus {
     Executed,
 }
 
-impl From<ProposalStatus> for Value {
-    fn from(status: ProposalStatus) -> Self {
-        match status {
-            ProposalStatus::Proposed => Value::text("Proposed".to_string()),
-            ProposalStatus::Accepted => Value::text("Accepted".to_string()),
-            ProposalStatus::Rejected => Value::text("Rejected".to_string()),
-            ProposalStatus::Canceled => Value::text("Canceled".to_string()),
-            ProposalStatus::Executed => Value::text("Executed".to_string()),
-        }
-    }
-}
-
-impl TryFrom<Value> for ProposalStatus {
-    type Error = TriplesConversionError;
-
-    fn try_from(value: Value) -> Result<Self, Self::Error> {
-        match value.value.as_str() {
-            "Proposed" => Ok(Self::Proposed),
-            "Accepted" => Ok(Self::Accepted),
-            "Rejected" => Ok(Self::Rejected),
-            "Canceled" => Ok(Self::Canceled),
-            "Executed" => Ok(Self::Executed),
-            _ => Err(TriplesConversionError::InvalidValue(format!(
-                "Invalid proposal status: {}",
-                value.value
-            ))),
-        }
-    }
-}
-
 impl Display for ProposalStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
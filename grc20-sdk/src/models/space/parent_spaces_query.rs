@@ -1,36 +1,38 @@
 use futures::{Stream, TryStreamExt};
 
 use grc20_core::{
+    connection::{with_query_policy, Graph},
     error::DatabaseError,
     indexer_ids,
     mapping::{
         aggregation::SpaceRanking,
         query_utils::{
             query_builder::{QueryBuilder, Subquery},
-            QueryStream,
+            QueryStream, VersionFilter,
         },
     },
-    neo4rs,
 };
 
 /// Query to find all parent spaces of a given space
 pub struct ParentSpacesQuery<T> {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     space_id: String,
     limit: usize,
     skip: Option<usize>,
     max_depth: Option<usize>,
+    version: Option<String>,
     _marker: std::marker::PhantomData<T>,
 }
 
 impl<T> ParentSpacesQuery<T> {
-    pub(crate) fn new(neo4j: neo4rs::Graph, space_id: String) -> Self {
+    pub(crate) fn new(neo4j: Graph, space_id: String) -> Self {
         Self {
             neo4j,
             space_id,
             limit: 100,
             skip: None,
             max_depth: Some(1),
+            version: None,
             _marker: std::marker::PhantomData,
         }
     }
@@ -53,19 +55,39 @@ impl<T> ParentSpacesQuery<T> {
         self
     }
 
+    /// Find the parent spaces as of the given space version (the current parent spaces by default)
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    pub fn version_opt(mut self, version: Option<String>) -> Self {
+        self.version = version;
+        self
+    }
+
     fn subquery(&self) -> QueryBuilder {
-        QueryBuilder::default()
+        // Only the relations of the hierarchy valid at the version are followed
+        let version_filter = VersionFilter::new(self.version.clone()).subquery("r");
+
+        let query = QueryBuilder::default()
             .subquery(format!(
-                r#"MATCH (start:Entity {{id: $space_id}}) (() -[r:RELATION {{relation_type: "{}", space_id: "{}"}} WHERE r.max_version IS NULL]-> (s:Entity)){{,}}"#,
+                r#"MATCH (start:Entity {{id: $space_id}}) (() -[r:RELATION {{relation_type: "{}", space_id: "{}"}} WHERE {}]-> (s:Entity)){{,}}"#,
                 indexer_ids::PARENT_SPACE,
                 indexer_ids::INDEXER_SPACE_ID,
+                version_filter.clauses.join(" AND "),
             ))
             .subquery("WHERE size(s) = size(COLLECT { WITH s UNWIND s AS _ RETURN DISTINCT _ })")
             .subquery_opt(self.max_depth.map(|depth| format!("AND size(s) <= {depth}")))
             .subquery("WITH {space_id: LAST([start] + s).id, depth: SIZE(s)} AS parent_spaces")
             .limit(self.limit)
             .skip_opt(self.skip)
-            .params("space_id", self.space_id.clone())
+            .params("space_id", self.space_id.clone());
+
+        version_filter
+            .params
+            .into_iter()
+            .fold(query, |query, (key, value)| query.params(key, value))
     }
 }
 
@@ -112,12 +134,12 @@ impl QueryStream<SpaceRanking> for ParentSpacesQuery<SpaceRanking> {
     ) -> Result<impl Stream<Item = Result<SpaceRanking, DatabaseError>>, DatabaseError> {
         let query = self.subquery().r#return("parent_spaces");
 
-        Ok(self
-            .neo4j
-            .execute(query.build())
-            .await?
-            .into_stream_as::<SpaceRanking>()
-            .map_err(DatabaseError::from)
-            .and_then(|row| async move { Ok(row) }))
+        Ok(
+            with_query_policy(&self.neo4j, || self.neo4j.execute(query.build()))
+                .await?
+                .into_stream_as::<SpaceRanking>()
+                .map_err(DatabaseError::from)
+                .and_then(|row| async move { Ok(row) }),
+        )
     }
 }
@@ -1,30 +1,33 @@
 use futures::{Stream, StreamExt};
 
 use grc20_core::{
+    connection::Graph,
     entity::{self, EntityNodeRef},
     error::DatabaseError,
     indexer_ids,
     mapping::{prop_filter, query_utils::QueryStream, Entity, PropFilter, Query, RelationEdge},
-    neo4rs, relation,
+    relation,
 };
 
 use crate::models::Account;
 
 /// Query to find all editors of a space
 pub struct SpaceEditorsQuery {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     space_id: String,
     limit: usize,
     skip: Option<usize>,
+    version: Option<String>,
 }
 
 impl SpaceEditorsQuery {
-    pub(crate) fn new(neo4j: neo4rs::Graph, space_id: String) -> Self {
+    pub(crate) fn new(neo4j: Graph, space_id: String) -> Self {
         Self {
             neo4j,
             space_id,
             limit: 100,
             skip: None,
+            version: None,
         }
     }
 
@@ -39,6 +42,17 @@ impl SpaceEditorsQuery {
         self.skip = Some(skip);
         self
     }
+
+    /// Find the accounts as of the given space version (the current accounts by default)
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    pub fn version_opt(mut self, version: Option<String>) -> Self {
+        self.version = version;
+        self
+    }
 }
 
 impl QueryStream<Entity<Account>> for SpaceEditorsQuery {
@@ -56,19 +70,23 @@ impl QueryStream<Entity<Account>> for SpaceEditorsQuery {
                     ),
             )
             .space_id(PropFilter::default().value(indexer_ids::INDEXER_SPACE_ID))
+            .version(self.version.clone())
             .limit(self.limit)
             .send()
             .await?;
 
         // Convert the stream of relations to a stream of accounts
         let neo4j = self.neo4j.clone();
+        let version = self.version;
         let account_stream = relations_stream
             .map(move |relation_result| {
                 let neo4j = neo4j.clone();
+                let version = version.clone();
                 async move {
                     let relation = relation_result?;
                     entity::find_one::<Entity<Account>>(&neo4j, &relation.from)
                         .space_id(indexer_ids::INDEXER_SPACE_ID)
+                        .version_opt(version)
                         .send()
                         .await?
                         .ok_or_else(|| {
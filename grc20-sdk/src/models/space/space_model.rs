@@ -1,22 +1,28 @@
-use futures::{pin_mut, StreamExt};
+use std::collections::HashMap;
+
+use futures::{pin_mut, StreamExt, TryStreamExt};
 use web3_utils::checksum_address;
 
 use grc20_core::{
     block::BlockMetadata,
+    connection::{with_query_policy, Graph},
     entity::{self, EntityNodeRef},
     error::DatabaseError,
     ids, indexer_ids,
     mapping::{
         prop_filter,
-        query_utils::{AttributeFilter, PropFilter, Query, QueryStream},
-        relation, Entity, EntityNode, Relation, TriplesConversionError, Value,
+        query_utils::{
+            query_builder::{QueryBuilder, Subquery},
+            AttributeFilter, PropFilter, Query, QueryStream,
+        },
+        relation, Entity, EntityNode, Relation,
     },
-    neo4rs, network_ids, system_ids,
+    network_ids, system_ids, Grc20Value,
 };
 
 use super::{
-    FindSpaceTypeQuery, FindSpaceTypesQuery, ParentSpacesQuery, SpaceEditorsQuery,
-    SpaceMembersQuery, SubspacesQuery,
+    FindSpaceTypeQuery, FindSpaceTypesQuery, FindTypesBySpacesQuery, ParentSpacesQuery,
+    SpaceEditorsQuery, SpaceMembersQuery, SubspacesQuery,
 };
 
 #[derive(Clone, PartialEq)]
@@ -61,7 +67,7 @@ pub fn builder(id: &str, dao_contract_address: &str) -> SpaceBuilder {
 
 /// Find a space by its DAO contract address.
 pub async fn find_by_dao_address(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     dao_contract_address: &str,
 ) -> Result<Option<Entity<Space>>, DatabaseError> {
     entity::find_one::<Entity<Space>>(neo4j, new_id(network_ids::GEO, dao_contract_address))
@@ -71,7 +77,7 @@ pub async fn find_by_dao_address(
 }
 
 pub async fn find_entity_by_dao_address(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     dao_contract_address: &str,
 ) -> Result<Option<EntityNode>, DatabaseError> {
     entity::find_one::<EntityNode>(neo4j, new_id(network_ids::GEO, dao_contract_address))
@@ -81,7 +87,7 @@ pub async fn find_entity_by_dao_address(
 
 /// Find a space by its space plugin address.
 pub async fn find_by_space_plugin_address(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     space_plugin_address: &str,
 ) -> Result<Option<Entity<Space>>, DatabaseError> {
     let stream = entity::find_many::<Entity<Space>>(neo4j)
@@ -100,7 +106,7 @@ pub async fn find_by_space_plugin_address(
 }
 
 pub async fn find_entity_by_space_plugin_address(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     space_plugin_address: &str,
 ) -> Result<Option<EntityNode>, DatabaseError> {
     let stream = entity::find_many::<EntityNode>(neo4j)
@@ -120,7 +126,7 @@ pub async fn find_entity_by_space_plugin_address(
 
 /// Find a space by its voting plugin address.
 pub async fn find_by_voting_plugin_address(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     voting_plugin_address: &str,
 ) -> Result<Option<Entity<Space>>, DatabaseError> {
     let stream = entity::find_many::<Entity<Space>>(neo4j)
@@ -139,7 +145,7 @@ pub async fn find_by_voting_plugin_address(
 }
 
 pub async fn find_entity_by_voting_plugin_address(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     voting_plugin_address: &str,
 ) -> Result<Option<EntityNode>, DatabaseError> {
     let stream = entity::find_many::<EntityNode>(neo4j)
@@ -160,7 +166,7 @@ pub async fn find_entity_by_voting_plugin_address(
 
 /// Find a space by its member access plugin address.
 pub async fn find_by_member_access_plugin(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     member_access_plugin: &str,
 ) -> Result<Option<Entity<Space>>, DatabaseError> {
     let stream = entity::find_many::<Entity<Space>>(neo4j)
@@ -180,7 +186,7 @@ pub async fn find_by_member_access_plugin(
 
 /// Find a space by its personal space admin plugin address.
 pub async fn find_by_personal_plugin_address(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     personal_space_admin_plugin: &str,
 ) -> Result<Option<Entity<Space>>, DatabaseError> {
     let stream = entity::find_many::<Entity<Space>>(neo4j)
@@ -199,66 +205,78 @@ pub async fn find_by_personal_plugin_address(
 }
 
 /// Find all members of a space
-pub fn members(neo4j: &neo4rs::Graph, space_id: &str) -> SpaceMembersQuery {
+pub fn members(neo4j: &Graph, space_id: &str) -> SpaceMembersQuery {
     SpaceMembersQuery::new(neo4j.clone(), space_id.to_string())
 }
 
 /// Find all editors of a space
-pub fn editors(neo4j: &neo4rs::Graph, space_id: &str) -> SpaceEditorsQuery {
+pub fn editors(neo4j: &Graph, space_id: &str) -> SpaceEditorsQuery {
     SpaceEditorsQuery::new(neo4j.clone(), space_id.to_string())
 }
 
 /// Find all parent spaces of a given space
-pub fn parent_spaces<T>(neo4j: &neo4rs::Graph, space_id: &str) -> ParentSpacesQuery<T> {
+pub fn parent_spaces<T>(neo4j: &Graph, space_id: &str) -> ParentSpacesQuery<T> {
     ParentSpacesQuery::new(neo4j.clone(), space_id.to_string())
 }
 
 /// Find all subspaces of a given space
-pub fn subspaces<T>(neo4j: &neo4rs::Graph, space_id: &str) -> SubspacesQuery<T> {
+pub fn subspaces<T>(neo4j: &Graph, space_id: &str) -> SubspacesQuery<T> {
     SubspacesQuery::new(neo4j.clone(), space_id.to_string())
 }
 
 /// Find all types defined in a space
-pub fn types(neo4j: &neo4rs::Graph, space_id: &str) -> FindSpaceTypesQuery {
+pub fn types(neo4j: &Graph, space_id: &str) -> FindSpaceTypesQuery {
     FindSpaceTypesQuery::new(neo4j.clone(), space_id.to_string())
 }
 
+/// Find the types defined in several spaces at once. Each space is paired with the
+/// spaces in which its types are looked up (e.g.: the space and its parent spaces).
+pub fn types_by_spaces(
+    neo4j: &Graph,
+    spaces: Vec<(String, Vec<String>)>,
+) -> FindTypesBySpacesQuery {
+    FindTypesBySpacesQuery::new(neo4j.clone(), spaces)
+}
+
 /// Find a single type defined in a space
-pub fn r#type(neo4j: &neo4rs::Graph, space_id: &str, id: &str) -> FindSpaceTypeQuery {
+pub fn r#type(neo4j: &Graph, space_id: &str, id: &str) -> FindSpaceTypeQuery {
     FindSpaceTypeQuery::new(neo4j.clone(), space_id.to_string(), id.to_string())
 }
 
-#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+/// Find the spaces in which the given entities are defined (i.e.: the spaces in which
+/// the entities have at least one attribute). Returns a map of entity IDs to space IDs.
+pub async fn find_entity_spaces(
+    neo4j: &Graph,
+    entity_ids: Vec<String>,
+) -> Result<HashMap<String, Vec<String>>, DatabaseError> {
+    #[derive(serde::Deserialize)]
+    struct EntitySpaces {
+        entity_id: String,
+        space_ids: Vec<String>,
+    }
+
+    let query = QueryBuilder::default()
+        .subquery("MATCH (e:Entity) -[r:ATTRIBUTE]-> (:Attribute)")
+        .subquery("WHERE e.id IN $entity_ids AND r.max_version IS NULL")
+        .params("entity_ids", entity_ids)
+        .r#return("e.id AS entity_id, COLLECT(DISTINCT r.space_id) AS space_ids");
+
+    with_query_policy(neo4j, || neo4j.execute(query.build()))
+        .await?
+        .into_stream_as::<EntitySpaces>()
+        .map_ok(|row| (row.entity_id, row.space_ids))
+        .map_err(DatabaseError::from)
+        .try_collect()
+        .await
+}
+
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize, Grc20Value)]
 pub enum SpaceGovernanceType {
     #[default]
     Public,
     Personal,
 }
 
-impl From<SpaceGovernanceType> for Value {
-    fn from(governance_type: SpaceGovernanceType) -> Self {
-        match governance_type {
-            SpaceGovernanceType::Public => Value::text("Public".to_string()),
-            SpaceGovernanceType::Personal => Value::text("Personal".to_string()),
-        }
-    }
-}
-
-impl TryFrom<Value> for SpaceGovernanceType {
-    type Error = TriplesConversionError;
-
-    fn try_from(value: Value) -> Result<Self, Self::Error> {
-        match value.value.as_str() {
-            "Public" => Ok(SpaceGovernanceType::Public),
-            "Personal" => Ok(SpaceGovernanceType::Personal),
-            _ => Err(TriplesConversionError::InvalidValue(format!(
-                "Invalid SpaceGovernanceType value: {}",
-                value.value
-            ))),
-        }
-    }
-}
-
 pub struct SpaceBuilder {
     id: String,
     network: String,
@@ -361,7 +379,7 @@ impl ParentSpace {
 
     /// Delete a relation between a space and its parent space.
     pub async fn remove(
-        neo4j: &neo4rs::Graph,
+        neo4j: &Graph,
         block: &BlockMetadata,
         space_id: &str,
         parent_space_id: &str,
@@ -1,24 +1,31 @@
+use std::collections::HashMap;
+
 use futures::{Stream, TryStreamExt};
 
 use grc20_core::{
-    entity::{self, TypesFilter},
+    connection::{with_query_policy, Graph},
+    entity::{self, merge::not_merged, TypesFilter},
     error::DatabaseError,
-    mapping::{prop_filter, query_utils::QueryStream, EntityFilter, EntityNode, PropFilter, Query},
-    neo4rs, system_ids,
+    mapping::{
+        prop_filter, query_utils::QueryStream, EntityFilter, EntityNode, PropFilter, Query,
+        QueryBuilder, Subquery,
+    },
+    neo4rs::BoltType,
+    system_ids,
 };
 
 use super::ParentSpacesQuery;
 
 /// Query to find all types defined in a space
 pub struct FindSpaceTypeQuery {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     space_id: String,
     id: String,
     strict: bool,
 }
 
 impl FindSpaceTypeQuery {
-    pub(crate) fn new(neo4j: neo4rs::Graph, space_id: String, id: String) -> Self {
+    pub(crate) fn new(neo4j: Graph, space_id: String, id: String) -> Self {
         Self {
             neo4j,
             space_id,
@@ -76,7 +83,7 @@ impl Query<Option<EntityNode>> for FindSpaceTypeQuery {
 
 /// Query to find all types defined in a space
 pub struct FindSpaceTypesQuery {
-    neo4j: neo4rs::Graph,
+    neo4j: Graph,
     space_id: String,
     limit: usize,
     skip: Option<usize>,
@@ -84,7 +91,7 @@ pub struct FindSpaceTypesQuery {
 }
 
 impl FindSpaceTypesQuery {
-    pub(crate) fn new(neo4j: neo4rs::Graph, space_id: String) -> Self {
+    pub(crate) fn new(neo4j: Graph, space_id: String) -> Self {
         Self {
             neo4j,
             space_id,
@@ -148,3 +155,91 @@ impl QueryStream<EntityNode> for FindSpaceTypesQuery {
         query.send().await
     }
 }
+
+/// Query to find the types defined in several spaces at once. Each space is paired
+/// with the spaces in which its types are looked up (e.g.: the space itself and its
+/// parent spaces). The pagination applies to the types of each space.
+pub struct FindTypesBySpacesQuery {
+    neo4j: Graph,
+    spaces: Vec<(String, Vec<String>)>,
+    limit: usize,
+    skip: Option<usize>,
+}
+
+impl FindTypesBySpacesQuery {
+    pub(crate) fn new(neo4j: Graph, spaces: Vec<(String, Vec<String>)>) -> Self {
+        Self {
+            neo4j,
+            spaces,
+            limit: 100,
+            skip: None,
+        }
+    }
+
+    /// Limit the number of results per space
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Skip a number of results per space
+    pub fn skip(mut self, skip: usize) -> Self {
+        self.skip = Some(skip);
+        self
+    }
+}
+
+impl Query<HashMap<String, Vec<EntityNode>>> for FindTypesBySpacesQuery {
+    async fn send(self) -> Result<HashMap<String, Vec<EntityNode>>, DatabaseError> {
+        if self.spaces.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let spaces = self
+            .spaces
+            .into_iter()
+            .map(|(space_id, space_ids)| {
+                HashMap::from([
+                    ("space_id".to_string(), BoltType::from(space_id)),
+                    ("space_ids".to_string(), BoltType::from(space_ids)),
+                ])
+            })
+            .collect::<Vec<_>>();
+
+        // Find all entities that have a TYPES relation to the Type entity, for each space
+        let query = QueryBuilder::default()
+            .subquery("UNWIND $spaces AS space")
+            .subquery("CALL {")
+            .subquery("WITH space")
+            .subquery("MATCH (e:Entity) -[t:RELATION {relation_type: $types_attribute}]-> (:Entity {id: $schema_type})")
+            .subquery(format!("WHERE t.max_version IS NULL AND {}", not_merged("e")))
+            .subquery("AND EXISTS { MATCH (e) -[a:ATTRIBUTE]- (:Attribute) WHERE a.space_id IN space.space_ids }")
+            .subquery("WITH DISTINCT e")
+            .subquery("ORDER BY e.id")
+            .skip_opt(self.skip)
+            .limit(self.limit)
+            .subquery("RETURN COLLECT(e) AS types")
+            .subquery("}")
+            .params("spaces", spaces)
+            .params("types_attribute", system_ids::TYPES_ATTRIBUTE)
+            .params("schema_type", system_ids::SCHEMA_TYPE)
+            .r#return("space.space_id AS space_id, types");
+
+        #[derive(Debug, serde::Deserialize)]
+        struct RowResult {
+            space_id: String,
+            types: Vec<EntityNode>,
+        }
+
+        let mut result =
+            with_query_policy(&self.neo4j, || self.neo4j.execute(query.build())).await?;
+        let mut types = HashMap::new();
+
+        while let Some(row) = result.next().await? {
+            let row = row.to::<RowResult>()?;
+            types.insert(row.space_id, row.types);
+        }
+
+        Ok(types)
+    }
+}
@@ -0,0 +1,144 @@
+//! Tentative overlay of the sink in hybrid mode: the edits published in the blocks of
+//! the head of the chain, which are not final yet. Tentative edits are stored apart
+//! from the knowledge graph (as `TentativeEdit` nodes, without versions) and discarded
+//! once their block is final, at which point the edit is indexed durably, or reverted.
+use futures::TryStreamExt;
+use grc20_core::{
+    connection::{with_query_policy, Graph},
+    error::DatabaseError,
+    mapping::query_utils::query_builder::{QueryBuilder, Subquery},
+    neo4j_utils::serde_value_to_bolt,
+    neo4rs::{self, BoltType},
+    pb,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct TentativeEdit {
+    /// ID of the edit (i.e.: of its proposal)
+    pub id: String,
+    pub name: String,
+    /// Space in which the edit was published
+    pub space_id: String,
+    pub creator: String,
+    pub content_uri: String,
+    /// Entities whose attributes or relations are changed by the edit, so that readers
+    /// can tell which entities have pending changes
+    pub entity_ids: Vec<String>,
+    pub block_number: u64,
+}
+
+impl TentativeEdit {
+    /// Returns the IDs of the entities changed by the ops, sorted and deduplicated
+    pub fn entity_ids(ops: &[pb::ipfs::Op]) -> Vec<String> {
+        let mut entity_ids = ops
+            .iter()
+            .flat_map(|op| {
+                op.triple
+                    .iter()
+                    .chain(&op.triples)
+                    .map(|triple| &triple.entity)
+                    .chain(op.entity.iter().map(|entity| &entity.id))
+                    .chain(op.relation.iter().map(|relation| &relation.from_entity))
+            })
+            .filter(|id| !id.is_empty())
+            .cloned()
+            .collect::<Vec<_>>();
+
+        entity_ids.sort();
+        entity_ids.dedup();
+        entity_ids
+    }
+}
+
+impl From<TentativeEdit> for BoltType {
+    fn from(edit: TentativeEdit) -> Self {
+        serde_value_to_bolt(serde_json::to_value(edit).expect("edit should serialize"))
+    }
+}
+
+/// Replaces the tentative edits of the block `block_number`, which is streamed again
+/// when the sink restarts
+pub async fn replace_block(
+    neo4j: &Graph,
+    block_number: u64,
+    edits: Vec<TentativeEdit>,
+) -> Result<(), DatabaseError> {
+    const QUERY: &str = r#"
+        OPTIONAL MATCH (old:TentativeEdit {block_number: $block_number})
+        DETACH DELETE old
+        WITH count(*) AS deleted
+        UNWIND $edits AS edit
+        CREATE (e:TentativeEdit)
+        SET e = edit
+    "#;
+
+    let query = neo4rs::query(QUERY)
+        .param("block_number", block_number as i64)
+        .param("edits", edits);
+
+    with_query_policy(neo4j, || neo4j.run(query.clone())).await?;
+
+    Ok(())
+}
+
+/// Discards the tentative edits of the blocks up to `block_number` (included), once
+/// they have been indexed durably
+pub async fn delete_until(neo4j: &Graph, block_number: u64) -> Result<(), DatabaseError> {
+    let query = neo4rs::query(
+        "MATCH (e:TentativeEdit) WHERE e.block_number <= $block_number DETACH DELETE e",
+    )
+    .param("block_number", block_number as i64);
+
+    with_query_policy(neo4j, || neo4j.run(query.clone())).await?;
+
+    Ok(())
+}
+
+/// Discards the tentative edits of the blocks above `block_number`, which have been
+/// reverted
+pub async fn delete_after(neo4j: &Graph, block_number: u64) -> Result<(), DatabaseError> {
+    let query = neo4rs::query(
+        "MATCH (e:TentativeEdit) WHERE e.block_number > $block_number DETACH DELETE e",
+    )
+    .param("block_number", block_number as i64);
+
+    with_query_policy(neo4j, || neo4j.run(query.clone())).await?;
+
+    Ok(())
+}
+
+/// Discards all the tentative edits, e.g.: when the sink starts since the tentative
+/// blocks are streamed again (or not at all if the hybrid mode is disabled)
+pub async fn delete_all(neo4j: &Graph) -> Result<(), DatabaseError> {
+    let query = neo4rs::query("MATCH (e:TentativeEdit) DETACH DELETE e");
+
+    with_query_policy(neo4j, || neo4j.run(query.clone())).await?;
+
+    Ok(())
+}
+
+/// Returns the tentative edits, optionally restricted to a space, in block order
+pub async fn find_many(
+    neo4j: &Graph,
+    space_id: Option<&str>,
+) -> Result<Vec<TentativeEdit>, DatabaseError> {
+    #[derive(Deserialize)]
+    struct Row {
+        edit: TentativeEdit,
+    }
+
+    let query = QueryBuilder::default()
+        .subquery("MATCH (e:TentativeEdit)")
+        .subquery_opt(space_id.map(|_| "WHERE e.space_id = $space_id"))
+        .params("space_id", space_id.unwrap_or_default())
+        .r#return("e{.*} AS edit ORDER BY e.block_number, e.id");
+
+    with_query_policy(neo4j, || neo4j.execute(query.build()))
+        .await?
+        .into_stream_as::<Row>()
+        .map_ok(|row| row.edit)
+        .map_err(DatabaseError::from)
+        .try_collect()
+        .await
+}
@@ -0,0 +1,127 @@
+use futures::TryStreamExt;
+use grc20_core::{
+    connection::Graph,
+    entity::{self, Entity, EntityFilter, EntityRelationFilter},
+    error::DatabaseError,
+    mapping::{prop_filter, EntityNode, Query, QueryStream, RelationEdge},
+    system_ids,
+};
+
+use super::{base_entity, property, BaseEntity};
+
+/// Property of a type, along with the type of its values. Relation properties also have
+/// the type of the entities they point to (if it is constrained).
+#[derive(Debug)]
+pub struct PropertySchema {
+    pub property: Entity<BaseEntity>,
+    pub value_type: Option<Entity<BaseEntity>>,
+    pub relation_value_type: Option<Entity<BaseEntity>>,
+}
+
+impl PropertySchema {
+    pub fn id(&self) -> &str {
+        self.property.id()
+    }
+
+    /// Whether the values of the property are relations to other entities
+    pub fn is_relation(&self) -> bool {
+        self.value_type
+            .as_ref()
+            .is_some_and(|value_type| value_type.id() == system_ids::RELATION_SCHEMA_TYPE)
+    }
+}
+
+/// Returns the properties of the type with their value types and relation value types
+pub async fn properties(
+    neo4j: &Graph,
+    type_id: impl Into<String>,
+    space_id: impl Into<String>,
+    space_version: Option<String>,
+    limit: Option<usize>,
+    skip: Option<usize>,
+    strict: bool,
+) -> Result<Vec<PropertySchema>, DatabaseError> {
+    let space_id = space_id.into();
+
+    let property_ids = property::get_outbound_relations::<RelationEdge<EntityNode>>(
+        neo4j,
+        system_ids::PROPERTIES,
+        type_id,
+        &space_id,
+        space_version.clone(),
+        limit,
+        skip,
+        strict,
+    )
+    .await?
+    .send()
+    .await?
+    .map_ok(|relation| relation.to.id)
+    .try_collect::<Vec<_>>()
+    .await?;
+
+    let mut properties = Vec::with_capacity(property_ids.len());
+    for property_id in property_ids {
+        let property = base_entity::find_one(neo4j, &property_id, &space_id)
+            .send()
+            .await?
+            .unwrap_or_else(|| {
+                Entity::new(
+                    &property_id,
+                    BaseEntity {
+                        name: None,
+                        description: None,
+                    },
+                )
+            });
+
+        let value_type = property::value_type(
+            neo4j,
+            &property_id,
+            &space_id,
+            space_version.clone(),
+            strict,
+        )
+        .await?;
+
+        let relation_value_type = property::relation_value_type(
+            neo4j,
+            &property_id,
+            &space_id,
+            space_version.clone(),
+            strict,
+        )
+        .await?;
+
+        properties.push(PropertySchema {
+            property,
+            value_type,
+            relation_value_type,
+        });
+    }
+
+    Ok(properties)
+}
+
+/// Find the entities of the type in the space, e.g.: to show examples of the type
+pub fn example_entities<T>(
+    neo4j: &Graph,
+    type_id: impl Into<String>,
+    space_id: impl Into<String>,
+    space_version: Option<String>,
+) -> entity::FindManyQuery<T> {
+    let space_id = space_id.into();
+
+    entity::find_many::<T>(neo4j)
+        .with_filter(
+            EntityFilter::default().relations(
+                EntityRelationFilter::default()
+                    .relation_type(system_ids::TYPES_ATTRIBUTE)
+                    .to_id(type_id.into())
+                    .space_id(prop_filter::value(&space_id)),
+            ),
+        )
+        .space_id(prop_filter::value(space_id))
+        .version_opt(space_version)
+        .limit(10)
+}
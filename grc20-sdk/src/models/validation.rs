@@ -0,0 +1,335 @@
+//! Validation of edit ops against the schema of the knowledge graph.
+//!
+//! Properties can declare the type of value they hold (via `VALUE_TYPE_ATTRIBUTE`)
+//! and, for relation properties, the type of the entities they point to (via
+//! `RELATION_VALUE_RELATIONSHIP_TYPE`). Since edits are processed in order, the
+//! schema known at the version of an edit is the current state of the knowledge
+//! graph plus the schema declared in the edit itself.
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+use chrono::DateTime;
+use grc20_core::{
+    connection::Graph,
+    entity,
+    error::DatabaseError,
+    ids, indexer_ids,
+    mapping::{query_utils::Query, Entity, ValueType},
+    pb, system_ids, Grc20Value,
+};
+
+use super::{property, BaseEntity};
+
+/// Schema violation found while indexing an edit. Diagnostics are stored in the
+/// indexer space and can be queried with [`find_many`], e.g.:
+/// ```rust,ignore
+/// validation::find_many(&neo4j, indexer_ids::INDEXER_SPACE_ID)
+///     .space(prop_filter::value(space_id))
+/// ```
+#[grc20_core::entity]
+#[grc20(schema_type = indexer_ids::VALIDATION_DIAGNOSTIC_TYPE)]
+pub struct ValidationDiagnostic {
+    #[grc20(attribute = indexer_ids::DIAGNOSTIC_KIND_ATTRIBUTE)]
+    pub kind: DiagnosticKind,
+
+    /// Space in which the edit was published
+    #[grc20(attribute = indexer_ids::DIAGNOSTIC_SPACE_ATTRIBUTE)]
+    pub space: String,
+
+    /// Entity on which the violating triple or relation was set
+    #[grc20(attribute = indexer_ids::DIAGNOSTIC_ENTITY_ATTRIBUTE)]
+    pub entity: String,
+
+    /// Property (i.e.: attribute or relation type) of the violating op
+    #[grc20(attribute = indexer_ids::DIAGNOSTIC_PROPERTY_ATTRIBUTE)]
+    pub property: String,
+
+    /// What the schema expects (e.g.: a value type or the ID of a type)
+    #[grc20(attribute = indexer_ids::DIAGNOSTIC_EXPECTED_ATTRIBUTE)]
+    pub expected: String,
+
+    /// What the edit contained
+    #[grc20(attribute = indexer_ids::DIAGNOSTIC_ACTUAL_ATTRIBUTE)]
+    pub actual: String,
+
+    /// Content URI of the edit
+    #[grc20(attribute = indexer_ids::EDIT_CONTENT_URI_ATTRIBUTE)]
+    pub content_uri: String,
+
+    /// Version index of the edit
+    #[grc20(attribute = indexer_ids::EDIT_INDEX_ATTRIBUTE)]
+    pub index: Option<String>,
+
+    /// Whether the op was rejected (i.e.: not written to the knowledge graph)
+    #[grc20(attribute = indexer_ids::DIAGNOSTIC_REJECTED_ATTRIBUTE)]
+    pub rejected: bool,
+}
+
+impl ValidationDiagnostic {
+    pub fn gen_id(content_uri: &str, kind: &DiagnosticKind, op_id: &str) -> String {
+        ids::create_id_from_unique_string(format!("{content_uri}:{kind}:{op_id}"))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Grc20Value)]
+pub enum DiagnosticKind {
+    /// The value of a triple cannot be parsed as its declared value type
+    InvalidValue,
+    /// The value type of a triple does not match the value type of its property
+    ValueTypeMismatch,
+    /// The target of a relation does not have the type required by the relation type
+    RelationTargetTypeMismatch,
+}
+
+impl Display for DiagnosticKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiagnosticKind::InvalidValue => write!(f, "InvalidValue"),
+            DiagnosticKind::ValueTypeMismatch => write!(f, "ValueTypeMismatch"),
+            DiagnosticKind::RelationTargetTypeMismatch => write!(f, "RelationTargetTypeMismatch"),
+        }
+    }
+}
+
+/// Reference to an op of an edit, by its position in its op group
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OpRef {
+    SetTriple(usize),
+    CreateRelation(usize),
+}
+
+/// Schema violation of a single op
+#[derive(Clone, Debug, PartialEq)]
+pub struct Violation {
+    pub kind: DiagnosticKind,
+    pub op: OpRef,
+    /// Unique identifier of the op within the edit
+    pub op_id: String,
+    pub entity_id: String,
+    pub property_id: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// Returns the value type corresponding to the ID of a value type entity
+pub fn value_type_from_id(id: &str) -> Option<ValueType> {
+    match id {
+        system_ids::TEXT => Some(ValueType::Text),
+        system_ids::NUMBER => Some(ValueType::Number),
+        system_ids::CHECKBOX => Some(ValueType::Checkbox),
+        system_ids::URL => Some(ValueType::Url),
+        system_ids::TIME => Some(ValueType::Time),
+        system_ids::POINT => Some(ValueType::Point),
+        _ => None,
+    }
+}
+
+/// Checks that `value` can be parsed as a value of type `value_type`
+pub fn check_value(value_type: &ValueType, value: &str) -> bool {
+    match value_type {
+        ValueType::Text => true,
+        ValueType::Number => value.parse::<f64>().is_ok(),
+        ValueType::Checkbox => value.parse::<bool>().is_ok(),
+        ValueType::Url => value.split_once("://").is_some_and(|(scheme, rest)| {
+            !scheme.is_empty()
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+                && !rest.is_empty()
+        }),
+        ValueType::Time => DateTime::parse_from_rfc3339(value).is_ok(),
+        ValueType::Point => {
+            let coords = value
+                .split(',')
+                .map(|coord| coord.trim().parse::<f64>())
+                .collect::<Result<Vec<_>, _>>();
+            matches!(coords, Ok(coords) if coords.len() == 2)
+        }
+    }
+}
+
+/// Validates the SET_TRIPLE and CREATE_RELATION ops of an edit published in
+/// `space_id`. Ops whose property does not declare a value type are not validated.
+pub async fn validate_ops(
+    neo4j: &Graph,
+    space_id: &str,
+    set_triples: &[pb::ipfs::Triple],
+    create_relations: &[pb::ipfs::Relation],
+) -> Result<Vec<Violation>, DatabaseError> {
+    // Schema declared in the edit itself
+    let mut value_types: HashMap<String, String> = HashMap::new();
+    let mut relation_value_types: HashMap<String, String> = HashMap::new();
+    let mut entity_types: HashMap<String, HashSet<String>> = HashMap::new();
+
+    for relation in create_relations {
+        match relation.r#type.as_str() {
+            system_ids::VALUE_TYPE_ATTRIBUTE => {
+                value_types.insert(relation.from_entity.clone(), relation.to_entity.clone());
+            }
+            system_ids::RELATION_VALUE_RELATIONSHIP_TYPE => {
+                relation_value_types
+                    .insert(relation.from_entity.clone(), relation.to_entity.clone());
+            }
+            system_ids::TYPES_ATTRIBUTE => {
+                entity_types
+                    .entry(relation.from_entity.clone())
+                    .or_default()
+                    .insert(relation.to_entity.clone());
+            }
+            _ => (),
+        }
+    }
+
+    // Value types of the properties not declared in the edit, looked up in a single query
+    // per kind of value type rather than per op
+    let attribute_ids = set_triples
+        .iter()
+        .map(|triple| &triple.attribute)
+        .filter(|id| !value_types.contains_key(*id))
+        .cloned()
+        .collect::<HashSet<_>>();
+    let stored = property::value_type_ids(
+        neo4j,
+        attribute_ids.into_iter().collect(),
+        space_id,
+        None,
+        false,
+    )
+    .await?;
+    for (attribute_id, value_type) in stored {
+        value_types.entry(attribute_id).or_insert(value_type);
+    }
+
+    let relation_type_ids = create_relations
+        .iter()
+        .map(|relation| &relation.r#type)
+        .filter(|id| !relation_value_types.contains_key(*id))
+        .cloned()
+        .collect::<HashSet<_>>();
+    let stored = property::relation_value_type_ids(
+        neo4j,
+        relation_type_ids.into_iter().collect(),
+        space_id,
+        None,
+        false,
+    )
+    .await?;
+    for (relation_type, value_type) in stored {
+        relation_value_types
+            .entry(relation_type)
+            .or_insert(value_type);
+    }
+
+    let mut violations = vec![];
+
+    // Validate triples
+    for (idx, triple) in set_triples.iter().enumerate() {
+        let Some(value) = &triple.value else {
+            continue;
+        };
+
+        let op_id = format!("{}:{}", triple.entity, triple.attribute);
+        let violation = |kind, expected: String, actual: String| Violation {
+            kind,
+            op: OpRef::SetTriple(idx),
+            op_id: op_id.clone(),
+            entity_id: triple.entity.clone(),
+            property_id: triple.attribute.clone(),
+            expected,
+            actual,
+        };
+
+        let actual_type: ValueType = match value.r#type().try_into() {
+            Ok(value_type) => value_type,
+            Err(_) => {
+                violations.push(violation(
+                    DiagnosticKind::InvalidValue,
+                    "known value type".to_string(),
+                    value.r#type().as_str_name().to_string(),
+                ));
+                continue;
+            }
+        };
+
+        if !check_value(&actual_type, &value.value) {
+            violations.push(violation(
+                DiagnosticKind::InvalidValue,
+                actual_type.to_string(),
+                value.value.clone(),
+            ));
+            continue;
+        }
+
+        let expected_type = value_types
+            .get(&triple.attribute)
+            .and_then(|id| value_type_from_id(id));
+
+        match expected_type {
+            Some(expected_type) if expected_type != actual_type => {
+                violations.push(violation(
+                    DiagnosticKind::ValueTypeMismatch,
+                    expected_type.to_string(),
+                    actual_type.to_string(),
+                ));
+            }
+            _ => (),
+        }
+    }
+
+    // Collect the relations whose relation type restricts the type of the target entity
+    let mut typed_relations = vec![];
+    for (idx, relation) in create_relations.iter().enumerate() {
+        if let Some(expected) = relation_value_types.get(&relation.r#type) {
+            typed_relations.push((idx, relation, expected.clone()));
+        }
+    }
+
+    if typed_relations.is_empty() {
+        return Ok(violations);
+    }
+
+    // Load the types of the target entities from the knowledge graph
+    let mut target_ids = typed_relations
+        .iter()
+        .map(|(_, relation, _)| relation.to_entity.clone())
+        .collect::<Vec<_>>();
+    target_ids.sort();
+    target_ids.dedup();
+
+    let targets = entity::find_many_by_ids::<Entity<BaseEntity>>(neo4j, target_ids)
+        .send()
+        .await?;
+
+    for target in targets {
+        entity_types
+            .entry(target.id().to_string())
+            .or_default()
+            .extend(target.types);
+    }
+
+    // Validate relations
+    for (idx, relation, expected) in typed_relations {
+        let types = entity_types.get(&relation.to_entity);
+
+        if !types.is_some_and(|types| types.contains(&expected)) {
+            let mut actual = types
+                .map(|types| types.iter().cloned().collect::<Vec<_>>())
+                .unwrap_or_default();
+            actual.sort();
+
+            violations.push(Violation {
+                kind: DiagnosticKind::RelationTargetTypeMismatch,
+                op: OpRef::CreateRelation(idx),
+                op_id: relation.id.clone(),
+                entity_id: relation.from_entity.clone(),
+                property_id: relation.r#type.clone(),
+                expected,
+                actual: actual.join(","),
+            });
+        }
+    }
+
+    Ok(violations)
+}
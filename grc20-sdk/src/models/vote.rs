@@ -2,7 +2,8 @@
 
 use grc20_core::{
     ids, indexer_ids,
-    mapping::{self, entity::EntityNodeRef, Relation, TriplesConversionError},
+    mapping::{entity::EntityNodeRef, Relation},
+    Grc20Value,
 };
 
 /// A vote cast by a user on a proposal.
@@ -38,7 +39,8 @@ impl VoteCast {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Grc20Value)]
+#[grc20(rename_all = "UPPERCASE")]
 pub enum VoteType {
     Accept,
     Reject,
@@ -55,26 +57,3 @@ impl TryFrom<u64> for VoteType {
         }
     }
 }
-
-impl From<VoteType> for mapping::Value {
-    fn from(vote_type: VoteType) -> Self {
-        match vote_type {
-            VoteType::Accept => mapping::Value::text("ACCEPT"),
-            VoteType::Reject => mapping::Value::text("REJECT"),
-        }
-    }
-}
-
-impl TryFrom<mapping::Value> for VoteType {
-    type Error = TriplesConversionError;
-
-    fn try_from(value: mapping::Value) -> Result<Self, Self::Error> {
-        match (value.value_type, value.value.as_str()) {
-            (mapping::ValueType::Text, "ACCEPT") => Ok(Self::Accept),
-            (mapping::ValueType::Text, "REJECT") => Ok(Self::Reject),
-            (value_type, _) => Err(TriplesConversionError::InvalidValue(format!(
-                "Invalid vote type value_type: {value_type:?}"
-            ))),
-        }
-    }
-}
@@ -0,0 +1,47 @@
+//! Pre-seeds or exports an IPFS cache, e.g.: to reindex without network access.
+//!
+//! ```sh
+//! # Load an archive into a cache directory (or into a pack archive if the target ends with .tar)
+//! ipfs_cache seed <archive.tar> [ipfs-cache]
+//!
+//! # Export a cache directory to an archive
+//! ipfs_cache export [ipfs-cache] <archive.tar>
+//! ```
+use std::{env::args, fs::File, io::BufReader, process::exit};
+
+use ipfs::{cache, FsCache, PackCache, IPFS_CACHE_DIR};
+
+fn main() -> Result<(), ipfs::Error> {
+    let args = args().skip(1).collect::<Vec<_>>();
+
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["seed", archive] => seed(archive, IPFS_CACHE_DIR),
+        ["seed", archive, target] => seed(archive, target),
+        ["export", archive] => export(IPFS_CACHE_DIR, archive),
+        ["export", dir, archive] => export(dir, archive),
+        _ => {
+            eprintln!("Usage: ipfs_cache seed <archive> [cache] | export [cache-dir] <archive>");
+            exit(1);
+        }
+    }
+}
+
+fn seed(archive: &str, target: &str) -> Result<(), ipfs::Error> {
+    let archive = BufReader::new(File::open(archive)?);
+
+    let count = if target.ends_with(".tar") {
+        cache::seed(&PackCache::open(target)?, archive)?
+    } else {
+        cache::seed(&FsCache::new(target), archive)?
+    };
+
+    println!("Loaded {count} objects into {target}");
+    Ok(())
+}
+
+fn export(dir: &str, archive: &str) -> Result<(), ipfs::Error> {
+    let count = FsCache::new(dir).export(File::create(archive)?)?;
+
+    println!("Exported {count} objects to {archive}");
+    Ok(())
+}
@@ -0,0 +1,657 @@
+//! Content caches used by the [`IpfsClient`](crate::IpfsClient). Since IPFS content
+//! is immutable, entries never need to be invalidated, only evicted when the cache
+//! grows past its size limit.
+//!
+//! - [`FsCache`]: one file per CID, optionally sharded in directories and bounded
+//! - [`MemoryCache`]: in-memory, optionally bounded
+//! - [`PackCache`]: single append-only tar archive (entries are named after their CID)
+//!
+//! Tar archives are also the format used to pre-seed a cache (see [`seed`] and
+//! [`FsCache::export`]), e.g.: to reindex without network access.
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, File},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+    time::SystemTime,
+};
+
+use crate::{cid::Cid, Error};
+
+pub trait Cache: Send + Sync {
+    fn get(&self, cid: &str) -> Result<Option<Vec<u8>>, Error>;
+
+    fn put(&self, cid: &str, bytes: &[u8]) -> Result<(), Error>;
+}
+
+impl<C: Cache + ?Sized> Cache for Arc<C> {
+    fn get(&self, cid: &str) -> Result<Option<Vec<u8>>, Error> {
+        (**self).get(cid)
+    }
+
+    fn put(&self, cid: &str, bytes: &[u8]) -> Result<(), Error> {
+        (**self).put(cid, bytes)
+    }
+}
+
+/// Cache storing each object in its own file under `root`. By default files are stored
+/// directly under `root` (i.e.: `<root>/<cid>`, the layout of existing cache
+/// directories). With [`FsCache::shard_width`], files are sharded in sub-directories
+/// named after the characters preceding the last character of the CID (like the
+/// `next-to-last/2` sharding of go-ipfs), which are evenly distributed. Objects cached
+/// with another shard width are not found, a sharded cache should be filled from an
+/// export of the previous one (see [`FsCache::export`] and [`seed`]).
+///
+/// Files are written atomically, so the same directory can be shared between
+/// processes. Note however that the size limit is enforced by each process
+/// independently.
+pub struct FsCache {
+    root: PathBuf,
+    shard_width: usize,
+    max_size: Option<u64>,
+    // Only maintained when the cache is bounded, built on first use
+    index: Mutex<Option<Lru>>,
+}
+
+impl FsCache {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            shard_width: 0,
+            max_size: None,
+            index: Mutex::new(None),
+        }
+    }
+
+    /// Number of characters of the shard directory names, 0 disables sharding (default: 0)
+    pub fn shard_width(mut self, shard_width: usize) -> Self {
+        self.shard_width = shard_width;
+        self
+    }
+
+    /// Maximum total size of the cached objects in bytes. The least recently used
+    /// objects are evicted when the limit is exceeded (default: unbounded).
+    pub fn max_size(mut self, max_size: u64) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    pub fn max_size_opt(mut self, max_size: Option<u64>) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Path of the file caching the object `cid`
+    pub fn path(&self, cid: &str) -> PathBuf {
+        let len = cid.len();
+
+        if self.shard_width == 0 || len <= self.shard_width {
+            return self.root.join(cid);
+        }
+
+        let shard = &cid[len - 1 - self.shard_width..len - 1];
+        self.root.join(shard).join(cid)
+    }
+
+    /// Writes all the cached objects to a tar archive which can be loaded with [`seed`]
+    pub fn export(&self, writer: impl Write) -> Result<usize, Error> {
+        let mut builder = tar::Builder::new(writer);
+        let mut count = 0;
+
+        for (cid, path, _) in self.files()? {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(fs::metadata(&path)?.len());
+            header.set_mode(0o644);
+            builder.append_data(&mut header, &cid, File::open(&path)?)?;
+            count += 1;
+        }
+
+        builder.finish()?;
+
+        Ok(count)
+    }
+
+    /// Lists the cached objects with their path and metadata
+    fn files(&self) -> Result<Vec<(String, PathBuf, fs::Metadata)>, Error> {
+        fn visit(
+            dir: &Path,
+            depth: usize,
+            files: &mut Vec<(String, PathBuf, fs::Metadata)>,
+        ) -> io::Result<()> {
+            let entries = match fs::read_dir(dir) {
+                Ok(entries) => entries,
+                Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(()),
+                Err(error) => return Err(error),
+            };
+
+            for entry in entries {
+                let entry = entry?;
+                let name = entry.file_name().to_string_lossy().to_string();
+                let metadata = entry.metadata()?;
+
+                if metadata.is_dir() && depth == 0 {
+                    visit(&entry.path(), depth + 1, files)?;
+                } else if metadata.is_file() && is_valid_key(&name) {
+                    files.push((name, entry.path(), metadata));
+                }
+            }
+
+            Ok(())
+        }
+
+        let mut files = vec![];
+        visit(&self.root, 0, &mut files)?;
+
+        Ok(files)
+    }
+
+    fn with_index<T>(&self, f: impl FnOnce(&mut Lru) -> T) -> Result<Option<T>, Error> {
+        if self.max_size.is_none() {
+            return Ok(None);
+        }
+
+        let mut index = self.index.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let index = match &mut *index {
+            Some(index) => index,
+            None => {
+                // Existing files are considered in the order in which they were modified
+                let mut files = self.files()?;
+                files.sort_by_key(|(_, _, metadata)| {
+                    metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH)
+                });
+
+                let mut lru = Lru::default();
+                for (cid, _, metadata) in files {
+                    lru.insert(cid, metadata.len());
+                }
+
+                index.insert(lru)
+            }
+        };
+
+        Ok(Some(f(index)))
+    }
+}
+
+impl Cache for FsCache {
+    fn get(&self, cid: &str) -> Result<Option<Vec<u8>>, Error> {
+        check_key(cid)?;
+
+        let path = self.path(cid);
+
+        match fs::read(&path) {
+            Ok(bytes) => {
+                self.with_index(|index| index.touch(cid))?;
+                Ok(Some(bytes))
+            }
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                // Evicted by another process
+                self.with_index(|index| index.remove(cid))?;
+                Ok(None)
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn put(&self, cid: &str, bytes: &[u8]) -> Result<(), Error> {
+        check_key(cid)?;
+
+        let path = self.path(cid);
+        let dir = path.parent().unwrap_or(&self.root);
+        fs::create_dir_all(dir)?;
+
+        // Write to a temporary file first so that readers never see partial objects
+        let tmp_path = dir.join(format!(".{cid}.{}.tmp", std::process::id()));
+        fs::write(&tmp_path, bytes)?;
+        fs::rename(&tmp_path, &path)?;
+
+        let max_size = self.max_size.unwrap_or(u64::MAX);
+        let evicted = self
+            .with_index(|index| {
+                index.insert(cid.to_string(), bytes.len() as u64);
+                index.evict(max_size)
+            })?
+            .unwrap_or_default();
+
+        for cid in evicted {
+            tracing::debug!("Evicting {cid} from IPFS cache");
+            match fs::remove_file(self.path(&cid)) {
+                Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error.into()),
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// In-memory cache, mostly useful for tests and short-lived processes
+#[derive(Default)]
+pub struct MemoryCache {
+    max_size: Option<u64>,
+    state: Mutex<(HashMap<String, Vec<u8>>, Lru)>,
+}
+
+impl MemoryCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum total size of the cached objects in bytes (default: unbounded)
+    pub fn max_size(mut self, max_size: u64) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .0
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Cache for MemoryCache {
+    fn get(&self, cid: &str) -> Result<Option<Vec<u8>>, Error> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let (objects, lru) = &mut *state;
+
+        lru.touch(cid);
+        Ok(objects.get(cid).cloned())
+    }
+
+    fn put(&self, cid: &str, bytes: &[u8]) -> Result<(), Error> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let (objects, lru) = &mut *state;
+
+        objects.insert(cid.to_string(), bytes.to_vec());
+        lru.insert(cid.to_string(), bytes.len() as u64);
+
+        for cid in lru.evict(self.max_size.unwrap_or(u64::MAX)) {
+            objects.remove(&cid);
+        }
+
+        Ok(())
+    }
+}
+
+/// Cache backed by a single tar archive. New objects are appended to the archive,
+/// which stays a valid tar archive (e.g.: it can be copied and loaded with [`seed`]).
+pub struct PackCache {
+    state: Mutex<PackState>,
+}
+
+struct PackState {
+    file: File,
+    /// Offset and size of the data of each object
+    index: HashMap<String, (u64, u64)>,
+    /// Offset of the end of the last entry
+    end: u64,
+}
+
+impl PackCache {
+    /// Opens the archive at `path`, creating it if it does not exist
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut file = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        let mut index = HashMap::new();
+        let mut end = 0;
+
+        if file.metadata()?.len() > 0 {
+            let mut archive = tar::Archive::new(&mut file);
+
+            for entry in archive.entries()? {
+                let entry = entry?;
+                let offset = entry.raw_file_position();
+                let size = entry.size();
+
+                if let Some(cid) = entry_cid(&entry)? {
+                    index.insert(cid, (offset, size));
+                }
+                end = offset + padded(size);
+            }
+        }
+
+        Ok(Self {
+            state: Mutex::new(PackState { file, index, end }),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .index
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Cache for PackCache {
+    fn get(&self, cid: &str) -> Result<Option<Vec<u8>>, Error> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let Some(&(offset, size)) = state.index.get(cid) else {
+            return Ok(None);
+        };
+
+        let mut bytes = vec![0; size as usize];
+        state.file.seek(SeekFrom::Start(offset))?;
+        state.file.read_exact(&mut bytes)?;
+
+        Ok(Some(bytes))
+    }
+
+    fn put(&self, cid: &str, bytes: &[u8]) -> Result<(), Error> {
+        check_key(cid)?;
+
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if state.index.contains_key(cid) {
+            return Ok(());
+        }
+
+        // Overwrite the end-of-archive marker with the new entry
+        let end = state.end;
+        state.file.seek(SeekFrom::Start(end))?;
+
+        let mut builder = tar::Builder::new(&mut state.file);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(bytes.len() as u64);
+        header.set_mode(0o644);
+        builder.append_data(&mut header, cid, bytes)?;
+        builder.finish()?;
+        drop(builder);
+
+        // The end-of-archive marker is 2 blocks long
+        let new_end = state.file.stream_position()? - 1024;
+        let size = bytes.len() as u64;
+
+        state
+            .index
+            .insert(cid.to_string(), (new_end - padded(size), size));
+        state.end = new_end;
+
+        Ok(())
+    }
+}
+
+/// Loads the objects of a tar archive into `cache`. Entries are named after the CID
+/// of their content; content of `raw` CIDs which does not match its CID is rejected.
+/// Returns the number of loaded objects.
+pub fn seed(cache: &dyn Cache, archive: impl Read) -> Result<usize, Error> {
+    let mut archive = tar::Archive::new(archive);
+    let mut count = 0;
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+
+        let Some(cid) = entry_cid(&entry)? else {
+            continue;
+        };
+
+        let mut bytes = Vec::with_capacity(entry.size() as usize);
+        entry.read_to_end(&mut bytes)?;
+
+        let parsed = Cid::parse(&cid)?;
+        if parsed.is_raw() {
+            parsed.verify(&bytes)?;
+        }
+
+        cache.put(&cid, &bytes)?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// Name of a tar entry if it is a file named after a CID
+fn entry_cid<R: Read>(entry: &tar::Entry<R>) -> Result<Option<String>, Error> {
+    if !entry.header().entry_type().is_file() {
+        return Ok(None);
+    }
+
+    Ok(entry
+        .path()?
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .filter(|name| is_valid_key(name)))
+}
+
+/// Size of an entry data, padded to the tar block size
+fn padded(size: u64) -> u64 {
+    size.div_ceil(512) * 512
+}
+
+fn is_valid_key(cid: &str) -> bool {
+    !cid.is_empty() && cid.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// Rejects keys which are not valid file names (e.g.: when CIDs are not verified)
+fn check_key(cid: &str) -> Result<(), Error> {
+    if is_valid_key(cid) {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid cache key: {cid}"),
+        )
+        .into())
+    }
+}
+
+/// Least recently used tracker of the cached objects sizes
+#[derive(Default)]
+struct Lru {
+    entries: HashMap<String, (u64, u64)>,
+    order: BTreeMap<u64, String>,
+    size: u64,
+    tick: u64,
+}
+
+impl Lru {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn touch(&mut self, key: &str) {
+        let tick = self.next_tick();
+
+        if let Some((_, last_used)) = self.entries.get_mut(key) {
+            let key = self.order.remove(last_used).expect("LRU order out of sync");
+            *last_used = tick;
+            self.order.insert(tick, key);
+        }
+    }
+
+    fn insert(&mut self, key: String, size: u64) {
+        self.remove(&key);
+
+        let tick = self.next_tick();
+        self.entries.insert(key.clone(), (size, tick));
+        self.order.insert(tick, key);
+        self.size += size;
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some((size, last_used)) = self.entries.remove(key) {
+            self.order.remove(&last_used);
+            self.size -= size;
+        }
+    }
+
+    /// Removes the least recently used entries until the total size is at most `max_size`
+    fn evict(&mut self, max_size: u64) -> Vec<String> {
+        let mut evicted = vec![];
+
+        while self.size > max_size {
+            let Some((_, key)) = self.order.pop_first() else {
+                break;
+            };
+            if let Some((size, _)) = self.entries.remove(&key) {
+                self.size -= size;
+            }
+            evicted.push(key);
+        }
+
+        evicted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Raw CID of "hello world"
+    const CID: &str = "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e";
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ipfs-cache-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_fs_cache_sharding() {
+        let dir = temp_dir("sharding");
+        let cache = FsCache::new(&dir).shard_width(2);
+
+        cache.put(CID, b"hello world").unwrap();
+
+        assert_eq!(cache.path(CID), dir.join("n5").join(CID));
+        assert!(cache.path(CID).exists());
+        assert_eq!(cache.get(CID).unwrap().unwrap(), b"hello world");
+        assert_eq!(cache.get("bafkreimissing").unwrap(), None);
+        assert!(cache.put("../escape", b"").is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_fs_cache_flat_layout() {
+        let dir = temp_dir("flat");
+
+        // Entry written by a previous version of the cache
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(CID), b"hello world").unwrap();
+
+        let cache = FsCache::new(&dir);
+        assert_eq!(cache.path(CID), dir.join(CID));
+        assert_eq!(cache.get(CID).unwrap().unwrap(), b"hello world");
+
+        let mut archive = vec![];
+        assert_eq!(cache.export(&mut archive).unwrap(), 1);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_fs_cache_eviction() {
+        let dir = temp_dir("eviction");
+        let cache = FsCache::new(&dir).max_size(10);
+
+        cache.put("a1", b"0123").unwrap();
+        cache.put("b2", b"0123").unwrap();
+        cache.get("a1").unwrap();
+        cache.put("c3", b"0123").unwrap();
+
+        // b2 is the least recently used object
+        assert!(cache.get("a1").unwrap().is_some());
+        assert!(cache.get("b2").unwrap().is_none());
+        assert!(cache.get("c3").unwrap().is_some());
+
+        // The index is rebuilt from the files when the cache is reopened
+        let cache = FsCache::new(&dir).max_size(4);
+        cache.put("d4", b"0123").unwrap();
+        assert!(cache.get("d4").unwrap().is_some());
+        assert_eq!(cache.files().unwrap().len(), 1);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_memory_cache_eviction() {
+        let cache = MemoryCache::new().max_size(8);
+
+        cache.put("a", b"0123").unwrap();
+        cache.put("b", b"0123").unwrap();
+        cache.put("c", b"0123").unwrap();
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("a").unwrap(), None);
+        assert_eq!(cache.get("c").unwrap().unwrap(), b"0123");
+    }
+
+    #[test]
+    fn test_pack_cache() {
+        let dir = temp_dir("pack");
+        let path = dir.join("cache.tar");
+
+        let cache = PackCache::open(&path).unwrap();
+        cache.put(CID, b"hello world").unwrap();
+        cache.put("other", &[1; 600]).unwrap();
+        assert_eq!(cache.get(CID).unwrap().unwrap(), b"hello world");
+
+        // Appended objects are found when the archive is reopened
+        let cache = PackCache::open(&path).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(CID).unwrap().unwrap(), b"hello world");
+        assert_eq!(cache.get("other").unwrap().unwrap(), vec![1; 600]);
+        cache.put("third", b"3").unwrap();
+        assert_eq!(cache.get("third").unwrap().unwrap(), b"3");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_export_and_seed() {
+        let dir = temp_dir("seed");
+        let source = FsCache::new(dir.join("source"));
+        source.put(CID, b"hello world").unwrap();
+
+        let mut archive = vec![];
+        assert_eq!(source.export(&mut archive).unwrap(), 1);
+
+        let target = MemoryCache::new();
+        assert_eq!(seed(&target, archive.as_slice()).unwrap(), 1);
+        assert_eq!(target.get(CID).unwrap().unwrap(), b"hello world");
+
+        // Corrupted objects are rejected
+        let corrupted = FsCache::new(dir.join("corrupted"));
+        corrupted.put(CID, b"hello world!").unwrap();
+
+        let mut archive = vec![];
+        corrupted.export(&mut archive).unwrap();
+        assert!(matches!(
+            seed(&MemoryCache::new(), archive.as_slice()),
+            Err(Error::Cid(_))
+        ));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,315 @@
+//! Minimal CID parsing and verification. Supports CIDv0 (base58btc) and CIDv1
+//! (base32) with `sha2-256` and `identity` multihashes.
+//!
+//! NOTE: Only `raw` CIDs address the content returned by gateways. `dag-pb` CIDs (which
+//! include all CIDv0) address an encoded UnixFS block, which is verified before being
+//! decoded (see [`unixfs`](crate::unixfs)). Other codecs cannot be verified.
+use sha2::{Digest, Sha256};
+
+const BASE32_ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz234567";
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Codec of CIDs addressing raw bytes
+pub const RAW: u64 = 0x55;
+/// Codec of CIDv0 and UnixFS CIDv1
+pub const DAG_PB: u64 = 0x70;
+
+/// Multihash code of `identity`
+pub const IDENTITY: u64 = 0x00;
+/// Multihash code of `sha2-256`
+pub const SHA2_256: u64 = 0x12;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CidError {
+    #[error("invalid CID {0}: {1}")]
+    Invalid(String, &'static str),
+    #[error("unsupported multihash 0x{code:x} in CID {cid}")]
+    UnsupportedMultihash { cid: String, code: u64 },
+    #[error("content does not match CID {0}")]
+    Mismatch(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cid {
+    cid: String,
+    pub version: u64,
+    pub codec: u64,
+    pub hash_code: u64,
+    pub digest: Vec<u8>,
+}
+
+impl Cid {
+    pub fn parse(cid: &str) -> Result<Self, CidError> {
+        let invalid = |reason| CidError::Invalid(cid.to_string(), reason);
+
+        // CIDv0 are base58btc encoded sha2-256 multihashes
+        if cid.len() == 46 && cid.starts_with("Qm") {
+            let bytes = decode_base58(cid).ok_or_else(|| invalid("invalid base58 encoding"))?;
+            let (hash_code, digest) =
+                decode_multihash(&bytes).ok_or_else(|| invalid("invalid multihash"))?;
+
+            return Ok(Self {
+                cid: cid.to_string(),
+                version: 0,
+                codec: DAG_PB,
+                hash_code,
+                digest,
+            });
+        }
+
+        let bytes = match cid.split_at_checked(1) {
+            Some(("b", data)) => decode_base32(data),
+            Some(("B", data)) => decode_base32(&data.to_ascii_lowercase()),
+            _ => return Err(invalid("unsupported multibase")),
+        }
+        .ok_or_else(|| invalid("invalid base32 encoding"))?;
+
+        let (version, rest) = decode_varint(&bytes).ok_or_else(|| invalid("invalid version"))?;
+        if version != 1 {
+            return Err(invalid("unsupported version"));
+        }
+        let (codec, rest) = decode_varint(rest).ok_or_else(|| invalid("invalid codec"))?;
+        let (hash_code, digest) =
+            decode_multihash(rest).ok_or_else(|| invalid("invalid multihash"))?;
+
+        Ok(Self {
+            cid: cid.to_string(),
+            version,
+            codec,
+            hash_code,
+            digest,
+        })
+    }
+
+    /// Parses a binary CID, e.g.: the link of a `dag-pb` block
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CidError> {
+        // CIDv0 are bare sha2-256 multihashes
+        if bytes.len() == 34 && bytes[..2] == [SHA2_256 as u8, 32] {
+            return Self::parse(&encode_base58(bytes));
+        }
+
+        Self::parse(&format!("b{}", encode_base32(bytes)))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.cid
+    }
+
+    /// Returns true if the CID addresses raw bytes, i.e.: the content can be verified
+    /// as is
+    pub fn is_raw(&self) -> bool {
+        self.codec == RAW
+    }
+
+    /// Returns true if the CID addresses a `dag-pb` block
+    pub fn is_dag_pb(&self) -> bool {
+        self.codec == DAG_PB
+    }
+
+    /// Returns true if the content of the CID can be verified, either as is or by
+    /// fetching and decoding its blocks
+    pub fn is_verifiable(&self) -> bool {
+        self.is_raw() || self.is_dag_pb()
+    }
+
+    /// Checks that `bytes` hash to the digest of the CID
+    pub fn verify(&self, bytes: &[u8]) -> Result<(), CidError> {
+        let matches = match self.hash_code {
+            SHA2_256 => Sha256::digest(bytes).as_slice() == self.digest,
+            IDENTITY => bytes == self.digest,
+            code => {
+                return Err(CidError::UnsupportedMultihash {
+                    cid: self.cid.clone(),
+                    code,
+                })
+            }
+        };
+
+        if matches {
+            Ok(())
+        } else {
+            Err(CidError::Mismatch(self.cid.clone()))
+        }
+    }
+}
+
+/// Decodes a multihash, returning its code and digest
+fn decode_multihash(bytes: &[u8]) -> Option<(u64, Vec<u8>)> {
+    let (code, rest) = decode_varint(bytes)?;
+    let (len, digest) = decode_varint(rest)?;
+
+    (digest.len() as u64 == len).then(|| (code, digest.to_vec()))
+}
+
+/// Decodes an unsigned varint, returning the value and the remaining bytes
+fn decode_varint(bytes: &[u8]) -> Option<(u64, &[u8])> {
+    let mut value = 0u64;
+
+    for (i, byte) in bytes.iter().enumerate().take(9) {
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((value, &bytes[i + 1..]));
+        }
+    }
+
+    None
+}
+
+/// Decodes lowercase unpadded base32 (RFC 4648)
+fn decode_base32(data: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(data.len() * 5 / 8);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for c in data.bytes() {
+        let value = BASE32_ALPHABET.iter().position(|&a| a == c)? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    Some(bytes)
+}
+
+/// Encodes lowercase unpadded base32 (RFC 4648)
+fn encode_base32(bytes: &[u8]) -> String {
+    let mut data = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for &byte in bytes {
+        buffer = (buffer << 8) | u32::from(byte);
+        bits += 8;
+
+        while bits >= 5 {
+            bits -= 5;
+            data.push(BASE32_ALPHABET[(buffer >> bits) as usize & 0x1f] as char);
+        }
+        buffer &= (1 << bits) - 1;
+    }
+
+    if bits > 0 {
+        data.push(BASE32_ALPHABET[(buffer << (5 - bits)) as usize & 0x1f] as char);
+    }
+
+    data
+}
+
+fn encode_base58(bytes: &[u8]) -> String {
+    let mut digits: Vec<u8> = vec![];
+
+    for &byte in bytes {
+        let mut carry = u32::from(byte);
+
+        for digit in digits.iter_mut().rev() {
+            carry += u32::from(*digit) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+
+        while carry > 0 {
+            digits.insert(0, (carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    // Leading zero bytes are encoded as '1's
+    let zeros = bytes.iter().take_while(|&&byte| byte == 0).count();
+    std::iter::repeat_n('1', zeros)
+        .chain(
+            digits
+                .into_iter()
+                .map(|digit| BASE58_ALPHABET[digit as usize] as char),
+        )
+        .collect()
+}
+
+fn decode_base58(data: &str) -> Option<Vec<u8>> {
+    let mut bytes: Vec<u8> = vec![];
+
+    for c in data.bytes() {
+        let mut carry = BASE58_ALPHABET.iter().position(|&a| a == c)? as u32;
+
+        for byte in bytes.iter_mut().rev() {
+            carry += u32::from(*byte) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+
+        while carry > 0 {
+            bytes.insert(0, carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    // Leading '1's encode leading zero bytes
+    let zeros = data.bytes().take_while(|&c| c == b'1').count();
+    let mut result = vec![0; zeros];
+    result.extend(bytes);
+
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Raw CID of "hello world"
+    const CID_V1: &str = "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e";
+    const CID_V0: &str = "Qmf412jQZiuVUtdgnB36FXFX7xg5V6KEbSJ4dpQuhkLyfD";
+
+    #[test]
+    fn test_parse_cid_v1() {
+        let cid = Cid::parse(CID_V1).unwrap();
+
+        assert_eq!(cid.version, 1);
+        assert!(cid.is_raw());
+        assert_eq!(cid.hash_code, SHA2_256);
+        assert_eq!(cid.digest.len(), 32);
+    }
+
+    #[test]
+    fn test_parse_cid_v0() {
+        let cid = Cid::parse(CID_V0).unwrap();
+
+        assert_eq!(cid.version, 0);
+        assert_eq!(cid.codec, DAG_PB);
+        assert_eq!(cid.hash_code, SHA2_256);
+        assert_eq!(cid.digest.len(), 32);
+    }
+
+    #[test]
+    fn test_verify() {
+        let cid = Cid::parse(CID_V1).unwrap();
+
+        cid.verify(b"hello world").unwrap();
+        assert!(matches!(
+            cid.verify(b"hello world!"),
+            Err(CidError::Mismatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_cid_from_bytes() {
+        let v1 = Cid::parse(CID_V1).unwrap();
+        let bytes = [&[1, RAW as u8, SHA2_256 as u8, 32][..], &v1.digest].concat();
+        assert_eq!(Cid::from_bytes(&bytes).unwrap(), v1);
+
+        let v0 = Cid::parse(CID_V0).unwrap();
+        let bytes = [&[SHA2_256 as u8, 32][..], &v0.digest].concat();
+        assert_eq!(Cid::from_bytes(&bytes).unwrap(), v0);
+        assert_eq!(Cid::from_bytes(&bytes).unwrap().as_str(), CID_V0);
+    }
+
+    #[test]
+    fn test_invalid_cid() {
+        assert!(Cid::parse("zb2rhe5P4gXftAwvA4eXQ5HJwsER2owDyS9sKaQRRVQPn93bA").is_err());
+        assert!(Cid::parse("bafkrei!").is_err());
+        assert!(Cid::parse("").is_err());
+    }
+}
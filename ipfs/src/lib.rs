@@ -1,8 +1,39 @@
-use std::{fs, path::Path};
+//! IPFS client fetching content from an optional local IPFS HTTP API and a list of
+//! gateways, with failover between them.
+//!
+//! ```rust,ignore
+//! let ipfs = IpfsClient::builder()
+//!     .local_api("http://localhost:5001")
+//!     .gateway("https://gateway.lighthouse.storage/ipfs/")
+//!     .gateway("https://ipfs.io/ipfs/")
+//!     .timeout(Duration::from_secs(10))
+//!     .build()?;
+//! ```
+//!
+//! Requests can be limited globally and per host with
+//! [`IpfsClientBuilder::max_concurrent_requests`] and
+//! [`IpfsClientBuilder::max_requests_per_host`].
+//!
+//! Content fetched for `raw` CIDs is verified against the CID (see [`cid`]) before
+//! being cached or decoded, so a misbehaving gateway cannot serve arbitrary data. For
+//! `dag-pb` CIDs (UnixFS files), the blocks of the file are fetched and verified one by
+//! one (see [`unixfs`]). Content of other codecs cannot be verified and is not cached.
+//! Cache hits are not verified again: caches are trusted, and seeded caches are verified
+//! when loaded (see [`cache::seed`]).
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use prost::Message;
+use tokio::sync::Semaphore;
 
-const IPFS_CACHE_DIR: &str = "ipfs-cache";
+pub mod cache;
+pub mod cid;
+pub mod unixfs;
+
+pub use cache::{Cache, FsCache, MemoryCache, PackCache};
+pub use cid::{Cid, CidError};
+
+/// Directory of the default [`FsCache`]
+pub const IPFS_CACHE_DIR: &str = "ipfs-cache";
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -12,6 +43,23 @@ pub enum Error {
     Io(#[from] std::io::Error),
     #[error("prost error: {0}")]
     Prost(#[from] prost::DecodeError),
+    #[error("CID error: {0}")]
+    Cid(#[from] CidError),
+    #[error("invalid UnixFS block {0}: {1}")]
+    UnixFs(String, &'static str),
+    #[error("{url} returned status {status}")]
+    Status {
+        url: String,
+        status: reqwest::StatusCode,
+    },
+    #[error("no IPFS source configured")]
+    NoSource,
+    #[error("failed to fetch {cid} from all sources: {source}")]
+    Unavailable {
+        cid: String,
+        #[source]
+        source: Box<Error>,
+    },
 }
 
 type Result<T> = std::result::Result<T, Error>;
@@ -20,17 +68,229 @@ pub fn deserialize<T: Message + Default>(buf: &[u8]) -> std::result::Result<T, p
     T::decode(buf)
 }
 
+/// Location from which content can be fetched
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Source {
+    /// Kubo-compatible IPFS HTTP API (e.g.: `http://localhost:5001`)
+    Api(String),
+    /// HTTP gateway, the CID is appended to the URL (e.g.: `https://ipfs.io/ipfs/`)
+    Gateway(String),
+}
+
+impl Source {
+    pub fn url(&self) -> &str {
+        match self {
+            Source::Api(url) | Source::Gateway(url) => url,
+        }
+    }
+
+    /// Host (and port) of the source, used to limit the number of requests per host
+    fn host(&self) -> String {
+        match reqwest::Url::parse(self.url()) {
+            Ok(url) => match (url.host_str(), url.port()) {
+                (Some(host), Some(port)) => format!("{host}:{port}"),
+                (Some(host), None) => host.to_string(),
+                _ => self.url().to_string(),
+            },
+            Err(_) => self.url().to_string(),
+        }
+    }
+
+    /// Request of the content of `cid`, or of its block if `block` is set
+    fn request(
+        &self,
+        client: &reqwest::Client,
+        cid: &str,
+        block: bool,
+    ) -> (String, reqwest::RequestBuilder) {
+        match (self, block) {
+            (Source::Api(url), false) => {
+                let url = format!("{}/api/v0/cat?arg={}", url.trim_end_matches('/'), cid);
+                let request = client.post(&url);
+                (url, request)
+            }
+            (Source::Api(url), true) => {
+                let url = format!("{}/api/v0/block/get?arg={}", url.trim_end_matches('/'), cid);
+                let request = client.post(&url);
+                (url, request)
+            }
+            (Source::Gateway(url), false) => {
+                let url = format!("{}{}", url, cid);
+                let request = client.get(&url);
+                (url, request)
+            }
+            // Trustless gateway request
+            (Source::Gateway(url), true) => {
+                let url = format!("{}{}?format=raw", url, cid);
+                let request = client
+                    .get(&url)
+                    .header(reqwest::header::ACCEPT, "application/vnd.ipld.raw");
+                (url, request)
+            }
+        }
+    }
+}
+
+pub struct IpfsClientBuilder {
+    api: Option<String>,
+    gateways: Vec<String>,
+    timeout: Duration,
+    max_retries: u32,
+    retry_backoff: Duration,
+    verify_cids: bool,
+    cache: Option<Arc<dyn Cache>>,
+    max_concurrent_requests: Option<usize>,
+    max_requests_per_host: Option<usize>,
+}
+
+impl Default for IpfsClientBuilder {
+    fn default() -> Self {
+        Self {
+            api: None,
+            gateways: vec![],
+            timeout: Duration::from_secs(30),
+            max_retries: 2,
+            retry_backoff: Duration::from_millis(500),
+            verify_cids: true,
+            cache: Some(Arc::new(FsCache::new(IPFS_CACHE_DIR))),
+            max_concurrent_requests: None,
+            max_requests_per_host: None,
+        }
+    }
+}
+
+impl IpfsClientBuilder {
+    /// Adds a gateway. Gateways are tried in the order in which they are added.
+    pub fn gateway(mut self, url: impl Into<String>) -> Self {
+        self.gateways.push(url.into());
+        self
+    }
+
+    pub fn gateways(mut self, urls: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.gateways.extend(urls.into_iter().map(Into::into));
+        self
+    }
+
+    /// Local IPFS HTTP API, tried before the gateways
+    pub fn local_api(mut self, url: impl Into<String>) -> Self {
+        self.api = Some(url.into());
+        self
+    }
+
+    /// Maximum duration of a request, including reading the body (default: 30s)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Number of times all the sources are retried after failing (default: 2)
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Delay before the first retry, doubled after each retry (default: 500ms)
+    pub fn retry_backoff(mut self, retry_backoff: Duration) -> Self {
+        self.retry_backoff = retry_backoff;
+        self
+    }
+
+    /// Whether to verify that fetched content matches its CID (default: true)
+    pub fn verify_cids(mut self, verify_cids: bool) -> Self {
+        self.verify_cids = verify_cids;
+        self
+    }
+
+    /// Cache used when fetching content with `cache` set (default: [`FsCache`] in
+    /// the [`IPFS_CACHE_DIR`] directory)
+    pub fn cache(mut self, cache: impl Cache + 'static) -> Self {
+        self.cache = Some(Arc::new(cache));
+        self
+    }
+
+    pub fn cache_opt(mut self, cache: Option<Arc<dyn Cache>>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Maximum number of requests in flight across all sources (default: unlimited).
+    /// Requests over the limit wait for a slot.
+    pub fn max_concurrent_requests(mut self, max_concurrent_requests: usize) -> Self {
+        self.max_concurrent_requests = Some(max_concurrent_requests);
+        self
+    }
+
+    /// Maximum number of requests in flight to the same host (default: unlimited)
+    pub fn max_requests_per_host(mut self, max_requests_per_host: usize) -> Self {
+        self.max_requests_per_host = Some(max_requests_per_host);
+        self
+    }
+
+    pub fn build(self) -> Result<IpfsClient> {
+        let sources = self
+            .api
+            .into_iter()
+            .map(Source::Api)
+            .chain(self.gateways.into_iter().map(Source::Gateway))
+            .collect::<Vec<_>>();
+
+        if sources.is_empty() {
+            return Err(Error::NoSource);
+        }
+
+        // Sources on the same host share their limit
+        let mut host_limits = HashMap::new();
+        let source_limits = sources
+            .iter()
+            .map(|source| {
+                self.max_requests_per_host.map(|max| {
+                    host_limits
+                        .entry(source.host())
+                        .or_insert_with(|| Arc::new(Semaphore::new(max)))
+                        .clone()
+                })
+            })
+            .collect();
+
+        Ok(IpfsClient {
+            sources,
+            source_limits,
+            limit: self.max_concurrent_requests.map(Semaphore::new),
+            client: reqwest::Client::builder().timeout(self.timeout).build()?,
+            max_retries: self.max_retries,
+            retry_backoff: self.retry_backoff,
+            verify_cids: self.verify_cids,
+            cache: self.cache,
+        })
+    }
+}
+
 pub struct IpfsClient {
-    url: String,
+    sources: Vec<Source>,
+    source_limits: Vec<Option<Arc<Semaphore>>>,
+    limit: Option<Semaphore>,
     client: reqwest::Client,
+    max_retries: u32,
+    retry_backoff: Duration,
+    verify_cids: bool,
+    cache: Option<Arc<dyn Cache>>,
 }
 
 impl IpfsClient {
+    pub fn builder() -> IpfsClientBuilder {
+        IpfsClientBuilder::default()
+    }
+
+    /// Client fetching content from a single gateway with the default settings
     pub fn from_url(url: &str) -> Self {
-        Self {
-            url: url.to_string(),
-            client: reqwest::Client::new(),
-        }
+        Self::builder()
+            .gateway(url)
+            .build()
+            .expect("Failed to build IPFS client")
+    }
+
+    pub fn sources(&self) -> &[Source] {
+        &self.sources
     }
 
     pub async fn get<T: prost::Message + Default>(&self, hash: &str, cache: bool) -> Result<T> {
@@ -39,25 +299,139 @@ impl IpfsClient {
         Ok(data)
     }
 
+    /// Fetches the content of `hash`, verified against its CID unless verification is
+    /// disabled. With `cache` set, the content is read from and written to the cache.
+    /// Cache hits are not verified again, and content which cannot be verified (i.e.:
+    /// neither `raw` nor `dag-pb`) is not cached.
     pub async fn get_bytes(&self, hash: &str, cache: bool) -> Result<Vec<u8>> {
-        let cache_path = Path::new(IPFS_CACHE_DIR).join(hash);
-
-        if cache && cache_path.exists() {
-            tracing::info!("Cache hit for {}", hash);
-            let cached_data = fs::read(&cache_path)?;
-            return Ok(cached_data);
+        // Invalid CIDs are rejected before hitting the network
+        let cid = if self.verify_cids {
+            Some(Cid::parse(hash)?)
         } else {
-            tracing::info!("Cache miss for {}", hash);
+            None
+        };
+
+        let cache = self
+            .cache
+            .as_deref()
+            .filter(|_| cache && cid.as_ref().is_none_or(Cid::is_verifiable));
+
+        if let Some(cache) = cache {
+            if let Some(cached_data) = cache.get(hash)? {
+                tracing::info!("Cache hit for {}", hash);
+                return Ok(cached_data);
+            } else {
+                tracing::info!("Cache miss for {}", hash);
+            }
         }
 
-        let url = format!("{}{}", self.url, hash);
-        let res = self.client.get(&url).send().await?;
-        let bytes = res.bytes().await?;
+        let bytes = match &cid {
+            Some(cid) if cid.is_dag_pb() => self.fetch_file(cid).await?,
+            _ => self.fetch(hash, cid.as_ref()).await?,
+        };
 
         // Cache the result
-        if cache {
-            fs::create_dir_all(IPFS_CACHE_DIR)?;
-            fs::write(&cache_path, &bytes)?;
+        if let Some(cache) = cache {
+            cache.put(hash, &bytes)?;
+        }
+
+        Ok(bytes)
+    }
+
+    /// Fetches the blocks of the UnixFS file `cid` and returns its content
+    async fn fetch_file(&self, cid: &Cid) -> Result<Vec<u8>> {
+        let block = self.fetch(cid.as_str(), Some(cid)).await?;
+        let file = unixfs::decode(cid, &block)?;
+
+        let mut content = file.data;
+        for link in file.links {
+            if link.is_dag_pb() {
+                content.extend(Box::pin(self.fetch_file(&link)).await?);
+            } else {
+                content.extend(self.fetch(link.as_str(), Some(&link)).await?);
+            }
+        }
+
+        Ok(content)
+    }
+
+    /// Tries each source in order until one returns valid content. All sources are
+    /// retried up to `max_retries` times with an exponential backoff.
+    async fn fetch(&self, hash: &str, cid: Option<&Cid>) -> Result<Vec<u8>> {
+        let mut retry = 0;
+
+        loop {
+            let mut last_error = None;
+
+            for (source, source_limit) in self.sources.iter().zip(&self.source_limits) {
+                match self
+                    .fetch_from(source, source_limit.as_deref(), hash, cid)
+                    .await
+                {
+                    Ok(bytes) => return Ok(bytes),
+                    Err(error) => {
+                        tracing::warn!("Failed to fetch {hash} from {source:?}: {error}");
+                        last_error = Some(error);
+                    }
+                }
+            }
+
+            let error = last_error.unwrap_or(Error::NoSource);
+
+            if retry >= self.max_retries {
+                return Err(Error::Unavailable {
+                    cid: hash.to_string(),
+                    source: Box::new(error),
+                });
+            }
+
+            let backoff = self
+                .retry_backoff
+                .saturating_mul(2u32.saturating_pow(retry));
+            tracing::warn!("Retrying {hash} in {backoff:?}");
+            tokio::time::sleep(backoff).await;
+            retry += 1;
+        }
+    }
+
+    async fn fetch_from(
+        &self,
+        source: &Source,
+        source_limit: Option<&Semaphore>,
+        hash: &str,
+        cid: Option<&Cid>,
+    ) -> Result<Vec<u8>> {
+        // Semaphores are never closed
+        let _source_permit = match source_limit {
+            Some(limit) => Some(limit.acquire().await.expect("semaphore closed")),
+            None => None,
+        };
+        let _permit = match &self.limit {
+            Some(limit) => Some(limit.acquire().await.expect("semaphore closed")),
+            None => None,
+        };
+
+        // The blocks of `dag-pb` CIDs are fetched to be verified
+        let block = cid.is_some_and(Cid::is_dag_pb);
+        let (url, request) = source.request(&self.client, hash, block);
+
+        let res = request.send().await?;
+        if !res.status().is_success() {
+            return Err(Error::Status {
+                url,
+                status: res.status(),
+            });
+        }
+
+        let bytes = res.bytes().await?;
+
+        match cid {
+            Some(cid) if cid.is_verifiable() => cid.verify(&bytes)?,
+            Some(cid) => tracing::warn!(
+                "Cannot verify the content of {hash} (codec 0x{:x}), it is not cached",
+                cid.codec
+            ),
+            None => (),
         }
 
         Ok(bytes.to_vec())
@@ -66,7 +440,211 @@ impl IpfsClient {
 
 #[cfg(test)]
 mod tests {
+    use std::{
+        sync::Arc,
+        time::{Duration, Instant},
+    };
+
+    use httpmock::prelude::*;
+
+    use super::{
+        cid::{DAG_PB, RAW},
+        unixfs::tests::{block_cid, file_block},
+        Error, IpfsClient, MemoryCache,
+    };
+
+    // CID of "hello world"
+    const CID: &str = "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e";
+
+    fn client(servers: &[&MockServer]) -> IpfsClient {
+        IpfsClient::builder()
+            .gateways(servers.iter().map(|server| server.url("/ipfs/")))
+            .max_retries(1)
+            .retry_backoff(Duration::from_millis(1))
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_failover() {
+        let failing = MockServer::start();
+        let failing_mock = failing.mock(|when, then| {
+            when.method(GET).path(format!("/ipfs/{CID}"));
+            then.status(500);
+        });
+
+        let corrupted = MockServer::start();
+        let corrupted_mock = corrupted.mock(|when, then| {
+            when.method(GET).path(format!("/ipfs/{CID}"));
+            then.status(200).body("hello world!");
+        });
+
+        let healthy = MockServer::start();
+        let healthy_mock = healthy.mock(|when, then| {
+            when.method(GET).path(format!("/ipfs/{CID}"));
+            then.status(200).body("hello world");
+        });
+
+        let client = client(&[&failing, &corrupted, &healthy]);
+
+        assert_eq!(client.get_bytes(CID, false).await.unwrap(), b"hello world");
+        failing_mock.assert();
+        corrupted_mock.assert();
+        healthy_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_local_api_first() {
+        let api = MockServer::start();
+        let api_mock = api.mock(|when, then| {
+            when.method(POST)
+                .path("/api/v0/cat")
+                .query_param("arg", CID);
+            then.status(200).body("hello world");
+        });
+
+        let gateway = MockServer::start();
+        let gateway_mock = gateway.mock(|when, then| {
+            when.method(GET).path(format!("/ipfs/{CID}"));
+            then.status(200).body("hello world");
+        });
+
+        let client = IpfsClient::builder()
+            .local_api(api.base_url())
+            .gateway(gateway.url("/ipfs/"))
+            .build()
+            .unwrap();
+
+        assert_eq!(client.get_bytes(CID, false).await.unwrap(), b"hello world");
+        api_mock.assert();
+        gateway_mock.assert_hits(0);
+    }
+
+    #[tokio::test]
+    async fn test_retry_exhausted() {
+        let corrupted = MockServer::start();
+        let corrupted_mock = corrupted.mock(|when, then| {
+            when.method(GET).path(format!("/ipfs/{CID}"));
+            then.status(200).body("not hello world");
+        });
+
+        let client = client(&[&corrupted]);
+
+        assert!(matches!(
+            client.get_bytes(CID, false).await,
+            Err(Error::Unavailable { .. })
+        ));
+        corrupted_mock.assert_hits(2);
+    }
+
+    #[tokio::test]
+    async fn test_cache() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path(format!("/ipfs/{CID}"));
+            then.status(200).body("hello world");
+        });
+
+        let cache = Arc::new(MemoryCache::new());
+        let client = IpfsClient::builder()
+            .gateway(server.url("/ipfs/"))
+            .cache(cache.clone())
+            .build()
+            .unwrap();
+
+        assert_eq!(client.get_bytes(CID, true).await.unwrap(), b"hello world");
+        assert_eq!(client.get_bytes(CID, true).await.unwrap(), b"hello world");
+        assert_eq!(cache.len(), 1);
+        mock.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn test_dag_pb_file() {
+        let leaf = block_cid(RAW, b" world");
+        let root_block = file_block(b"hello", &[&leaf]);
+        let root = block_cid(DAG_PB, &root_block);
+
+        let server = MockServer::start();
+        let root_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("/ipfs/{}", root.as_str()))
+                .query_param("format", "raw");
+            then.status(200).body(&root_block);
+        });
+        let leaf_mock = server.mock(|when, then| {
+            when.method(GET).path(format!("/ipfs/{}", leaf.as_str()));
+            then.status(200).body(" world");
+        });
+
+        let cache = Arc::new(MemoryCache::new());
+        let cached_client = IpfsClient::builder()
+            .gateway(server.url("/ipfs/"))
+            .cache(cache.clone())
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            cached_client.get_bytes(root.as_str(), true).await.unwrap(),
+            b"hello world"
+        );
+        assert_eq!(cache.len(), 1);
+        root_mock.assert();
+        leaf_mock.assert();
+
+        // Blocks which do not match their CID are rejected
+        let corrupted = MockServer::start();
+        corrupted.mock(|when, then| {
+            when.method(GET).path(format!("/ipfs/{}", root.as_str()));
+            then.status(200).body(file_block(b"hello!", &[&leaf]));
+        });
+
+        assert!(matches!(
+            client(&[&corrupted]).get_bytes(root.as_str(), false).await,
+            Err(Error::Unavailable { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_max_requests_per_host() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path(format!("/ipfs/{CID}"));
+            then.status(200)
+                .body("hello world")
+                .delay(Duration::from_millis(200));
+        });
+
+        // Both gateways are on the same host
+        let client = IpfsClient::builder()
+            .gateway(server.url("/ipfs/"))
+            .gateway(server.url("/ipfs/"))
+            .max_requests_per_host(1)
+            .build()
+            .unwrap();
+
+        let start = Instant::now();
+        let (a, b) = tokio::join!(client.get_bytes(CID, false), client.get_bytes(CID, false));
+
+        assert_eq!(a.unwrap(), b"hello world");
+        assert_eq!(b.unwrap(), b"hello world");
+        assert!(start.elapsed() >= Duration::from_millis(400));
+        mock.assert_hits(2);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_cid() {
+        let server = MockServer::start();
+        let client = client(&[&server]);
+
+        assert!(matches!(
+            client.get_bytes("not-a-cid", false).await,
+            Err(Error::Cid(_))
+        ));
+    }
+
     #[tokio::test]
+    // The enums mirror the generated protobuf types
+    #[allow(clippy::enum_variant_names)]
     async fn test_import() {
         #[derive(
             Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration,
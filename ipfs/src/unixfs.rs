@@ -0,0 +1,143 @@
+//! Minimal decoding of UnixFS files encoded as `dag-pb` blocks. The content of a `dag-pb`
+//! CID cannot be verified as returned by gateways, so the [`IpfsClient`](crate::IpfsClient)
+//! fetches the blocks of the file, verifies each of them against its CID and rebuilds
+//! the content from the decoded blocks.
+use prost::Message;
+
+use crate::{cid::Cid, Error};
+
+/// `dag-pb` node
+#[derive(Clone, PartialEq, Message)]
+struct PbNode {
+    #[prost(bytes = "vec", optional, tag = "1")]
+    data: Option<Vec<u8>>,
+    #[prost(message, repeated, tag = "2")]
+    links: Vec<PbLink>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct PbLink {
+    /// Binary CID of the linked block
+    #[prost(bytes = "vec", optional, tag = "1")]
+    hash: Option<Vec<u8>>,
+}
+
+/// UnixFS data of a `dag-pb` node
+#[derive(Clone, PartialEq, Message)]
+struct UnixFsData {
+    #[prost(int32, optional, tag = "1")]
+    r#type: Option<i32>,
+    #[prost(bytes = "vec", optional, tag = "2")]
+    data: Option<Vec<u8>>,
+}
+
+const RAW_TYPE: i32 = 0;
+const FILE_TYPE: i32 = 2;
+
+/// Block of a UnixFS file. The content of the file is the data of the block followed by
+/// the content of its links, in order.
+#[derive(Debug, Default, PartialEq)]
+pub struct FileBlock {
+    pub data: Vec<u8>,
+    pub links: Vec<Cid>,
+}
+
+/// Decodes the `dag-pb` block of a UnixFS file, the block should have been verified
+/// against its CID
+pub fn decode(cid: &Cid, block: &[u8]) -> Result<FileBlock, Error> {
+    let node = PbNode::decode(block)?;
+    let data = UnixFsData::decode(node.data.as_deref().unwrap_or_default())?;
+
+    if !matches!(data.r#type, Some(RAW_TYPE | FILE_TYPE)) {
+        return Err(Error::UnixFs(cid.as_str().to_string(), "not a file"));
+    }
+
+    let links = node
+        .links
+        .into_iter()
+        .map(|link| Cid::from_bytes(link.hash.as_deref().unwrap_or_default()))
+        .collect::<Result<_, _>>()?;
+
+    Ok(FileBlock {
+        data: data.data.unwrap_or_default(),
+        links,
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::cid::{DAG_PB, RAW, SHA2_256};
+    use sha2::{Digest, Sha256};
+
+    /// CID of a block of the given codec
+    pub(crate) fn block_cid(codec: u64, block: &[u8]) -> Cid {
+        let bytes = [
+            &[1, codec as u8, SHA2_256 as u8, 32][..],
+            &Sha256::digest(block),
+        ]
+        .concat();
+        Cid::from_bytes(&bytes).unwrap()
+    }
+
+    /// `dag-pb` block of a UnixFS file
+    pub(crate) fn file_block(data: &[u8], links: &[&Cid]) -> Vec<u8> {
+        let links = links
+            .iter()
+            .map(|cid| PbLink {
+                hash: Some(
+                    [
+                        &[1, cid.codec as u8, cid.hash_code as u8, 32][..],
+                        &cid.digest,
+                    ]
+                    .concat(),
+                ),
+            })
+            .collect();
+
+        PbNode {
+            data: Some(
+                UnixFsData {
+                    r#type: Some(FILE_TYPE),
+                    data: Some(data.to_vec()),
+                }
+                .encode_to_vec(),
+            ),
+            links,
+        }
+        .encode_to_vec()
+    }
+
+    #[test]
+    fn test_decode_file() {
+        let leaf = block_cid(RAW, b" world");
+        let block = file_block(b"hello", &[&leaf]);
+        let cid = block_cid(DAG_PB, &block);
+
+        assert_eq!(
+            decode(&cid, &block).unwrap(),
+            FileBlock {
+                data: b"hello".to_vec(),
+                links: vec![leaf],
+            }
+        );
+    }
+
+    #[test]
+    fn test_decode_directory() {
+        let block = PbNode {
+            data: Some(
+                UnixFsData {
+                    r#type: Some(1),
+                    data: None,
+                }
+                .encode_to_vec(),
+            ),
+            links: vec![],
+        }
+        .encode_to_vec();
+        let cid = block_cid(DAG_PB, &block);
+
+        assert!(matches!(decode(&cid, &block), Err(Error::UnixFs(..))));
+    }
+}
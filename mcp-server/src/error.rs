@@ -0,0 +1,177 @@
+//! Errors returned by the MCP tools. Every error is sent to the client as an MCP error
+//! whose `data.code` is one of the stable codes of [`ToolError::code`].
+use std::time::Duration;
+
+use grc20_core::error::DatabaseError;
+use rmcp::{Error as McpError, model::ErrorCode};
+use serde_json::json;
+
+/// Maximum length of an id, ids are base58 encoded UUIDs (22 characters) but some
+/// indexer ids are longer
+const MAX_ID_LENGTH: usize = 64;
+
+#[derive(Debug)]
+pub enum ToolError {
+    InvalidId(String),
+    InvalidArgument(String),
+    NotFound { kind: &'static str, id: String },
+    Embedding(String),
+    DatabaseTimeout(Duration),
+    Database(DatabaseError),
+    Serialization(String),
+}
+
+impl ToolError {
+    pub fn not_found(kind: &'static str, id: impl Into<String>) -> Self {
+        Self::NotFound {
+            kind,
+            id: id.into(),
+        }
+    }
+
+    /// Stable code of the error, exposed to the clients and used as metrics label
+    pub fn code(&self) -> &'static str {
+        match self {
+            ToolError::InvalidId(_) => "invalid_id",
+            ToolError::InvalidArgument(_) => "invalid_argument",
+            ToolError::NotFound { .. } => "not_found",
+            ToolError::Embedding(_) => "embedding_failed",
+            ToolError::DatabaseTimeout(_) => "database_timeout",
+            ToolError::Database(_) => "database_error",
+            ToolError::Serialization(_) => "serialization_failed",
+        }
+    }
+}
+
+impl std::fmt::Display for ToolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ToolError::InvalidId(id) => write!(f, "invalid id {id:?}"),
+            ToolError::InvalidArgument(message) => write!(f, "invalid argument: {message}"),
+            ToolError::NotFound { kind, id } => write!(f, "{kind} {id} not found"),
+            ToolError::Embedding(message) => write!(f, "failed to embed the query: {message}"),
+            ToolError::DatabaseTimeout(timeout) => {
+                write!(f, "database query timed out after {timeout:?}")
+            }
+            ToolError::Database(error) => write!(f, "database error: {error}"),
+            ToolError::Serialization(message) => {
+                write!(f, "failed to serialize the result: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ToolError {}
+
+impl From<DatabaseError> for ToolError {
+    fn from(error: DatabaseError) -> Self {
+        match error {
+            DatabaseError::Timeout(timeout) => ToolError::DatabaseTimeout(timeout),
+            error => ToolError::Database(error),
+        }
+    }
+}
+
+impl From<ToolError> for McpError {
+    fn from(error: ToolError) -> Self {
+        let code = match error {
+            ToolError::InvalidId(_) | ToolError::InvalidArgument(_) => ErrorCode::INVALID_PARAMS,
+            ToolError::NotFound { .. } => ErrorCode::RESOURCE_NOT_FOUND,
+            _ => ErrorCode::INTERNAL_ERROR,
+        };
+        let mut data = json!({ "code": error.code() });
+        match &error {
+            ToolError::InvalidId(id) => data["id"] = json!(id),
+            ToolError::NotFound { kind, id } => {
+                data["kind"] = json!(kind);
+                data["id"] = json!(id);
+            }
+            ToolError::DatabaseTimeout(timeout) => {
+                data["timeout_ms"] = json!(timeout.as_millis() as u64)
+            }
+            _ => (),
+        }
+
+        McpError::new(code, error.to_string(), Some(data))
+    }
+}
+
+/// Checks that the id can be a GRC-20 id (alphanumeric, `-` and `_` characters)
+pub fn validate_id(id: &str) -> Result<&str, ToolError> {
+    let valid = !id.is_empty()
+        && id.len() <= MAX_ID_LENGTH
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    if valid {
+        Ok(id)
+    } else {
+        Err(ToolError::InvalidId(id.to_string()))
+    }
+}
+
+/// Stable code of an MCP error, see [`ToolError::code`]. Errors not created from a
+/// [`ToolError`] (e.g.: arguments that cannot be deserialized) are labelled by their
+/// JSON-RPC code.
+pub fn error_code(error: &McpError) -> &'static str {
+    let code = error
+        .data
+        .as_ref()
+        .and_then(|data| data.get("code"))
+        .and_then(|code| code.as_str());
+
+    match code {
+        Some("invalid_id") => "invalid_id",
+        Some("invalid_argument") => "invalid_argument",
+        Some("not_found") => "not_found",
+        Some("embedding_failed") => "embedding_failed",
+        Some("database_timeout") => "database_timeout",
+        Some("database_error") => "database_error",
+        Some("serialization_failed") => "serialization_failed",
+        _ if error.code == ErrorCode::INVALID_PARAMS => "invalid_params",
+        _ if error.code == ErrorCode::METHOD_NOT_FOUND => "method_not_found",
+        _ => "internal_error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_id() {
+        assert_eq!(
+            validate_id("LuBWqZAu6pz54eiJS5mLv8").unwrap(),
+            "LuBWqZAu6pz54eiJS5mLv8"
+        );
+        assert!(validate_id("0x1234-abcd_5678").is_ok());
+        assert!(validate_id(&"a".repeat(MAX_ID_LENGTH)).is_ok());
+
+        assert!(validate_id("").is_err());
+        assert!(validate_id(&"a".repeat(MAX_ID_LENGTH + 1)).is_err());
+        assert!(validate_id("abc def").is_err());
+        assert!(validate_id("abc\"}) DETACH DELETE n").is_err());
+        assert!(validate_id("graph://entity/abc").is_err());
+    }
+
+    #[test]
+    fn test_invalid_id_error_code() {
+        let error = McpError::from(validate_id("abc def").unwrap_err());
+
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+        assert_eq!(error_code(&error), "invalid_id");
+        assert_eq!(error.data.unwrap()["id"], "abc def");
+    }
+
+    #[test]
+    fn test_database_timeout_error_code() {
+        let error = McpError::from(ToolError::from(DatabaseError::Timeout(
+            Duration::from_millis(1500),
+        )));
+
+        assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+        assert_eq!(error_code(&error), "database_timeout");
+        assert_eq!(error.data.unwrap()["timeout_ms"], 1500);
+    }
+}
@@ -0,0 +1,730 @@
+//! Queries backing the space and governance tools of the MCP server. Spaces, accounts,
+//! proposals and votes are indexed in the indexer space.
+//!
+//! The queries are read at the version of the scope. The spaces of the scope restrict the
+//! listed spaces and the activity of accounts, the queries of a given space ignore them.
+use std::collections::{HashMap, HashSet};
+
+use futures::{StreamExt, TryStreamExt, stream};
+use grc20_core::{
+    connection::Graph,
+    entity::{self, Entity, EntityFilter, EntityNodeRef, TypesFilter},
+    error::DatabaseError,
+    indexer_ids,
+    mapping::{
+        AttributeFilter, PropFilter, Query, QueryStream, RelationEdge, aggregation::SpaceRanking,
+        prop_filter, triple,
+    },
+    relation, system_ids,
+};
+use grc20_sdk::models::{
+    Account, BaseEntity, Proposal, Space, SpaceGovernanceType, account, proposal::ProposalStatus,
+    space,
+};
+use serde::Serialize;
+
+use crate::{resources, scope::Scope};
+
+/// Number of spaces whose names are resolved concurrently
+const CONCURRENCY: usize = 8;
+
+/// Maximum number of votes counted for a proposal
+const MAX_VOTES: usize = 10_000;
+
+#[derive(Debug, Serialize)]
+pub struct SpaceSummary {
+    pub id: String,
+    /// Name of the space's root entity
+    pub name: Option<String>,
+    pub network: String,
+    pub governance_type: SpaceGovernanceType,
+    pub dao_contract_address: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RankedSpace {
+    /// Distance to the space in the hierarchy
+    pub depth: usize,
+    #[serde(flatten)]
+    pub space: SpaceSummary,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SpaceHierarchy {
+    #[serde(flatten)]
+    pub space: SpaceSummary,
+    pub parent_spaces: Vec<RankedSpace>,
+    pub subspaces: Vec<RankedSpace>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountSummary {
+    pub id: String,
+    pub address: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct VoteTally {
+    pub accept: usize,
+    pub reject: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProposalSummary {
+    pub id: String,
+    pub onchain_proposal_id: String,
+    /// Kind of proposal (e.g.: `EDIT`, `ADD_MEMBER`), derived from its types
+    pub kind: Option<&'static str>,
+    pub status: String,
+    pub start_time: String,
+    pub end_time: String,
+    /// Account that created the proposal
+    pub creator: Option<String>,
+    pub votes: VoteTally,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Vote {
+    pub proposal_id: String,
+    pub vote: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountActivity {
+    #[serde(flatten)]
+    pub account: AccountSummary,
+    pub member_of: Vec<String>,
+    pub editor_of: Vec<String>,
+    pub proposals_created: Vec<ProposalSummary>,
+    pub votes: Vec<Vote>,
+}
+
+/// Lists the spaces, optionally filtered by network and governance type
+pub async fn list_spaces(
+    neo4j: &Graph,
+    network: Option<String>,
+    governance_type: Option<SpaceGovernanceType>,
+    skip: usize,
+    limit: usize,
+    scope: &Scope,
+) -> Result<Vec<SpaceSummary>, DatabaseError> {
+    let mut query =
+        space::find_many(neo4j, indexer_ids::INDEXER_SPACE_ID).version_opt(scope.version());
+
+    if let Some(space_filter) = scope.space_filter() {
+        query = query.id(space_filter);
+    }
+
+    if let Some(network) = network {
+        query = query.network(prop_filter::value(network));
+    }
+    if let Some(governance_type) = governance_type {
+        query = query.governance_type(prop_filter::value(governance_type));
+    }
+
+    let spaces = query
+        .skip(skip)
+        .limit(limit)
+        .send()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    summarize_spaces(neo4j, spaces, scope).await
+}
+
+/// Finds the spaces whose root entity is semantically close to the query embedding
+pub async fn search_spaces(
+    neo4j: &Graph,
+    embedding: Vec<f64>,
+    skip: usize,
+    limit: usize,
+    scope: &Scope,
+) -> Result<Vec<SpaceSummary>, DatabaseError> {
+    let mut search = entity::prefiltered_search::<Entity<BaseEntity>>(neo4j, embedding)
+        .filter(
+            EntityFilter::default()
+                .relations(TypesFilter::default().r#type(system_ids::SPACE_TYPE)),
+        )
+        .skip(skip)
+        .limit(limit);
+    if let Some(space_filter) = scope.space_filter() {
+        search = search.space_id(space_filter);
+    }
+    if let Some(version) = scope.version() {
+        search = search.version(version);
+    }
+
+    let root_entities = search
+        .send()
+        .await?
+        .map_ok(|result| result.entity.id().to_string())
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    let mut entity_spaces = space::find_entity_spaces(neo4j, root_entities.clone()).await?;
+
+    // Keep the ranking of the search results
+    let mut space_ids = Vec::new();
+    for id in root_entities {
+        for space_id in entity_spaces.remove(&id).unwrap_or_default() {
+            if space_id != indexer_ids::INDEXER_SPACE_ID
+                && scope.contains_space(&space_id)
+                && !space_ids.contains(&space_id)
+            {
+                space_ids.push(space_id);
+            }
+        }
+    }
+    space_ids.truncate(limit);
+
+    let spaces = find_spaces(neo4j, space_ids, scope).await?;
+    summarize_spaces(neo4j, spaces, scope).await
+}
+
+/// Returns the space, or `None` if it does not exist
+pub async fn find_space(
+    neo4j: &Graph,
+    space_id: &str,
+    scope: &Scope,
+) -> Result<Option<SpaceSummary>, DatabaseError> {
+    match space::find_one(neo4j, space_id, indexer_ids::INDEXER_SPACE_ID)
+        .version_opt(scope.version())
+        .send()
+        .await?
+    {
+        Some(space) => Ok(Some(summarize_space(neo4j, space, scope).await?)),
+        None => Ok(None),
+    }
+}
+
+/// Returns the space with its parent spaces and subspaces, or `None` if the space does
+/// not exist
+pub async fn space_hierarchy(
+    neo4j: &Graph,
+    space_id: &str,
+    max_depth: Option<usize>,
+    scope: &Scope,
+) -> Result<Option<SpaceHierarchy>, DatabaseError> {
+    let Some(entity) = space::find_one(neo4j, space_id, indexer_ids::INDEXER_SPACE_ID)
+        .version_opt(scope.version())
+        .send()
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let parent_spaces = space::parent_spaces::<SpaceRanking>(neo4j, space_id)
+        .max_depth(max_depth)
+        .version_opt(scope.version())
+        .send()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    let subspaces = space::subspaces::<SpaceRanking>(neo4j, space_id)
+        .max_depth(max_depth)
+        .version_opt(scope.version())
+        .send()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    Ok(Some(SpaceHierarchy {
+        space: summarize_space(neo4j, entity, scope).await?,
+        parent_spaces: rank_spaces(neo4j, parent_spaces, scope).await?,
+        subspaces: rank_spaces(neo4j, subspaces, scope).await?,
+    }))
+}
+
+pub async fn members(
+    neo4j: &Graph,
+    space_id: &str,
+    skip: usize,
+    limit: usize,
+    scope: &Scope,
+) -> Result<Vec<AccountSummary>, DatabaseError> {
+    space::members(neo4j, space_id)
+        .version_opt(scope.version())
+        .skip(skip)
+        .limit(limit)
+        .send()
+        .await?
+        .map_ok(AccountSummary::from)
+        .try_collect()
+        .await
+}
+
+pub async fn editors(
+    neo4j: &Graph,
+    space_id: &str,
+    skip: usize,
+    limit: usize,
+    scope: &Scope,
+) -> Result<Vec<AccountSummary>, DatabaseError> {
+    space::editors(neo4j, space_id)
+        .version_opt(scope.version())
+        .skip(skip)
+        .limit(limit)
+        .send()
+        .await?
+        .map_ok(AccountSummary::from)
+        .try_collect()
+        .await
+}
+
+/// Lists the proposals of a space with their vote tallies
+pub async fn proposals(
+    neo4j: &Graph,
+    space_id: &str,
+    status: Option<ProposalStatus>,
+    skip: usize,
+    limit: usize,
+    scope: &Scope,
+) -> Result<Vec<ProposalSummary>, DatabaseError> {
+    let mut proposal_filter = EntityFilter::default();
+    if let Some(status) = status {
+        proposal_filter = proposal_filter.attribute(
+            AttributeFilter::new(indexer_ids::PROPOSAL_STATUS_ATTRIBUTE)
+                .value(prop_filter::value(status.to_string()))
+                .version_opt(scope.version()),
+        );
+    }
+
+    let proposals = relation::find_many::<RelationEdge<EntityNodeRef>>(neo4j)
+        .filter(
+            relation::RelationFilter::default()
+                .from_(EntityFilter::default().id(prop_filter::value(space_id)))
+                .relation_type(
+                    EntityFilter::default().id(prop_filter::value(indexer_ids::PROPOSALS)),
+                )
+                .to_(proposal_filter),
+        )
+        .space_id(prop_filter::value(indexer_ids::INDEXER_SPACE_ID))
+        .version(scope.version())
+        .skip(skip)
+        .limit(limit)
+        .select_to::<Entity<Proposal>>()
+        .send()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    summarize_proposals(neo4j, proposals, scope).await
+}
+
+/// Returns the memberships, editorships, proposals and votes of an account, or `None` if
+/// the account does not exist. The account can be given by ID or address. With a space
+/// scope, only the activity in the spaces of the scope is returned.
+pub async fn account_activity(
+    neo4j: &Graph,
+    account: &str,
+    limit: usize,
+    scope: &Scope,
+) -> Result<Option<AccountActivity>, DatabaseError> {
+    let account_id = if account.starts_with("0x") {
+        account::new_id(account)
+    } else {
+        account.to_string()
+    };
+
+    let Some(entity) = account::find_one(neo4j, &account_id, indexer_ids::INDEXER_SPACE_ID)
+        .version_opt(scope.version())
+        .send()
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let member_of = outbound_relations(
+        neo4j,
+        &account_id,
+        indexer_ids::MEMBER_RELATION,
+        scope.space_filter(),
+        limit,
+        scope,
+    )
+    .await?
+    .into_iter()
+    .map(|relation| relation.to.0)
+    .collect();
+
+    let editor_of = outbound_relations(
+        neo4j,
+        &account_id,
+        indexer_ids::EDITOR_RELATION,
+        scope.space_filter(),
+        limit,
+        scope,
+    )
+    .await?
+    .into_iter()
+    .map(|relation| relation.to.0)
+    .collect();
+
+    let proposals_created = relation::find_many::<RelationEdge<EntityNodeRef>>(neo4j)
+        .filter(
+            relation::RelationFilter::default()
+                .to_(EntityFilter::default().id(prop_filter::value(&account_id)))
+                .relation_type(
+                    EntityFilter::default().id(prop_filter::value(indexer_ids::PROPOSAL_CREATOR)),
+                ),
+        )
+        .space_id(prop_filter::value(indexer_ids::INDEXER_SPACE_ID))
+        .version(scope.version())
+        .limit(limit)
+        .send()
+        .await?
+        .map_ok(|relation| relation.from.0)
+        .try_collect::<Vec<_>>()
+        .await?;
+    let proposals_created = scoped_proposals(neo4j, proposals_created, scope).await?;
+
+    let proposals_created = if proposals_created.is_empty() {
+        vec![]
+    } else {
+        let proposals = entity::find_many::<Entity<Proposal>>(neo4j)
+            .with_filter(EntityFilter::default().id(prop_filter::value_in(proposals_created)))
+            .space_id(indexer_ids::INDEXER_SPACE_ID)
+            .version_opt(scope.version())
+            .limit(limit)
+            .send()
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        summarize_proposals(neo4j, proposals, scope).await?
+    };
+
+    let vote_relations = outbound_relations(
+        neo4j,
+        &account_id,
+        indexer_ids::VOTE_CAST_TYPE,
+        None,
+        limit,
+        scope,
+    )
+    .await?;
+    let voted_proposals = scoped_proposals(
+        neo4j,
+        vote_relations
+            .iter()
+            .map(|relation| relation.to.0.clone())
+            .collect(),
+        scope,
+    )
+    .await?;
+    let vote_relations = vote_relations
+        .into_iter()
+        .filter(|relation| voted_proposals.contains(&relation.to.0))
+        .collect::<Vec<_>>();
+
+    let mut vote_types = vote_types(
+        neo4j,
+        vote_relations
+            .iter()
+            .map(|relation| relation.id.clone())
+            .collect(),
+        scope,
+    )
+    .await?;
+    let votes = vote_relations
+        .into_iter()
+        .filter_map(|relation| {
+            vote_types.remove(&relation.id).map(|vote| Vote {
+                proposal_id: relation.to.0,
+                vote,
+            })
+        })
+        .collect();
+
+    Ok(Some(AccountActivity {
+        account: entity.into(),
+        member_of,
+        editor_of,
+        proposals_created,
+        votes,
+    }))
+}
+
+impl From<Entity<Account>> for AccountSummary {
+    fn from(entity: Entity<Account>) -> Self {
+        Self {
+            id: entity.id().to_string(),
+            address: entity.attributes.address,
+        }
+    }
+}
+
+async fn find_spaces(
+    neo4j: &Graph,
+    space_ids: Vec<String>,
+    scope: &Scope,
+) -> Result<Vec<Entity<Space>>, DatabaseError> {
+    if space_ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let limit = space_ids.len();
+    let mut spaces = space::find_many(neo4j, indexer_ids::INDEXER_SPACE_ID)
+        .id(prop_filter::value_in(space_ids.clone()))
+        .version_opt(scope.version())
+        .limit(limit)
+        .send()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    // Restore the order of the given IDs
+    spaces.sort_by_key(|space| space_ids.iter().position(|id| id == space.id()));
+    Ok(spaces)
+}
+
+async fn summarize_space(
+    neo4j: &Graph,
+    space: Entity<Space>,
+    scope: &Scope,
+) -> Result<SpaceSummary, DatabaseError> {
+    let name = resources::find_root_entities(neo4j, space.id(), scope)
+        .await?
+        .into_iter()
+        .find_map(|entity| entity.attributes.name);
+
+    Ok(SpaceSummary {
+        id: space.id().to_string(),
+        name,
+        network: space.attributes.network,
+        governance_type: space.attributes.governance_type,
+        dao_contract_address: space.attributes.dao_contract_address,
+    })
+}
+
+async fn summarize_spaces(
+    neo4j: &Graph,
+    spaces: Vec<Entity<Space>>,
+    scope: &Scope,
+) -> Result<Vec<SpaceSummary>, DatabaseError> {
+    stream::iter(spaces)
+        .map(|space| summarize_space(neo4j, space, scope))
+        .buffered(CONCURRENCY)
+        .try_collect()
+        .await
+}
+
+/// Loads the ranked spaces, excluding the space from which the hierarchy is computed
+async fn rank_spaces(
+    neo4j: &Graph,
+    rankings: Vec<SpaceRanking>,
+    scope: &Scope,
+) -> Result<Vec<RankedSpace>, DatabaseError> {
+    let depths = rankings
+        .into_iter()
+        .filter(|ranking| ranking.depth > 0)
+        .map(|ranking| (ranking.space_id, ranking.depth))
+        .collect::<HashMap<_, _>>();
+
+    let spaces = find_spaces(neo4j, depths.keys().cloned().collect(), scope).await?;
+
+    let mut ranked = summarize_spaces(neo4j, spaces, scope)
+        .await?
+        .into_iter()
+        .map(|space| RankedSpace {
+            depth: depths[&space.id],
+            space,
+        })
+        .collect::<Vec<_>>();
+    ranked.sort_by_key(|ranked| ranked.depth);
+
+    Ok(ranked)
+}
+
+async fn summarize_proposals(
+    neo4j: &Graph,
+    proposals: Vec<Entity<Proposal>>,
+    scope: &Scope,
+) -> Result<Vec<ProposalSummary>, DatabaseError> {
+    let proposal_ids = proposals
+        .iter()
+        .map(|proposal| proposal.id().to_string())
+        .collect::<Vec<_>>();
+
+    let mut creators = if proposal_ids.is_empty() {
+        HashMap::new()
+    } else {
+        relation::find_many::<RelationEdge<EntityNodeRef>>(neo4j)
+            .filter(
+                relation::RelationFilter::default()
+                    .from_(EntityFilter::default().id(prop_filter::value_in(proposal_ids)))
+                    .relation_type(
+                        EntityFilter::default()
+                            .id(prop_filter::value(indexer_ids::PROPOSAL_CREATOR)),
+                    ),
+            )
+            .space_id(prop_filter::value(indexer_ids::INDEXER_SPACE_ID))
+            .version(scope.version())
+            .limit(proposals.len())
+            .send()
+            .await?
+            .map_ok(|relation| (relation.from.0, relation.to.0))
+            .try_collect::<HashMap<_, _>>()
+            .await?
+    };
+
+    let mut summaries = Vec::with_capacity(proposals.len());
+    for proposal in proposals {
+        let id = proposal.id().to_string();
+
+        summaries.push(ProposalSummary {
+            votes: vote_tally(neo4j, &id, scope).await?,
+            creator: creators.remove(&id),
+            kind: proposal.types.iter().find_map(|id| proposal_kind(id)),
+            onchain_proposal_id: proposal.attributes.onchain_proposal_id,
+            status: proposal.attributes.status.to_string(),
+            start_time: proposal.attributes.start_time,
+            end_time: proposal.attributes.end_time,
+            id,
+        });
+    }
+
+    Ok(summaries)
+}
+
+async fn vote_tally(
+    neo4j: &Graph,
+    proposal_id: &str,
+    scope: &Scope,
+) -> Result<VoteTally, DatabaseError> {
+    let vote_ids = relation::find_many::<RelationEdge<EntityNodeRef>>(neo4j)
+        .filter(
+            relation::RelationFilter::default()
+                .to_(EntityFilter::default().id(prop_filter::value(proposal_id)))
+                .relation_type(
+                    EntityFilter::default().id(prop_filter::value(indexer_ids::VOTE_CAST_TYPE)),
+                ),
+        )
+        .space_id(prop_filter::value(indexer_ids::INDEXER_SPACE_ID))
+        .version(scope.version())
+        .limit(MAX_VOTES)
+        .send()
+        .await?
+        .map_ok(|relation| relation.id)
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    Ok(vote_types(neo4j, vote_ids, scope)
+        .await?
+        .into_values()
+        .fold(VoteTally::default(), |mut tally, vote| {
+            match vote.as_str() {
+                "ACCEPT" => tally.accept += 1,
+                "REJECT" => tally.reject += 1,
+                _ => (),
+            }
+            tally
+        }))
+}
+
+/// Returns the vote type (`ACCEPT` or `REJECT`) of the given vote relations
+async fn vote_types(
+    neo4j: &Graph,
+    vote_ids: Vec<String>,
+    scope: &Scope,
+) -> Result<HashMap<String, String>, DatabaseError> {
+    if vote_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let mut query = triple::find_many(neo4j)
+        .entity_id(prop_filter::value_in(vote_ids))
+        .attribute_id(prop_filter::value(indexer_ids::VOTE_TYPE_ATTRIBUTE))
+        .space_id(prop_filter::value(indexer_ids::INDEXER_SPACE_ID));
+    if let Some(version) = scope.version() {
+        query = query.space_version(version);
+    }
+
+    query
+        .send()
+        .await?
+        .map_ok(|triple| (triple.entity, triple.value.value))
+        .try_collect()
+        .await
+}
+
+/// Relations of the given type from the entity, optionally restricted to some targets
+async fn outbound_relations(
+    neo4j: &Graph,
+    entity_id: &str,
+    relation_type: &str,
+    to: Option<PropFilter<String>>,
+    limit: usize,
+    scope: &Scope,
+) -> Result<Vec<RelationEdge<EntityNodeRef>>, DatabaseError> {
+    let mut filter = relation::RelationFilter::default()
+        .from_(EntityFilter::default().id(prop_filter::value(entity_id)))
+        .relation_type(EntityFilter::default().id(prop_filter::value(relation_type)));
+    if let Some(to) = to {
+        filter = filter.to_(EntityFilter::default().id(to));
+    }
+
+    relation::find_many::<RelationEdge<EntityNodeRef>>(neo4j)
+        .filter(filter)
+        .space_id(prop_filter::value(indexer_ids::INDEXER_SPACE_ID))
+        .version(scope.version())
+        .limit(limit)
+        .send()
+        .await?
+        .try_collect()
+        .await
+}
+
+/// Keeps the proposals made in the spaces of the scope (all the proposals without space
+/// scope), in the given order
+async fn scoped_proposals(
+    neo4j: &Graph,
+    proposal_ids: Vec<String>,
+    scope: &Scope,
+) -> Result<Vec<String>, DatabaseError> {
+    let Some(space_filter) = scope.space_filter() else {
+        return Ok(proposal_ids);
+    };
+    if proposal_ids.is_empty() {
+        return Ok(proposal_ids);
+    }
+
+    let limit = proposal_ids.len();
+    let scoped = relation::find_many::<RelationEdge<EntityNodeRef>>(neo4j)
+        .filter(
+            relation::RelationFilter::default()
+                .from_(EntityFilter::default().id(space_filter))
+                .relation_type(
+                    EntityFilter::default().id(prop_filter::value(indexer_ids::PROPOSALS)),
+                )
+                .to_(EntityFilter::default().id(prop_filter::value_in(proposal_ids.clone()))),
+        )
+        .space_id(prop_filter::value(indexer_ids::INDEXER_SPACE_ID))
+        .version(scope.version())
+        .limit(limit)
+        .send()
+        .await?
+        .map_ok(|relation| relation.to.0)
+        .try_collect::<HashSet<_>>()
+        .await?;
+
+    Ok(proposal_ids
+        .into_iter()
+        .filter(|id| scoped.contains(id))
+        .collect())
+}
+
+fn proposal_kind(type_id: &str) -> Option<&'static str> {
+    match type_id {
+        indexer_ids::EDIT_PROPOSAL => Some("EDIT"),
+        indexer_ids::ADD_MEMBER_PROPOSAL => Some("ADD_MEMBER"),
+        indexer_ids::REMOVE_MEMBER_PROPOSAL => Some("REMOVE_MEMBER"),
+        indexer_ids::ADD_EDITOR_PROPOSAL => Some("ADD_EDITOR"),
+        indexer_ids::REMOVE_EDITOR_PROPOSAL => Some("REMOVE_EDITOR"),
+        indexer_ids::ADD_SUBSPACE_PROPOSAL => Some("ADD_SUBSPACE"),
+        indexer_ids::REMOVE_SUBSPACE_PROPOSAL => Some("REMOVE_SUBSPACE"),
+        _ => None,
+    }
+}
@@ -1,3 +1,6 @@
+use grc20_core::mapping::ValueType;
+use grc20_sdk::models::{Change, SpaceGovernanceType, proposal};
+
 #[derive(Debug, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
 pub struct SearchTraversalInputFilter {
     pub query: String,
@@ -48,3 +51,172 @@ impl Iterator for IntoIter {
         })
     }
 }
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, schemars::JsonSchema, Clone, Copy)]
+pub enum GovernanceType {
+    Public,
+    Personal,
+}
+
+impl From<GovernanceType> for SpaceGovernanceType {
+    fn from(governance_type: GovernanceType) -> Self {
+        match governance_type {
+            GovernanceType::Public => SpaceGovernanceType::Public,
+            GovernanceType::Personal => SpaceGovernanceType::Personal,
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, schemars::JsonSchema, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ProposalStatus {
+    Proposed,
+    Accepted,
+    Rejected,
+    Canceled,
+    Executed,
+}
+
+impl From<ProposalStatus> for proposal::ProposalStatus {
+    fn from(status: ProposalStatus) -> Self {
+        match status {
+            ProposalStatus::Proposed => proposal::ProposalStatus::Proposed,
+            ProposalStatus::Accepted => proposal::ProposalStatus::Accepted,
+            ProposalStatus::Rejected => proposal::ProposalStatus::Rejected,
+            ProposalStatus::Canceled => proposal::ProposalStatus::Canceled,
+            ProposalStatus::Executed => proposal::ProposalStatus::Executed,
+        }
+    }
+}
+
+/// Amount of information returned for each result
+#[derive(
+    Debug,
+    Default,
+    serde::Deserialize,
+    serde::Serialize,
+    schemars::JsonSchema,
+    Clone,
+    Copy,
+    PartialEq,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Detail {
+    /// Only the ids
+    Ids,
+    /// Ids, names and descriptions
+    #[default]
+    Summary,
+    /// Everything known about the results (e.g.: the types and attributes of entities)
+    Full,
+}
+
+/// Change to the knowledge graph drafted with `draft_edit`
+#[derive(Debug, serde::Deserialize, serde::Serialize, schemars::JsonSchema, Clone)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum ChangeInput {
+    /// Set the value of an attribute of an entity
+    SetAttribute {
+        entity_id: String,
+        attribute_id: String,
+        value: String,
+        /// Type of the value (default: the value type of the attribute)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        value_type: Option<ValueTypeInput>,
+    },
+    /// Add a relation from an entity to another
+    AddRelation {
+        from_id: String,
+        relation_type_id: String,
+        to_id: String,
+    },
+    /// Create an entity with types
+    CreateEntity {
+        name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+        /// Ids of the types of the entity
+        #[serde(default)]
+        types: Vec<String>,
+    },
+}
+
+impl ChangeInput {
+    /// Ids of the entities, attributes and types referred to by the change
+    pub fn ids(&self) -> Vec<&str> {
+        match self {
+            ChangeInput::SetAttribute {
+                entity_id,
+                attribute_id,
+                ..
+            } => vec![entity_id, attribute_id],
+            ChangeInput::AddRelation {
+                from_id,
+                relation_type_id,
+                to_id,
+            } => vec![from_id, relation_type_id, to_id],
+            ChangeInput::CreateEntity { types, .. } => types.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+impl From<ChangeInput> for Change {
+    fn from(change: ChangeInput) -> Self {
+        match change {
+            ChangeInput::SetAttribute {
+                entity_id,
+                attribute_id,
+                value,
+                value_type,
+            } => Change::SetAttribute {
+                entity_id,
+                attribute_id,
+                value,
+                value_type: value_type.map(ValueType::from),
+            },
+            ChangeInput::AddRelation {
+                from_id,
+                relation_type_id,
+                to_id,
+            } => Change::AddRelation {
+                from_id,
+                relation_type_id,
+                to_id,
+            },
+            ChangeInput::CreateEntity {
+                name,
+                description,
+                types,
+            } => Change::CreateEntity {
+                id: None,
+                name,
+                description,
+                types,
+            },
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, schemars::JsonSchema, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ValueTypeInput {
+    Text,
+    Number,
+    Checkbox,
+    Url,
+    Time,
+    Point,
+}
+
+impl From<ValueTypeInput> for ValueType {
+    fn from(value_type: ValueTypeInput) -> Self {
+        match value_type {
+            ValueTypeInput::Text => ValueType::Text,
+            ValueTypeInput::Number => ValueType::Number,
+            ValueTypeInput::Checkbox => ValueType::Checkbox,
+            ValueTypeInput::Url => ValueType::Url,
+            ValueTypeInput::Time => ValueType::Time,
+            ValueTypeInput::Point => ValueType::Point,
+        }
+    }
+}
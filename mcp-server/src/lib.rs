@@ -1 +1,8 @@
+pub mod error;
+pub mod governance;
 pub mod input_types;
+pub mod metrics;
+pub mod pagination;
+pub mod prompts;
+pub mod resources;
+pub mod scope;
@@ -1,121 +1,237 @@
-use clap::{Args, Parser};
+use base64::{Engine, prelude::BASE64_STANDARD};
+use clap::{Parser, ValueEnum};
 use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
 use futures::{TryStreamExt, future::join_all};
 use grc20_core::{
+    connection::{Graph, Neo4jArgs},
     entity::{
-        self, Entity, EntityFilter, EntityNode, EntityRelationFilter, utils::TraverseRelation,
+        self, Entity, EntityFilter, EntityNode, EntityRelationFilter,
+        search_with_traversals::SearchWithTraversalsResult, utils::TraverseRelation,
     },
     mapping::{
-        Query, QueryStream, RelationEdge, prop_filter, query_utils::RelationDirection, triple,
+        Query, QueryStream, RelationEdge, pluralism::Pluralism, prop_filter,
+        query_utils::RelationDirection, triple,
     },
-    neo4rs, relation, system_ids,
+    relation, system_ids,
+};
+use grc20_sdk::models::{BaseEntity, PropertySchema, base_entity, draft, space, type_schema};
+use mcp_server::{
+    error::{ToolError, error_code, validate_id},
+    governance,
+    input_types::{
+        self, ChangeInput, Detail, GovernanceType, ProposalStatus, SearchTraversalInputFilter,
+    },
+    metrics,
+    pagination::{self, Page},
+    prompts::{self, ArgumentKind, PromptError, ResolvedArgument},
+    resources::{self, ResourceUri, ScopeParams},
+    scope::Scope,
 };
-use grc20_sdk::models::BaseEntity;
-use mcp_server::input_types::{self, SearchTraversalInputFilter};
 use rmcp::{
-    Error as McpError, RoleServer, ServerHandler,
+    Error as McpError, RoleServer, ServerHandler, ServiceExt,
+    handler::server::tool::ToolCallContext,
     model::*,
     service::RequestContext,
     tool,
-    transport::sse_server::{SseServer, SseServerConfig},
+    transport::{
+        sse_server::{SseServer, SseServerConfig},
+        stdio,
+        streamable_http_server::axum::StreamableHttpServer,
+    },
 };
+use serde::Serialize;
 use serde_json::{Value, json};
-use std::{collections::HashSet, sync::Arc, time::Instant, vec};
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+    sync::Arc,
+    time::Instant,
+    vec,
+};
 use tracing_subscriber::{
     layer::SubscriberExt,
     util::SubscriberInitExt,
     {self},
 };
 
-const BIND_ADDRESS: &str = "0.0.0.0:8080";
-
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    // Logs are written to stderr since stdout is used by the stdio transport
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| "debug".to_string().into()),
         )
-        .with(tracing_subscriber::fmt::layer())
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
         .init();
 
     let args = AppArgs::parse();
 
-    let neo4j = neo4rs::Graph::new(
-        &args.neo4j_args.neo4j_uri,
-        &args.neo4j_args.neo4j_user,
-        &args.neo4j_args.neo4j_pass,
-    )
-    .await?;
-
-    let config = SseServerConfig {
-        bind: BIND_ADDRESS.parse()?,
-        sse_path: "/sse".to_string(),
-        post_path: "/message".to_string(),
-        ct: tokio_util::sync::CancellationToken::new(),
-        sse_keep_alive: None,
+    let neo4j = args.neo4j_args.connect().await?;
+
+    let config = ServerConfig {
+        default_limit: args.default_limit,
+        max_limit: args.max_limit,
+        max_output_size: args.max_output_size,
     };
 
-    let (sse_server, router) = SseServer::new(config);
+    if let Some(metrics_bind) = args.metrics_bind {
+        let listener = tokio::net::TcpListener::bind(metrics_bind).await?;
+        let app =
+            axum::Router::new().route("/metrics", axum::routing::get(metrics::metrics_handler));
+        tracing::info!("Metrics available on {metrics_bind}/metrics");
+
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                tracing::error!(error = %e, "metrics server shutdown with error");
+            }
+        });
+    }
 
-    // Do something with the router, e.g., add routes or middleware
+    // Loaded once and shared by all the sessions
+    let state = Arc::new(ServerState::new(neo4j, config)?);
+
+    match args.transport {
+        Transport::Stdio => {
+            tracing::info!("Serving MCP over stdio");
+            KnowledgeGraph::new(state)
+                .serve(stdio())
+                .await?
+                .waiting()
+                .await?;
+        }
+        Transport::Sse => {
+            tracing::info!("Serving MCP over SSE on {}", args.bind);
+            let config = SseServerConfig {
+                bind: args.bind,
+                sse_path: "/sse".to_string(),
+                post_path: "/message".to_string(),
+                ct: tokio_util::sync::CancellationToken::new(),
+                sse_keep_alive: None,
+            };
 
-    let listener = tokio::net::TcpListener::bind(sse_server.config.bind).await?;
+            let (sse_server, router) = SseServer::new(config);
 
-    let ct = sse_server.config.ct.child_token();
+            let listener = tokio::net::TcpListener::bind(sse_server.config.bind).await?;
 
-    let server = axum::serve(listener, router).with_graceful_shutdown(async move {
-        ct.cancelled().await;
-        tracing::info!("sse server cancelled");
-    });
+            let ct = sse_server.config.ct.child_token();
 
-    tokio::spawn(async move {
-        if let Err(e) = server.await {
-            tracing::error!(error = %e, "sse server shutdown with error");
-        }
-    });
+            let server = axum::serve(listener, router).with_graceful_shutdown(async move {
+                ct.cancelled().await;
+                tracing::info!("sse server cancelled");
+            });
 
-    let ct = sse_server.with_service(move || KnowledgeGraph::new(neo4j.clone()));
+            tokio::spawn(async move {
+                if let Err(e) = server.await {
+                    tracing::error!(error = %e, "sse server shutdown with error");
+                }
+            });
+
+            let ct = sse_server.with_service(move || KnowledgeGraph::new(state.clone()));
+
+            tokio::signal::ctrl_c().await?;
+            ct.cancel();
+        }
+        Transport::StreamableHttp => {
+            tracing::info!("Serving MCP over streamable HTTP on {}/mcp", args.bind);
+            let ct = StreamableHttpServer::serve(args.bind)
+                .await?
+                .with_service(move || KnowledgeGraph::new(state.clone()));
+
+            tokio::signal::ctrl_c().await?;
+            ct.cancel();
+        }
+    }
 
-    tokio::signal::ctrl_c().await?;
-    ct.cancel();
     Ok(())
 }
 
 const EMBEDDING_MODEL: EmbeddingModel = EmbeddingModel::AllMiniLML6V2;
 
+/// Number of example entities returned by `describe_type` when none is given
+const DEFAULT_EXAMPLES: usize = 3;
+
+pub struct ServerConfig {
+    /// Number of results returned by the listing tools when no limit is given
+    pub default_limit: usize,
+    /// Maximum number of results returned by the listing tools
+    pub max_limit: usize,
+    /// Maximum size in bytes of a tool result, results not fitting are left for the next page
+    pub max_output_size: usize,
+}
+
+/// State shared by all the MCP sessions
+pub struct ServerState {
+    pub neo4j: Graph,
+    pub embedding_model: TextEmbedding,
+    pub config: ServerConfig,
+}
+
+impl ServerState {
+    pub fn new(neo4j: Graph, config: ServerConfig) -> anyhow::Result<Self> {
+        Ok(Self {
+            neo4j,
+            embedding_model: TextEmbedding::try_new(
+                InitOptions::new(EMBEDDING_MODEL).with_show_download_progress(true),
+            )?,
+            config,
+        })
+    }
+}
+
+/// MCP service, one instance is created per session
 #[derive(Clone)]
 pub struct KnowledgeGraph {
-    neo4j: neo4rs::Graph,
-    pub embedding_model: Arc<TextEmbedding>,
+    state: Arc<ServerState>,
 }
 
 #[tool(tool_box)]
 impl KnowledgeGraph {
-    #[allow(dead_code)]
-    pub fn new(neo4j: neo4rs::Graph) -> Self {
-        Self {
-            neo4j,
-            embedding_model: Arc::new(
-                TextEmbedding::try_new(
-                    InitOptions::new(EMBEDDING_MODEL).with_show_download_progress(true),
-                )
-                .expect("Failed to initialize embedding model"),
-            ),
-        }
-    }
-
-    fn _create_resource_text(&self, uri: &str, name: &str) -> Resource {
-        RawResource::new(uri, name.to_string()).no_annotation()
+    pub fn new(state: Arc<ServerState>) -> Self {
+        Self { state }
     }
 
+    #[allow(clippy::too_many_arguments)]
     #[tool(description = include_str!("../resources/search_type_description.md"))]
     async fn search_types(
         &self,
         #[tool(param)]
         #[schemars(description = "The query string to search for types")]
         query: String,
+        #[tool(param)]
+        #[schemars(description = "Maximum number of results to return")]
+        limit: Option<usize>,
+        #[tool(param)]
+        #[schemars(description = "The next_cursor of the previous call, to get the next results")]
+        cursor: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "Amount of information returned for each result: ids, summary (default) or full"
+        )]
+        detail: Option<Detail>,
+        #[tool(param)]
+        #[schemars(description = "Only read the data of this space (default: all spaces)")]
+        space_id: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "Also read the data of the subspaces of space_id (default: false)"
+        )]
+        include_subspaces: Option<bool>,
+        #[tool(param)]
+        #[schemars(
+            description = "Read the data as it was at the end of this block (default: the latest data)"
+        )]
+        as_of_block: Option<u64>,
+        #[tool(param)]
+        #[schemars(
+            description = "Read the data as it was at this RFC 3339 date and time, e.g. 2025-01-31T00:00:00Z (default: the latest data)"
+        )]
+        as_of_time: Option<String>,
     ) -> Result<CallToolResult, McpError> {
+        let scope = self
+            .scope(space_id, include_subspaces, as_of_block, as_of_time)
+            .await?;
+        let page = self.page(cursor.as_deref(), limit)?;
         let filter_type = EntityFilter::default().relations(
             EntityRelationFilter::default()
                 .relation_type(prop_filter::value(system_ids::TYPES_ATTRIBUTE))
@@ -123,26 +239,56 @@ impl KnowledgeGraph {
         );
 
         let search_ids = self
-            .query_search(query, None, filter_type)
-            .await
-            .unwrap_or_default();
+            .query_search(query, page.skip, page.fetch_limit(), filter_type, &scope)
+            .await?;
+        let results = self
+            .describe_ids(search_ids, detail.unwrap_or_default(), &scope)
+            .await?;
 
-        Ok(CallToolResult::success(
-            join_all(search_ids.into_iter().map(|id| async {
-                Content::json(self.format_from_id(id).await).expect("Failed to create JSON content")
-            }))
-            .await
-            .to_vec(),
-        ))
+        self.output(page, results)
     }
 
+    #[allow(clippy::too_many_arguments)]
     #[tool(description = include_str!("../resources/search_relation_type_description.md"))]
     async fn search_relation_types(
         &self,
         #[tool(param)]
         #[schemars(description = "The query string to search for relation types")]
         query: String,
+        #[tool(param)]
+        #[schemars(description = "Maximum number of results to return")]
+        limit: Option<usize>,
+        #[tool(param)]
+        #[schemars(description = "The next_cursor of the previous call, to get the next results")]
+        cursor: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "Amount of information returned for each result: ids, summary (default) or full"
+        )]
+        detail: Option<Detail>,
+        #[tool(param)]
+        #[schemars(description = "Only read the data of this space (default: all spaces)")]
+        space_id: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "Also read the data of the subspaces of space_id (default: false)"
+        )]
+        include_subspaces: Option<bool>,
+        #[tool(param)]
+        #[schemars(
+            description = "Read the data as it was at the end of this block (default: the latest data)"
+        )]
+        as_of_block: Option<u64>,
+        #[tool(param)]
+        #[schemars(
+            description = "Read the data as it was at this RFC 3339 date and time, e.g. 2025-01-31T00:00:00Z (default: the latest data)"
+        )]
+        as_of_time: Option<String>,
     ) -> Result<CallToolResult, McpError> {
+        let scope = self
+            .scope(space_id, include_subspaces, as_of_block, as_of_time)
+            .await?;
+        let page = self.page(cursor.as_deref(), limit)?;
         let filter_relation_type = EntityFilter::default().relations(
             EntityRelationFilter::default()
                 .relation_type(prop_filter::value(system_ids::VALUE_TYPE_ATTRIBUTE))
@@ -150,188 +296,313 @@ impl KnowledgeGraph {
         );
 
         let search_ids = self
-            .query_search(query, None, filter_relation_type)
+            .query_search(
+                query,
+                page.skip,
+                page.fetch_limit(),
+                filter_relation_type,
+                &scope,
+            )
+            .await?;
+        let results = self
+            .describe_ids(search_ids, detail.unwrap_or_default(), &scope)
+            .await?;
+
+        self.output(page, results)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[tool(description = include_str!("../resources/describe_type_description.md"))]
+    async fn describe_type(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The id of the type to describe")]
+        id: String,
+        #[tool(param)]
+        #[schemars(
+            description = "The id of the space in which to describe the type (default: a space in which the type is defined)"
+        )]
+        space_id: Option<String>,
+        #[tool(param)]
+        #[schemars(description = "Maximum number of properties to return")]
+        limit: Option<usize>,
+        #[tool(param)]
+        #[schemars(
+            description = "The next_cursor of the previous call, to get the next properties"
+        )]
+        cursor: Option<String>,
+        #[tool(param)]
+        #[schemars(description = "Number of example entities of the type to return (default: 3)")]
+        examples: Option<usize>,
+        #[tool(param)]
+        #[schemars(
+            description = "Describe the type as it was at the end of this block (default: the latest schema)"
+        )]
+        as_of_block: Option<u64>,
+        #[tool(param)]
+        #[schemars(
+            description = "Describe the type as it was at this RFC 3339 date and time, e.g. 2025-01-31T00:00:00Z (default: the latest schema)"
+        )]
+        as_of_time: Option<String>,
+    ) -> Result<CallToolResult, McpError> {
+        validate_id(&id)?;
+        let page = self.page(cursor.as_deref(), limit)?;
+        // The properties of a type are inherited from the parent spaces of the space
+        // rather than read from a fixed set of spaces, only the version is scoped
+        let scope = self.scope(None, None, as_of_block, as_of_time).await?;
+        let examples = examples
+            .unwrap_or(DEFAULT_EXAMPLES)
+            .min(self.state.config.max_limit);
+
+        let space_id = match space_id {
+            Some(space_id) => validate_id(&space_id)?.to_string(),
+            None => space::find_entity_spaces(&self.state.neo4j, vec![id.clone()])
+                .await
+                .map_err(ToolError::from)?
+                .remove(&id)
+                .and_then(|spaces| spaces.into_iter().next())
+                .ok_or_else(|| ToolError::not_found("type", &id))?,
+        };
+
+        // Types are looked up in the parent spaces as well since they are inherited
+        space::r#type(&self.state.neo4j, &space_id, &id)
+            .strict(false)
+            .send()
             .await
-            .unwrap_or_default();
+            .map_err(ToolError::from)?
+            .ok_or_else(|| ToolError::not_found("type", &id))?;
 
-        Ok(CallToolResult::success(
-            join_all(search_ids.into_iter().map(|id| async {
-                Content::json(self.format_from_id(id).await).expect("Failed to create JSON content")
-            }))
+        let type_ = base_entity::find_one(&self.state.neo4j, &id, &space_id)
+            .send()
             .await
-            .to_vec(),
-        ))
+            .map_err(ToolError::from)?;
+
+        let properties = type_schema::properties(
+            &self.state.neo4j,
+            &id,
+            &space_id,
+            scope.version(),
+            Some(page.fetch_limit()),
+            Some(page.skip),
+            false,
+        )
+        .await
+        .map_err(ToolError::from)?
+        .into_iter()
+        .map(describe_property)
+        .collect::<Vec<_>>();
+
+        let example_entities = type_schema::example_entities::<Entity<BaseEntity>>(
+            &self.state.neo4j,
+            &id,
+            &space_id,
+            scope.version(),
+        )
+        .limit(examples)
+        .send()
+        .await
+        .map_err(ToolError::from)?
+        .try_collect::<Vec<_>>()
+        .await
+        .map_err(ToolError::from)?;
+        let mut example_entities = Value::Array(
+            self.describe_entities(example_entities, Detail::Summary, &scope)
+                .await?,
+        );
+
+        // The examples take at most a quarter of the output, the rest is left to the
+        // properties
+        let max_output_size = self.state.config.max_output_size;
+        let examples_truncated = pagination::shorten(&mut example_entities, max_output_size / 4);
+        let properties = page.output(
+            properties,
+            max_output_size.saturating_sub(example_entities.to_string().len()),
+        )?;
+
+        let mut result = json!({
+            "id": id,
+            "name": type_.as_ref().and_then(|type_| type_.attributes.name.clone()),
+            "description": type_.and_then(|type_| type_.attributes.description),
+            "space_id": space_id,
+            "properties": properties.results,
+            "example_entities": example_entities,
+        });
+        if let Some(next_cursor) = properties.next_cursor {
+            result["next_cursor"] = json!(next_cursor);
+        }
+        if examples_truncated || properties.truncated {
+            result["truncated"] = json!(true);
+        }
+
+        Ok(CallToolResult::success(vec![json_content(result)?]))
     }
 
+    #[allow(clippy::too_many_arguments)]
     #[tool(description = include_str!("../resources/search_entity_using_ids_description.md"))]
     async fn search_entity_using_ids(
         &self,
         #[tool(param)]
         #[schemars(description = "A filter of the relation(s) to traverse from the query")]
         search_traversal_filter: SearchTraversalInputFilter,
+        #[tool(param)]
+        #[schemars(description = "Maximum number of results to return")]
+        limit: Option<usize>,
+        #[tool(param)]
+        #[schemars(description = "The next_cursor of the previous call, to get the next results")]
+        cursor: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "Amount of information returned for each result: ids, summary (default) or full"
+        )]
+        detail: Option<Detail>,
+        #[tool(param)]
+        #[schemars(description = "Only read the data of this space (default: all spaces)")]
+        space_id: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "Also read the data of the subspaces of space_id (default: false)"
+        )]
+        include_subspaces: Option<bool>,
+        #[tool(param)]
+        #[schemars(
+            description = "Read the data as it was at the end of this block (default: the latest data)"
+        )]
+        as_of_block: Option<u64>,
+        #[tool(param)]
+        #[schemars(
+            description = "Read the data as it was at this RFC 3339 date and time, e.g. 2025-01-31T00:00:00Z (default: the latest data)"
+        )]
+        as_of_time: Option<String>,
     ) -> Result<CallToolResult, McpError> {
+        let scope = self
+            .scope(space_id, include_subspaces, as_of_block, as_of_time)
+            .await?;
         tracing::info!("SearchTraversalFilter query: {:?}", search_traversal_filter);
 
-        let embedding = self
-            .embedding_model
-            .embed(vec![&search_traversal_filter.query], None)
-            .expect("Failed to get embedding")
-            .pop()
-            .expect("Embedding is empty")
-            .into_iter()
-            .map(|v| v as f64)
-            .collect::<Vec<_>>();
-
-        let traversal_filters: Vec<Result<TraverseRelation, McpError>> =
-            match search_traversal_filter.traversal_filter {
-                Some(traversal_filter_input) => {
-                    join_all(traversal_filter_input.into_iter().map(|filter| async move {
-                        Ok(TraverseRelation::default()
-                            .direction(match filter.direction {
-                                input_types::RelationDirection::From => RelationDirection::From,
-                                input_types::RelationDirection::To => RelationDirection::To,
-                            })
-                            .relation_type_id(prop_filter::value(filter.relation_type)))
-                    }))
-                    .await
-                    .to_vec()
-                }
-                None => Vec::new(),
-            };
+        let page = self.page(cursor.as_deref(), limit)?;
+        let embedding = self.embed(&search_traversal_filter.query)?;
 
-        let results_search = traversal_filters
+        let traversal_filters = search_traversal_filter
+            .traversal_filter
             .into_iter()
-            .fold(
-                entity::search_from_restictions::<Entity<BaseEntity>>(
-                    &self.neo4j,
-                    embedding.clone(),
-                ),
-                |query, result_traversal_filter: Result<_, McpError>| match result_traversal_filter
-                {
-                    Ok(traversal_filter) => {
-                        query.filter(EntityFilter::default().traverse_relation(traversal_filter))
-                    }
-                    Err(_) => query,
-                },
-            )
-            .limit(10)
-            .send()
-            .await
-            .map_err(|e| {
-                McpError::internal_error(
-                    "search_properties",
-                    Some(json!({ "error": e.to_string() })),
-                )
-            })?
-            .try_collect::<Vec<_>>()
-            .await
-            .map_err(|e| {
-                McpError::internal_error(
-                    "search_properties",
-                    Some(json!({ "error": e.to_string() })),
-                )
-            })?;
-
-        Ok(CallToolResult::success(vec![
-            Content::json(
+            .flatten()
+            .map(|filter| {
+                validate_id(&filter.relation_type)?;
+                Ok(TraverseRelation::default()
+                    .direction(match filter.direction {
+                        input_types::RelationDirection::From => RelationDirection::From,
+                        input_types::RelationDirection::To => RelationDirection::To,
+                    })
+                    .relation_type_id(prop_filter::value(filter.relation_type)))
+            })
+            .collect::<Result<Vec<_>, ToolError>>()?;
+
+        let results_search = self
+            .search_with_traversals(embedding, traversal_filters, page, &scope)
+            .await?;
+        let results = self
+            .describe_entities(
                 results_search
                     .into_iter()
-                    .map(|result| {
-                        json!({
-                            "id": result.entity.id(),
-                            "name": result.entity.attributes.name,
-                            "description": result.entity.attributes.description,
-                        })
-                    })
-                    .collect::<Vec<_>>(),
+                    .map(|result| result.entity)
+                    .collect(),
+                detail.unwrap_or_default(),
+                &scope,
             )
-            .expect("Failed to create JSON content"),
-        ]))
+            .await?;
+
+        self.output(page, results)
     }
 
+    #[allow(clippy::too_many_arguments)]
     #[tool(description = include_str!("../resources/search_entity_description.md"))]
     async fn search_entity(
         &self,
         #[tool(param)]
         #[schemars(description = "A filter of the relation(s) to traverse from the query")]
         search_traversal_filter: SearchTraversalInputFilter,
+        #[tool(param)]
+        #[schemars(description = "Maximum number of results to return")]
+        limit: Option<usize>,
+        #[tool(param)]
+        #[schemars(description = "The next_cursor of the previous call, to get the next results")]
+        cursor: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "Amount of information returned for each result: ids, summary (default) or full"
+        )]
+        detail: Option<Detail>,
+        #[tool(param)]
+        #[schemars(description = "Only read the data of this space (default: all spaces)")]
+        space_id: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "Also read the data of the subspaces of space_id (default: false)"
+        )]
+        include_subspaces: Option<bool>,
+        #[tool(param)]
+        #[schemars(
+            description = "Read the data as it was at the end of this block (default: the latest data)"
+        )]
+        as_of_block: Option<u64>,
+        #[tool(param)]
+        #[schemars(
+            description = "Read the data as it was at this RFC 3339 date and time, e.g. 2025-01-31T00:00:00Z (default: the latest data)"
+        )]
+        as_of_time: Option<String>,
     ) -> Result<CallToolResult, McpError> {
+        let scope = self
+            .scope(space_id, include_subspaces, as_of_block, as_of_time)
+            .await?;
         tracing::info!("SearchTraversalFilter query: {:?}", search_traversal_filter);
 
-        let embedding = self
-            .embedding_model
-            .embed(vec![&search_traversal_filter.query], None)
-            .expect("Failed to get embedding")
-            .pop()
-            .expect("Embedding is empty")
-            .into_iter()
-            .map(|v| v as f64)
-            .collect::<Vec<_>>();
+        let page = self.page(cursor.as_deref(), limit)?;
+        let embedding = self.embed(&search_traversal_filter.query)?;
 
         let start_filters = Instant::now();
 
-        let traversal_filters: Vec<Result<TraverseRelation, McpError>> =
-            match search_traversal_filter.traversal_filter {
-                Some(traversal_filter_input) => {
-                    join_all(traversal_filter_input.into_iter().map(|filter| async move {
-                        let filter_relation_type = EntityFilter::default().relations(
-                            EntityRelationFilter::default()
-                                .relation_type(prop_filter::value(system_ids::VALUE_TYPE_ATTRIBUTE))
-                                .to_id(prop_filter::value(system_ids::RELATION_SCHEMA_TYPE)),
-                        );
-
-                        let relation_ids = self
-                            .query_search(filter.relation_type, None, filter_relation_type)
-                            .await
-                            .unwrap_or_default();
-
-                        Ok(TraverseRelation::default()
-                            .direction(match filter.direction {
-                                input_types::RelationDirection::From => RelationDirection::From,
-                                input_types::RelationDirection::To => RelationDirection::To,
-                            })
-                            .relation_type_id(prop_filter::value_in(relation_ids)))
-                    }))
-                    .await
-                    .to_vec()
-                }
-                None => Vec::new(),
-            };
+        // Borrowed by the searches of the relation types
+        let scope = &scope;
+
+        let traversal_filters = join_all(
+            search_traversal_filter
+                .traversal_filter
+                .into_iter()
+                .flatten()
+                .map(|filter| async move {
+                    let filter_relation_type = EntityFilter::default().relations(
+                        EntityRelationFilter::default()
+                            .relation_type(prop_filter::value(system_ids::VALUE_TYPE_ATTRIBUTE))
+                            .to_id(prop_filter::value(system_ids::RELATION_SCHEMA_TYPE)),
+                    );
+
+                    let relation_ids = self
+                        .query_search(
+                            filter.relation_type,
+                            0,
+                            self.state.config.default_limit,
+                            filter_relation_type,
+                            scope,
+                        )
+                        .await?;
+
+                    Ok(TraverseRelation::default()
+                        .direction(match filter.direction {
+                            input_types::RelationDirection::From => RelationDirection::From,
+                            input_types::RelationDirection::To => RelationDirection::To,
+                        })
+                        .relation_type_id(prop_filter::value_in(relation_ids)))
+                }),
+        )
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, ToolError>>()?;
 
         let end_filters = Instant::now();
 
-        let results_search = traversal_filters
-            .into_iter()
-            .fold(
-                entity::search_from_restictions::<Entity<BaseEntity>>(
-                    &self.neo4j,
-                    embedding.clone(),
-                ),
-                |query, result_traversal_filter: Result<_, McpError>| match result_traversal_filter
-                {
-                    Ok(traversal_filter) => {
-                        query.filter(EntityFilter::default().traverse_relation(traversal_filter))
-                    }
-                    Err(_) => query,
-                },
-            )
-            .limit(10)
-            .send()
-            .await
-            .map_err(|e| {
-                McpError::internal_error(
-                    "search_properties",
-                    Some(json!({ "error": e.to_string() })),
-                )
-            })?
-            .try_collect::<Vec<_>>()
-            .await
-            .map_err(|e| {
-                McpError::internal_error(
-                    "search_properties",
-                    Some(json!({ "error": e.to_string() })),
-                )
-            })?;
+        let results_search = self
+            .search_with_traversals(embedding, traversal_filters, page, scope)
+            .await?;
 
         let end_search = Instant::now();
         let search_time = end_search - end_filters;
@@ -340,23 +611,21 @@ impl KnowledgeGraph {
         tracing::info!("filters time: {filters_time:?}");
         tracing::info!("search time: {search_time:?}");
 
-        Ok(CallToolResult::success(vec![
-            Content::json(
+        let results = self
+            .describe_entities(
                 results_search
                     .into_iter()
-                    .map(|result| {
-                        json!({
-                            "id": result.entity.id(),
-                            "name": result.entity.attributes.name,
-                            "description": result.entity.attributes.description,
-                        })
-                    })
-                    .collect::<Vec<_>>(),
+                    .map(|result| result.entity)
+                    .collect(),
+                detail.unwrap_or_default(),
+                scope,
             )
-            .expect("Failed to create JSON content"),
-        ]))
+            .await?;
+
+        self.output(page, results)
     }
 
+    #[allow(clippy::too_many_arguments)]
     #[tool(description = include_str!("../resources/get_entity_info_description.md"))]
     async fn get_entity_info(
         &self,
@@ -365,106 +634,209 @@ impl KnowledgeGraph {
             description = "Return an entity by its ID along with its attributes (name, description, etc.), relations and types"
         )]
         id: String,
+        #[tool(param)]
+        #[schemars(description = "Maximum number of inbound and outbound relations to return")]
+        limit: Option<usize>,
+        #[tool(param)]
+        #[schemars(
+            description = "The next_cursor of the previous call, to get the next relations"
+        )]
+        cursor: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "Amount of information returned for the attributes and relations: ids, summary (default: names) or full (names and ids)"
+        )]
+        detail: Option<Detail>,
+        #[tool(param)]
+        #[schemars(description = "Only read the data of this space (default: all spaces)")]
+        space_id: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "Also read the data of the subspaces of space_id (default: false)"
+        )]
+        include_subspaces: Option<bool>,
+        #[tool(param)]
+        #[schemars(
+            description = "Read the data as it was at the end of this block (default: the latest data)"
+        )]
+        as_of_block: Option<u64>,
+        #[tool(param)]
+        #[schemars(
+            description = "Read the data as it was at this RFC 3339 date and time, e.g. 2025-01-31T00:00:00Z (default: the latest data)"
+        )]
+        as_of_time: Option<String>,
     ) -> Result<CallToolResult, McpError> {
-        let entity_attributes = triple::find_many(&self.neo4j)
-            .entity_id(prop_filter::value(&id))
+        validate_id(&id)?;
+        let scope = self
+            .scope(space_id, include_subspaces, as_of_block, as_of_time)
+            .await?;
+        let detail = detail.unwrap_or_default();
+        let [outbound_skip, inbound_skip] = pagination::decode_cursor(cursor.as_deref())?;
+        let limit = pagination::check_limit(
+            limit,
+            self.state.config.default_limit,
+            self.state.config.max_limit,
+        )?;
+
+        entity::find_one::<Entity<BaseEntity>>(&self.state.neo4j, &id)
             .send()
             .await
-            .map_err(|e| {
-                McpError::internal_error("get_entity_info", Some(json!({ "error": e.to_string() })))
-            })?
-            .try_collect::<Vec<_>>()
-            .await
-            .map_err(|e| {
-                McpError::internal_error("get_entity_info", Some(json!({ "error": e.to_string() })))
-            })?;
+            .map_err(ToolError::from)?
+            .ok_or_else(|| ToolError::not_found("entity", &id))?;
+
+        let entity_attributes = self.find_attributes(&id, &scope).await?;
 
-        let out_relations = relation::find_many::<RelationEdge<EntityNode>>(&self.neo4j)
-            .filter(
+        let out_relations = self
+            .find_relations(
                 relation::RelationFilter::default()
                     .from_(EntityFilter::default().id(prop_filter::value(id.clone()))),
+                outbound_skip,
+                limit + 1,
+                &scope,
             )
-            .limit(10)
-            .send()
-            .await
-            .map_err(|e| {
-                McpError::internal_error(
-                    "get_relation_by_id",
-                    Some(json!({ "error": e.to_string() })),
-                )
-            })?
-            .try_collect::<Vec<_>>()
-            .await
-            .map_err(|e| {
-                McpError::internal_error(
-                    "get_relation_by_id_not_found",
-                    Some(json!({ "error": e.to_string() })),
-                )
-            })?;
+            .await?;
 
-        let in_relations = relation::find_many::<RelationEdge<EntityNode>>(&self.neo4j)
-            .filter(
+        let in_relations = self
+            .find_relations(
                 relation::RelationFilter::default()
                     .to_(EntityFilter::default().id(prop_filter::value(id.clone()))),
+                inbound_skip,
+                limit + 1,
+                &scope,
             )
-            .limit(10)
-            .send()
-            .await
-            .map_err(|e| {
-                McpError::internal_error(
-                    "get_relation_by_id",
-                    Some(json!({ "error": e.to_string() })),
-                )
-            })?
-            .try_collect::<Vec<_>>()
-            .await
-            .map_err(|e| {
-                McpError::internal_error(
-                    "get_relation_by_id_not_found",
-                    Some(json!({ "error": e.to_string() })),
-                )
-            })?;
+            .await?;
 
         tracing::info!("Found entity with ID '{}'", id);
 
-        let clean_up_relations = |relations: Vec<RelationEdge<EntityNode>>, is_inbound: bool| async move {
-            join_all(relations
+        let names = match detail {
+            Detail::Ids => HashMap::new(),
+            _ => {
+                let ids = entity_attributes
+                    .iter()
+                    .map(|attr| attr.attribute.clone())
+                    .chain(out_relations.iter().flat_map(|relation| {
+                        [relation.relation_type.clone(), relation.to.id.clone()]
+                    }))
+                    .chain(in_relations.iter().flat_map(|relation| {
+                        [relation.relation_type.clone(), relation.from.id.clone()]
+                    }))
+                    .collect::<HashSet<_>>();
+                resources::find_names(&self.state.neo4j, ids.into_iter().collect())
+                    .await
+                    .map_err(ToolError::from)?
+            }
+        };
+
+        let clean_up_relations = |relations: Vec<RelationEdge<EntityNode>>, is_inbound: bool| {
+            relations
                 .into_iter()
-                .map(|result| async move {
-                    json!({
-                        "relation_id": result.id,
-                        "relation_type": self.get_name_of_id(result.relation_type.clone()).await.unwrap_or(result.relation_type.to_string()),
-                        "id": if is_inbound {result.from.id.clone()} else {result.to.id.clone()},
-                        "name": self.get_name_of_id(if is_inbound {result.from.id.clone()} else {result.to.id.clone()}).await.unwrap_or("No name".to_string()),
-                    })
-                })).await.to_vec()
+                .map(|result| {
+                    let other_id = if is_inbound {
+                        result.from.id
+                    } else {
+                        result.to.id
+                    };
+                    let relation_type = names.get(&result.relation_type).cloned();
+                    let name = names.get(&other_id).cloned();
+                    match detail {
+                        Detail::Ids => json!({
+                            "relation_id": result.id,
+                            "relation_type_id": result.relation_type,
+                            "id": other_id,
+                        }),
+                        Detail::Summary => json!({
+                            "relation_id": result.id,
+                            "relation_type": relation_type.unwrap_or(result.relation_type),
+                            "id": other_id,
+                            "name": name.unwrap_or("No name".to_string()),
+                        }),
+                        Detail::Full => json!({
+                            "relation_id": result.id,
+                            "relation_type_id": result.relation_type,
+                            "relation_type": relation_type,
+                            "id": other_id,
+                            "name": name,
+                        }),
+                    }
+                })
+                .collect::<Vec<_>>()
         };
-        let inbound_relations = clean_up_relations(in_relations, true).await;
-        let outbound_relations = clean_up_relations(out_relations, false).await;
+        let inbound_relations = clean_up_relations(in_relations, true);
+        let outbound_relations = clean_up_relations(out_relations, false);
 
-        let attributes_vec: Vec<_> = join_all(entity_attributes.into_iter().map(
-            |attr| async {
-                json!({
-                    "attribute_name": self.get_name_of_id(attr.attribute).await.unwrap_or("No attribute name".to_string()),
-                    "attribute_value": String::try_from(attr.value).unwrap_or("No attributes".to_string()),
+        let mut attributes_vec = Value::Array(
+            entity_attributes
+                .into_iter()
+                .map(|attr| {
+                    let attribute_name = names.get(&attr.attribute).cloned();
+                    match detail {
+                        Detail::Ids => json!({
+                            "attribute_id": attr.attribute,
+                            "attribute_value": attr.value.value,
+                        }),
+                        Detail::Summary => json!({
+                            "attribute_name": attribute_name.unwrap_or("No attribute name".to_string()),
+                            "attribute_value": attr.value.value,
+                        }),
+                        Detail::Full => json!({
+                            "attribute_id": attr.attribute,
+                            "attribute_name": attribute_name,
+                            "attribute_value": attr.value.value,
+                        }),
+                    }
                 })
-            },
-        ))
-        .await
-        .to_vec();
+                .collect(),
+        );
 
-        Ok(CallToolResult::success(vec![
-            Content::json(json!({
-                "id": id,
-                "all_attributes": attributes_vec,
-                "inbound_relations": inbound_relations,
-                "outbound_relations": outbound_relations,
-            }))
-            .expect("Failed to create JSON content"),
-        ]))
-    }
+        // The attributes take at most a quarter of the output, the rest is shared by the
+        // inbound and outbound relations
+        let max_output_size = self.state.config.max_output_size;
+        let attributes_truncated = pagination::shorten(&mut attributes_vec, max_output_size / 4);
+        let relations_size = max_output_size.saturating_sub(attributes_vec.to_string().len()) / 2;
 
-    #[tool(description = include_str!("../resources/get_relations_between_entities_description.md"))]
+        let outbound = Page {
+            skip: outbound_skip,
+            limit,
+        }
+        .output(outbound_relations, relations_size)?;
+        let inbound = Page {
+            skip: inbound_skip,
+            limit,
+        }
+        .output(inbound_relations, relations_size)?;
+
+        // Both lists are paginated by the same cursor, a list without more relations is
+        // left past its end
+        let next_cursor =
+            (outbound.next_skip.is_some() || inbound.next_skip.is_some()).then(|| {
+                pagination::encode_cursor([
+                    outbound
+                        .next_skip
+                        .unwrap_or(outbound_skip + outbound.results.len()),
+                    inbound
+                        .next_skip
+                        .unwrap_or(inbound_skip + inbound.results.len()),
+                ])
+            });
+
+        let mut result = json!({
+            "id": id,
+            "all_attributes": attributes_vec,
+            "inbound_relations": inbound.results,
+            "outbound_relations": outbound.results,
+        });
+        if let Some(next_cursor) = next_cursor {
+            result["next_cursor"] = json!(next_cursor);
+        }
+        if attributes_truncated || outbound.truncated || inbound.truncated {
+            result["truncated"] = json!(true);
+        }
+
+        Ok(CallToolResult::success(vec![json_content(result)?]))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[tool(description = include_str!("../resources/get_relations_between_entities_description.md"))]
     async fn get_relations_between_entities(
         &self,
         #[tool(param)]
@@ -473,74 +845,521 @@ impl KnowledgeGraph {
         #[tool(param)]
         #[schemars(description = "The id of the second Entity to find relations")]
         entity2_id: String,
+        #[tool(param)]
+        #[schemars(description = "Maximum number of paths to return")]
+        limit: Option<usize>,
+        #[tool(param)]
+        #[schemars(description = "The next_cursor of the previous call, to get the next paths")]
+        cursor: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "Amount of information returned for each path: ids, summary (default: names) or full (names and ids)"
+        )]
+        detail: Option<Detail>,
+        #[tool(param)]
+        #[schemars(description = "Only read the data of this space (default: all spaces)")]
+        space_id: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "Also read the data of the subspaces of space_id (default: false)"
+        )]
+        include_subspaces: Option<bool>,
+        #[tool(param)]
+        #[schemars(
+            description = "Read the data as it was at the end of this block (default: the latest data)"
+        )]
+        as_of_block: Option<u64>,
+        #[tool(param)]
+        #[schemars(
+            description = "Read the data as it was at this RFC 3339 date and time, e.g. 2025-01-31T00:00:00Z (default: the latest data)"
+        )]
+        as_of_time: Option<String>,
     ) -> Result<CallToolResult, McpError> {
-        let relations = entity::find_path(&self.neo4j, entity1_id.clone(), entity2_id.clone())
-            .limit(10)
-            .send()
-            .await
-            .map_err(|e| {
-                McpError::internal_error(
-                    "get_relation_by_ids",
-                    Some(json!({ "error": e.to_string() })),
-                )
-            })?
+        validate_id(&entity1_id)?;
+        validate_id(&entity2_id)?;
+        let scope = self
+            .scope(space_id, include_subspaces, as_of_block, as_of_time)
+            .await?;
+        let page = self.page(cursor.as_deref(), limit)?;
+        let detail = detail.unwrap_or_default();
+
+        let mut query = entity::find_path(&self.state.neo4j, entity1_id, entity2_id)
+            .version_opt(scope.version())
+            .skip(page.skip)
+            .limit(page.fetch_limit());
+        if let Some(space_filter) = scope.space_filter() {
+            query = query.space_id(space_filter);
+        }
+        let paths = query.send().await.map_err(ToolError::from)?;
+
+        let names = match detail {
+            Detail::Ids => HashMap::new(),
+            _ => {
+                let ids = paths
+                    .iter()
+                    .flat_map(|path| path.nodes_ids.iter().chain(&path.relations_ids).cloned())
+                    .collect::<HashSet<_>>();
+                resources::find_names(&self.state.neo4j, ids.into_iter().collect())
+                    .await
+                    .map_err(ToolError::from)?
+            }
+        };
+
+        let describe = |ids: Vec<String>| -> Vec<Value> {
+            ids.into_iter()
+                .map(|id| {
+                    let name = names.get(&id).cloned();
+                    match detail {
+                        Detail::Ids => json!(id),
+                        Detail::Summary => json!(name.unwrap_or(id)),
+                        Detail::Full => json!({ "id": id, "name": name }),
+                    }
+                })
+                .collect()
+        };
+
+        let results = paths
             .into_iter()
+            .map(|path| {
+                json!({
+                    "nodes": describe(path.nodes_ids),
+                    "relations": describe(path.relations_ids),
+                })
+            })
             .collect::<Vec<_>>();
 
-        Ok(CallToolResult::success(
-            join_all(relations
-                .into_iter()
-                .map(|result| async {
-                    Content::json(json!({
-                    "nodes": join_all(result.nodes_ids.into_iter().map(|node_id| async {self.get_name_of_id(node_id).await.unwrap_or("No attribute name".to_string())})).await.to_vec(),
-                    "relations": join_all(result.relations_ids.into_iter().map(|node_id| async {self.get_name_of_id(node_id).await.unwrap_or("No attribute name".to_string())})).await.to_vec(),
-                    }))
-                    .expect("Failed to create JSON content")
-                }))
-                .await
-                .to_vec(),
-        ))
+        self.output(page, results)
     }
 
-    async fn query_search(
+    #[allow(clippy::too_many_arguments)]
+    #[tool(description = include_str!("../resources/list_spaces_description.md"))]
+    async fn list_spaces(
         &self,
+        #[tool(param)]
+        #[schemars(description = "Only return the spaces of this network (e.g.: GEO)")]
+        network: Option<String>,
+        #[tool(param)]
+        #[schemars(description = "Only return the spaces with this governance type")]
+        governance_type: Option<GovernanceType>,
+        #[tool(param)]
+        #[schemars(description = "Maximum number of spaces to return")]
+        limit: Option<usize>,
+        #[tool(param)]
+        #[schemars(description = "The next_cursor of the previous call, to get the next spaces")]
+        cursor: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "Amount of information returned for each space: ids or summary (default)"
+        )]
+        detail: Option<Detail>,
+        #[tool(param)]
+        #[schemars(description = "Only return this space (default: all spaces)")]
+        space_id: Option<String>,
+        #[tool(param)]
+        #[schemars(description = "Also return the subspaces of space_id (default: false)")]
+        include_subspaces: Option<bool>,
+        #[tool(param)]
+        #[schemars(
+            description = "Read the spaces as it was at the end of this block (default: the current state)"
+        )]
+        as_of_block: Option<u64>,
+        #[tool(param)]
+        #[schemars(
+            description = "Read the spaces as it was at this RFC 3339 date and time, e.g. 2025-01-31T00:00:00Z (default: the current state)"
+        )]
+        as_of_time: Option<String>,
+    ) -> Result<CallToolResult, McpError> {
+        let scope = self
+            .scope(space_id, include_subspaces, as_of_block, as_of_time)
+            .await?;
+        let page = self.page(cursor.as_deref(), limit)?;
+        let spaces = governance::list_spaces(
+            &self.state.neo4j,
+            network,
+            governance_type.map(Into::into),
+            page.skip,
+            page.fetch_limit(),
+            &scope,
+        )
+        .await
+        .map_err(ToolError::from)?;
+
+        self.output(page, select_detail(spaces, detail.unwrap_or_default())?)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[tool(description = include_str!("../resources/search_spaces_description.md"))]
+    async fn search_spaces(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The query string to search for spaces")]
         query: String,
+        #[tool(param)]
+        #[schemars(description = "Maximum number of spaces to return")]
         limit: Option<usize>,
-        filter: EntityFilter,
-    ) -> Result<Vec<String>, McpError> {
-        let embedding = self
-            .embedding_model
-            .embed(vec![&query], None)
-            .expect("Failed to get embedding")
-            .pop()
-            .expect("Embedding is empty")
+        #[tool(param)]
+        #[schemars(description = "The next_cursor of the previous call, to get the next spaces")]
+        cursor: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "Amount of information returned for each space: ids or summary (default)"
+        )]
+        detail: Option<Detail>,
+        #[tool(param)]
+        #[schemars(description = "Only search this space (default: all spaces)")]
+        space_id: Option<String>,
+        #[tool(param)]
+        #[schemars(description = "Also search the subspaces of space_id (default: false)")]
+        include_subspaces: Option<bool>,
+        #[tool(param)]
+        #[schemars(
+            description = "Read the spaces as it was at the end of this block (default: the current state)"
+        )]
+        as_of_block: Option<u64>,
+        #[tool(param)]
+        #[schemars(
+            description = "Read the spaces as it was at this RFC 3339 date and time, e.g. 2025-01-31T00:00:00Z (default: the current state)"
+        )]
+        as_of_time: Option<String>,
+    ) -> Result<CallToolResult, McpError> {
+        let scope = self
+            .scope(space_id, include_subspaces, as_of_block, as_of_time)
+            .await?;
+        let page = self.page(cursor.as_deref(), limit)?;
+        let spaces = governance::search_spaces(
+            &self.state.neo4j,
+            self.embed(&query)?,
+            page.skip,
+            page.fetch_limit(),
+            &scope,
+        )
+        .await
+        .map_err(ToolError::from)?;
+
+        self.output(page, select_detail(spaces, detail.unwrap_or_default())?)
+    }
+
+    #[tool(description = include_str!("../resources/get_space_hierarchy_description.md"))]
+    async fn get_space_hierarchy(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The id of the space")]
+        space_id: String,
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum depth of the parent spaces and subspaces (default: unlimited)"
+        )]
+        max_depth: Option<usize>,
+        #[tool(param)]
+        #[schemars(
+            description = "Read the hierarchy as it was at the end of this block (default: the current state)"
+        )]
+        as_of_block: Option<u64>,
+        #[tool(param)]
+        #[schemars(
+            description = "Read the hierarchy as it was at this RFC 3339 date and time, e.g. 2025-01-31T00:00:00Z (default: the current state)"
+        )]
+        as_of_time: Option<String>,
+    ) -> Result<CallToolResult, McpError> {
+        validate_id(&space_id)?;
+        let scope = self.scope(None, None, as_of_block, as_of_time).await?;
+
+        let hierarchy =
+            governance::space_hierarchy(&self.state.neo4j, &space_id, max_depth, &scope)
+                .await
+                .map_err(ToolError::from)?
+                .ok_or_else(|| ToolError::not_found("space", &space_id))?;
+
+        Ok(CallToolResult::success(vec![
+            self.bounded_content(hierarchy)?,
+        ]))
+    }
+
+    #[tool(description = include_str!("../resources/get_space_members_description.md"))]
+    async fn get_space_members(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The id of the space")]
+        space_id: String,
+        #[tool(param)]
+        #[schemars(description = "Maximum number of members to return")]
+        limit: Option<usize>,
+        #[tool(param)]
+        #[schemars(description = "The next_cursor of the previous call, to get the next members")]
+        cursor: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "Amount of information returned for each member: ids or summary (default)"
+        )]
+        detail: Option<Detail>,
+        #[tool(param)]
+        #[schemars(
+            description = "Read the members as it was at the end of this block (default: the current state)"
+        )]
+        as_of_block: Option<u64>,
+        #[tool(param)]
+        #[schemars(
+            description = "Read the members as it was at this RFC 3339 date and time, e.g. 2025-01-31T00:00:00Z (default: the current state)"
+        )]
+        as_of_time: Option<String>,
+    ) -> Result<CallToolResult, McpError> {
+        validate_id(&space_id)?;
+        let scope = self.scope(None, None, as_of_block, as_of_time).await?;
+        let page = self.page(cursor.as_deref(), limit)?;
+
+        let members = governance::members(
+            &self.state.neo4j,
+            &space_id,
+            page.skip,
+            page.fetch_limit(),
+            &scope,
+        )
+        .await
+        .map_err(ToolError::from)?;
+
+        self.output(page, select_detail(members, detail.unwrap_or_default())?)
+    }
+
+    #[tool(description = include_str!("../resources/get_space_editors_description.md"))]
+    async fn get_space_editors(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The id of the space")]
+        space_id: String,
+        #[tool(param)]
+        #[schemars(description = "Maximum number of editors to return")]
+        limit: Option<usize>,
+        #[tool(param)]
+        #[schemars(description = "The next_cursor of the previous call, to get the next editors")]
+        cursor: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "Amount of information returned for each editor: ids or summary (default)"
+        )]
+        detail: Option<Detail>,
+        #[tool(param)]
+        #[schemars(
+            description = "Read the editors as it was at the end of this block (default: the current state)"
+        )]
+        as_of_block: Option<u64>,
+        #[tool(param)]
+        #[schemars(
+            description = "Read the editors as it was at this RFC 3339 date and time, e.g. 2025-01-31T00:00:00Z (default: the current state)"
+        )]
+        as_of_time: Option<String>,
+    ) -> Result<CallToolResult, McpError> {
+        validate_id(&space_id)?;
+        let scope = self.scope(None, None, as_of_block, as_of_time).await?;
+        let page = self.page(cursor.as_deref(), limit)?;
+
+        let editors = governance::editors(
+            &self.state.neo4j,
+            &space_id,
+            page.skip,
+            page.fetch_limit(),
+            &scope,
+        )
+        .await
+        .map_err(ToolError::from)?;
+
+        self.output(page, select_detail(editors, detail.unwrap_or_default())?)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[tool(description = include_str!("../resources/list_proposals_description.md"))]
+    async fn list_proposals(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The id of the space")]
+        space_id: String,
+        #[tool(param)]
+        #[schemars(description = "Only return the proposals with this status")]
+        status: Option<ProposalStatus>,
+        #[tool(param)]
+        #[schemars(description = "Maximum number of proposals to return")]
+        limit: Option<usize>,
+        #[tool(param)]
+        #[schemars(
+            description = "The next_cursor of the previous call, to get the next proposals"
+        )]
+        cursor: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "Amount of information returned for each proposal: ids or summary (default)"
+        )]
+        detail: Option<Detail>,
+        #[tool(param)]
+        #[schemars(
+            description = "Read the proposals as it was at the end of this block (default: the current state)"
+        )]
+        as_of_block: Option<u64>,
+        #[tool(param)]
+        #[schemars(
+            description = "Read the proposals as it was at this RFC 3339 date and time, e.g. 2025-01-31T00:00:00Z (default: the current state)"
+        )]
+        as_of_time: Option<String>,
+    ) -> Result<CallToolResult, McpError> {
+        validate_id(&space_id)?;
+        let scope = self.scope(None, None, as_of_block, as_of_time).await?;
+        let page = self.page(cursor.as_deref(), limit)?;
+
+        let proposals = governance::proposals(
+            &self.state.neo4j,
+            &space_id,
+            status.map(Into::into),
+            page.skip,
+            page.fetch_limit(),
+            &scope,
+        )
+        .await
+        .map_err(ToolError::from)?;
+
+        self.output(page, select_detail(proposals, detail.unwrap_or_default())?)
+    }
+
+    #[tool(description = include_str!("../resources/draft_edit_description.md"))]
+    async fn draft_edit(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The id of the space in which the edit is to be proposed")]
+        space_id: String,
+        #[tool(param)]
+        #[schemars(description = "Short description of the edit, shown to its reviewers")]
+        name: String,
+        #[tool(param)]
+        #[schemars(
+            description = "The changes of the edit: set_attribute, add_relation or create_entity"
+        )]
+        changes: Vec<ChangeInput>,
+        #[tool(param)]
+        #[schemars(description = "Addresses of the accounts authoring the edit")]
+        authors: Option<Vec<String>>,
+    ) -> Result<CallToolResult, McpError> {
+        validate_id(&space_id)?;
+        if changes.is_empty() {
+            return Err(ToolError::InvalidArgument("changes must not be empty".to_string()).into());
+        }
+        for id in changes.iter().flat_map(ChangeInput::ids) {
+            validate_id(id)?;
+        }
+
+        governance::find_space(&self.state.neo4j, &space_id, &Scope::default())
+            .await
+            .map_err(ToolError::from)?
+            .ok_or_else(|| ToolError::not_found("space", &space_id))?;
+
+        let drafted = authors
             .into_iter()
-            .map(|v| v as f64)
+            .flatten()
+            .fold(
+                draft::draft(&self.state.neo4j, &space_id, name),
+                |query, author| query.author(author),
+            )
+            .changes(changes.into_iter().map(Into::into))
+            .send()
+            .await
+            .map_err(ToolError::from)?;
+
+        let violations = drafted
+            .violations
+            .iter()
+            .map(|violation| {
+                json!({
+                    "kind": violation.kind.to_string(),
+                    "entity_id": violation.entity_id,
+                    "property_id": violation.property_id,
+                    "expected": violation.expected,
+                    "actual": violation.actual,
+                })
+            })
             .collect::<Vec<_>>();
 
-        let limit = limit.unwrap_or(10);
-        let semantic_search_triples =
-            entity::prefiltered_search::<EntityNode>(&self.neo4j, embedding.clone())
-                .filter(filter.clone())
-                .limit(limit)
-                .send()
-                .await
-                .map_err(|e| {
-                    tracing::error!("Error: {e:?}");
-                    McpError::internal_error(
-                        "search_types_failed",
-                        Some(json!({ "error": e.to_string() })),
-                    )
-                })?
-                .try_collect::<Vec<_>>()
-                .await
-                .map_err(|e| {
-                    tracing::error!("Error changing to vec: {e:?}");
-                    McpError::internal_error(
-                        "search_types_failed",
-                        Some(json!({ "error": e.to_string() })),
-                    )
-                })?;
+        // The encoded edit is never shortened since it has to be published as is
+        let result = json!({
+            "edit_id": drafted.edit.id,
+            "space_id": space_id,
+            "valid": drafted.is_valid(),
+            "violations": violations,
+            "created_entities": drafted.created_entities,
+            "diff": drafted.diff,
+            "edit": BASE64_STANDARD.encode(drafted.encode()),
+        });
+
+        Ok(CallToolResult::success(vec![json_content(result)?]))
+    }
+
+    #[tool(description = include_str!("../resources/get_account_activity_description.md"))]
+    async fn get_account_activity(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The id or the address (0x...) of the account")]
+        account: String,
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum number of memberships, editorships, proposals and votes to return"
+        )]
+        limit: Option<usize>,
+        #[tool(param)]
+        #[schemars(description = "Only return the activity in this space (default: all spaces)")]
+        space_id: Option<String>,
+        #[tool(param)]
+        #[schemars(
+            description = "Also return the activity in the subspaces of space_id (default: false)"
+        )]
+        include_subspaces: Option<bool>,
+        #[tool(param)]
+        #[schemars(
+            description = "Read the activity as it was at the end of this block (default: the current state)"
+        )]
+        as_of_block: Option<u64>,
+        #[tool(param)]
+        #[schemars(
+            description = "Read the activity as it was at this RFC 3339 date and time, e.g. 2025-01-31T00:00:00Z (default: the current state)"
+        )]
+        as_of_time: Option<String>,
+    ) -> Result<CallToolResult, McpError> {
+        validate_id(&account)?;
+        let scope = self
+            .scope(space_id, include_subspaces, as_of_block, as_of_time)
+            .await?;
+        let limit = pagination::check_limit(
+            limit,
+            self.state.config.default_limit,
+            self.state.config.max_limit,
+        )?;
+
+        let activity = governance::account_activity(&self.state.neo4j, &account, limit, &scope)
+            .await
+            .map_err(ToolError::from)?
+            .ok_or_else(|| ToolError::not_found("account", &account))?;
+
+        Ok(CallToolResult::success(vec![
+            self.bounded_content(activity)?,
+        ]))
+    }
+
+    async fn query_search(
+        &self,
+        query: String,
+        skip: usize,
+        limit: usize,
+        filter: EntityFilter,
+        scope: &Scope,
+    ) -> Result<Vec<String>, ToolError> {
+        let embedding = self.embed(&query)?;
+
+        let mut search = entity::prefiltered_search::<EntityNode>(&self.state.neo4j, embedding)
+            .filter(filter)
+            .skip(skip)
+            .limit(limit);
+        if let Some(space_filter) = scope.space_filter() {
+            search = search.space_id(space_filter);
+        }
+        if let Some(version) = scope.version() {
+            search = search.version(version);
+        }
+
+        let semantic_search_triples = search.send().await?.try_collect::<Vec<_>>().await?;
 
         let mut ids: Vec<String> = semantic_search_triples
             .into_iter()
@@ -552,46 +1371,344 @@ impl KnowledgeGraph {
         Ok(ids)
     }
 
+    /// Semantic search restricted to the entities reachable with the traversals
+    async fn search_with_traversals(
+        &self,
+        embedding: Vec<f64>,
+        traversal_filters: Vec<TraverseRelation>,
+        page: Page,
+        scope: &Scope,
+    ) -> Result<Vec<SearchWithTraversalsResult<Entity<BaseEntity>>>, ToolError> {
+        let mut search = traversal_filters
+            .into_iter()
+            .fold(
+                entity::search_from_restictions::<Entity<BaseEntity>>(&self.state.neo4j, embedding),
+                |query, traversal_filter| {
+                    query.filter(EntityFilter::default().traverse_relation(traversal_filter))
+                },
+            )
+            .skip(page.skip)
+            .limit(page.fetch_limit());
+        if let Some(space_filter) = scope.space_filter() {
+            search = search.space_id(space_filter);
+        }
+        if let Some(version) = scope.version() {
+            search = search.version(version);
+        }
+
+        Ok(search.send().await?.try_collect::<Vec<_>>().await?)
+    }
+
+    async fn find_relations(
+        &self,
+        filter: relation::RelationFilter,
+        skip: usize,
+        limit: usize,
+        scope: &Scope,
+    ) -> Result<Vec<RelationEdge<EntityNode>>, ToolError> {
+        let mut query = relation::find_many::<RelationEdge<EntityNode>>(&self.state.neo4j)
+            .filter(filter)
+            .version(scope.version())
+            .skip(skip)
+            .limit(limit);
+        if let Some(space_filter) = scope.space_filter() {
+            query = query.space_id(space_filter);
+        }
+
+        Ok(query.send().await?.try_collect::<Vec<_>>().await?)
+    }
+
+    /// Attributes of an entity in the scope. An attribute set in several spaces of the
+    /// scope takes the value of the closest space.
+    async fn find_attributes(
+        &self,
+        entity_id: &str,
+        scope: &Scope,
+    ) -> Result<Vec<triple::Triple>, ToolError> {
+        let mut query =
+            triple::find_many(&self.state.neo4j).entity_id(prop_filter::value(entity_id));
+        if let Some(space_filter) = scope.space_filter() {
+            query = query.space_id(space_filter);
+        }
+        if let Some(version) = scope.version() {
+            query = query.space_version(version);
+        }
+
+        let triples = query.send().await?.try_collect::<Vec<_>>().await?;
+        let (Some(space_id), [_, _, ..]) = (scope.space_id(), scope.spaces()) else {
+            return Ok(triples);
+        };
+
+        let mut seen = HashSet::new();
+        let duplicates = triples
+            .iter()
+            .filter(|triple| !seen.insert(&triple.attribute))
+            .map(|triple| triple.attribute.clone())
+            .collect::<HashSet<_>>();
+
+        let mut attributes = vec![];
+        let mut resolved = HashSet::new();
+        for triple in triples {
+            if !duplicates.contains(&triple.attribute) {
+                attributes.push(triple);
+            } else if resolved.insert(triple.attribute.clone()) {
+                let closest = triple::find_one(
+                    &self.state.neo4j,
+                    &triple.attribute,
+                    entity_id,
+                    space_id,
+                    scope.version(),
+                )
+                .pluralism(Pluralism::Hierarchy(scope.spaces().to_vec()))
+                .send()
+                .await?;
+                attributes.extend(closest);
+            }
+        }
+
+        Ok(attributes)
+    }
+
+    async fn scope(
+        &self,
+        space_id: Option<String>,
+        include_subspaces: Option<bool>,
+        as_of_block: Option<u64>,
+        as_of_time: Option<String>,
+    ) -> Result<Scope, ToolError> {
+        Scope::resolve(
+            &self.state.neo4j,
+            space_id.as_deref(),
+            include_subspaces.unwrap_or(false),
+            as_of_block,
+            as_of_time.as_deref(),
+        )
+        .await
+    }
+
+    /// Resolves an entity given by id or by name (the closest match is used)
+    async fn resolve_entity(&self, value: String) -> Result<ResolvedArgument, ToolError> {
+        let entity = entity::find_one::<Entity<BaseEntity>>(&self.state.neo4j, &value)
+            .send()
+            .await?;
+
+        let entity = match entity {
+            Some(entity) => Some(entity),
+            None => entity::prefiltered_search::<Entity<BaseEntity>>(
+                &self.state.neo4j,
+                self.embed(&value)?,
+            )
+            .limit(1)
+            .send()
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?
+            .pop()
+            .map(|result| result.entity),
+        };
+
+        let entity = entity.ok_or_else(|| ToolError::not_found("entity", &value))?;
+
+        Ok(ResolvedArgument {
+            value: entity.attributes.name.clone().unwrap_or(value),
+            id: Some(entity.id().to_string()),
+        })
+    }
+
+    /// Resolves a space given by id or by name (the closest match is used)
+    async fn resolve_space(&self, value: String) -> Result<ResolvedArgument, ToolError> {
+        let space = governance::find_space(&self.state.neo4j, &value, &Scope::default()).await?;
+
+        let space = match space {
+            Some(space) => Some(space),
+            None => governance::search_spaces(
+                &self.state.neo4j,
+                self.embed(&value)?,
+                0,
+                1,
+                &Scope::default(),
+            )
+            .await?
+            .pop(),
+        };
+
+        let space = space.ok_or_else(|| ToolError::not_found("space", &value))?;
+
+        Ok(ResolvedArgument {
+            value: space.name.unwrap_or(value),
+            id: Some(space.id),
+        })
+    }
+
+    fn embed(&self, query: &str) -> Result<Vec<f64>, ToolError> {
+        Ok(self
+            .state
+            .embedding_model
+            .embed(vec![query], None)
+            .map_err(|e| ToolError::Embedding(e.to_string()))?
+            .pop()
+            .ok_or_else(|| ToolError::Embedding("no embedding returned".to_string()))?
+            .into_iter()
+            .map(|v| v as f64)
+            .collect())
+    }
+
     fn dedup(&self, v: &mut Vec<String>) {
         let mut set = HashSet::new();
 
         v.retain(|x| set.insert(x.clone()));
     }
 
-    async fn format_from_id(&self, id: String) -> Value {
+    fn page(&self, cursor: Option<&str>, limit: Option<usize>) -> Result<Page, ToolError> {
+        Page::new(
+            cursor,
+            limit,
+            self.state.config.default_limit,
+            self.state.config.max_limit,
+        )
+    }
+
+    /// Result of a listing tool: the page of results fitting in the output size
+    fn output<T: Serialize>(
+        &self,
+        page: Page,
+        results: Vec<T>,
+    ) -> Result<CallToolResult, McpError> {
+        let output = page.output(results, self.state.config.max_output_size)?;
+        Ok(CallToolResult::success(vec![json_content(output)?]))
+    }
+
+    /// Content of a result that is not paginated, shortened to fit in the output size
+    fn bounded_content(&self, value: impl Serialize) -> Result<Content, ToolError> {
+        let mut value = pagination::to_value(value)?;
+        let truncated = pagination::shorten(&mut value, self.state.config.max_output_size);
+        if let (true, Some(object)) = (truncated, value.as_object_mut()) {
+            object.insert("truncated".to_string(), json!(true));
+        }
+        json_content(value)
+    }
+
+    /// Describes the entities with the given ids (in the same order)
+    async fn describe_ids(
+        &self,
+        ids: Vec<String>,
+        detail: Detail,
+        scope: &Scope,
+    ) -> Result<Vec<Value>, ToolError> {
+        if detail == Detail::Ids {
+            return Ok(ids.into_iter().map(|id| json!({ "id": id })).collect());
+        }
+
+        let limit = ids.len();
+        let mut query = entity::find_many::<Entity<BaseEntity>>(&self.state.neo4j)
+            .with_filter(EntityFilter::default().id(prop_filter::value_in(ids.clone())))
+            .version_opt(scope.version())
+            .limit(limit);
+        if let Some(space_filter) = scope.space_filter() {
+            query = query.space_id(space_filter);
+        }
+
+        let mut entities = query.send().await?.try_collect::<Vec<_>>().await?;
+        entities.sort_by_key(|entity| ids.iter().position(|id| id == entity.id()));
+
+        self.describe_entities(entities, detail, scope).await
+    }
+
+    async fn describe_entities(
+        &self,
+        entities: Vec<Entity<BaseEntity>>,
+        detail: Detail,
+        scope: &Scope,
+    ) -> Result<Vec<Value>, ToolError> {
+        join_all(
+            entities
+                .into_iter()
+                .map(|entity| self.describe_entity(entity, detail, scope)),
+        )
+        .await
+        .into_iter()
+        .collect()
+    }
+
+    /// Describes an entity: its id, name and description, with its types and attributes
+    /// when the detail level is `full`
+    async fn describe_entity(
+        &self,
+        entity: Entity<BaseEntity>,
+        detail: Detail,
+        scope: &Scope,
+    ) -> Result<Value, ToolError> {
+        let id = entity.id().to_string();
+        if detail == Detail::Ids {
+            return Ok(json!({ "id": id }));
+        }
+
         let mut value = json!({
-            "entity_not_found_with_id": id.clone(),
+            "id": id,
+            "name": entity.attributes.name,
+            "description": entity.attributes.description,
         });
+        if detail != Detail::Full {
+            return Ok(value);
+        }
 
-        let result_entity = entity::find_one::<Entity<BaseEntity>>(&self.neo4j, id)
-            .send()
-            .await;
+        let attributes = self
+            .find_attributes(&id, scope)
+            .await?
+            .into_iter()
+            .filter(|triple| {
+                triple.attribute != system_ids::NAME_ATTRIBUTE
+                    && triple.attribute != system_ids::DESCRIPTION_ATTRIBUTE
+            })
+            .collect::<Vec<_>>();
 
-        if let Ok(Some(entity_node)) = result_entity {
-            value = json!({
-                "entity_id": entity_node.id(),
-                "name": entity_node.attributes.name,
-                "description": entity_node.attributes.description
-            });
-        };
-        value
-    }
+        let types = self
+            .find_relations(
+                relation::RelationFilter::default()
+                    .from_(EntityFilter::default().id(prop_filter::value(&id)))
+                    .relation_type(
+                        EntityFilter::default().id(prop_filter::value(system_ids::TYPES_ATTRIBUTE)),
+                    ),
+                0,
+                self.state.config.max_limit,
+                scope,
+            )
+            .await?
+            .into_iter()
+            .map(|relation| relation.to.id)
+            .collect::<Vec<_>>();
 
-    async fn get_name_of_id(&self, id: String) -> Result<String, McpError> {
-        let entity = entity::find_one::<Entity<BaseEntity>>(&self.neo4j, &id)
-            .send()
-            .await
-            .map_err(|e| {
-                McpError::internal_error("get_entity_name", Some(json!({ "error": e.to_string() })))
-            })?
-            .ok_or_else(|| {
-                McpError::internal_error("entity_name_not_found", Some(json!({ "id": id })))
-            })?;
-        Ok(entity.attributes.name.unwrap_or("No name".to_string()))
+        let names = resources::find_names(
+            &self.state.neo4j,
+            attributes
+                .iter()
+                .map(|triple| triple.attribute.clone())
+                .chain(types.iter().cloned())
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect(),
+        )
+        .await?;
+
+        value["types"] = types
+            .into_iter()
+            .map(|id| json!({ "id": id, "name": names.get(&id) }))
+            .collect();
+        value["attributes"] = attributes
+            .into_iter()
+            .map(|triple| {
+                json!({
+                    "attribute_id": triple.attribute,
+                    "attribute_name": names.get(&triple.attribute),
+                    "attribute_value": triple.value.value,
+                })
+            })
+            .collect();
+
+        Ok(value)
     }
 }
 
-#[tool(tool_box)]
 impl ServerHandler for KnowledgeGraph {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
@@ -619,7 +1736,143 @@ impl ServerHandler for KnowledgeGraph {
         Ok(self.get_info())
     }
 
-    //TODO: make prompt examples to use on data
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        Ok(ListToolsResult {
+            next_cursor: None,
+            tools: Self::tool_box().list(),
+        })
+    }
+
+    /// Dispatches the call to the tool box, every call is logged to the `audit` target
+    /// and measured
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let tool = request.name.to_string();
+        let arguments = Value::Object(request.arguments.clone().unwrap_or_default());
+        let start = Instant::now();
+
+        let result = Self::tool_box()
+            .call(ToolCallContext::new(self, request, context))
+            .await;
+
+        let duration = start.elapsed();
+        let code = match &result {
+            Ok(_) => "ok",
+            Err(error) => error_code(error),
+        };
+
+        // Unknown tools share a label to bound the cardinality of the metrics
+        let tool_label = if Self::tool_box().map.contains_key(tool.as_str()) {
+            tool.as_str()
+        } else {
+            "unknown"
+        };
+        metrics::TOOL_CALLS
+            .with_label_values(&[tool_label, code])
+            .inc();
+        metrics::TOOL_CALL_DURATION
+            .with_label_values(&[tool_label, code])
+            .observe(duration.as_secs_f64());
+
+        tracing::info!(
+            target: "audit",
+            tool = %tool,
+            %arguments,
+            code,
+            duration_ms = duration.as_millis() as u64,
+            error = result.as_ref().err().map(|error| error.message.as_ref()),
+            "tool call"
+        );
+
+        result
+    }
+
+    async fn list_resources(
+        &self,
+        request: Option<PaginatedRequestParam>,
+        _: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, McpError> {
+        // The cursor is the number of spaces already listed
+        let skip = match request.and_then(|request| request.cursor) {
+            Some(cursor) => cursor
+                .parse::<usize>()
+                .map_err(|_| McpError::invalid_params("invalid cursor", None))?,
+            None => 0,
+        };
+        let limit = self.state.config.default_limit;
+
+        let resources = resources::list(&self.state.neo4j, skip, limit)
+            .await
+            .map_err(ToolError::from)?;
+
+        // Only the space resources count towards the page size
+        let spaces = resources
+            .iter()
+            .filter(|resource| resource.uri.starts_with(resources::SPACE_URI_PREFIX))
+            .count();
+
+        Ok(ListResourcesResult {
+            next_cursor: (spaces == limit).then(|| (skip + limit).to_string()),
+            resources,
+        })
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _: RequestContext<RoleServer>,
+    ) -> Result<ListResourceTemplatesResult, McpError> {
+        Ok(ListResourceTemplatesResult {
+            next_cursor: None,
+            resource_templates: resources::templates(),
+        })
+    }
+
+    async fn read_resource(
+        &self,
+        ReadResourceRequestParam { uri }: ReadResourceRequestParam,
+        _: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        let (Some(resource_uri), Some(params)) =
+            (ResourceUri::parse(&uri), ScopeParams::parse(&uri))
+        else {
+            return Err(McpError::invalid_params(
+                "invalid resource uri",
+                Some(json!({ "uri": uri })),
+            ));
+        };
+        let scope = self
+            .scope(
+                params.space_id,
+                Some(params.include_subspaces),
+                params.as_of_block,
+                params.as_of_time,
+            )
+            .await?;
+
+        let text = resources::read(&self.state.neo4j, &resource_uri, &scope)
+            .await
+            .map_err(ToolError::from)?
+            .ok_or_else(|| {
+                McpError::resource_not_found("resource_not_found", Some(json!({ "uri": uri })))
+            })?;
+
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::TextResourceContents {
+                uri,
+                mime_type: Some(resources::MIME_TYPE.to_string()),
+                text,
+            }],
+        })
+    }
+
     async fn list_prompts(
         &self,
         _request: Option<PaginatedRequestParam>,
@@ -627,15 +1880,7 @@ impl ServerHandler for KnowledgeGraph {
     ) -> Result<ListPromptsResult, McpError> {
         Ok(ListPromptsResult {
             next_cursor: None,
-            prompts: vec![Prompt::new(
-                "example_prompt",
-                Some("This is an example prompt that takes one required argument, message"),
-                Some(vec![PromptArgument {
-                    name: "message".to_string(),
-                    description: Some("A message to put in the prompt".to_string()),
-                    required: Some(true),
-                }]),
-            )],
+            prompts: prompts::list(),
         })
     }
 
@@ -644,27 +1889,75 @@ impl ServerHandler for KnowledgeGraph {
         GetPromptRequestParam { name, arguments }: GetPromptRequestParam,
         _: RequestContext<RoleServer>,
     ) -> Result<GetPromptResult, McpError> {
-        match name.as_str() {
-            "example_prompt" => {
-                let message = arguments
-                    .and_then(|json| json.get("message")?.as_str().map(|s| s.to_string()))
-                    .ok_or_else(|| {
-                        McpError::invalid_params("No message provided to example_prompt", None)
-                    })?;
-
-                let prompt =
-                    format!("This is an example prompt with your message here: '{message}'");
-                Ok(GetPromptResult {
-                    description: None,
-                    messages: vec![PromptMessage {
-                        role: PromptMessageRole::User,
-                        content: PromptMessageContent::text(prompt),
-                    }],
-                })
-            }
-            _ => Err(McpError::invalid_params("prompt not found", None)),
+        let prompt = prompts::find(&name).map_err(prompt_error)?;
+        let arguments = prompt
+            .validate(&arguments.unwrap_or_default())
+            .map_err(prompt_error)?;
+
+        let mut resolved = Vec::with_capacity(arguments.len());
+        for (spec, value) in arguments {
+            let argument = match spec.kind {
+                ArgumentKind::Entity => self.resolve_entity(value).await?,
+                ArgumentKind::Space => self.resolve_space(value).await?,
+                ArgumentKind::Text => ResolvedArgument { value, id: None },
+            };
+            resolved.push((spec.name, argument));
         }
+
+        Ok(GetPromptResult {
+            description: Some(prompt.description.to_string()),
+            messages: vec![PromptMessage {
+                role: PromptMessageRole::User,
+                content: PromptMessageContent::text(prompt.render(&resolved)),
+            }],
+        })
+    }
+}
+
+fn prompt_error(error: PromptError) -> McpError {
+    McpError::invalid_params(error.to_string(), None)
+}
+
+/// Keeps only the ids of the results when the detail level is `ids`
+fn select_detail<T: Serialize>(results: Vec<T>, detail: Detail) -> Result<Vec<Value>, ToolError> {
+    results
+        .into_iter()
+        .map(|result| {
+            let value = pagination::to_value(result)?;
+            Ok(match detail {
+                Detail::Ids => json!({ "id": value["id"] }),
+                _ => value,
+            })
+        })
+        .collect()
+}
+
+/// Describes a property of a type: its value type and, for relation properties, the type
+/// of the entities it points to
+fn describe_property(property: PropertySchema) -> Value {
+    let summary = |entity: Entity<BaseEntity>| {
+        json!({
+            "id": entity.id(),
+            "name": entity.attributes.name,
+        })
+    };
+
+    let mut value = json!({
+        "id": property.id(),
+        "name": property.property.attributes.name,
+        "description": property.property.attributes.description,
+        "value_type": property.value_type.map(summary),
+    });
+    if let Some(relation_value_type) = property.relation_value_type {
+        value["relation_value_type"] = summary(relation_value_type);
     }
+
+    value
+}
+
+/// Serializes a tool result
+fn json_content(value: impl Serialize) -> Result<Content, ToolError> {
+    Content::json(value).map_err(|e| ToolError::Serialization(e.message.to_string()))
 }
 
 #[derive(Debug, Parser)]
@@ -672,19 +1965,35 @@ impl ServerHandler for KnowledgeGraph {
 struct AppArgs {
     #[clap(flatten)]
     neo4j_args: Neo4jArgs,
-}
 
-#[derive(Debug, Args)]
-struct Neo4jArgs {
-    /// Neo4j database host
-    #[arg(long)]
-    neo4j_uri: String,
+    /// MCP transport (use stdio when the server is launched by the MCP client)
+    #[arg(long, value_enum, default_value = "sse")]
+    transport: Transport,
+
+    /// Address on which the SSE and streamable HTTP transports listen
+    #[arg(long, env = "MCP_BIND_ADDRESS", default_value = "0.0.0.0:8080")]
+    bind: SocketAddr,
+
+    /// Number of results returned by the listing tools when no limit is given
+    #[arg(long, default_value = "10")]
+    default_limit: usize,
 
-    /// Neo4j database user name
-    #[arg(long)]
-    neo4j_user: String,
+    /// Maximum number of results returned by the listing tools
+    #[arg(long, default_value = "100")]
+    max_limit: usize,
+
+    /// Maximum size in bytes of a tool result
+    #[arg(long, default_value = "20000")]
+    max_output_size: usize,
+
+    /// Address on which the Prometheus metrics are served (default: metrics are not served)
+    #[arg(long, env = "MCP_METRICS_ADDRESS")]
+    metrics_bind: Option<SocketAddr>,
+}
 
-    /// Neo4j database user password
-    #[arg(long)]
-    neo4j_pass: String,
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Transport {
+    Stdio,
+    Sse,
+    StreamableHttp,
 }
@@ -0,0 +1,38 @@
+use axum::http::{StatusCode, header};
+use axum::response::Response;
+use lazy_static::lazy_static;
+use prometheus::{
+    Encoder, HistogramVec, IntCounterVec, TextEncoder, register_histogram_vec,
+    register_int_counter_vec,
+};
+
+lazy_static! {
+    pub static ref TOOL_CALLS: IntCounterVec = register_int_counter_vec!(
+        "mcp_tool_calls_total",
+        "Number of MCP tool calls by tool and result code",
+        &["tool", "code"]
+    )
+    .expect("Failed to create mcp_tool_calls_total counter");
+    pub static ref TOOL_CALL_DURATION: HistogramVec = register_histogram_vec!(
+        "mcp_tool_call_duration_seconds",
+        "Time spent handling MCP tool calls",
+        &["tool", "code"],
+        vec![0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0]
+    )
+    .expect("Failed to create mcp_tool_call_duration_seconds histogram");
+}
+
+pub async fn metrics_handler() -> Response<String> {
+    let encoder = TextEncoder::new();
+    let metric_families = prometheus::gather();
+    let mut buffer = vec![];
+    encoder
+        .encode(&metric_families, &mut buffer)
+        .expect("Failed to encode metrics");
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, encoder.format_type())
+        .body(String::from_utf8(buffer).unwrap())
+        .expect("Failed to create metrics response")
+}
@@ -0,0 +1,286 @@
+//! Pagination and output size of the MCP tools. Cursors are opaque to the clients: they
+//! are the offsets of the next results (one offset per paginated list, separated by `:`).
+//! Results that do not fit in the output size are left for the next page and the output
+//! is marked as truncated.
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::ToolError;
+
+/// Appended to the strings shortened to fit in the output size
+pub const TRUNCATION_MARKER: &str = "… [truncated]";
+
+/// Strings are not shortened below this number of characters
+const MIN_STRING_LENGTH: usize = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Page {
+    pub skip: usize,
+    pub limit: usize,
+}
+
+/// Page of results sent to the client
+#[derive(Debug, Serialize)]
+pub struct Output {
+    pub results: Vec<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    /// Set when results were left out or shortened to fit in the output size
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+    /// Offset of the next result, if any
+    #[serde(skip)]
+    pub next_skip: Option<usize>,
+}
+
+impl Page {
+    /// Page starting at the cursor. The limit defaults to `default_limit` and is capped
+    /// at `max_limit`.
+    pub fn new(
+        cursor: Option<&str>,
+        limit: Option<usize>,
+        default_limit: usize,
+        max_limit: usize,
+    ) -> Result<Self, ToolError> {
+        let [skip] = decode_cursor(cursor)?;
+        Ok(Self {
+            skip,
+            limit: check_limit(limit, default_limit, max_limit)?,
+        })
+    }
+
+    /// Number of results to fetch, one more than the limit to know if there is a next page
+    pub fn fetch_limit(&self) -> usize {
+        self.limit + 1
+    }
+
+    /// Keeps the results of the page (at most `fetch_limit` results are expected) that fit
+    /// in `max_output_size` bytes
+    pub fn output<T: Serialize>(
+        &self,
+        results: Vec<T>,
+        max_output_size: usize,
+    ) -> Result<Output, ToolError> {
+        let has_more = results.len() > self.limit;
+        let mut results = results
+            .into_iter()
+            .take(self.limit)
+            .map(to_value)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let fetched = results.len();
+        let (kept, truncated) = fit(&mut results, max_output_size);
+        let next_skip = (has_more || kept < fetched).then_some(self.skip + kept);
+
+        Ok(Output {
+            results,
+            next_cursor: next_skip.map(|skip| encode_cursor([skip])),
+            truncated,
+            next_skip,
+        })
+    }
+}
+
+pub fn check_limit(
+    limit: Option<usize>,
+    default_limit: usize,
+    max_limit: usize,
+) -> Result<usize, ToolError> {
+    match limit {
+        Some(0) => Err(ToolError::InvalidArgument(
+            "limit must be greater than 0".to_string(),
+        )),
+        Some(limit) => Ok(limit.min(max_limit)),
+        None => Ok(default_limit.min(max_limit)),
+    }
+}
+
+/// Decodes the `N` offsets of a cursor, no cursor starts all the lists at 0
+pub fn decode_cursor<const N: usize>(cursor: Option<&str>) -> Result<[usize; N], ToolError> {
+    let mut offsets = [0; N];
+    let Some(cursor) = cursor else {
+        return Ok(offsets);
+    };
+
+    let invalid = || ToolError::InvalidArgument(format!("invalid cursor {cursor:?}"));
+    let mut parts = cursor.split(':');
+    for offset in offsets.iter_mut() {
+        *offset = parts
+            .next()
+            .and_then(|part| part.parse().ok())
+            .ok_or_else(invalid)?;
+    }
+
+    match parts.next() {
+        Some(_) => Err(invalid()),
+        None => Ok(offsets),
+    }
+}
+
+pub fn encode_cursor<const N: usize>(offsets: [usize; N]) -> String {
+    offsets
+        .iter()
+        .map(usize::to_string)
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+pub fn to_value<T: Serialize>(value: T) -> Result<Value, ToolError> {
+    serde_json::to_value(value).map_err(|e| ToolError::Serialization(e.to_string()))
+}
+
+/// Keeps the first values fitting in `max_size` bytes. A first value larger than
+/// `max_size` is shortened so that the client can move on to the next page. Returns the
+/// number of values kept and whether values were left out or shortened.
+pub fn fit(values: &mut Vec<Value>, max_size: usize) -> (usize, bool) {
+    let mut truncated = false;
+    if let Some(first) = values.first_mut() {
+        truncated = shorten(first, max_size);
+    }
+
+    let mut size = 0;
+    let kept = values
+        .iter()
+        .take_while(|value| {
+            size += value.to_string().len() + 1;
+            size <= max_size
+        })
+        .count()
+        .max(values.len().min(1));
+
+    truncated |= kept < values.len();
+    values.truncate(kept);
+
+    (kept, truncated)
+}
+
+/// Shortens the strings of the value until its serialization fits in `max_size` bytes
+/// (or the strings cannot be shortened anymore). Returns whether the value was shortened.
+pub fn shorten(value: &mut Value, max_size: usize) -> bool {
+    let mut shortened = false;
+    let mut max_length = max_size / 2;
+
+    while value.to_string().len() > max_size && max_length >= MIN_STRING_LENGTH {
+        shortened |= shorten_strings(value, max_length);
+        max_length /= 2;
+    }
+
+    shortened
+}
+
+fn shorten_strings(value: &mut Value, max_length: usize) -> bool {
+    match value {
+        Value::String(string)
+            if string.chars().count() > max_length + TRUNCATION_MARKER.chars().count() =>
+        {
+            *string = string
+                .chars()
+                .take(max_length)
+                .chain(TRUNCATION_MARKER.chars())
+                .collect();
+            true
+        }
+        Value::Array(values) => values.iter_mut().fold(false, |shortened, value| {
+            shorten_strings(value, max_length) | shortened
+        }),
+        Value::Object(map) => map.values_mut().fold(false, |shortened, value| {
+            shorten_strings(value, max_length) | shortened
+        }),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        assert_eq!(
+            decode_cursor::<2>(Some(&encode_cursor([3, 15]))).unwrap(),
+            [3, 15]
+        );
+        assert_eq!(decode_cursor::<1>(Some(&encode_cursor([0]))).unwrap(), [0]);
+        assert_eq!(decode_cursor::<3>(None).unwrap(), [0, 0, 0]);
+    }
+
+    #[test]
+    fn test_invalid_cursors() {
+        assert!(decode_cursor::<1>(Some("")).is_err());
+        assert!(decode_cursor::<1>(Some("abc")).is_err());
+        assert!(decode_cursor::<1>(Some("-1")).is_err());
+        // Wrong number of offsets
+        assert!(decode_cursor::<2>(Some("1")).is_err());
+        assert!(decode_cursor::<1>(Some("1:2")).is_err());
+    }
+
+    #[test]
+    fn test_fit_keeps_values_fitting_in_size() {
+        let mut values = vec![json!("a"), json!("b"), json!("c")];
+
+        // Each value takes 4 bytes: its serialization and a separator
+        assert_eq!(fit(&mut values, 8), (2, true));
+        assert_eq!(values, vec![json!("a"), json!("b")]);
+
+        let mut values = vec![json!("a"), json!("b")];
+        assert_eq!(fit(&mut values, 100), (2, false));
+    }
+
+    #[test]
+    fn test_fit_keeps_oversized_first_value() {
+        // Strings are shortened so that the first value fits
+        let mut values = vec![
+            json!({"description": "x".repeat(1000)}),
+            json!("y".repeat(200)),
+        ];
+        let (kept, truncated) = fit(&mut values, 200);
+
+        assert_eq!(kept, 1);
+        assert!(truncated);
+        assert!(values[0].to_string().len() <= 200);
+        assert!(
+            values[0]["description"]
+                .as_str()
+                .unwrap()
+                .ends_with(TRUNCATION_MARKER)
+        );
+
+        // Values which cannot be shortened are still kept so that the client can move
+        // on to the next page
+        let numbers = json!((0..100).collect::<Vec<_>>());
+        let mut values = vec![numbers.clone(), json!("b")];
+
+        assert_eq!(fit(&mut values, 10), (1, true));
+        assert_eq!(values, vec![numbers]);
+    }
+
+    #[test]
+    fn test_output_next_cursor() {
+        let page = Page::new(Some("10"), Some(2), 10, 100).unwrap();
+        assert_eq!(page.fetch_limit(), 3);
+
+        // One more result than the limit: there is a next page
+        let output = page.output(vec!["a", "b", "c"], 1000).unwrap();
+        assert_eq!(output.results, vec![json!("a"), json!("b")]);
+        assert_eq!(output.next_cursor.as_deref(), Some("12"));
+        assert!(!output.truncated);
+
+        // Results left out to fit in the output size are on the next page
+        let output = page.output(vec!["a", "b"], 4).unwrap();
+        assert_eq!(output.results, vec![json!("a")]);
+        assert_eq!(output.next_cursor.as_deref(), Some("11"));
+        assert!(output.truncated);
+
+        let output = page.output(vec!["a"], 1000).unwrap();
+        assert_eq!(output.next_cursor, None);
+    }
+
+    #[test]
+    fn test_check_limit() {
+        assert_eq!(check_limit(None, 10, 100).unwrap(), 10);
+        assert_eq!(check_limit(Some(500), 10, 100).unwrap(), 100);
+        assert!(check_limit(Some(0), 10, 100).is_err());
+    }
+}
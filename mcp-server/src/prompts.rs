@@ -0,0 +1,316 @@
+//! Prompt library of the MCP server. Prompts are markdown templates (see
+//! `resources/prompts`) in which `{{argument}}` is replaced by the value of the argument.
+//! Entity and space arguments are resolved against the graph and also expose their id
+//! as `{{argument_id}}`.
+use rmcp::model::{Prompt, PromptArgument};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArgumentKind {
+    /// Name or id of an entity
+    Entity,
+    /// Name or id of a space
+    Space,
+    /// Free text
+    Text,
+}
+
+pub struct ArgumentSpec {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub kind: ArgumentKind,
+}
+
+pub struct PromptTemplate {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// All arguments are required
+    pub arguments: &'static [ArgumentSpec],
+    pub template: &'static str,
+}
+
+/// Argument resolved against the graph
+#[derive(Clone, Debug)]
+pub struct ResolvedArgument {
+    pub value: String,
+    pub id: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PromptError {
+    NotFound(String),
+    MissingArgument(&'static str),
+    UnknownArgument(String),
+    InvalidArgument(String),
+}
+
+impl std::fmt::Display for PromptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PromptError::NotFound(name) => write!(f, "prompt {name} not found"),
+            PromptError::MissingArgument(name) => write!(f, "missing argument {name}"),
+            PromptError::UnknownArgument(name) => write!(f, "unknown argument {name}"),
+            PromptError::InvalidArgument(name) => write!(f, "argument {name} must be a string"),
+        }
+    }
+}
+
+pub const PROMPTS: &[PromptTemplate] = &[
+    PromptTemplate {
+        name: "explore_entity",
+        description: "Give an overview of an entity, its attributes and the entities related to it",
+        arguments: &[ArgumentSpec {
+            name: "entity",
+            description: "Name or id of the entity to explore",
+            kind: ArgumentKind::Entity,
+        }],
+        template: include_str!("../resources/prompts/explore_entity.md"),
+    },
+    PromptTemplate {
+        name: "compare_entities",
+        description: "Compare two entities and explain how they are connected",
+        arguments: &[
+            ArgumentSpec {
+                name: "entity_a",
+                description: "Name or id of the first entity",
+                kind: ArgumentKind::Entity,
+            },
+            ArgumentSpec {
+                name: "entity_b",
+                description: "Name or id of the second entity",
+                kind: ArgumentKind::Entity,
+            },
+        ],
+        template: include_str!("../resources/prompts/compare_entities.md"),
+    },
+    PromptTemplate {
+        name: "summarize_space",
+        description: "Summarize the topics, hierarchy and governance of a space",
+        arguments: &[ArgumentSpec {
+            name: "space",
+            description: "Name or id of the space to summarize",
+            kind: ArgumentKind::Space,
+        }],
+        template: include_str!("../resources/prompts/summarize_space.md"),
+    },
+    PromptTemplate {
+        name: "find_relation",
+        description: "Find how an entity relates to another entity",
+        arguments: &[
+            ArgumentSpec {
+                name: "from",
+                description: "Name or id of the first entity",
+                kind: ArgumentKind::Entity,
+            },
+            ArgumentSpec {
+                name: "to",
+                description: "Name or id of the second entity",
+                kind: ArgumentKind::Entity,
+            },
+        ],
+        template: include_str!("../resources/prompts/find_relation.md"),
+    },
+    PromptTemplate {
+        name: "draft_edit",
+        description: "Draft an edit changing an entity with the draft_edit tool",
+        arguments: &[
+            ArgumentSpec {
+                name: "entity",
+                description: "Name or id of the entity to edit",
+                kind: ArgumentKind::Entity,
+            },
+            ArgumentSpec {
+                name: "change",
+                description: "Description of the change to make",
+                kind: ArgumentKind::Text,
+            },
+        ],
+        template: include_str!("../resources/prompts/draft_edit.md"),
+    },
+];
+
+pub fn find(name: &str) -> Result<&'static PromptTemplate, PromptError> {
+    PROMPTS
+        .iter()
+        .find(|prompt| prompt.name == name)
+        .ok_or_else(|| PromptError::NotFound(name.to_string()))
+}
+
+pub fn list() -> Vec<Prompt> {
+    PROMPTS.iter().map(PromptTemplate::to_prompt).collect()
+}
+
+impl PromptTemplate {
+    pub fn to_prompt(&self) -> Prompt {
+        Prompt::new(
+            self.name,
+            Some(self.description),
+            Some(
+                self.arguments
+                    .iter()
+                    .map(|argument| PromptArgument {
+                        name: argument.name.to_string(),
+                        description: Some(argument.description.to_string()),
+                        required: Some(true),
+                    })
+                    .collect(),
+            ),
+        )
+    }
+
+    /// Checks that all the arguments are given as strings and that there are no unknown
+    /// arguments. Returns the arguments in the order of the specification.
+    pub fn validate<'a>(
+        &'a self,
+        arguments: &serde_json::Map<String, serde_json::Value>,
+    ) -> Result<Vec<(&'a ArgumentSpec, String)>, PromptError> {
+        if let Some(unknown) = arguments
+            .keys()
+            .find(|name| !self.arguments.iter().any(|spec| &spec.name == name))
+        {
+            return Err(PromptError::UnknownArgument(unknown.clone()));
+        }
+
+        self.arguments
+            .iter()
+            .map(|spec| match arguments.get(spec.name) {
+                Some(serde_json::Value::String(value)) if !value.trim().is_empty() => {
+                    Ok((spec, value.trim().to_string()))
+                }
+                Some(serde_json::Value::String(_)) | None => {
+                    Err(PromptError::MissingArgument(spec.name))
+                }
+                Some(_) => Err(PromptError::InvalidArgument(spec.name.to_string())),
+            })
+            .collect()
+    }
+
+    pub fn render(&self, arguments: &[(&str, ResolvedArgument)]) -> String {
+        arguments
+            .iter()
+            .fold(self.template.to_string(), |text, (name, argument)| {
+                let text = text.replace(&format!("{{{{{name}}}}}"), &argument.value);
+                match &argument.id {
+                    Some(id) => text.replace(&format!("{{{{{name}_id}}}}"), id),
+                    None => text,
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn arguments(value: serde_json::Value) -> serde_json::Map<String, serde_json::Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_validate() {
+        let prompt = find("draft_edit").unwrap();
+
+        let validated = prompt
+            .validate(&arguments(
+                json!({"change": "Add a founder ", "entity": " Geo"}),
+            ))
+            .unwrap();
+        assert_eq!(
+            validated
+                .iter()
+                .map(|(spec, value)| (spec.name, value.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("entity", "Geo"), ("change", "Add a founder")]
+        );
+
+        assert_eq!(
+            prompt.validate(&arguments(json!({"entity": "Geo"}))).err(),
+            Some(PromptError::MissingArgument("change"))
+        );
+        assert_eq!(
+            prompt
+                .validate(&arguments(json!({"entity": "Geo", "change": "  "})))
+                .err(),
+            Some(PromptError::MissingArgument("change"))
+        );
+        assert_eq!(
+            prompt
+                .validate(&arguments(json!({"entity": 1, "change": "Add"})))
+                .err(),
+            Some(PromptError::InvalidArgument("entity".to_string()))
+        );
+        assert_eq!(
+            prompt
+                .validate(&arguments(
+                    json!({"entity": "Geo", "change": "Add", "space": "Root"})
+                ))
+                .err(),
+            Some(PromptError::UnknownArgument("space".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let prompt = find("draft_edit").unwrap();
+
+        let text = prompt.render(&[
+            (
+                "entity",
+                ResolvedArgument {
+                    value: "Geo".to_string(),
+                    id: Some("LuBWqZAu6pz54eiJS5mLv8".to_string()),
+                },
+            ),
+            (
+                "change",
+                ResolvedArgument {
+                    value: "Add a founder".to_string(),
+                    id: None,
+                },
+            ),
+        ]);
+
+        assert!(text.contains("\"Geo\" (id: LuBWqZAu6pz54eiJS5mLv8)"));
+        assert!(text.contains("get_entity_info with the id LuBWqZAu6pz54eiJS5mLv8"));
+        assert!(text.contains("Add a founder"));
+        assert!(!text.contains("{{"));
+    }
+
+    #[test]
+    fn test_templates_only_use_their_arguments() {
+        for prompt in PROMPTS {
+            let resolved = prompt
+                .arguments
+                .iter()
+                .map(|spec| {
+                    let id = (spec.kind != ArgumentKind::Text).then(|| format!("{}-id", spec.name));
+                    (
+                        spec.name,
+                        ResolvedArgument {
+                            value: spec.name.to_string(),
+                            id,
+                        },
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            let text = prompt.render(&resolved);
+            assert!(
+                !text.contains("{{"),
+                "{} has unknown arguments",
+                prompt.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_find() {
+        assert_eq!(find("explore_entity").unwrap().name, "explore_entity");
+        assert_eq!(
+            find("unknown").err(),
+            Some(PromptError::NotFound("unknown".to_string()))
+        );
+        assert_eq!(list().len(), PROMPTS.len());
+    }
+}
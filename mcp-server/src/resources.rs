@@ -0,0 +1,526 @@
+//! MCP resources exposing the knowledge graph as `graph://` URIs:
+//! - `graph://<entity_id>`: an entity rendered as markdown
+//! - `graph://space/<space_id>`: a space and its root entities
+//!
+//! Both accept the scope parameters of the tools as a query string, e.g.:
+//! `graph://<entity_id>?space_id=<space_id>&include_subspaces=true&as_of_block=<block>`.
+use std::collections::HashMap;
+
+use futures::TryStreamExt;
+use grc20_core::{
+    connection::Graph,
+    entity::{self, Entity, EntityFilter, EntityNode, TypesFilter},
+    error::DatabaseError,
+    graph_uri::GraphUri,
+    indexer_ids,
+    mapping::{PropFilter, Query, QueryStream, RelationEdge, prop_filter, triple},
+    relation, system_ids,
+};
+use grc20_sdk::models::{BaseEntity, Space};
+use rmcp::model::{AnnotateAble, RawResource, RawResourceTemplate, Resource, ResourceTemplate};
+
+use crate::scope::Scope;
+
+pub const MIME_TYPE: &str = "text/markdown";
+
+/// Prefix of space URIs (entity URIs are plain `graph://` URIs)
+pub const SPACE_URI_PREFIX: &str = "graph://space/";
+
+/// Maximum number of relations rendered for an entity
+const MAX_RELATIONS: usize = 100;
+
+/// Maximum number of root entities listed for a space
+const MAX_ROOT_ENTITIES: usize = 10;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ResourceUri {
+    Entity(String),
+    Space(String),
+}
+
+/// Scope parameters of a resource URI, resolved into a [`Scope`] like the ones of the tools
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ScopeParams {
+    pub space_id: Option<String>,
+    pub include_subspaces: bool,
+    pub as_of_block: Option<u64>,
+    /// RFC 3339 date and time, e.g. `2025-01-31T00:00:00Z`
+    pub as_of_time: Option<String>,
+}
+
+impl ScopeParams {
+    /// Parses the query string of a resource URI, returns `None` if a parameter is
+    /// unknown or invalid
+    pub fn parse(uri: &str) -> Option<Self> {
+        let mut params = Self::default();
+        let Some((_, query)) = uri.split_once('?') else {
+            return Some(params);
+        };
+
+        for param in query.split('&').filter(|param| !param.is_empty()) {
+            let (key, value) = param.split_once('=')?;
+            match key {
+                "space_id" => params.space_id = Some(value.to_string()),
+                "include_subspaces" => params.include_subspaces = value.parse().ok()?,
+                "as_of_block" => params.as_of_block = Some(value.parse().ok()?),
+                "as_of_time" => params.as_of_time = Some(value.to_string()),
+                _ => return None,
+            }
+        }
+
+        Some(params)
+    }
+}
+
+impl ResourceUri {
+    /// Parses the URI of a resource, ignoring its scope parameters
+    pub fn parse(uri: &str) -> Option<Self> {
+        let uri = uri.split_once('?').map_or(uri, |(uri, _)| uri);
+
+        if let Some(space_id) = uri.strip_prefix(SPACE_URI_PREFIX) {
+            return (!space_id.is_empty()).then(|| Self::Space(space_id.to_string()));
+        }
+
+        let id = GraphUri::from_uri(uri).ok()?.id;
+        (!id.is_empty() && !id.contains('/')).then_some(Self::Entity(id))
+    }
+
+    pub fn space_uri(space_id: &str) -> String {
+        format!("{SPACE_URI_PREFIX}{space_id}")
+    }
+
+    pub fn entity_uri(entity_id: &str) -> String {
+        GraphUri::from_id_str(entity_id).to_string()
+    }
+}
+
+/// Optional scope parameters of the resource templates (RFC 6570 query expansion)
+const SCOPE_TEMPLATE: &str = "{?space_id,include_subspaces,as_of_block,as_of_time}";
+
+/// Resource templates of entities and spaces
+pub fn templates() -> Vec<ResourceTemplate> {
+    vec![
+        RawResourceTemplate {
+            uri_template: format!("graph://{{entity_id}}{SCOPE_TEMPLATE}"),
+            name: "Entity".to_string(),
+            description: Some(
+                "An entity of the knowledge graph with its types, attributes, relations and content blocks"
+                    .to_string(),
+            ),
+            mime_type: Some(MIME_TYPE.to_string()),
+        }
+        .no_annotation(),
+        RawResourceTemplate {
+            uri_template: format!("{SPACE_URI_PREFIX}{{space_id}}{SCOPE_TEMPLATE}"),
+            name: "Space".to_string(),
+            description: Some("A space of the knowledge graph and its root entities".to_string()),
+            mime_type: Some(MIME_TYPE.to_string()),
+        }
+        .no_annotation(),
+    ]
+}
+
+/// Lists a page of spaces, each followed by its root entities
+pub async fn list(
+    neo4j: &Graph,
+    skip: usize,
+    limit: usize,
+) -> Result<Vec<Resource>, DatabaseError> {
+    let spaces = find_spaces(neo4j, skip, limit).await?;
+
+    let mut resources = Vec::new();
+    for space in spaces {
+        let root_entities = find_root_entities(neo4j, space.id(), &Scope::default()).await?;
+        let space_name = root_entities
+            .iter()
+            .find_map(|entity| entity.attributes.name.clone())
+            .unwrap_or_else(|| space.id().to_string());
+
+        resources.push(resource(
+            ResourceUri::space_uri(space.id()),
+            format!("Space: {space_name}"),
+            Some(format!(
+                "Space {} on {}",
+                space.id(),
+                space.attributes.network
+            )),
+        ));
+
+        resources.extend(root_entities.into_iter().map(|entity| {
+            let id = entity.id().to_string();
+            resource(
+                ResourceUri::entity_uri(&id),
+                entity.attributes.name.unwrap_or(id),
+                entity.attributes.description,
+            )
+        }));
+    }
+
+    Ok(resources)
+}
+
+/// Renders the resource in the scope as markdown, returns `None` if it does not exist.
+/// The spaces of the scope restrict the attributes and relations of an entity, only the
+/// version applies to a space.
+pub async fn read(
+    neo4j: &Graph,
+    uri: &ResourceUri,
+    scope: &Scope,
+) -> Result<Option<String>, DatabaseError> {
+    match uri {
+        ResourceUri::Entity(id) => render_entity(neo4j, id, scope).await,
+        ResourceUri::Space(id) => render_space(neo4j, id, scope).await,
+    }
+}
+
+fn resource(uri: String, name: String, description: Option<String>) -> Resource {
+    let mut resource = RawResource::new(uri, name);
+    resource.description = description;
+    resource.mime_type = Some(MIME_TYPE.to_string());
+    resource.no_annotation()
+}
+
+async fn find_spaces(
+    neo4j: &Graph,
+    skip: usize,
+    limit: usize,
+) -> Result<Vec<Entity<Space>>, DatabaseError> {
+    entity::find_many::<Entity<Space>>(neo4j)
+        .with_filter(
+            EntityFilter::default()
+                .relations(TypesFilter::default().r#type(system_ids::SPACE_TYPE)),
+        )
+        .space_id(indexer_ids::INDEXER_SPACE_ID)
+        .skip(skip)
+        .limit(limit)
+        .send()
+        .await?
+        .try_collect()
+        .await
+}
+
+/// Root entities are the entities of the space with the `Space` type
+pub(crate) async fn find_root_entities(
+    neo4j: &Graph,
+    space_id: &str,
+    scope: &Scope,
+) -> Result<Vec<Entity<BaseEntity>>, DatabaseError> {
+    entity::find_many::<Entity<BaseEntity>>(neo4j)
+        .with_filter(
+            EntityFilter::default()
+                .relations(TypesFilter::default().r#type(system_ids::SPACE_TYPE)),
+        )
+        .space_id(space_id)
+        .version_opt(scope.version())
+        .limit(MAX_ROOT_ENTITIES)
+        .send()
+        .await?
+        .try_collect()
+        .await
+}
+
+/// Names of the given entities, entities without a name are omitted
+pub async fn find_names(
+    neo4j: &Graph,
+    ids: Vec<String>,
+) -> Result<HashMap<String, String>, DatabaseError> {
+    if ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let limit = ids.len();
+    Ok(entity::find_many::<Entity<BaseEntity>>(neo4j)
+        .with_filter(EntityFilter::default().id(prop_filter::value_in(ids)))
+        .limit(limit)
+        .send()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?
+        .into_iter()
+        .filter_map(|entity| {
+            let id = entity.id().to_string();
+            entity.attributes.name.map(|name| (id, name))
+        })
+        .collect())
+}
+
+async fn render_entity(
+    neo4j: &Graph,
+    id: &str,
+    scope: &Scope,
+) -> Result<Option<String>, DatabaseError> {
+    let Some(entity) = entity::find_one::<Entity<BaseEntity>>(neo4j, id)
+        .version_opt(scope.version())
+        .send()
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let attributes = scoped_triples(neo4j, prop_filter::value(id), scope)
+        .send()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?
+        .into_iter()
+        .filter(|triple| {
+            triple.attribute != system_ids::NAME_ATTRIBUTE
+                && triple.attribute != system_ids::DESCRIPTION_ATTRIBUTE
+        })
+        .collect::<Vec<_>>();
+
+    let mut relations = relation::find_many::<RelationEdge<EntityNode>>(neo4j)
+        .filter(
+            relation::RelationFilter::default()
+                .from_(EntityFilter::default().id(prop_filter::value(id))),
+        )
+        .version(scope.version())
+        .limit(MAX_RELATIONS);
+    if let Some(space_filter) = scope.space_filter() {
+        relations = relations.space_id(space_filter);
+    }
+    let mut relations = relations.send().await?.try_collect::<Vec<_>>().await?;
+    relations.sort_by(|a, b| a.index.cmp(&b.index));
+
+    let (blocks, relations): (Vec<_>, Vec<_>) = relations
+        .into_iter()
+        .partition(|relation| relation.relation_type == system_ids::BLOCKS);
+
+    let block_ids = blocks
+        .iter()
+        .map(|block| block.to.id.clone())
+        .collect::<Vec<_>>();
+    let mut block_content = if block_ids.is_empty() {
+        HashMap::new()
+    } else {
+        scoped_triples(neo4j, prop_filter::value_in(block_ids), scope)
+            .attribute_id(prop_filter::value(system_ids::MARKDOWN_CONTENT))
+            .send()
+            .await?
+            .map_ok(|triple| (triple.entity, triple.value.value))
+            .try_collect::<HashMap<_, _>>()
+            .await?
+    };
+
+    let mut ids = attributes
+        .iter()
+        .map(|triple| triple.attribute.clone())
+        .chain(
+            relations
+                .iter()
+                .flat_map(|relation| [relation.relation_type.clone(), relation.to.id.clone()]),
+        )
+        .collect::<Vec<_>>();
+    ids.sort();
+    ids.dedup();
+    let names = find_names(neo4j, ids).await?;
+    let name_of = |id: &str| names.get(id).cloned().unwrap_or_else(|| id.to_string());
+
+    let mut markdown = format!(
+        "# {}\n\nID: `{id}`\n",
+        entity.attributes.name.as_deref().unwrap_or(id)
+    );
+
+    if let Some(description) = &entity.attributes.description {
+        markdown.push_str(&format!("\n{description}\n"));
+    }
+
+    let types = relations
+        .iter()
+        .filter(|relation| relation.relation_type == system_ids::TYPES_ATTRIBUTE)
+        .map(|relation| link(&name_of(&relation.to.id), &relation.to.id))
+        .collect::<Vec<_>>();
+    if !types.is_empty() {
+        markdown.push_str(&format!("\nTypes: {}\n", types.join(", ")));
+    }
+
+    if !attributes.is_empty() {
+        markdown.push_str("\n## Attributes\n\n");
+        for triple in &attributes {
+            markdown.push_str(&format!(
+                "- **{}**: {}\n",
+                name_of(&triple.attribute),
+                triple.value.value
+            ));
+        }
+    }
+
+    let relations = relations
+        .iter()
+        .filter(|relation| relation.relation_type != system_ids::TYPES_ATTRIBUTE)
+        .collect::<Vec<_>>();
+    if !relations.is_empty() {
+        markdown.push_str("\n## Relations\n\n");
+        for relation in relations {
+            markdown.push_str(&format!(
+                "- **{}**: {}\n",
+                name_of(&relation.relation_type),
+                link(&name_of(&relation.to.id), &relation.to.id)
+            ));
+        }
+    }
+
+    let blocks = blocks
+        .iter()
+        .filter_map(|block| block_content.remove(&block.to.id))
+        .collect::<Vec<_>>();
+    if !blocks.is_empty() {
+        markdown.push_str("\n## Content\n\n");
+        markdown.push_str(&blocks.join("\n\n"));
+        markdown.push('\n');
+    }
+
+    Ok(Some(markdown))
+}
+
+async fn render_space(
+    neo4j: &Graph,
+    space_id: &str,
+    scope: &Scope,
+) -> Result<Option<String>, DatabaseError> {
+    let Some(space) = entity::find_many::<Entity<Space>>(neo4j)
+        .with_filter(
+            EntityFilter::default()
+                .id(prop_filter::value(space_id))
+                .relations(TypesFilter::default().r#type(system_ids::SPACE_TYPE)),
+        )
+        .space_id(indexer_ids::INDEXER_SPACE_ID)
+        .version_opt(scope.version())
+        .limit(1)
+        .send()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?
+        .pop()
+    else {
+        return Ok(None);
+    };
+
+    let root_entities = find_root_entities(neo4j, space_id, scope).await?;
+    let space_name = root_entities
+        .iter()
+        .find_map(|entity| entity.attributes.name.as_deref())
+        .unwrap_or(space_id);
+
+    let mut markdown = format!(
+        "# Space: {space_name}\n\nID: `{space_id}`\n\n\
+        - **Network**: {}\n\
+        - **Governance**: {:?}\n\
+        - **DAO address**: {}\n",
+        space.attributes.network,
+        space.attributes.governance_type,
+        space.attributes.dao_contract_address,
+    );
+
+    if !root_entities.is_empty() {
+        markdown.push_str("\n## Root entities\n\n");
+        for entity in &root_entities {
+            markdown.push_str(&format!(
+                "- {}",
+                link(
+                    entity.attributes.name.as_deref().unwrap_or(entity.id()),
+                    entity.id()
+                )
+            ));
+            if let Some(description) = &entity.attributes.description {
+                markdown.push_str(&format!(": {description}"));
+            }
+            markdown.push('\n');
+        }
+    }
+
+    Ok(Some(markdown))
+}
+
+/// Attributes of the entities in the scope
+fn scoped_triples(
+    neo4j: &Graph,
+    entity_id: PropFilter<String>,
+    scope: &Scope,
+) -> triple::FindManyQuery {
+    let mut query = triple::find_many(neo4j).entity_id(entity_id);
+    if let Some(space_filter) = scope.space_filter() {
+        query = query.space_id(space_filter);
+    }
+    if let Some(version) = scope.version() {
+        query = query.space_version(version);
+    }
+    query
+}
+
+fn link(name: &str, id: &str) -> String {
+    format!("[{name}]({})", ResourceUri::entity_uri(id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_entity_uri() {
+        assert_eq!(
+            ResourceUri::parse("graph://LuBWqZAu6pz54eiJS5mLv8"),
+            Some(ResourceUri::Entity("LuBWqZAu6pz54eiJS5mLv8".to_string()))
+        );
+        assert_eq!(
+            ResourceUri::parse(&ResourceUri::entity_uri("abc")),
+            Some(ResourceUri::Entity("abc".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_space_uri() {
+        assert_eq!(
+            ResourceUri::parse("graph://space/25omwWh6HYgeRQKCaSpVpa"),
+            Some(ResourceUri::Space("25omwWh6HYgeRQKCaSpVpa".to_string()))
+        );
+        assert_eq!(
+            ResourceUri::parse(&ResourceUri::space_uri("abc")),
+            Some(ResourceUri::Space("abc".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_scoped_uri() {
+        let uri = "graph://abc?space_id=def&include_subspaces=true&as_of_block=42";
+        assert_eq!(
+            ResourceUri::parse(uri),
+            Some(ResourceUri::Entity("abc".to_string()))
+        );
+        assert_eq!(
+            ScopeParams::parse(uri),
+            Some(ScopeParams {
+                space_id: Some("def".to_string()),
+                include_subspaces: true,
+                as_of_block: Some(42),
+                as_of_time: None,
+            })
+        );
+
+        let uri = "graph://space/abc?as_of_time=2025-01-31T00:00:00Z";
+        assert_eq!(
+            ResourceUri::parse(uri),
+            Some(ResourceUri::Space("abc".to_string()))
+        );
+        assert_eq!(
+            ScopeParams::parse(uri).and_then(|params| params.as_of_time),
+            Some("2025-01-31T00:00:00Z".to_string())
+        );
+
+        assert_eq!(
+            ScopeParams::parse("graph://abc"),
+            Some(ScopeParams::default())
+        );
+        assert_eq!(ScopeParams::parse("graph://abc?as_of_block=latest"), None);
+        assert_eq!(ScopeParams::parse("graph://abc?version=1"), None);
+    }
+
+    #[test]
+    fn test_parse_invalid_uri() {
+        assert_eq!(ResourceUri::parse("graph://"), None);
+        assert_eq!(ResourceUri::parse("graph://space/"), None);
+        assert_eq!(ResourceUri::parse("graph://abc/def"), None);
+        assert_eq!(ResourceUri::parse("https://abc"), None);
+        assert_eq!(ResourceUri::parse("abc"), None);
+    }
+}
@@ -0,0 +1,133 @@
+//! Scope of the tools and resources: the spaces from which the data is read and the
+//! version at which it is read. Without scope, the tools read the current data of every
+//! space.
+//!
+//! When a space is scoped with its subspaces, an attribute set in several of these spaces
+//! takes the value of the closest space to the scoped space (i.e.: the same rules as the
+//! pluralism of the knowledge graph).
+use futures::TryStreamExt;
+use grc20_core::{
+    chrono::{DateTime, Utc},
+    connection::Graph,
+    mapping::{self, PropFilter, QueryStream, aggregation::SpaceRanking, prop_filter},
+};
+use grc20_sdk::models::{edit, space};
+
+use crate::error::{ToolError, validate_id};
+
+#[derive(Clone, Debug, Default)]
+pub struct Scope {
+    /// Spaces of the scope ordered by depth (the scoped space first), empty for all spaces
+    spaces: Vec<SpaceRanking>,
+    /// Version index at which the data is read, the current data if `None`
+    version: Option<String>,
+}
+
+impl Scope {
+    /// Resolves the scope parameters of a tool. `as_of_time` is an RFC 3339 date and time.
+    pub async fn resolve(
+        neo4j: &Graph,
+        space_id: Option<&str>,
+        include_subspaces: bool,
+        as_of_block: Option<u64>,
+        as_of_time: Option<&str>,
+    ) -> Result<Self, ToolError> {
+        let version = match (as_of_block, as_of_time) {
+            (Some(_), Some(_)) => {
+                return Err(ToolError::InvalidArgument(
+                    "as_of_block and as_of_time cannot be used together".to_string(),
+                ));
+            }
+            (Some(block), None) => Some(mapping::block_version_index(block)),
+            (None, Some(time)) => {
+                let time = DateTime::parse_from_rfc3339(time)
+                    .map_err(|e| {
+                        ToolError::InvalidArgument(format!("invalid as_of_time {time:?}: {e}"))
+                    })?
+                    .with_timezone(&Utc);
+
+                // Before the first edit, nothing is visible
+                Some(
+                    edit::version_index_at(neo4j, time)
+                        .await?
+                        .unwrap_or_else(|| mapping::new_version_index(0, 0)),
+                )
+            }
+            (None, None) => None,
+        };
+
+        let spaces = match (space_id, include_subspaces) {
+            (Some(space_id), include_subspaces) => {
+                validate_id(space_id)?;
+                let mut spaces = vec![SpaceRanking {
+                    space_id: space_id.to_string(),
+                    depth: 0,
+                }];
+
+                if include_subspaces {
+                    // The subspaces at the version of the scope
+                    let subspaces = space::subspaces::<SpaceRanking>(neo4j, space_id)
+                        .max_depth(None)
+                        .version_opt(version.clone())
+                        .send()
+                        .await?
+                        .try_collect::<Vec<_>>()
+                        .await?;
+                    spaces.extend(
+                        subspaces
+                            .into_iter()
+                            .filter(|subspace| subspace.space_id != space_id),
+                    );
+                    spaces.sort_by_key(|ranking| ranking.depth);
+                }
+
+                spaces
+            }
+            (None, true) => {
+                return Err(ToolError::InvalidArgument(
+                    "include_subspaces requires a space_id".to_string(),
+                ));
+            }
+            (None, false) => vec![],
+        };
+
+        Ok(Self { spaces, version })
+    }
+
+    /// The scoped space, if any
+    pub fn space_id(&self) -> Option<&str> {
+        self.spaces.first().map(|ranking| ranking.space_id.as_str())
+    }
+
+    /// Spaces of the scope ordered by depth, empty for all spaces
+    pub fn spaces(&self) -> &[SpaceRanking] {
+        &self.spaces
+    }
+
+    /// Filter on the space of the attributes and relations, `None` for all spaces
+    pub fn space_filter(&self) -> Option<PropFilter<String>> {
+        match &self.spaces[..] {
+            [] => None,
+            [ranking] => Some(prop_filter::value(ranking.space_id.clone())),
+            spaces => Some(prop_filter::value_in(
+                spaces
+                    .iter()
+                    .map(|ranking| ranking.space_id.clone())
+                    .collect(),
+            )),
+        }
+    }
+
+    /// Whether the space is one of the spaces of the scope (any space without scope)
+    pub fn contains_space(&self, space_id: &str) -> bool {
+        self.spaces.is_empty()
+            || self
+                .spaces
+                .iter()
+                .any(|ranking| ranking.space_id == space_id)
+    }
+
+    pub fn version(&self) -> Option<String> {
+        self.version.clone()
+    }
+}
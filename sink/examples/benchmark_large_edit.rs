@@ -1,4 +1,5 @@
-use grc20_core::{block::BlockMetadata, neo4rs, pb::geo};
+use grc20_core::{block::BlockMetadata, connection::Graph, neo4rs, pb::geo};
+use ipfs::IpfsClient;
 use sink::events::EventHandler;
 use testcontainers::{
     core::{IntoContainerPort, WaitFor},
@@ -31,7 +32,7 @@ async fn main() -> anyhow::Result<()> {
     let port = container.get_host_port_ipv4(BOLT_PORT).await.unwrap();
     let host = container.get_host().await.unwrap().to_string();
 
-    let neo4j = neo4rs::Graph::new(format!("neo4j://{host}:{port}"), "user", "password")
+    let neo4j = Graph::new(format!("neo4j://{host}:{port}"), "user", "password")
         .await
         .unwrap();
 
@@ -50,7 +51,8 @@ async fn main() -> anyhow::Result<()> {
 
     println!("Neo4J database reset");
 
-    let sink = EventHandler::new(neo4j, None)?;
+    let ipfs = IpfsClient::from_url("https://gateway.lighthouse.storage/ipfs/");
+    let sink = EventHandler::new(neo4j, ipfs, None)?;
 
     let block = BlockMetadata::default();
 
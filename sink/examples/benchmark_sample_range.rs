@@ -2,9 +2,11 @@ use std::env;
 
 use grc20_core::{
     block::BlockMetadata,
+    connection::Graph,
     mapping::{triple, Query},
     neo4rs,
 };
+use ipfs::IpfsClient;
 use sink::events::EventHandler;
 use substreams_utils::sink::Sink;
 use testcontainers::{
@@ -51,7 +53,7 @@ async fn main() -> anyhow::Result<()> {
     let port = container.get_host_port_ipv4(BOLT_PORT).await.unwrap();
     let host = container.get_host().await.unwrap().to_string();
 
-    let neo4j = neo4rs::Graph::new(format!("neo4j://{host}:{port}"), "user", "password")
+    let neo4j = Graph::new(format!("neo4j://{host}:{port}"), "user", "password")
         .await
         .unwrap();
 
@@ -78,7 +80,8 @@ async fn main() -> anyhow::Result<()> {
 
     println!("Neo4J database reset");
 
-    let sink = EventHandler::new(neo4j, None)?;
+    let ipfs = IpfsClient::from_url("https://gateway.lighthouse.storage/ipfs/");
+    let sink = EventHandler::new(neo4j, ipfs, None)?;
 
     let endpoint_url =
         env::var("SUBSTREAMS_ENDPOINT_URL").expect("SUBSTREAMS_ENDPOINT_URL not set");
@@ -2,6 +2,7 @@ use chrono::Local;
 use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
 use grc20_core::{
     block::BlockMetadata,
+    connection::Graph,
     entity::EntityNodeRef,
     ids, indexer_ids,
     mapping::{triple, Query, RelationEdge, Triple, Value},
@@ -72,7 +73,7 @@ const _: &str = "YyATjD7HyDrVq4SKkQGBu";
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    let neo4j = neo4rs::Graph::new(NEO4J_URL, NEO4J_USER, NEO4J_PASSWORD)
+    let neo4j = Graph::new(NEO4J_URL, NEO4J_USER, NEO4J_PASSWORD)
         .await
         .expect("Failed to connect to Neo4j");
 
@@ -656,10 +657,7 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-pub async fn bootstrap(
-    neo4j: &neo4rs::Graph,
-    embedding_model: &TextEmbedding,
-) -> anyhow::Result<()> {
+pub async fn bootstrap(neo4j: &Graph, embedding_model: &TextEmbedding) -> anyhow::Result<()> {
     let triples = vec![
         // Value types
         Triple::new(system_ids::CHECKBOX, system_ids::NAME_ATTRIBUTE, "Checkbox"),
@@ -902,7 +900,7 @@ pub async fn bootstrap(
 }
 
 pub async fn create_entity(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     embedding_model: &TextEmbedding,
     name: impl Into<String>,
     description: Option<&str>,
@@ -979,7 +977,7 @@ pub async fn create_entity(
 }
 
 pub async fn insert_attribute(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     entity_id: impl Into<String>,
     attribute_id: impl Into<String>,
     attribute_value: impl Into<String>,
@@ -1004,7 +1002,7 @@ pub async fn insert_attribute(
 }
 
 pub async fn insert_relation(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     entity_from_id: impl Into<String>,
     relation_id: impl Into<String>,
     entity_to_id: impl Into<String>,
@@ -1035,7 +1033,7 @@ pub async fn insert_relation(
 }
 
 pub async fn insert_attribute_with_embedding(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     embedding_model: &TextEmbedding,
     entity_id: impl Into<String>,
     attribute_id: impl Into<String>,
@@ -1074,7 +1072,7 @@ pub async fn insert_attribute_with_embedding(
 
 /// Creates a type with the given name, types, and properties.
 pub async fn create_type(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     embedding_model: &TextEmbedding,
     name: impl Into<String>,
     types: impl IntoIterator<Item = &str>,
@@ -1135,7 +1133,7 @@ pub async fn create_type(
 /// If `relation_value_type` is provided, it will be set as the relation value type (
 /// Note: if that is the case, then `value_type` should be the system_ids::RELATION_SCHEMA_TYPE type).
 pub async fn create_property(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     embedding_model: &TextEmbedding,
     name: impl Into<String>,
     value_type: impl Into<String>,
@@ -1210,7 +1208,7 @@ pub async fn create_property(
 }
 
 pub async fn set_types(
-    neo4j: &neo4rs::Graph,
+    neo4j: &Graph,
     entity_id: impl Into<String>,
     types: impl IntoIterator<Item = &str>,
 ) -> anyhow::Result<()> {
@@ -1239,7 +1237,7 @@ pub async fn set_types(
     Ok(())
 }
 
-pub async fn reset_db(neo4j: &neo4rs::Graph) -> anyhow::Result<()> {
+pub async fn reset_db(neo4j: &Graph) -> anyhow::Result<()> {
     let embedding_dim = TextEmbedding::get_model_info(&EMBEDDING_MODEL)?.dim;
 
     // Delete indexes
@@ -1,3 +1,5 @@
+use std::{collections::HashSet, time::Instant};
+
 use futures::{stream, StreamExt, TryStreamExt};
 use grc20_core::{
     block::BlockMetadata,
@@ -12,10 +14,15 @@ use grc20_core::{
 use grc20_sdk::models::{
     self,
     edit::{Edits, ProposedEdit},
-    space, Proposal,
+    space, Proposal, ValidationDiagnostic,
 };
 use ipfs::deserialize;
 
+use crate::{
+    metrics,
+    validation::{self, OpRef},
+};
+
 use super::{handler::HandlerError, EventHandler};
 
 pub struct Edit {
@@ -51,8 +58,7 @@ impl EventHandler {
             .try_for_each(
                 |(idx, proposal)| async move { self.process_edit(block, proposal, idx).await },
             )
-            .await
-            .map_err(|e| HandlerError::Other(format!("{e:?}").into()))?; // TODO: Convert anyhow::Error to HandlerError properly
+            .await?;
 
         Ok(())
     }
@@ -63,10 +69,7 @@ impl EventHandler {
     ) -> Result<Vec<Edit>, HandlerError> {
         let space_id = space::new_id(network_ids::GEO, &edit_published.dao_address);
 
-        let bytes = self
-            .ipfs
-            .get_bytes(&edit_published.content_uri.replace("ipfs://", ""), true)
-            .await?;
+        let bytes = self.fetch_ipfs("edit", &edit_published.content_uri).await?;
 
         let metadata = if let Ok(metadata) = deserialize::<pb::ipfs::IpfsMetadata>(&bytes) {
             metadata
@@ -96,8 +99,8 @@ impl EventHandler {
                         let space_plugin_address = edit_published.plugin_address.clone();
 
                         async move {
-                            let hash = edit_uri.replace("ipfs://", "");
-                            let edit = self.ipfs.get::<pb::ipfs::ImportEdit>(&hash, true).await?;
+                            let bytes = self.fetch_ipfs("import_edit", &edit_uri).await?;
+                            let edit = deserialize::<pb::ipfs::ImportEdit>(&bytes)?;
 
                             Ok(Edit {
                                 name: edit.name,
@@ -110,7 +113,7 @@ impl EventHandler {
                             })
                         }
                     })
-                    .buffered(16)
+                    .buffered(self.prefetch_concurrency)
                     .try_collect::<Vec<_>>()
                     .await
             }
@@ -118,6 +121,21 @@ impl EventHandler {
         }
     }
 
+    /// Fetches the content at `uri` (cached) and records the fetch latency
+    pub(crate) async fn fetch_ipfs(&self, kind: &str, uri: &str) -> Result<Vec<u8>, ipfs::Error> {
+        metrics::IPFS_FETCHES_IN_FLIGHT.inc();
+        let start = Instant::now();
+
+        let result = self.ipfs.get_bytes(&uri.replace("ipfs://", ""), true).await;
+
+        metrics::IPFS_FETCHES_IN_FLIGHT.dec();
+        metrics::IPFS_FETCH_DURATION
+            .with_label_values(&[kind, if result.is_ok() { "ok" } else { "error" }])
+            .observe(start.elapsed().as_secs_f64());
+
+        result
+    }
+
     pub async fn process_edit(
         &self,
         block: &BlockMetadata,
@@ -146,8 +164,11 @@ impl EventHandler {
         };
 
         if self.governance {
-            let edit_medatata =
-                models::Edit::new(edit.name, edit.content_uri, Some(version_index.clone()));
+            let edit_medatata = models::Edit::new(
+                edit.name,
+                edit.content_uri.clone(),
+                Some(version_index.clone()),
+            );
             let proposal_id = Proposal::gen_id(&edit.space_plugin_address, &edit.proposal_id);
             self.create_edit_relations(block, edit_medatata, &edit.space_id, &proposal_id)
                 .await?;
@@ -155,7 +176,18 @@ impl EventHandler {
 
         // Group ops by type
         let num_ops = edit.ops.len();
-        let op_groups = OpGroups::from_ops(edit.ops);
+        let mut op_groups = OpGroups::from_ops(edit.ops);
+
+        if self.validation {
+            self.validate_ops(
+                block,
+                &edit.space_id,
+                &edit.content_uri,
+                &version_index,
+                &mut op_groups,
+            )
+            .await?;
+        }
 
         tracing::info!(
             "Block #{} ({}): Processing {} ops for proposal {}: {} set triples, {} delete triples, {} create relations, {} delete relations",
@@ -230,6 +262,75 @@ impl EventHandler {
         Ok(())
     }
 
+    /// Validate the ops against the schema and record the violations as diagnostics.
+    /// If the space is strict, the violating ops are removed from `op_groups`.
+    async fn validate_ops(
+        &self,
+        block: &BlockMetadata,
+        space_id: &str,
+        content_uri: &str,
+        version_index: &str,
+        op_groups: &mut OpGroups,
+    ) -> Result<(), DatabaseError> {
+        let violations = validation::validate_ops(
+            &self.neo4j,
+            space_id,
+            &op_groups.set_triples,
+            &op_groups.create_relations,
+        )
+        .await?;
+
+        if violations.is_empty() {
+            return Ok(());
+        }
+
+        let strict = self.strict_spaces.iter().any(|id| id == space_id);
+
+        tracing::warn!(
+            "Block #{} ({}): {} ops of edit {} violate the schema of space {}{}",
+            block.block_number,
+            block.timestamp,
+            violations.len(),
+            content_uri,
+            space_id,
+            if strict { ", rejecting them" } else { "" },
+        );
+
+        for violation in &violations {
+            metrics::VALIDATION_VIOLATIONS
+                .with_label_values(&[
+                    violation.kind.to_string().as_str(),
+                    if strict { "true" } else { "false" },
+                ])
+                .inc();
+
+            Entity::new(
+                ValidationDiagnostic::gen_id(content_uri, &violation.kind, &violation.op_id),
+                ValidationDiagnostic {
+                    kind: violation.kind.clone(),
+                    space: space_id.to_string(),
+                    entity: violation.entity_id.clone(),
+                    property: violation.property_id.clone(),
+                    expected: violation.expected.clone(),
+                    actual: violation.actual.clone(),
+                    content_uri: content_uri.to_string(),
+                    index: Some(version_index.to_string()),
+                    rejected: strict,
+                },
+            )
+            .with_type(indexer_ids::VALIDATION_DIAGNOSTIC_TYPE)
+            .insert(&self.neo4j, block, indexer_ids::INDEXER_SPACE_ID, "0")
+            .send()
+            .await?;
+        }
+
+        if strict {
+            op_groups.remove(violations.iter().map(|violation| violation.op));
+        }
+
+        Ok(())
+    }
+
     async fn create_edit_relations(
         &self,
         block: &BlockMetadata,
@@ -321,4 +422,23 @@ impl OpGroups {
 
         op_groups
     }
+
+    /// Remove the given ops from the groups
+    pub fn remove(&mut self, ops: impl IntoIterator<Item = OpRef>) {
+        let ops = ops.into_iter().collect::<HashSet<_>>();
+
+        self.set_triples = std::mem::take(&mut self.set_triples)
+            .into_iter()
+            .enumerate()
+            .filter(|(idx, _)| !ops.contains(&OpRef::SetTriple(*idx)))
+            .map(|(_, triple)| triple)
+            .collect();
+
+        self.create_relations = std::mem::take(&mut self.create_relations)
+            .into_iter()
+            .enumerate()
+            .filter(|(idx, _)| !ops.contains(&OpRef::CreateRelation(*idx)))
+            .map(|(_, relation)| relation)
+            .collect();
+    }
 }
@@ -2,12 +2,18 @@ use chrono::DateTime;
 use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
 use futures::{stream, StreamExt, TryStreamExt};
 use grc20_core::{
-    block::BlockMetadata, error::DatabaseError, ids::create_geo_id, indexer_ids, mapping::Query,
-    neo4rs, pb::geo::GeoOutput,
+    block::BlockMetadata,
+    connection::{self, Graph},
+    error::DatabaseError,
+    ids::create_geo_id,
+    indexer_ids,
+    mapping::Query,
+    pb::geo::GeoOutput,
 };
+use grc20_sdk::models::{tentative_edit, TentativeEdit};
 use ipfs::IpfsClient;
 use prost::Message;
-use substreams_utils::pb::sf::substreams::rpc::v2::BlockScopedData;
+use substreams_utils::pb::sf::substreams::rpc::v2::{BlockScopedData, BlockUndoSignal};
 
 use crate::{
     blacklist, metrics,
@@ -17,6 +23,7 @@ use cache::KgCache;
 use std::sync::Arc;
 
 const EMBEDDING_MODEL: EmbeddingModel = EmbeddingModel::AllMiniLML6V2;
+const DEFAULT_PREFETCH_CONCURRENCY: usize = 16;
 
 #[derive(thiserror::Error, Debug)]
 pub enum HandlerError {
@@ -32,6 +39,9 @@ pub enum HandlerError {
     #[error("Cache error: {0}")]
     CacheError(#[from] cache::CacheError),
 
+    #[error("Block undo is not supported (last valid block: #{0}), only final blocks can be indexed durably")]
+    UndoUnsupported(u64),
+
     // #[error("KG error: {0}")]
     // KgError(#[from] kg::Error),
     #[error("Error processing event: {0}")]
@@ -40,7 +50,7 @@ pub enum HandlerError {
 
 pub struct EventHandler {
     pub(crate) ipfs: IpfsClient,
-    pub(crate) neo4j: neo4rs::Graph,
+    pub(crate) neo4j: Graph,
     #[allow(dead_code)]
     pub(crate) cache: Option<Arc<KgCache>>,
     pub(crate) spaces_blacklist: Vec<String>,
@@ -50,10 +60,15 @@ pub struct EventHandler {
     // Handler config
     pub(crate) versioning: bool,
     pub(crate) governance: bool,
+    pub(crate) validation: bool,
+    /// Spaces in which ops violating the schema are rejected
+    pub(crate) strict_spaces: Vec<String>,
+    /// Maximum number of edits of a block fetched concurrently
+    pub(crate) prefetch_concurrency: usize,
 }
 
 impl EventHandler {
-    pub fn neo4j(&self) -> &neo4rs::Graph {
+    pub fn neo4j(&self) -> &Graph {
         &self.neo4j
     }
 
@@ -61,16 +76,8 @@ impl EventHandler {
         self.embedding_model_dim
     }
 
-    pub fn new(neo4j: neo4rs::Graph, cache: Option<Arc<KgCache>>) -> Result<Self, HandlerError> {
-        Self::new_with_ipfs(
-            neo4j,
-            IpfsClient::from_url("https://gateway.lighthouse.storage/ipfs/"),
-            cache,
-        )
-    }
-
-    pub fn new_with_ipfs(
-        neo4j: neo4rs::Graph,
+    pub fn new(
+        neo4j: Graph,
         ipfs: IpfsClient,
         cache: Option<Arc<KgCache>>,
     ) -> Result<Self, HandlerError> {
@@ -107,6 +114,9 @@ impl EventHandler {
                 .dim,
             versioning: false,
             governance: false,
+            validation: false,
+            strict_spaces: vec![],
+            prefetch_concurrency: DEFAULT_PREFETCH_CONCURRENCY,
         })
     }
 
@@ -119,6 +129,27 @@ impl EventHandler {
         self.governance = governance;
         self
     }
+
+    /// Validate the ops of edits against the schema and record violations as
+    /// diagnostics in the indexer space
+    pub fn validation(mut self, validation: bool) -> Self {
+        self.validation = validation;
+        self
+    }
+
+    /// Reject the ops violating the schema in the given spaces (requires validation)
+    pub fn strict_spaces(mut self, strict_spaces: Vec<String>) -> Self {
+        self.strict_spaces = strict_spaces;
+        self
+    }
+
+    /// Maximum number of edits (and edits of imported spaces) of a block fetched
+    /// concurrently (default: 16). Requests to IPFS are further limited by the
+    /// limits of the IPFS client, which are shared by all blocks.
+    pub fn prefetch_concurrency(mut self, prefetch_concurrency: usize) -> Self {
+        self.prefetch_concurrency = prefetch_concurrency.max(1);
+        self
+    }
 }
 
 fn get_block_metadata(block: &BlockScopedData) -> anyhow::Result<BlockMetadata> {
@@ -140,6 +171,20 @@ fn get_block_metadata(block: &BlockScopedData) -> anyhow::Result<BlockMetadata>
 impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
     type Error = HandlerError;
 
+    fn is_transient_error(&self, error: &Self::Error) -> bool {
+        match error {
+            // Invalid CIDs and content will not change when retried
+            HandlerError::IpfsError(ipfs::Error::Cid(_) | ipfs::Error::Prost(_)) => false,
+            HandlerError::IpfsError(_) => true,
+            HandlerError::Prost(_) => false,
+            HandlerError::DatabaseError(error) => connection::is_transient(error),
+            HandlerError::CacheError(_) => true,
+            HandlerError::UndoUnsupported(_) => false,
+            // Invalid block data, event values or embedding model setup
+            HandlerError::Other(_) => false,
+        }
+    }
+
     async fn preprocess_block_scoped_data(
         &self,
         raw_block: &BlockScopedData,
@@ -157,14 +202,13 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
 
         let data = GeoOutput::decode(output.value.as_slice())?;
 
+        // Edits are fetched concurrently, `buffered` keeps them in the order of the events
         let prefetched_edits = stream::iter(data.edits_published)
-            .then(|edit_event| async {
-                let edit = self
-                    .fetch_edit(&edit_event)
-                    .await
-                    .map_err(|e| HandlerError::Other(format!("{e:?}").into()))?;
+            .map(|edit_event| async {
+                let edit = self.fetch_edit(&edit_event).await?;
                 Result::<_, Self::Error>::Ok((edit_event, edit))
             })
+            .buffered(self.prefetch_concurrency)
             .try_collect::<Vec<_>>()
             .await?;
 
@@ -542,6 +586,80 @@ impl substreams_utils::Sink<preprocess::EventData> for EventHandler {
         Ok(())
     }
 
+    fn process_block_undo_signal(&self, undo_signal: &BlockUndoSignal) -> Result<(), Self::Error> {
+        // Blocks are indexed durably (and versioned by edit, not by block) so they cannot
+        // be reverted. Final blocks only must be requested, or the hybrid mode.
+        Err(HandlerError::UndoUnsupported(
+            undo_signal
+                .last_valid_block
+                .as_ref()
+                .map(|block| block.number)
+                .unwrap_or_default(),
+        ))
+    }
+
+    fn supports_hybrid(&self) -> bool {
+        true
+    }
+
+    /// Stores the edits published in the block in the tentative overlay (see
+    /// [`tentative_edit`]). Other events only take effect once the block is final.
+    async fn process_tentative_block_scoped_data(
+        &self,
+        _raw_block: &BlockScopedData,
+        data: preprocess::EventData,
+    ) -> Result<(), Self::Error> {
+        let edits = data
+            .edits_published
+            .into_iter()
+            .flat_map(|(_, edits)| edits)
+            .filter(|edit| !self.spaces_blacklist.contains(&edit.space_id))
+            .map(|edit| TentativeEdit {
+                entity_ids: TentativeEdit::entity_ids(&edit.ops),
+                id: edit.proposal_id,
+                name: edit.name,
+                space_id: edit.space_id,
+                creator: edit.creator,
+                content_uri: edit.content_uri,
+                block_number: data.block.block_number,
+            })
+            .collect::<Vec<_>>();
+
+        if !edits.is_empty() {
+            tracing::info!(
+                "Block #{} ({}): Storing {} tentative edits",
+                data.block.block_number,
+                data.block.timestamp,
+                edits.len()
+            );
+        }
+        tentative_edit::replace_block(&self.neo4j, data.block.block_number, edits).await?;
+
+        Ok(())
+    }
+
+    async fn finalize_tentative_blocks(&self, block_number: u64) -> Result<(), Self::Error> {
+        tentative_edit::delete_until(&self.neo4j, block_number).await?;
+
+        Ok(())
+    }
+
+    async fn undo_tentative_blocks(
+        &self,
+        undo_signal: &BlockUndoSignal,
+    ) -> Result<(), Self::Error> {
+        let last_valid_block = undo_signal
+            .last_valid_block
+            .as_ref()
+            .map(|block| block.number)
+            .unwrap_or_default();
+        tracing::info!("Undoing the tentative blocks after block #{last_valid_block}");
+
+        tentative_edit::delete_after(&self.neo4j, last_valid_block).await?;
+
+        Ok(())
+    }
+
     async fn load_persisted_cursor(&self) -> Result<Option<String>, Self::Error> {
         let cursor = grc20_core::mapping::triple::find_one(
             &self.neo4j,
@@ -27,8 +27,7 @@ impl EventHandler {
         let maybe_existing_space_id = match maybe_initial_proposal {
             Some(initial_proposal) => {
                 let bytes = self
-                    .ipfs
-                    .get_bytes(&initial_proposal.0.content_uri.replace("ipfs://", ""), true)
+                    .fetch_ipfs("edit", &initial_proposal.0.content_uri)
                     .await?;
 
                 if let Ok(metadata) = ipfs::deserialize::<pb::ipfs::IpfsMetadata>(&bytes) {
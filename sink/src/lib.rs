@@ -3,3 +3,4 @@ pub mod bootstrap;
 pub mod events;
 pub mod metrics;
 pub mod preprocess;
+pub mod validation;
@@ -1,4 +1,4 @@
-use std::{env, sync::Arc};
+use std::{env, path::PathBuf, sync::Arc};
 
 use anyhow::Error;
 use axum::{response::Json, routing::get, Router};
@@ -6,14 +6,19 @@ use cache::{CacheConfig, KgCache};
 use clap::{Args, Parser};
 use grc20_core::{
     block::BlockMetadata,
+    connection::Neo4jArgs,
     indexer_ids,
     mapping::{self, query_utils::Query, triple},
     neo4rs,
 };
+use grc20_sdk::models::tentative_edit;
+use ipfs::{FsCache, IpfsClient, PackCache};
 use sink::bootstrap;
 use sink::{events::EventHandler, metrics};
 use std::time::Duration;
-use substreams_utils::Sink;
+use substreams_utils::{
+    CancellationToken, RetryPolicy as BlockRetryPolicy, RunOptions, Sink, StreamConfig,
+};
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 
@@ -48,12 +53,7 @@ async fn main() -> Result<(), Error> {
     set_log_level();
     let _guard = init_tracing(args.log_file);
 
-    let neo4j = neo4rs::Graph::new(
-        &args.neo4j_args.neo4j_uri,
-        &args.neo4j_args.neo4j_user,
-        &args.neo4j_args.neo4j_pass,
-    )
-    .await?;
+    let neo4j = args.neo4j_args.connect().await?;
 
     let cache = if let Some(uri) = args.cache_args.memcache_uri {
         let cache_config = CacheConfig::new(vec![uri])
@@ -63,9 +63,12 @@ async fn main() -> Result<(), Error> {
         None
     };
 
-    let sink = EventHandler::new(neo4j, cache)?
+    let sink = EventHandler::new(neo4j, args.ipfs_args.client()?, cache)?
         .versioning(!args.no_versioning)
-        .governance(!args.no_governance);
+        .governance(!args.no_governance)
+        .validation(!args.no_validation)
+        .strict_spaces(args.strict_validation_spaces)
+        .prefetch_concurrency(args.prefetch_concurrency);
 
     if args.reset_db {
         reset_db(&sink).await?;
@@ -73,9 +76,30 @@ async fn main() -> Result<(), Error> {
         migration_check(&sink).await?;
     }
 
+    // Tentative blocks are streamed again after the last final block
+    tentative_edit::delete_all(sink.neo4j()).await?;
+
     start_http_server().await;
 
-    sink.run(
+    let options = RunOptions::new()
+        .preprocess_buffer(args.preprocess_buffer)
+        .retry_policy(
+            BlockRetryPolicy::default()
+                .max_retries(args.max_block_retries)
+                .max_backoff(Duration::from_secs(args.max_block_retry_backoff)),
+        )
+        .cancellation_token(shutdown_token())
+        // Indexed blocks cannot be reverted (see `EventHandler::process_block_undo_signal`),
+        // the unfinalized blocks are only streamed in hybrid mode
+        .stream_config(
+            StreamConfig::new()
+                .final_blocks_only(!args.hybrid)
+                .hybrid(args.hybrid)
+                .production_mode(!args.development_mode)
+                .debug_initial_store_snapshot_for_modules(args.debug_store_snapshots),
+        );
+
+    sink.run_with_options(
         &endpoint_url,
         PKG_FILE,
         MODULE_NAME,
@@ -85,7 +109,7 @@ async fn main() -> Result<(), Error> {
         end_block
             .parse()
             .unwrap_or_else(|_| panic!("Invalid end block: {end_block}! Must be integer")),
-        Some(64),
+        options,
     )
     .await?;
 
@@ -101,6 +125,9 @@ struct AppArgs {
     #[clap(flatten)]
     cache_args: CacheArgs,
 
+    #[clap(flatten)]
+    ipfs_args: IpfsArgs,
+
     /// Whether or not to reset the database
     #[arg(long)]
     reset_db: bool,
@@ -116,21 +143,123 @@ struct AppArgs {
     /// Whether to index governance events
     #[arg(long, default_value = "false")]
     no_governance: bool,
-}
 
-#[derive(Debug, Args)]
-struct Neo4jArgs {
-    /// Neo4j database host
+    /// Whether to validate edits against the schema
+    #[arg(long, default_value = "false")]
+    no_validation: bool,
+
+    /// Space in which ops violating the schema are rejected (can be repeated)
+    #[arg(long = "strict-validation-space")]
+    strict_validation_spaces: Vec<String>,
+
+    /// Number of blocks preprocessed (i.e.: with their edits fetched) ahead of the
+    /// block being processed
+    #[arg(long, default_value = "64")]
+    preprocess_buffer: usize,
+
+    /// Maximum number of edits of a block fetched concurrently
+    #[arg(long, default_value = "16")]
+    prefetch_concurrency: usize,
+
+    /// Maximum number of retries of a block failing with a transient error
+    #[arg(long, default_value = "5")]
+    max_block_retries: u32,
+
+    /// Maximum delay between two retries of a block in seconds
+    #[arg(long, default_value = "60")]
+    max_block_retry_backoff: u64,
+
+    /// Also stream the blocks which are not final yet and store their edits in the
+    /// tentative overlay until they are final (blocks are otherwise indexed with the
+    /// delay of the chain finality)
     #[arg(long)]
-    neo4j_uri: String,
+    hybrid: bool,
 
-    /// Neo4j database user name
+    /// Run the substreams request in development mode, in which the outputs of all the
+    /// modules are sent
     #[arg(long)]
-    neo4j_user: String,
+    development_mode: bool,
+
+    /// Store module whose initial state is sent before the first block (development
+    /// mode only, can be repeated)
+    #[arg(long = "debug-store-snapshot")]
+    debug_store_snapshots: Vec<String>,
+}
 
-    /// Neo4j database user password
+#[derive(Debug, Args)]
+struct IpfsArgs {
+    /// IPFS gateway URL, gateways are tried in order (can be repeated)
+    #[arg(
+        long = "ipfs-gateway",
+        env = "IPFS_GATEWAYS",
+        value_delimiter = ',',
+        default_value = "https://gateway.lighthouse.storage/ipfs/"
+    )]
+    ipfs_gateways: Vec<String>,
+
+    /// URL of a local IPFS HTTP API, tried before the gateways (e.g.: http://localhost:5001)
+    #[arg(long, env = "IPFS_API_URL")]
+    ipfs_api_url: Option<String>,
+
+    /// IPFS request timeout in seconds
+    #[arg(long, default_value = "30")]
+    ipfs_timeout: u64,
+
+    /// Maximum number of retries of all the IPFS sources
+    #[arg(long, default_value = "2")]
+    ipfs_max_retries: u32,
+
+    /// Maximum number of IPFS requests in flight (shared by all the preprocessed blocks)
+    #[arg(long, default_value = "64")]
+    ipfs_max_concurrent_requests: usize,
+
+    /// Maximum number of IPFS requests in flight to the same host
+    #[arg(long, default_value = "16")]
+    ipfs_max_requests_per_host: usize,
+
+    /// Directory of the IPFS cache (can be shared between sink instances)
+    #[arg(long, env = "IPFS_CACHE_DIR", default_value = ipfs::IPFS_CACHE_DIR)]
+    ipfs_cache_dir: PathBuf,
+
+    /// Maximum size of the IPFS cache directory in MB (default: unbounded)
     #[arg(long)]
-    neo4j_pass: String,
+    ipfs_cache_max_size_mb: Option<u64>,
+
+    /// Number of characters of the CIDs naming the sub-directories of the IPFS cache
+    /// directory (0: no sub-directories). Objects cached with another width are not found.
+    #[arg(long, default_value = "0")]
+    ipfs_cache_shard_width: usize,
+
+    /// Tar archive used as IPFS cache instead of the cache directory (e.g.: an archive
+    /// exported with `ipfs_cache export` to reindex without network access)
+    #[arg(long, env = "IPFS_CACHE_PACK")]
+    ipfs_cache_pack: Option<PathBuf>,
+}
+
+impl IpfsArgs {
+    fn client(&self) -> Result<IpfsClient, ipfs::Error> {
+        let mut builder = IpfsClient::builder()
+            .gateways(&self.ipfs_gateways)
+            .timeout(Duration::from_secs(self.ipfs_timeout))
+            .max_retries(self.ipfs_max_retries)
+            .max_concurrent_requests(self.ipfs_max_concurrent_requests.max(1))
+            .max_requests_per_host(self.ipfs_max_requests_per_host.max(1));
+
+        builder = match &self.ipfs_cache_pack {
+            Some(path) => builder.cache(PackCache::open(path)?),
+            None => builder.cache(
+                FsCache::new(&self.ipfs_cache_dir)
+                    .shard_width(self.ipfs_cache_shard_width)
+                    .max_size_opt(self.ipfs_cache_max_size_mb.map(|mb| mb * 1024 * 1024)),
+            ),
+        };
+
+        if let Some(api_url) = &self.ipfs_api_url {
+            builder = builder.local_api(api_url);
+        }
+
+        builder.build()
+    }
 }
 
 #[derive(Debug, Args)]
@@ -243,6 +372,28 @@ async fn migration_check(handler: &EventHandler) -> Result<(), Error> {
     Ok(())
 }
 
+/// Token cancelled on SIGINT or SIGTERM, after which the sink finishes the current
+/// block, persists its cursor and exits.
+fn shutdown_token() -> CancellationToken {
+    let token = CancellationToken::new();
+    let cancel = token.clone();
+
+    tokio::spawn(async move {
+        let mut sigterm = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler");
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => (),
+            _ = sigterm.recv() => (),
+        }
+
+        tracing::info!("Shutdown requested, finishing the current block");
+        cancel.cancel();
+    });
+
+    token
+}
+
 fn init_tracing(log_file: Option<String>) -> Option<tracing_appender::non_blocking::WorkerGuard> {
     if let Some(log_file) = log_file {
         // Set the path of the log file
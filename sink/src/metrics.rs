@@ -1,7 +1,11 @@
 use axum::http::{header, StatusCode};
 use axum::response::Response;
 use lazy_static::lazy_static;
-use prometheus::{register_gauge, register_histogram, Encoder, Gauge, Histogram, TextEncoder};
+use prometheus::{
+    register_gauge, register_histogram, register_histogram_vec, register_int_counter_vec,
+    register_int_gauge, Encoder, Gauge, Histogram, HistogramVec, IntCounterVec, IntGauge,
+    TextEncoder,
+};
 use std::time::SystemTime;
 
 lazy_static! {
@@ -25,6 +29,24 @@ lazy_static! {
         "Time spent processing each block"
     )
     .expect("Failed to create block_processing_duration_seconds histogram");
+    pub static ref VALIDATION_VIOLATIONS: IntCounterVec = register_int_counter_vec!(
+        "validation_violations_total",
+        "Number of ops violating the schema",
+        &["kind", "rejected"]
+    )
+    .expect("Failed to create validation_violations_total counter");
+    pub static ref IPFS_FETCH_DURATION: HistogramVec = register_histogram_vec!(
+        "ipfs_fetch_duration_seconds",
+        "Time spent fetching IPFS content (including cache hits)",
+        &["kind", "status"],
+        vec![0.001, 0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0]
+    )
+    .expect("Failed to create ipfs_fetch_duration_seconds histogram");
+    pub static ref IPFS_FETCHES_IN_FLIGHT: IntGauge = register_int_gauge!(
+        "ipfs_fetches_in_flight",
+        "Number of IPFS fetches in progress"
+    )
+    .expect("Failed to create ipfs_fetches_in_flight gauge");
 }
 
 pub async fn metrics_handler() -> Response<String> {
@@ -1,6 +1,7 @@
 use crate::events::Edit;
 use grc20_core::{block::BlockMetadata, pb::geo};
 
+#[derive(Default)]
 pub struct EventData {
     pub block: BlockMetadata,
 
@@ -0,0 +1,6 @@
+//! Write-time validation of edit ops against the schema of the knowledge graph. The
+//! validation is shared with the edit drafts of the SDK, see
+//! [`grc20_sdk::models::validation`].
+pub use grc20_sdk::models::validation::{
+    check_value, validate_ops, value_type_from_id, OpRef, Violation,
+};
@@ -1,4 +1,5 @@
 use grc20_core::{
+    connection::Graph,
     mapping::{triple, Query},
     neo4rs,
 };
@@ -11,7 +12,7 @@ use testcontainers::{
 const BOLT_PORT: u16 = 7687;
 const HTTP_PORT: u16 = 7474;
 
-pub async fn setup_neo4j() -> (testcontainers::ContainerAsync<GenericImage>, neo4rs::Graph) {
+pub async fn setup_neo4j() -> (testcontainers::ContainerAsync<GenericImage>, Graph) {
     // Setup a local Neo4J container for testing
     let container = GenericImage::new("neo4j", "2025.01.0-community")
         .with_wait_for(WaitFor::Duration {
@@ -27,7 +28,7 @@ pub async fn setup_neo4j() -> (testcontainers::ContainerAsync<GenericImage>, neo
     let port = container.get_host_port_ipv4(BOLT_PORT).await.unwrap();
     let host = container.get_host().await.unwrap().to_string();
 
-    let neo4j = neo4rs::Graph::new(format!("neo4j://{host}:{port}"), "", "")
+    let neo4j = Graph::new(format!("neo4j://{host}:{port}"), "", "")
         .await
         .unwrap();
 
@@ -0,0 +1,141 @@
+use grc20_core::{block::BlockMetadata, pb};
+use grc20_sdk::models::{tentative_edit, TentativeEdit};
+use sink::{events::Edit, preprocess::EventData};
+use substreams_utils::{
+    pb::sf::substreams::{
+        rpc::v2::{BlockScopedData, BlockUndoSignal},
+        v1::BlockRef,
+    },
+    Sink,
+};
+
+mod common;
+
+fn set_triple(entity: &str, attribute: &str, value: &str) -> pb::ipfs::Op {
+    pb::ipfs::Op {
+        r#type: pb::ipfs::OpType::SetTriple as i32,
+        triple: Some(pb::ipfs::Triple {
+            entity: entity.to_string(),
+            attribute: attribute.to_string(),
+            value: Some(pb::ipfs::Value {
+                r#type: pb::ipfs::ValueType::Text as i32,
+                value: value.to_string(),
+            }),
+        }),
+        ..Default::default()
+    }
+}
+
+fn block_data(block_number: u64, edit_ids: &[&str]) -> EventData {
+    let edits = edit_ids
+        .iter()
+        .map(|id| Edit {
+            name: format!("Edit {id}"),
+            proposal_id: id.to_string(),
+            space_id: "space".to_string(),
+            space_plugin_address: "0x0".to_string(),
+            creator: "0x0".to_string(),
+            content_uri: format!("ipfs://{id}"),
+            ops: vec![set_triple(&format!("{id}_entity"), "name", id)],
+        })
+        .collect();
+
+    EventData {
+        block: BlockMetadata {
+            block_number,
+            ..Default::default()
+        },
+        edits_published: vec![(Default::default(), edits)],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_entity_ids() {
+    let ops = vec![
+        set_triple("bob", "name", "Bob"),
+        set_triple("alice", "name", "Alice"),
+        set_triple("alice", "age", "42"),
+        pb::ipfs::Op {
+            r#type: pb::ipfs::OpType::CreateRelation as i32,
+            relation: Some(pb::ipfs::Relation {
+                id: "relation".to_string(),
+                r#type: "knows".to_string(),
+                from_entity: "charlie".to_string(),
+                to_entity: "alice".to_string(),
+                index: "a0".to_string(),
+            }),
+            ..Default::default()
+        },
+    ];
+
+    assert_eq!(
+        TentativeEdit::entity_ids(&ops),
+        vec!["alice", "bob", "charlie"]
+    );
+}
+
+#[tokio::test]
+async fn test_tentative_overlay() {
+    // Setup Neo4j and IPFS mock
+    let (_container, neo4j) = common::neo4j::setup_neo4j().await;
+    let (_server, ipfs_client) = common::ipfs_mock::setup_ipfs_mock();
+
+    let handler = common::create_handler(neo4j.clone(), ipfs_client).unwrap();
+    assert!(handler.supports_hybrid());
+
+    let raw_block = BlockScopedData::default();
+    for (block_number, edit_ids) in [(10, vec!["a", "b"]), (11, vec!["c"]), (12, vec!["d"])] {
+        handler
+            .process_tentative_block_scoped_data(&raw_block, block_data(block_number, &edit_ids))
+            .await
+            .unwrap();
+    }
+
+    // Processing a block again replaces its tentative edits
+    handler
+        .process_tentative_block_scoped_data(&raw_block, block_data(10, &["a"]))
+        .await
+        .unwrap();
+
+    let edit_ids = || async {
+        tentative_edit::find_many(&neo4j, Some("space"))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|edit| edit.id)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(edit_ids().await, vec!["a", "c", "d"]);
+
+    let edit = tentative_edit::find_many(&neo4j, None)
+        .await
+        .unwrap()
+        .remove(0);
+    assert_eq!(edit.block_number, 10);
+    assert_eq!(edit.content_uri, "ipfs://a");
+    assert_eq!(edit.entity_ids, vec!["a_entity"]);
+
+    // Blocks up to #10 are final
+    handler.finalize_tentative_blocks(10).await.unwrap();
+    assert_eq!(edit_ids().await, vec!["c", "d"]);
+
+    // Block #12 is reverted
+    handler
+        .undo_tentative_blocks(&BlockUndoSignal {
+            last_valid_block: Some(BlockRef {
+                id: "block_11".to_string(),
+                number: 11,
+            }),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(edit_ids().await, vec!["c"]);
+
+    // Durable blocks cannot be reverted
+    assert!(handler
+        .process_block_undo_signal(&BlockUndoSignal::default())
+        .is_err());
+}
@@ -0,0 +1,128 @@
+use futures::TryStreamExt;
+use grc20_core::{
+    indexer_ids,
+    mapping::{prop_filter, triple, Query, QueryStream, ValueType},
+    pb, system_ids,
+};
+use grc20_sdk::models::{validation, DiagnosticKind};
+use sink::{events::Edit, validation::check_value};
+
+mod common;
+
+fn set_triple(
+    entity: &str,
+    attribute: &str,
+    value_type: pb::ipfs::ValueType,
+    value: &str,
+) -> pb::ipfs::Op {
+    pb::ipfs::Op {
+        r#type: pb::ipfs::OpType::SetTriple as i32,
+        triple: Some(pb::ipfs::Triple {
+            entity: entity.to_string(),
+            attribute: attribute.to_string(),
+            value: Some(pb::ipfs::Value {
+                r#type: value_type as i32,
+                value: value.to_string(),
+            }),
+        }),
+        ..Default::default()
+    }
+}
+
+fn create_relation(id: &str, from: &str, to: &str, relation_type: &str) -> pb::ipfs::Op {
+    pb::ipfs::Op {
+        r#type: pb::ipfs::OpType::CreateRelation as i32,
+        relation: Some(pb::ipfs::Relation {
+            id: id.to_string(),
+            r#type: relation_type.to_string(),
+            from_entity: from.to_string(),
+            to_entity: to.to_string(),
+            index: "a0".to_string(),
+        }),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_check_value() {
+    assert!(check_value(&ValueType::Text, "anything"));
+    assert!(check_value(&ValueType::Number, "42.5"));
+    assert!(!check_value(&ValueType::Number, "forty two"));
+    assert!(check_value(&ValueType::Checkbox, "true"));
+    assert!(!check_value(&ValueType::Checkbox, "yes"));
+    assert!(check_value(&ValueType::Url, "https://geobrowser.io"));
+    assert!(check_value(&ValueType::Url, "ipfs://bafkrei"));
+    assert!(!check_value(&ValueType::Url, "geobrowser.io"));
+    assert!(check_value(&ValueType::Time, "2025-01-01T00:00:00Z"));
+    assert!(!check_value(&ValueType::Time, "yesterday"));
+    assert!(check_value(&ValueType::Point, "45.5, -73.5"));
+    assert!(!check_value(&ValueType::Point, "45.5"));
+}
+
+#[tokio::test]
+async fn test_strict_space_rejects_violations() {
+    // Setup Neo4j and IPFS mock
+    let (_container, neo4j) = common::neo4j::setup_neo4j().await;
+    let (_server, ipfs_client) = common::ipfs_mock::setup_ipfs_mock();
+
+    // Create handler
+    let handler = common::create_handler(neo4j.clone(), ipfs_client)
+        .unwrap()
+        .validation(true)
+        .strict_spaces(vec!["strict_space".to_string()]);
+    let block = common::create_block_metadata();
+
+    let edit = Edit {
+        name: "Ages".to_string(),
+        proposal_id: "proposal".to_string(),
+        space_id: "strict_space".to_string(),
+        space_plugin_address: "0x0".to_string(),
+        creator: "0x0".to_string(),
+        content_uri: "ipfs://ages".to_string(),
+        ops: vec![
+            // Schema declared in the edit itself
+            create_relation(
+                "age_value_type",
+                "age",
+                system_ids::NUMBER,
+                system_ids::VALUE_TYPE_ATTRIBUTE,
+            ),
+            set_triple("alice", "age", pb::ipfs::ValueType::Text, "thirty"),
+            set_triple("bob", "age", pb::ipfs::ValueType::Number, "forty two"),
+            set_triple("charlie", "age", pb::ipfs::ValueType::Number, "42"),
+        ],
+    };
+
+    handler.process_edit(&block, edit, 0).await.unwrap();
+
+    let mut diagnostics = validation::find_many(&neo4j, indexer_ids::INDEXER_SPACE_ID)
+        .space(prop_filter::value("strict_space"))
+        .send()
+        .await
+        .unwrap()
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    diagnostics.sort_by(|a, b| a.attributes.entity.cmp(&b.attributes.entity));
+
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].attributes.entity, "alice");
+    assert_eq!(
+        diagnostics[0].attributes.kind,
+        DiagnosticKind::ValueTypeMismatch
+    );
+    assert_eq!(diagnostics[1].attributes.entity, "bob");
+    assert_eq!(diagnostics[1].attributes.kind, DiagnosticKind::InvalidValue);
+    assert!(diagnostics
+        .iter()
+        .all(|diagnostic| diagnostic.attributes.rejected));
+
+    // Violating triples are not written, valid ones are
+    for (entity_id, expected) in [("alice", false), ("bob", false), ("charlie", true)] {
+        let found = triple::find_one(&neo4j, "age", entity_id, "strict_space", None)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(found.is_some(), expected, "{entity_id}");
+    }
+}
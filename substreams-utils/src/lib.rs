@@ -3,4 +3,5 @@ pub mod sink;
 pub mod substreams;
 pub mod substreams_stream;
 
-pub use sink::Sink;
+pub use sink::{CancellationToken, RetryPolicy, RunOptions, Sink};
+pub use substreams_stream::StreamConfig;
@@ -1,16 +1,18 @@
-use std::{env, process::exit, sync::Arc};
+use std::{collections::VecDeque, env, fmt::Display, future::Future, sync::Arc, time::Duration};
 
 use anyhow::{format_err, Context};
-use futures::StreamExt;
+use futures::{Stream, StreamExt};
 use prost::Message;
 
+pub use tokio_util::sync::CancellationToken;
+
 use crate::{
     pb::sf::substreams::{
         rpc::v2::{BlockScopedData, BlockUndoSignal},
         v1::Package,
     },
     substreams::SubstreamsEndpoint,
-    substreams_stream::{RawBlockResponse, SubstreamsStream},
+    substreams_stream::{RawBlockResponse, StreamConfig, SubstreamsStream},
 };
 
 pub enum PreprocessedBlockResponse<T> {
@@ -18,6 +20,112 @@ pub enum PreprocessedBlockResponse<T> {
     Undo(BlockUndoSignal),
 }
 
+/// Retry policy applied when preprocessing or processing a block fails with a
+/// transient error (see [`Sink::is_transient_error`]). Retries are delayed with an
+/// exponential backoff.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Policy which never retries
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Maximum number of retries of a block (default: 5)
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Delay before the first retry (default: 1s)
+    pub fn initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// Maximum delay between two retries (default: 60s)
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Delay before the retry number `retry` (starting at 0)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Options of [`Sink::run_with_options`]
+#[derive(Clone, Debug)]
+pub struct RunOptions {
+    preprocess_buffer: usize,
+    retry_policy: RetryPolicy,
+    cancellation_token: CancellationToken,
+    stream_config: StreamConfig,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        Self {
+            preprocess_buffer: 1,
+            retry_policy: RetryPolicy::default(),
+            cancellation_token: CancellationToken::new(),
+            stream_config: StreamConfig::default(),
+        }
+    }
+}
+
+impl RunOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of blocks preprocessed concurrently, ahead of the block being
+    /// processed (default: 1)
+    pub fn preprocess_buffer(mut self, preprocess_buffer: usize) -> Self {
+        self.preprocess_buffer = preprocess_buffer.max(1);
+        self
+    }
+
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Token used to stop the sink gracefully: the block being processed (if any)
+    /// is completed and its cursor persisted before [`Sink::run_with_options`] returns.
+    pub fn cancellation_token(mut self, cancellation_token: CancellationToken) -> Self {
+        self.cancellation_token = cancellation_token;
+        self
+    }
+
+    /// Parameters of the substreams request, e.g.: to only stream final blocks
+    pub fn stream_config(mut self, stream_config: StreamConfig) -> Self {
+        self.stream_config = stream_config;
+        self
+    }
+}
+
 pub trait Sink<T: Send>: Send + Sync {
     type Error: std::error::Error + Send + Sync + 'static;
 
@@ -32,6 +140,12 @@ pub trait Sink<T: Send>: Send + Sync {
         data: T,
     ) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send;
 
+    /// Returns true if the block may be processed successfully when retried (e.g.:
+    /// network or database errors). Errors are considered transient by default.
+    fn is_transient_error(&self, _error: &Self::Error) -> bool {
+        true
+    }
+
     fn process_block_undo_signal(&self, _undo_signal: &BlockUndoSignal) -> Result<(), Self::Error> {
         // `BlockUndoSignal` must be treated as "delete every data that has been recorded after
         // block height specified by block in BlockUndoSignal". In the example above, this means
@@ -39,7 +153,51 @@ pub trait Sink<T: Send>: Send + Sync {
         // on your own logic. If for example all your added record contain a block number, a
         // simple way is to do `delete all records where block_num > 5` which is the block num
         // received in the `BlockUndoSignal` (this is true for append only records, so when only `INSERT` are allowed).
-        unimplemented!("you must implement some kind of block undo handling, or request only final blocks (tweak substreams_stream.rs)")
+        unimplemented!("you must implement some kind of block undo handling, or request only final blocks (see `StreamConfig::final_blocks_only`)")
+    }
+
+    /// Returns true if the sink implements the hybrid mode (see [`StreamConfig::hybrid`]),
+    /// i.e.: [`Sink::process_tentative_block_scoped_data`],
+    /// [`Sink::finalize_tentative_blocks`] and [`Sink::undo_tentative_blocks`].
+    /// [`Sink::run_with_options`] refuses to run in hybrid mode otherwise, since the
+    /// tentative blocks would be silently dropped (default: false).
+    fn supports_hybrid(&self) -> bool {
+        false
+    }
+
+    /// Processes a block which is not final yet (hybrid mode only, see
+    /// [`StreamConfig::hybrid`]). Its data must be kept apart from the durable data
+    /// (e.g.: in an overlay) since the block may still be reverted. The block is
+    /// processed again with [`Sink::process_block_scoped_data`] once it is final.
+    ///
+    /// Tentative blocks are not tracked across runs: the stream resumes after the last
+    /// final block and the tentative blocks are streamed again, so processing a block
+    /// already in the overlay must replace its data.
+    fn process_tentative_block_scoped_data(
+        &self,
+        _block_data: &BlockScopedData,
+        _data: T,
+    ) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send {
+        async { Ok(()) }
+    }
+
+    /// Discards the tentative data of the blocks up to `block_number` (included) once
+    /// they have been processed durably (hybrid mode only)
+    fn finalize_tentative_blocks(
+        &self,
+        _block_number: u64,
+    ) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send {
+        async { Ok(()) }
+    }
+
+    /// Discards the tentative data of the blocks above the last valid block of the
+    /// undo signal (hybrid mode only). Final blocks are never undone so the durable data
+    /// is left untouched.
+    fn undo_tentative_blocks(
+        &self,
+        _undo_signal: &BlockUndoSignal,
+    ) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send {
+        async { Ok(()) }
     }
 
     fn persist_cursor(
@@ -74,6 +232,33 @@ pub trait Sink<T: Send>: Send + Sync {
         start_block: i64,
         end_block: u64,
         preprocess_buffer: Option<usize>,
+    ) -> impl std::future::Future<Output = Result<(), anyhow::Error>> + Send {
+        self.run_with_options(
+            endpoint_url,
+            spkg_file,
+            module_name,
+            start_block,
+            end_block,
+            RunOptions::default().preprocess_buffer(preprocess_buffer.unwrap_or(1)),
+        )
+    }
+
+    /// Streams the blocks and processes them in order, persisting the cursor after
+    /// each block. Failing blocks are retried according to the retry policy of the
+    /// options. Returns when the stream is consumed, when the cancellation token is
+    /// cancelled, or with the first error which is fatal or outlasts the retries.
+    ///
+    /// In hybrid mode, only the cursors of final blocks are persisted. The blocks which
+    /// are not final yet are processed as tentative blocks and kept in memory until they
+    /// become final.
+    fn run_with_options(
+        &self,
+        endpoint_url: &str,
+        spkg_file: &str,
+        module_name: &str,
+        start_block: i64,
+        end_block: u64,
+        options: RunOptions,
     ) -> impl std::future::Future<Output = Result<(), anyhow::Error>> + Send {
         async move {
             let token_env = env::var("SUBSTREAMS_API_TOKEN").unwrap_or("".to_string());
@@ -82,63 +267,278 @@ pub trait Sink<T: Send>: Send + Sync {
                 token = Some(token_env);
             }
 
+            let stream_config = options.stream_config.clone();
+            stream_config.validate()?;
+            let hybrid = stream_config.is_hybrid();
+            if hybrid && !self.supports_hybrid() {
+                return Err(format_err!(
+                    "the hybrid mode is not supported by this sink (see `Sink::supports_hybrid`)"
+                ));
+            }
+
             let cursor: Option<String> = self.load_persisted_cursor().await?;
 
             let package = read_package(spkg_file).await?;
 
             let endpoint = Arc::new(SubstreamsEndpoint::new(&endpoint_url, token).await?);
 
-            let mut stream = SubstreamsStream::new(
+            let stream = SubstreamsStream::new(
                 endpoint.clone(),
                 cursor,
                 package.modules.clone(),
                 module_name.to_string(),
                 start_block,
                 end_block,
-            )
-            .map(|raw_block_response| async move {
-                match raw_block_response {
-                    Ok(RawBlockResponse::New(raw_block)) => {
-                        let data = self.preprocess_block_scoped_data(&raw_block).await?;
-                        Ok(PreprocessedBlockResponse::New(raw_block, data))
-                    }
-                    Ok(RawBlockResponse::Undo(undo_signal)) => {
-                        self.process_block_undo_signal(&undo_signal)?;
-                        Ok(PreprocessedBlockResponse::Undo(undo_signal))
-                    }
-                    Err(err) => Err(err),
+                stream_config,
+            );
+
+            process_stream(self, stream, &options, hybrid).await
+        }
+    }
+}
+
+/// Preprocesses and processes the blocks of `stream` in order (see
+/// [`Sink::run_with_options`])
+async fn process_stream<T: Send, S: Sink<T> + ?Sized>(
+    sink: &S,
+    stream: impl Stream<Item = Result<RawBlockResponse, anyhow::Error>>,
+    options: &RunOptions,
+    hybrid: bool,
+) -> Result<(), anyhow::Error> {
+    let policy = &options.retry_policy;
+    let cancellation_token = &options.cancellation_token;
+    let is_transient = |error: &S::Error| sink.is_transient_error(error);
+
+    let stream = stream
+        .map(|raw_block_response| async move {
+            match raw_block_response {
+                Ok(RawBlockResponse::New(raw_block)) => {
+                    let data = with_retries(policy, cancellation_token, is_transient, || {
+                        sink.preprocess_block_scoped_data(&raw_block)
+                    })
+                    .await
+                    .with_context(|| {
+                        format!("Failed to preprocess block {}", block_number(&raw_block))
+                    })?;
+
+                    Ok(data.map(|data| PreprocessedBlockResponse::New(raw_block, data)))
                 }
-            })
-            .buffered(preprocess_buffer.unwrap_or(1));
-
-            loop {
-                match stream.next().await {
-                    None => {
-                        println!("Stream consumed");
-                        break;
-                    }
-                    Some(Ok(PreprocessedBlockResponse::New(raw_block, data))) => {
-                        self.process_block_scoped_data(&raw_block, data).await?;
-                        self.persist_cursor(raw_block.cursor).await?;
-                    }
-                    Some(Ok(PreprocessedBlockResponse::Undo(undo_signal))) => {
-                        self.process_block_undo_signal(&undo_signal)?;
-                        self.persist_cursor(undo_signal.last_valid_cursor).await?;
+                Ok(RawBlockResponse::Undo(undo_signal)) => {
+                    Ok(Some(PreprocessedBlockResponse::Undo(undo_signal)))
+                }
+                Err(err) => Err(err.context("Substreams stream terminated with error")),
+            }
+        })
+        .buffered(options.preprocess_buffer);
+    let mut stream = std::pin::pin!(stream);
+
+    // Blocks processed as tentative blocks, waiting to become final (hybrid mode)
+    let mut tentative_blocks: VecDeque<BlockScopedData> = VecDeque::new();
+
+    loop {
+        // Cancellation is only checked between blocks so that blocks are
+        // always processed completely
+        let response = tokio::select! {
+            biased;
+            _ = cancellation_token.cancelled() => {
+                tracing::info!("Sink cancelled, stopping");
+                break;
+            }
+            response = stream.next() => response,
+        };
+
+        match response {
+            None => {
+                tracing::info!("Stream consumed");
+                break;
+            }
+            // Cancelled while waiting to retry preprocessing
+            Some(Ok(None)) => break,
+            Some(Ok(Some(PreprocessedBlockResponse::New(raw_block, data)))) => {
+                let raw_block = &raw_block;
+
+                if hybrid {
+                    // Tentative blocks which became final with this block
+                    while let Some(pending) = tentative_blocks.front() {
+                        if clock_number(pending) > raw_block.final_block_height {
+                            break;
+                        }
+
+                        let pending = tentative_blocks.pop_front().unwrap();
+                        let processed =
+                            process_block(sink, &pending, None, false, policy, cancellation_token)
+                                .await
+                                .with_context(|| {
+                                    format!("Failed to process block {}", block_number(&pending))
+                                })?;
+                        // Cancelled while waiting to retry
+                        if processed.is_none() {
+                            return Ok(());
+                        }
+
+                        sink.persist_cursor(pending.cursor.clone())
+                            .await
+                            .context("Failed to persist cursor")?;
+                        sink.finalize_tentative_blocks(clock_number(&pending))
+                            .await
+                            .context("Failed to finalize tentative blocks")?;
                     }
-                    Some(Err(err)) => {
-                        println!();
-                        println!("Stream terminated with error");
-                        println!("{err:?}");
-                        exit(1);
+
+                    if clock_number(raw_block) > raw_block.final_block_height {
+                        let processed = process_block(
+                            sink,
+                            raw_block,
+                            Some(data),
+                            true,
+                            policy,
+                            cancellation_token,
+                        )
+                        .await
+                        .with_context(|| {
+                            format!(
+                                "Failed to process tentative block {}",
+                                block_number(raw_block)
+                            )
+                        })?;
+                        if processed.is_none() {
+                            break;
+                        }
+
+                        tentative_blocks.push_back(raw_block.clone());
+                        continue;
                     }
                 }
+
+                let processed = process_block(
+                    sink,
+                    raw_block,
+                    Some(data),
+                    false,
+                    policy,
+                    cancellation_token,
+                )
+                .await
+                .with_context(|| format!("Failed to process block {}", block_number(raw_block)))?;
+
+                if processed.is_none() {
+                    break;
+                }
+
+                sink.persist_cursor(raw_block.cursor.clone())
+                    .await
+                    .context("Failed to persist cursor")?;
             }
+            Some(Ok(Some(PreprocessedBlockResponse::Undo(undo_signal)))) if hybrid => {
+                let last_valid_block = undo_signal
+                    .last_valid_block
+                    .as_ref()
+                    .map(|block| block.number)
+                    .unwrap_or_default();
+                tentative_blocks.retain(|block| clock_number(block) <= last_valid_block);
 
-            Ok(())
+                sink.undo_tentative_blocks(&undo_signal)
+                    .await
+                    .context("Failed to undo tentative blocks")?;
+            }
+            Some(Ok(Some(PreprocessedBlockResponse::Undo(undo_signal)))) => {
+                sink.process_block_undo_signal(&undo_signal)
+                    .context("Failed to process block undo signal")?;
+                sink.persist_cursor(undo_signal.last_valid_cursor)
+                    .await
+                    .context("Failed to persist cursor")?;
+            }
+            Some(Err(err)) => return Err(err),
+        }
+    }
+
+    Ok(())
+}
+
+/// Processes the block durably or as a tentative block, retrying transient errors.
+/// Retries preprocess the block again since the data is consumed by the failed attempt
+/// (as is the data of a tentative block, which is preprocessed again once final).
+async fn process_block<T: Send, S: Sink<T> + ?Sized>(
+    sink: &S,
+    raw_block: &BlockScopedData,
+    data: Option<T>,
+    tentative: bool,
+    policy: &RetryPolicy,
+    token: &CancellationToken,
+) -> Result<Option<()>, S::Error> {
+    let mut data = data;
+    with_retries(
+        policy,
+        token,
+        |error| sink.is_transient_error(error),
+        || {
+            let data = data.take();
+            async move {
+                let data = match data {
+                    Some(data) => data,
+                    None => sink.preprocess_block_scoped_data(raw_block).await?,
+                };
+
+                if tentative {
+                    sink.process_tentative_block_scoped_data(raw_block, data)
+                        .await
+                } else {
+                    sink.process_block_scoped_data(raw_block, data).await
+                }
+            }
+        },
+    )
+    .await
+}
+
+/// Runs `attempt` until it succeeds, retrying transient errors according to `policy`.
+/// Returns `None` if `token` is cancelled while waiting to retry.
+async fn with_retries<T, E, F, Fut>(
+    policy: &RetryPolicy,
+    token: &CancellationToken,
+    is_transient: impl Fn(&E) -> bool,
+    mut attempt: F,
+) -> Result<Option<T>, E>
+where
+    E: Display,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut retry = 0;
+
+    loop {
+        match attempt().await {
+            Ok(value) => return Ok(Some(value)),
+            Err(error) if retry < policy.max_retries && is_transient(&error) => {
+                let backoff = policy.backoff(retry);
+                tracing::warn!("Retrying in {backoff:?} after error: {error}");
+
+                tokio::select! {
+                    _ = token.cancelled() => return Ok(None),
+                    _ = tokio::time::sleep(backoff) => (),
+                }
+                retry += 1;
+            }
+            Err(error) => return Err(error),
         }
     }
 }
 
+/// Number of the block, 0 if unknown
+fn clock_number(block: &BlockScopedData) -> u64 {
+    block
+        .clock
+        .as_ref()
+        .map(|clock| clock.number)
+        .unwrap_or_default()
+}
+
+fn block_number(block: &BlockScopedData) -> String {
+    match &block.clock {
+        Some(clock) => format!("#{}", clock.number),
+        None => "(unknown)".to_string(),
+    }
+}
+
 async fn read_package(input: &str) -> Result<Package, anyhow::Error> {
     if input.starts_with("http") {
         return read_http_package(input).await;
@@ -210,3 +610,343 @@ fn read_block_range(pkg: &Package, module_name: &str) -> Result<(i64, u64), anyh
 
     Ok((start, stop))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Mutex,
+    };
+
+    use futures::stream;
+
+    use super::*;
+    use crate::pb::sf::substreams::v1::{BlockRef, Clock};
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("fake error")]
+    struct FakeError {
+        transient: bool,
+    }
+
+    /// Sink recording the calls it receives. Processing a block fails as long as
+    /// `failures` is positive.
+    #[derive(Default)]
+    struct FakeSink {
+        failures: AtomicU32,
+        fatal: bool,
+        events: Mutex<Vec<String>>,
+    }
+
+    impl FakeSink {
+        fn failing(failures: u32) -> Self {
+            Self {
+                failures: AtomicU32::new(failures),
+                ..Self::default()
+            }
+        }
+
+        fn record(&self, event: String) {
+            self.events.lock().unwrap().push(event);
+        }
+
+        fn events(&self) -> Vec<String> {
+            self.events.lock().unwrap().clone()
+        }
+    }
+
+    impl Sink<u64> for FakeSink {
+        type Error = FakeError;
+
+        fn is_transient_error(&self, error: &Self::Error) -> bool {
+            error.transient
+        }
+
+        fn supports_hybrid(&self) -> bool {
+            true
+        }
+
+        async fn preprocess_block_scoped_data(
+            &self,
+            block_data: &BlockScopedData,
+        ) -> Result<u64, Self::Error> {
+            Ok(clock_number(block_data))
+        }
+
+        async fn process_block_scoped_data(
+            &self,
+            _block_data: &BlockScopedData,
+            data: u64,
+        ) -> Result<(), Self::Error> {
+            if self
+                .failures
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |failures| {
+                    failures.checked_sub(1)
+                })
+                .is_ok()
+            {
+                self.record(format!("fail {data}"));
+                return Err(FakeError {
+                    transient: !self.fatal,
+                });
+            }
+
+            self.record(format!("process {data}"));
+            Ok(())
+        }
+
+        async fn process_tentative_block_scoped_data(
+            &self,
+            _block_data: &BlockScopedData,
+            data: u64,
+        ) -> Result<(), Self::Error> {
+            self.record(format!("tentative {data}"));
+            Ok(())
+        }
+
+        async fn finalize_tentative_blocks(&self, block_number: u64) -> Result<(), Self::Error> {
+            self.record(format!("finalize {block_number}"));
+            Ok(())
+        }
+
+        async fn undo_tentative_blocks(
+            &self,
+            undo_signal: &BlockUndoSignal,
+        ) -> Result<(), Self::Error> {
+            let last_valid_block = undo_signal.last_valid_block.as_ref().unwrap().number;
+            self.record(format!("undo {last_valid_block}"));
+            Ok(())
+        }
+
+        async fn persist_cursor(&self, cursor: String) -> Result<(), Self::Error> {
+            self.record(format!("cursor {cursor}"));
+            Ok(())
+        }
+    }
+
+    fn new_block(number: u64, final_block_height: u64) -> Result<RawBlockResponse, anyhow::Error> {
+        Ok(RawBlockResponse::New(BlockScopedData {
+            clock: Some(Clock {
+                number,
+                ..Default::default()
+            }),
+            cursor: number.to_string(),
+            final_block_height,
+            ..Default::default()
+        }))
+    }
+
+    fn undo(last_valid_block: u64) -> Result<RawBlockResponse, anyhow::Error> {
+        Ok(RawBlockResponse::Undo(BlockUndoSignal {
+            last_valid_block: Some(BlockRef {
+                number: last_valid_block,
+                ..Default::default()
+            }),
+            last_valid_cursor: last_valid_block.to_string(),
+        }))
+    }
+
+    fn options(max_retries: u32) -> RunOptions {
+        RunOptions::new().retry_policy(
+            RetryPolicy::default()
+                .max_retries(max_retries)
+                .initial_backoff(Duration::from_secs(1))
+                .max_backoff(Duration::from_secs(10)),
+        )
+    }
+
+    #[test]
+    fn test_backoff_is_capped() {
+        let policy = RetryPolicy::default()
+            .initial_backoff(Duration::from_secs(1))
+            .max_backoff(Duration::from_secs(5));
+
+        assert_eq!(policy.backoff(0), Duration::from_secs(1));
+        assert_eq!(policy.backoff(1), Duration::from_secs(2));
+        assert_eq!(policy.backoff(2), Duration::from_secs(4));
+        assert_eq!(policy.backoff(3), Duration::from_secs(5));
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(5));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_transient_errors_are_retried() {
+        let sink = FakeSink::failing(2);
+
+        process_stream(&sink, stream::iter([new_block(1, 1)]), &options(2), false)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            sink.events(),
+            vec!["fail 1", "fail 1", "process 1", "cursor 1"]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retries_are_limited() {
+        let sink = FakeSink::failing(3);
+
+        let result =
+            process_stream(&sink, stream::iter([new_block(1, 1)]), &options(2), false).await;
+
+        assert!(result.is_err());
+        // The first attempt and 2 retries
+        assert_eq!(sink.events(), vec!["fail 1", "fail 1", "fail 1"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_fatal_errors_are_not_retried() {
+        let sink = FakeSink {
+            fatal: true,
+            ..FakeSink::failing(1)
+        };
+
+        let result =
+            process_stream(&sink, stream::iter([new_block(1, 1)]), &options(2), false).await;
+
+        assert!(result.is_err());
+        assert_eq!(sink.events(), vec!["fail 1"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_cancellation_during_backoff() {
+        let sink = FakeSink::failing(u32::MAX);
+        let options = options(10);
+
+        let token = options.cancellation_token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(1500)).await;
+            token.cancel();
+        });
+
+        process_stream(
+            &sink,
+            stream::iter([new_block(1, 1), new_block(2, 2)]),
+            &options,
+            false,
+        )
+        .await
+        .unwrap();
+
+        // Cancelled while waiting for the second retry: the cursor is not persisted
+        // and the next block is not processed
+        assert_eq!(sink.events(), vec!["fail 1", "fail 1"]);
+    }
+
+    #[tokio::test]
+    async fn test_tentative_blocks_are_promoted_in_order() {
+        let sink = FakeSink::default();
+
+        process_stream(
+            &sink,
+            stream::iter([
+                new_block(1, 0),
+                new_block(2, 0),
+                new_block(3, 2),
+                new_block(4, 4),
+            ]),
+            &options(0),
+            true,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            sink.events(),
+            vec![
+                "tentative 1",
+                "tentative 2",
+                // Blocks 1 and 2 become final with block 3
+                "process 1",
+                "cursor 1",
+                "finalize 1",
+                "process 2",
+                "cursor 2",
+                "finalize 2",
+                "tentative 3",
+                // Block 4 is final
+                "process 3",
+                "cursor 3",
+                "finalize 3",
+                "process 4",
+                "cursor 4",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_undone_tentative_blocks_are_dropped() {
+        let sink = FakeSink::default();
+
+        process_stream(
+            &sink,
+            stream::iter([
+                new_block(1, 0),
+                new_block(2, 0),
+                new_block(3, 0),
+                undo(1),
+                new_block(2, 2),
+            ]),
+            &options(0),
+            true,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            sink.events(),
+            vec![
+                "tentative 1",
+                "tentative 2",
+                "tentative 3",
+                "undo 1",
+                // Only block 1 is kept, the new block 2 replaces the undone one
+                "process 1",
+                "cursor 1",
+                "finalize 1",
+                "process 2",
+                "cursor 2",
+            ]
+        );
+    }
+
+    struct FinalOnlySink;
+
+    impl Sink<()> for FinalOnlySink {
+        type Error = FakeError;
+
+        async fn preprocess_block_scoped_data(
+            &self,
+            _block_data: &BlockScopedData,
+        ) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        async fn process_block_scoped_data(
+            &self,
+            _block_data: &BlockScopedData,
+            _data: (),
+        ) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hybrid_mode_requires_opt_in() {
+        let result = FinalOnlySink
+            .run_with_options(
+                "http://localhost:1",
+                "missing.spkg",
+                "map_events",
+                0,
+                0,
+                RunOptions::new().stream_config(StreamConfig::default().hybrid(true)),
+            )
+            .await;
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("hybrid mode is not supported"));
+    }
+}
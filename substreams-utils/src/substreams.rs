@@ -51,6 +51,8 @@ impl SubstreamsEndpoint {
         })
     }
 
+    // The interceptor must return a `tonic::Status` as error, which is large
+    #[allow(clippy::result_large_err)]
     pub async fn substreams(
         self: Arc<Self>,
         request: Request,
@@ -17,6 +17,90 @@ use crate::pb::sf::substreams::v1::Modules;
 
 use crate::substreams::SubstreamsEndpoint;
 
+/// Parameters of the substreams request
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StreamConfig {
+    final_blocks_only: bool,
+    production_mode: bool,
+    debug_initial_store_snapshot_for_modules: Vec<String>,
+    hybrid: bool,
+}
+
+impl Default for StreamConfig {
+    fn default() -> Self {
+        Self {
+            final_blocks_only: false,
+            production_mode: true,
+            debug_initial_store_snapshot_for_modules: vec![],
+            hybrid: false,
+        }
+    }
+}
+
+impl StreamConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only stream final blocks: blocks are received once they cannot be reverted, so
+    /// no undo signal is ever sent (default: false)
+    pub fn final_blocks_only(mut self, final_blocks_only: bool) -> Self {
+        self.final_blocks_only = final_blocks_only;
+        self
+    }
+
+    /// Whether the request is executed in production mode (default: true). In development
+    /// mode, the outputs of every module are sent along with the output module.
+    pub fn production_mode(mut self, production_mode: bool) -> Self {
+        self.production_mode = production_mode;
+        self
+    }
+
+    /// Stores whose initial state is sent before the first block (development mode only)
+    pub fn debug_initial_store_snapshot_for_modules(
+        mut self,
+        modules: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.debug_initial_store_snapshot_for_modules =
+            modules.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Hybrid mode: final blocks are processed durably while the blocks of the head of
+    /// the chain, which are not final yet, are processed as tentative blocks (see
+    /// [`crate::Sink::process_tentative_block_scoped_data`]) and processed durably once
+    /// they become final (default: false). Requires unfinalized blocks and a sink
+    /// supporting it (see [`crate::Sink::supports_hybrid`]).
+    pub fn hybrid(mut self, hybrid: bool) -> Self {
+        self.hybrid = hybrid;
+        self
+    }
+
+    pub fn is_final_blocks_only(&self) -> bool {
+        self.final_blocks_only
+    }
+
+    pub fn is_hybrid(&self) -> bool {
+        self.hybrid
+    }
+
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.hybrid && self.final_blocks_only {
+            return Err(anyhow!(
+                "the hybrid mode requires unfinalized blocks, it cannot be used with final blocks only"
+            ));
+        }
+
+        if !self.debug_initial_store_snapshot_for_modules.is_empty() && self.production_mode {
+            return Err(anyhow!(
+                "initial store snapshots are only sent in development mode"
+            ));
+        }
+
+        Ok(())
+    }
+}
+
 pub enum RawBlockResponse {
     New(BlockScopedData),
     Undo(BlockUndoSignal),
@@ -34,6 +118,7 @@ impl SubstreamsStream {
         output_module_name: String,
         start_block: i64,
         end_block: u64,
+        config: StreamConfig,
     ) -> Self {
         SubstreamsStream {
             stream: Box::pin(stream_blocks(
@@ -43,6 +128,7 @@ impl SubstreamsStream {
                 output_module_name,
                 start_block,
                 end_block,
+                config,
             )),
         }
     }
@@ -56,6 +142,7 @@ fn stream_blocks(
     output_module_name: String,
     start_block_num: i64,
     stop_block_num: u64,
+    config: StreamConfig,
 ) -> impl Stream<Item = Result<RawBlockResponse, Error>> {
     let mut latest_cursor = cursor.unwrap_or_default();
     let mut backoff = ExponentialBackoff::from_millis(500).max_delay(Duration::from_secs(45));
@@ -74,15 +161,14 @@ fn stream_blocks(
                 start_block_num,
                 start_cursor: latest_cursor.clone(),
                 stop_block_num,
-                final_blocks_only: false,
+                final_blocks_only: config.final_blocks_only,
                 modules: modules.clone(),
                 output_module: output_module_name.clone(),
                 // There is usually no good reason for you to consume the stream development mode (so switching `true`
-                // to `false`). If you do switch it, be aware that more than one output module will be send back to you,
-                // and the current code in `process_block_scoped_data` (within your 'main.rs' file) expects a single
-                // module.
-                production_mode: true,
-                debug_initial_store_snapshot_for_modules: vec![],
+                // to `false`). If you do switch it, be aware that more than one output module will be send back to you
+                // (in the `debug_map_outputs` and `debug_store_outputs` of the blocks).
+                production_mode: config.production_mode,
+                debug_initial_store_snapshot_for_modules: config.debug_initial_store_snapshot_for_modules.clone(),
                 noop_mode: false,
             }).await;
 